@@ -0,0 +1,32 @@
+# Meldungen der `heuristics`-Kommandozeile, auf Deutsch.
+#
+# Schlüssel und Platzhalter wie in en.ftl; fehlende Meldungen erscheinen
+# auf Englisch.
+
+search-none = Keine Heuristiken zu diesen Suchbegriffen gefunden.
+search-hint = Versuche allgemeinere Begriffe wie: hash, cache, distributed, concurrent usw.
+search-found = { $count } Heuristik(en) gefunden:
+search-more = ... und { $count } weitere. Mit --limit werden mehr Ergebnisse angezeigt.
+select-prompt = Welche anzeigen? (z. B. 1 3, 2-4, all; Enter für keine)
+select-invalid = Wähle Nummern von 1 bis { $count }, nicht '{ $text }'
+
+category-none = Keine Kategorie gefunden: { $name }
+category-hint = 'heuristics categories' zeigt die verfügbaren Kategorien.
+category-header = Heuristiken in der Kategorie '{ $name }':
+categories-hint = 'heuristics category <name>' zeigt die Heuristiken einer Kategorie.
+list-header = Alle { $count } Heuristiken:
+crates-header = { $count } empfohlene Crates:
+random-daily = Heuristik des Tages:
+daily-header = Tipp des Tages ({ $number } von { $total }, { $streak } Tage in Folge):
+random-header = Zufällige Heuristik:
+history-none = Noch keine Suchen.
+
+label-notes = Notizen:
+label-related = Verwandt:
+label-sources = Quellen:
+label-benchmarks = Benchmarks:
+label-from = Aus: { $source }
+
+unknown-alias = Unbekannter Befehl oder Alias: { $name }
+unknown-alias-hint = Siehe `heuristics --help`, oder speichere eine Suche mit `heuristics alias set`.
+repl-hint = `help` listet die Befehle, `quit` beendet.
@@ -0,0 +1,33 @@
+# Messages of the `heuristics` command line, in English.
+#
+# Each message is `key = text` on one line, as in Fluent; `{ $name }` is
+# replaced by a value. Other locales translate the same keys, and fall
+# back to these for any they leave out.
+
+search-none = No heuristics found matching your keywords.
+search-hint = Try broader terms like: hash, cache, distributed, concurrent, etc.
+search-found = Found { $count } heuristic(s):
+search-more = ... and { $count } more. Use --limit to show more results.
+select-prompt = Show which? (e.g. 1 3, 2-4, all; Enter for none)
+select-invalid = Choose numbers from 1 to { $count }, not '{ $text }'
+
+category-none = No category found: { $name }
+category-hint = Use 'heuristics categories' to see available categories.
+category-header = Heuristics in category '{ $name }':
+categories-hint = Use 'heuristics category <name>' to see heuristics in a category.
+list-header = All { $count } heuristics:
+crates-header = { $count } recommended crates:
+random-daily = Heuristic of the day:
+daily-header = Tip of the day ({ $number } of { $total }, { $streak }-day streak):
+random-header = Random heuristic:
+history-none = No searches yet.
+
+label-notes = Notes:
+label-related = Related:
+label-sources = Sources:
+label-benchmarks = Benchmarks:
+label-from = From: { $source }
+
+unknown-alias = Unknown command or alias: { $name }
+unknown-alias-hint = See `heuristics --help`, or save a query with `heuristics alias set`.
+repl-hint = Type `help` for the commands, `quit` to leave.
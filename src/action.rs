@@ -0,0 +1,144 @@
+//! Structured reading of a heuristic's `**Action:**` line.
+//!
+//! Actions are written as prose for people. [`Heuristic::structured_action`]
+//! recognizes the common shapes ("Use `BTreeMap`", "Use a binary heap",
+//! "Prefer append-only logs") so tools can act on them without parsing
+//! prose themselves.
+
+use crate::Heuristic;
+
+/// What a heuristic's action recommends
+///
+/// Every variant keeps the original action text for display.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Action {
+    /// Use a standard library type, named as in `**Std types:**`
+    UseStdType { ty: String, text: String },
+    /// Add a crate, named as in `**Crates:**`
+    UseCrate { name: String, text: String },
+    /// Apply a design pattern or technique, e.g. "skip lists"
+    ApplyPattern { pattern: String, text: String },
+    /// Advice that doesn't fit any of the shapes above
+    Freeform(String),
+}
+
+/// Leading verbs that introduce the recommended pattern
+const PATTERN_VERBS: [&str; 9] = [
+    "use",
+    "prefer",
+    "apply",
+    "build",
+    "implement",
+    "add",
+    "reach for",
+    "consider",
+    "default to",
+];
+
+impl Action {
+    /// The action as written
+    pub fn text(&self) -> &str {
+        match self {
+            Self::UseStdType { text, .. }
+            | Self::UseCrate { text, .. }
+            | Self::ApplyPattern { text, .. }
+            | Self::Freeform(text) => text,
+        }
+    }
+}
+
+impl Heuristic {
+    /// Interpret the action line against the heuristic's crates and std types
+    ///
+    /// Backticked names win; otherwise a std type or crate whose name
+    /// appears in the prose (`BinaryHeap` as "binary heap") is picked up,
+    /// then a leading verb such as "Use" or "Prefer" marks a pattern.
+    pub fn structured_action(&self) -> Action {
+        let text = self.action.trim().to_string();
+        let lower = text.to_lowercase();
+
+        for quoted in text.split('`').skip(1).step_by(2) {
+            if let Some(ty) = self
+                .std_types
+                .iter()
+                .find(|ty| *ty == quoted || short_name(ty) == quoted)
+            {
+                return Action::UseStdType { ty: ty.clone(), text };
+            }
+            if self.crates.iter().any(|name| name == quoted) {
+                return Action::UseCrate { name: quoted.to_string(), text };
+            }
+        }
+
+        if let Some(ty) = self
+            .std_types
+            .iter()
+            .find(|ty| lower.contains(&spaced(short_name(ty))))
+        {
+            return Action::UseStdType { ty: ty.clone(), text };
+        }
+        if let Some(name) = self.crates.iter().find(|name| {
+            lower
+                .split(|c: char| {
+                    !(c.is_alphanumeric() || c == '-' || c == '_')
+                })
+                .any(|word| word == name.as_str())
+        }) {
+            return Action::UseCrate { name: name.clone(), text };
+        }
+
+        // ASCII lowercasing keeps every byte offset, so the offsets found in
+        // it slice the original text; full lowercasing can change lengths
+        let ascii = text.to_ascii_lowercase();
+        for verb in PATTERN_VERBS {
+            let Some(rest) =
+                ascii.strip_prefix(verb).filter(|rest| rest.starts_with(' '))
+            else {
+                continue;
+            };
+            // Keep the object of the verb, dropping articles and qualifiers
+            let trimmed = rest.trim_start();
+            let object = ["a ", "an ", "the "]
+                .iter()
+                .find_map(|a| trimmed.strip_prefix(a))
+                .unwrap_or(trimmed);
+            let end =
+                [" unless ", " then ", " in front of ", "(", ",", ";", "."]
+                    .iter()
+                    .filter_map(|stop| object.find(stop))
+                    .min()
+                    .unwrap_or(object.len());
+            // Slice the original text to keep its capitalization
+            let start = ascii.len() - object.len();
+            let pattern = text[start..start + end].trim();
+            if !pattern.is_empty() {
+                return Action::ApplyPattern {
+                    pattern: pattern.to_string(),
+                    text,
+                };
+            }
+        }
+
+        Action::Freeform(text)
+    }
+}
+
+/// `std::collections::BinaryHeap` -> `BinaryHeap`
+fn short_name(path: &str) -> &str {
+    let path = path.split('<').next().unwrap_or(path);
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+/// `BinaryHeap` -> `binary heap`
+fn spaced(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            out.push(' ');
+        }
+        out.extend(c.to_lowercase());
+    }
+    out
+}
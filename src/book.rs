@@ -0,0 +1,136 @@
+//! mdBook sources for publishing a pack with the Rust documentation
+//! toolchain.
+//!
+//! [`to_mdbook`] writes `book.toml`, `src/SUMMARY.md`, and a chapter per
+//! category, nested in the summary the way categories nest. `mdbook build`
+//! turns the directory into a book.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::{Heuristic, category_path, slug};
+
+/// An mdBook with a chapter per category, as paths relative to the book's
+/// root and their contents
+///
+/// Chapters are `src/<slug>.md`, with each heuristic in its heuristic file
+/// form under a heading anchored at its id, so `[...](#id)` links keep
+/// working, across chapters too. Categories with subcategories but no
+/// heuristics of their own become draft chapters.
+pub fn to_mdbook<'a>(
+    title: &str,
+    heuristics: impl IntoIterator<Item = &'a Heuristic>,
+) -> Vec<(PathBuf, String)> {
+    let heuristics: Vec<&Heuristic> = heuristics.into_iter().collect();
+    let chapter_of: HashMap<&str, String> = heuristics
+        .iter()
+        .map(|h| (h.id.as_str(), slug(&h.category)))
+        .collect();
+
+    let mut chapters: Vec<(&str, Vec<&Heuristic>)> = Vec::new();
+    for &heuristic in &heuristics {
+        match chapters.iter_mut().find(|(name, _)| *name == heuristic.category)
+        {
+            Some((_, members)) => members.push(heuristic),
+            None => chapters.push((&heuristic.category, vec![heuristic])),
+        }
+    }
+
+    let mut summary = format!("# Summary\n\n[{}](README.md)\n\n", title);
+    // Each level of the category path once, parents before their children
+    let mut listed: Vec<Vec<&str>> = Vec::new();
+    for (name, _) in &chapters {
+        let path = category_path(name);
+        for depth in 1..=path.len() {
+            let prefix = path[..depth].to_vec();
+            if listed.contains(&prefix) {
+                continue;
+            }
+            let full = prefix.join(" / ");
+            let link = match chapters
+                .iter()
+                .any(|(other, _)| category_path(other) == prefix)
+            {
+                true => format!("{}.md", slug(&full)),
+                false => String::new(),
+            };
+            summary.push_str(&format!(
+                "{}- [{}]({})\n",
+                "  ".repeat(depth - 1),
+                prefix[depth - 1],
+                link
+            ));
+            listed.push(prefix);
+        }
+    }
+
+    let intro = format!(
+        "# {}\n\n{} heuristics in {} categories.\n",
+        title,
+        heuristics.len(),
+        chapters.len()
+    );
+    let book = format!(
+        "[book]\ntitle = \"{}\"\nsrc = \"src\"\n",
+        title.replace('\\', "\\\\").replace('"', "\\\"")
+    );
+    let mut files = vec![
+        (PathBuf::from("book.toml"), book),
+        (PathBuf::from("src/SUMMARY.md"), summary),
+        (PathBuf::from("src/README.md"), intro),
+    ];
+    for (name, members) in &chapters {
+        let chapter = slug(name);
+        let mut out = format!("# {}\n", name);
+        for heuristic in members {
+            let mut section = chapter_section(heuristic);
+            // Links into other chapters name the chapter's file
+            for id in &heuristic.related {
+                if let Some(other) = chapter_of
+                    .get(id.as_str())
+                    .filter(|other| **other != chapter)
+                {
+                    section = section.replace(
+                        &format!("](#{})", id),
+                        &format!("]({}.md#{})", other, id),
+                    );
+                }
+            }
+            out.push('\n');
+            out.push_str(&section);
+        }
+        files.push((PathBuf::from(format!("src/{}.md", chapter)), out));
+    }
+    files
+}
+
+/// A heuristic's block of its heuristic file, with the header anchored at
+/// its id and the metadata block as labeled lines
+fn chapter_section(heuristic: &Heuristic) -> String {
+    let block = heuristic.to_markdown_block();
+    let (header, rest) = block.split_once('\n').unwrap_or((&block, ""));
+    let mut out = format!("{} {{#{}}}\n", header, heuristic.id);
+    let rest = match rest
+        .strip_prefix("---\n")
+        .and_then(|meta| meta.split_once("---\n"))
+    {
+        Some((meta, rest)) => {
+            for (key, value) in
+                meta.lines().filter_map(|line| line.split_once(':'))
+            {
+                let mut key = key.trim().chars();
+                let label: String = key
+                    .next()
+                    .map(|c| c.to_ascii_uppercase())
+                    .into_iter()
+                    .chain(key)
+                    .collect();
+                out.push_str(&format!("**{}:** {}\n", label, value.trim()));
+            }
+            rest
+        }
+        None => rest,
+    };
+    out.push_str(rest);
+    out
+}
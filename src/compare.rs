@@ -0,0 +1,226 @@
+//! Side-by-side comparison of heuristics.
+//!
+//! [`compare`] lines up what several heuristics recommend, one column per
+//! heuristic, so choosing between them doesn't mean reading each entry and
+//! diffing them mentally.
+
+use crate::parser::Fences;
+use crate::render::{DIM, LABEL, MIN_WIDTH, TITLE, paint, wrap};
+use crate::{Heuristic, RenderStyle};
+
+impl Heuristic {
+    /// Big-O complexities the heuristic states, such as `O(1)`, in order
+    ///
+    /// A bare `log(n)` is read as `O(log n)`. Code blocks are skipped.
+    pub fn complexity(&self) -> Vec<String> {
+        let mut found: Vec<String> = Vec::new();
+        let mut fences = Fences::default();
+        let text =
+            if self.content.is_empty() { &self.title } else { &self.content };
+        for line in text.lines().filter(|line| !fences.contains(line)) {
+            for notation in big_o(line) {
+                if !found.contains(&notation) {
+                    found.push(notation);
+                }
+            }
+        }
+        found
+    }
+}
+
+/// Every `O(...)` and bare `log(...)` in `line`, as big-O notation
+fn big_o(line: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut rest = line;
+    while let Some(start) = rest.find(['O', 'l']) {
+        let preceded_by_word = rest[..start]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_alphanumeric);
+        let tail = &rest[start..];
+        let opening = match tail {
+            _ if preceded_by_word => None,
+            _ if tail.starts_with("O(") => Some(1),
+            _ if tail.starts_with("log(") => Some(3),
+            _ => None,
+        };
+        let Some(opening) = opening else {
+            rest = &rest[start + 1..];
+            continue;
+        };
+
+        // Find the matching parenthesis, as in O(n log(n))
+        let mut depth = 0;
+        let close = tail.char_indices().skip(opening).find_map(|(i, c)| {
+            match c {
+                '(' => depth += 1,
+                ')' if depth == 1 => return Some(i),
+                ')' => depth -= 1,
+                _ => {}
+            }
+            None
+        });
+        let Some(close) = close else { break };
+        match opening {
+            1 => found.push(tail[..=close].to_string()),
+            _ => found.push(format!("O(log {})", &tail[opening + 1..close])),
+        }
+        rest = &tail[close + 1..];
+    }
+    found
+}
+
+/// Render `heuristics` side by side in `width` columns
+///
+/// Rows cover the action, complexity, trade-offs, recommended crates and
+/// std types, and memory overhead; rows no heuristic fills are left out.
+/// The markdown style is a table with one column per heuristic.
+pub fn compare(
+    heuristics: &[&Heuristic],
+    style: RenderStyle,
+    width: usize,
+) -> String {
+    let rows: [(&str, Vec<Vec<String>>); 8] = [
+        (
+            "Action",
+            heuristics.iter().map(|h| vec![h.action.clone()]).collect(),
+        ),
+        (
+            "Complexity",
+            heuristics
+                .iter()
+                .map(|h| vec![h.complexity().join(", ")])
+                .collect(),
+        ),
+        (
+            "Pros",
+            heuristics.iter().map(|h| h.tradeoffs.pros.clone()).collect(),
+        ),
+        (
+            "Cons",
+            heuristics.iter().map(|h| h.tradeoffs.cons.clone()).collect(),
+        ),
+        (
+            "Crates",
+            heuristics.iter().map(|h| vec![h.crates.join(", ")]).collect(),
+        ),
+        (
+            "Std types",
+            heuristics.iter().map(|h| vec![h.std_types.join(", ")]).collect(),
+        ),
+        (
+            "Memory",
+            heuristics
+                .iter()
+                .map(|h| {
+                    h.memory_overhead.iter().map(|m| m.text.clone()).collect()
+                })
+                .collect(),
+        ),
+        (
+            "Category",
+            heuristics.iter().map(|h| vec![h.category.clone()]).collect(),
+        ),
+    ];
+    let rows: Vec<(&str, Vec<Vec<String>>)> = rows
+        .into_iter()
+        .filter(|(_, cells)| {
+            cells.iter().flatten().any(|item| !item.is_empty())
+        })
+        .collect();
+
+    if style == RenderStyle::Markdown {
+        return compare_markdown(heuristics, &rows);
+    }
+
+    let label_width =
+        rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+    let gaps = 2 * heuristics.len();
+    let column = (width.saturating_sub(label_width + gaps)
+        / heuristics.len().max(1))
+    .max(MIN_WIDTH);
+    // Each cell as wrapped lines; several items become a bulleted list
+    let cell = |items: &[String]| -> Vec<String> {
+        let items: Vec<&String> =
+            items.iter().filter(|item| !item.is_empty()).collect();
+        let (first, rest) =
+            if items.len() > 1 { ("- ", "  ") } else { ("", "") };
+        items
+            .iter()
+            .flat_map(|item| {
+                wrap(item, column, first, rest)
+                    .lines()
+                    .map(String::from)
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    };
+
+    let mut out = String::new();
+    let mut line =
+        |label: &str, cells: &[Vec<String>], codes: Option<&str>| {
+            let height = cells.iter().map(Vec::len).max().unwrap_or(0).max(1);
+            for i in 0..height {
+                let label = if i == 0 { label } else { "" };
+                let mut text = paint(
+                    style,
+                    LABEL,
+                    &format!("{:<w$}", label, w = label_width),
+                );
+                for cell in cells {
+                    let value = cell.get(i).map(String::as_str).unwrap_or("");
+                    let value = format!("{:<w$}", value, w = column);
+                    text.push_str("  ");
+                    text.push_str(&codes.map_or(value.clone(), |codes| {
+                        paint(style, codes, &value)
+                    }));
+                }
+                out.push_str(text.trim_end());
+                out.push('\n');
+            }
+        };
+
+    let titles: Vec<Vec<String>> = heuristics
+        .iter()
+        .map(|h| cell(std::slice::from_ref(&h.title)))
+        .collect();
+    line("", &titles, Some(TITLE));
+    let rule: Vec<Vec<String>> =
+        heuristics.iter().map(|_| vec!["-".repeat(column)]).collect();
+    line("", &rule, Some(DIM));
+    for (label, cells) in &rows {
+        let cells: Vec<Vec<String>> =
+            cells.iter().map(|items| cell(items)).collect();
+        line(label, &cells, None);
+    }
+    out
+}
+
+fn compare_markdown(
+    heuristics: &[&Heuristic],
+    rows: &[(&str, Vec<Vec<String>>)],
+) -> String {
+    // Pipes would end a cell early
+    let escape = |text: &str| text.replace('|', "\\|");
+    let titles: Vec<String> = heuristics
+        .iter()
+        .map(|h| format!("[{}](#{})", escape(&h.title), h.id))
+        .collect();
+    let mut out = format!("| | {} |\n", titles.join(" | "));
+    out.push_str(&format!("|---|{}\n", "---|".repeat(heuristics.len())));
+    for (label, cells) in rows {
+        let cells: Vec<String> = cells
+            .iter()
+            .map(|items| {
+                items
+                    .iter()
+                    .filter(|item| !item.is_empty())
+                    .map(|item| escape(item))
+                    .collect::<Vec<_>>()
+                    .join("<br>")
+            })
+            .collect();
+        out.push_str(&format!("| **{}** | {} |\n", label, cells.join(" | ")));
+    }
+    out
+}
@@ -0,0 +1,256 @@
+//! Structured TOML heuristic definitions.
+//!
+//! Programs that generate heuristics can write TOML instead of markdown.
+//! Only `title` is required; every other field is optional and means the
+//! same as its markdown counterpart:
+//!
+//! ```toml
+//! version = 1                     # pack metadata, all optional
+//! pack-name = "team-heuristics"
+//! license = "MIT"
+//! min-crate-version = "0.1.0"
+//! namespace = "team"              # front matter settings
+//! stopwords = ["service"]
+//! boost = ["cache"]
+//!
+//! [categories]
+//! "Caching" = "Keeping results around instead of recomputing them."
+//!
+//! [[heuristic]]
+//! id = "need-a-cache"             # defaults to a slug of the title
+//! title = "Need a cache?"
+//! action = "Use `moka`."
+//! category = "Caching"
+//! aliases = ["How do I memoize results?"]
+//! parent = "need-key-value-lookups"
+//! crates = ["moka"]
+//! std_types = ["std::collections::HashMap"]
+//! keywords = ["memoize"]          # indexed on top of extracted keywords
+//! tags = ["caching"]              # also indexed
+//! level = "beginner"              # beginner, intermediate, or advanced
+//! when_to_use = "Expensive computations"
+//! pros = ["Fast repeated reads"]
+//! cons = ["Stale data"]
+//! sources = [{ title = "Moka", url = "https://github.com/moka-rs/moka" }]
+//! benchmarks = [{ text = "mokabench", url = "https://github.com/moka-rs/mokabench" }]
+//! alternatives = ["`lru`", "need-to-cache-expensive-results"]
+//! msrv = ["`moka` 1.70 (edition 2021)"]
+//! memory = "~1.2x of data"
+//! platforms = "no_std, wasm"
+//! since = "0.1.0"
+//! updated = "0.2.0"
+//! content = "Extra markdown shown with the heuristic."
+//! ```
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::keywords::{TermDictionary, add_keywords, body_keywords};
+use crate::parser::{finish_heuristic, make_ids_unique};
+use crate::{
+    Alternative, Corpus, CrateRequirement, Document, Heuristic, Level, Link,
+    MemoryOverhead, PACK_SCHEMA_VERSION, PackInfo, ParseError, Platforms,
+    Source, TradeOffs, slug,
+};
+
+/// A whole TOML heuristic file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct PackDefinition {
+    /// File format version, see [`crate::PACK_SCHEMA_VERSION`]
+    pub version: Option<u32>,
+    /// Human-readable name of the pack
+    #[serde(rename = "pack-name")]
+    pub pack_name: Option<String>,
+    /// License the pack's content is published under
+    pub license: Option<String>,
+    /// Oldest version of this crate that can load the pack
+    #[serde(rename = "min-crate-version")]
+    pub min_crate_version: Option<String>,
+    /// Namespace shared by every heuristic in the file
+    pub namespace: Option<String>,
+    /// Terms that are never indexed
+    pub stopwords: Vec<String>,
+    /// Terms that rank higher when matched
+    pub boost: Vec<String>,
+    /// Category descriptions, keyed by category name
+    pub categories: HashMap<String, String>,
+    /// The heuristics, in order
+    pub heuristic: Vec<HeuristicDefinition>,
+}
+
+/// One `[[heuristic]]` table of a TOML heuristic file
+///
+/// Fields mirror the markdown labels; see the [module docs](self).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+// Every field has a default, but loading rejects heuristics without a title
+#[cfg_attr(feature = "schema", schemars(extend("required" = ["title"])))]
+#[serde(default, deny_unknown_fields)]
+pub struct HeuristicDefinition {
+    pub id: Option<String>,
+    #[cfg_attr(feature = "schema", schemars(length(min = 1)))]
+    pub title: String,
+    pub action: String,
+    pub category: String,
+    pub aliases: Vec<String>,
+    pub parent: Option<String>,
+    pub crates: Vec<String>,
+    pub std_types: Vec<String>,
+    pub keywords: Vec<String>,
+    pub tags: Vec<String>,
+    pub level: Option<String>,
+    pub when_to_use: Option<String>,
+    pub pros: Vec<String>,
+    pub cons: Vec<String>,
+    pub sources: Vec<Source>,
+    pub benchmarks: Vec<Link>,
+    pub alternatives: Vec<String>,
+    pub msrv: Vec<String>,
+    pub memory: Option<String>,
+    pub platforms: Option<String>,
+    pub since: Option<String>,
+    pub updated: Option<String>,
+    pub content: Option<String>,
+}
+
+impl HeuristicDefinition {
+    fn into_heuristic(self, namespace: &str) -> Option<Heuristic> {
+        let dictionary = TermDictionary::builtin();
+        let mut keywords = Vec::new();
+        for text in
+            [Some(&self.title), Some(&self.action), self.when_to_use.as_ref()]
+                .into_iter()
+                .flatten()
+        {
+            add_keywords(&dictionary, text, &mut keywords);
+        }
+        for alias in &self.aliases {
+            add_keywords(&dictionary, alias, &mut keywords);
+        }
+        keywords.extend(self.crates.iter().cloned());
+        keywords.extend(self.std_types.iter().cloned());
+        keywords.extend(self.keywords.iter().map(|k| k.to_lowercase()));
+        let tags: Vec<String> =
+            self.tags.iter().map(|t| t.to_lowercase()).collect();
+        for tag in &tags {
+            if !keywords.contains(tag) {
+                keywords.push(tag.clone());
+            }
+        }
+
+        let mut content = format!("### {}\n", self.title);
+        if !self.action.is_empty() {
+            content.push_str(&format!("**Action:** {}\n", self.action));
+        }
+        if let Some(extra) = &self.content {
+            content.push('\n');
+            content.push_str(extra);
+        }
+        let body_keywords = body_keywords(&dictionary, &content, &keywords);
+
+        finish_heuristic(Heuristic {
+            id: self
+                .id
+                .as_deref()
+                .map(slug)
+                .unwrap_or_else(|| slug(&self.title)),
+            aliases: self.aliases,
+            action: self.action,
+            category: self.category,
+            content,
+            crates: self.crates,
+            std_types: self.std_types,
+            keywords,
+            body_keywords,
+            tradeoffs: TradeOffs { pros: self.pros, cons: self.cons },
+            sources: self.sources,
+            namespace: namespace.to_string(),
+            since: self.since,
+            updated: self.updated,
+            applies_to: self
+                .platforms
+                .as_deref()
+                .map(Platforms::parse)
+                .unwrap_or_default(),
+            alternatives: self
+                .alternatives
+                .iter()
+                .filter_map(|a| Alternative::parse(a))
+                .collect(),
+            benchmarks: self.benchmarks,
+            memory_overhead: self.memory.as_deref().map(MemoryOverhead::parse),
+            parent: self.parent,
+            crate_requirements: self
+                .msrv
+                .iter()
+                .filter_map(|r| CrateRequirement::parse(r))
+                .collect(),
+            tags,
+            level: self.level.as_deref().and_then(Level::parse),
+            title: self.title,
+            ..Default::default()
+        })
+    }
+}
+
+/// Parse a TOML heuristic file into the same shape as a markdown one
+pub(crate) fn parse_toml(content: &str) -> Result<Document, ParseError> {
+    let pack: PackDefinition = toml::from_str(content)
+        .map_err(|e| ParseError::InvalidDefinition(e.message().to_string()))?;
+
+    let declares_pack = pack.version.is_some()
+        || pack.pack_name.is_some()
+        || pack.license.is_some()
+        || pack.min_crate_version.is_some();
+    let info = declares_pack.then(|| PackInfo {
+        version: pack.version.unwrap_or(PACK_SCHEMA_VERSION),
+        name: pack.pack_name,
+        license: pack.license,
+        min_crate_version: pack.min_crate_version,
+    });
+    if let Some(info) = &info {
+        info.check()?;
+    }
+
+    let mut corpus = Corpus::default();
+    if let Some(namespace) = pack.namespace {
+        corpus.namespace = namespace;
+    }
+    corpus.stopwords =
+        pack.stopwords.iter().map(|t| t.to_lowercase()).collect();
+    corpus.boost = pack.boost.iter().map(|t| t.to_lowercase()).collect();
+    corpus.category_descriptions = pack.categories;
+
+    if pack.heuristic.iter().any(|h| h.title.trim().is_empty()) {
+        return Err(ParseError::InvalidDefinition(
+            "every heuristic needs a title".to_string(),
+        ));
+    }
+    if let Some(level) = pack
+        .heuristic
+        .iter()
+        .filter_map(|h| h.level.as_deref())
+        .find(|l| Level::parse(l).is_none())
+    {
+        let message = format!(
+            "level '{}' is not beginner, intermediate, or advanced",
+            level
+        );
+        return Err(ParseError::InvalidDefinition(message));
+    }
+    let mut heuristics: Vec<Heuristic> = pack
+        .heuristic
+        .into_iter()
+        .filter_map(|h| h.into_heuristic(&corpus.namespace))
+        .collect();
+    if heuristics.is_empty() {
+        return Err(ParseError::NoHeuristics);
+    }
+    // Like markdown packs, repeated ids get `-2`, `-3`, ... suffixes
+    make_ids_unique(&mut heuristics);
+
+    Ok(Document { corpus, heuristics, diagnostics: Vec::new(), pack: info })
+}
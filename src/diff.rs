@@ -0,0 +1,236 @@
+//! What changed between two versions of a pack.
+//!
+//! A changed fingerprint says a pack was edited, not what the edit means
+//! for readers. [`HeuristicDb::diff`] matches heuristics by id and reports
+//! which were added or removed, and for the rest, changed actions and crate
+//! recommendations apart from the other fields that changed.
+
+use crate::parser::split_prose;
+use crate::render::{DIM, LABEL, MIN_WIDTH, TITLE, paint, wrap};
+use crate::{Heuristic, HeuristicDb, RenderStyle};
+
+/// Differences between an older and a newer database
+#[derive(Debug, Clone, Default)]
+pub struct PackDiff<'a> {
+    /// Heuristics only the newer database has, in its order
+    pub added: Vec<&'a Heuristic>,
+    /// Heuristics only the older database has, in its order
+    pub removed: Vec<&'a Heuristic>,
+    /// Heuristics both have that differ, in the newer database's order
+    pub changed: Vec<HeuristicChange<'a>>,
+}
+
+/// How a heuristic with the same id differs between two databases
+#[derive(Debug, Clone)]
+pub struct HeuristicChange<'a> {
+    /// The heuristic in the older database
+    pub old: &'a Heuristic,
+    /// The heuristic in the newer database
+    pub new: &'a Heuristic,
+    /// Whether the recommended action changed
+    pub action_changed: bool,
+    /// Crates the newer version recommends and the older didn't
+    pub crates_added: Vec<&'a str>,
+    /// Crates the older version recommended and the newer doesn't
+    pub crates_removed: Vec<&'a str>,
+    /// Labels of the other fields that changed, e.g. `Title` and `Pros`
+    pub fields: Vec<&'static str>,
+}
+
+impl HeuristicDb {
+    /// What changed from this database to `newer`
+    ///
+    /// Heuristics are matched by id, so a retitled heuristic that kept its
+    /// id is changed rather than removed and added. Crate names are
+    /// compared ignoring case, and reordering crates isn't a change.
+    pub fn diff<'a>(&'a self, newer: &'a HeuristicDb) -> PackDiff<'a> {
+        let mut diff = PackDiff {
+            added: newer
+                .heuristics
+                .iter()
+                .filter(|h| self.get(&h.id).is_none())
+                .collect(),
+            removed: self
+                .heuristics
+                .iter()
+                .filter(|h| newer.get(&h.id).is_none())
+                .collect(),
+            changed: Vec::new(),
+        };
+        for new in &newer.heuristics {
+            if let Some(old) = self.get(&new.id)
+                && let Some(change) = HeuristicChange::between(old, new)
+            {
+                diff.changed.push(change);
+            }
+        }
+        diff
+    }
+}
+
+impl<'a> HeuristicChange<'a> {
+    /// The change from `old` to `new`, or `None` if they're the same
+    fn between(old: &'a Heuristic, new: &'a Heuristic) -> Option<Self> {
+        let missing_from = |names: &'a [String],
+                            others: &[String]|
+         -> Vec<&'a str> {
+            let names = names.iter().filter(|name| {
+                !others.iter().any(|other| other.eq_ignore_ascii_case(name))
+            });
+            names.map(String::as_str).collect()
+        };
+        let compared = [
+            ("Title", old.title == new.title),
+            ("Category", old.category == new.category),
+            ("Also asked as", old.aliases == new.aliases),
+            ("Std types", old.std_types == new.std_types),
+            ("Tags", old.tags == new.tags),
+            ("Level", old.level == new.level),
+            ("Pros", old.tradeoffs.pros == new.tradeoffs.pros),
+            ("Cons", old.tradeoffs.cons == new.tradeoffs.cons),
+            ("Alternatives", old.alternatives == new.alternatives),
+            ("See also", old.related == new.related),
+            ("Parent", old.parent == new.parent),
+            ("Memory", old.memory_overhead == new.memory_overhead),
+            ("Platforms", old.applies_to == new.applies_to),
+            ("MSRV", old.crate_requirements == new.crate_requirements),
+            ("Sources", old.sources == new.sources),
+            ("Benchmarks", old.benchmarks == new.benchmarks),
+            ("Since", old.since == new.since),
+            ("Updated", old.updated == new.updated),
+            (
+                "Notes",
+                split_prose(&old.content).0 == split_prose(&new.content).0,
+            ),
+        ];
+        let change = Self {
+            old,
+            new,
+            action_changed: old.action != new.action,
+            crates_added: missing_from(&new.crates, &old.crates),
+            crates_removed: missing_from(&old.crates, &new.crates),
+            fields: compared
+                .into_iter()
+                .filter(|(_, same)| !same)
+                .map(|(label, _)| label)
+                .collect(),
+        };
+        let changed = change.action_changed
+            || !change.crates_added.is_empty()
+            || !change.crates_removed.is_empty()
+            || !change.fields.is_empty();
+        changed.then_some(change)
+    }
+}
+
+impl PackDiff<'_> {
+    /// Whether the databases hold the same heuristics
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+    }
+
+    /// Render the differences in `width` columns
+    ///
+    /// Added heuristics are listed with their action, changed ones with
+    /// their old and new action, the crates they stopped and started
+    /// recommending, and the other fields that changed. The markdown style
+    /// uses a heading per section.
+    pub fn render(&self, style: RenderStyle, width: usize) -> String {
+        let width = width.max(MIN_WIDTH);
+        let markdown = style == RenderStyle::Markdown;
+        let (bullet, indent) =
+            if markdown { ("- ", "  ") } else { ("  ", "    ") };
+        let mut out = String::new();
+        let section =
+            |out: &mut String, label: &str, count: usize| match markdown {
+                true => out.push_str(&format!(
+                    "{}## {} ({})\n\n",
+                    if out.is_empty() { "" } else { "\n" },
+                    label,
+                    count
+                )),
+                false => out.push_str(&format!(
+                    "{}{}\n",
+                    if out.is_empty() { "" } else { "\n" },
+                    paint(style, LABEL, &format!("{} ({}):", label, count))
+                )),
+            };
+        let title = |h: &Heuristic| match markdown {
+            true => format!("**{}** (`{}`)", h.title, h.id),
+            false => format!(
+                "{} {}",
+                paint(style, TITLE, &h.title),
+                paint(style, DIM, &format!("({})", h.id))
+            ),
+        };
+
+        for (label, heuristics) in
+            [("Added", &self.added), ("Removed", &self.removed)]
+        {
+            if heuristics.is_empty() {
+                continue;
+            }
+            section(&mut out, label, heuristics.len());
+            for h in heuristics.iter() {
+                out.push_str(&wrap(&title(h), width, bullet, indent));
+                if label == "Added" && !h.action.is_empty() {
+                    out.push_str(&wrap(&h.action, width, indent, indent));
+                }
+            }
+        }
+
+        if !self.changed.is_empty() {
+            section(&mut out, "Changed", self.changed.len());
+            for change in &self.changed {
+                out.push_str(&wrap(&title(change.new), width, bullet, indent));
+                let mut details: Vec<String> = Vec::new();
+                if change.action_changed {
+                    details.push(format!("Action was: {}", change.old.action));
+                    details.push(format!("Action now: {}", change.new.action));
+                }
+                let code = |names: &[&str]| -> String {
+                    let names: Vec<String> = names
+                        .iter()
+                        .map(|name| {
+                            if markdown {
+                                format!("`{}`", name)
+                            } else {
+                                name.to_string()
+                            }
+                        })
+                        .collect();
+                    names.join(", ")
+                };
+                if !change.crates_added.is_empty() {
+                    details.push(format!(
+                        "Now recommends: {}",
+                        code(&change.crates_added)
+                    ));
+                }
+                if !change.crates_removed.is_empty() {
+                    details.push(format!(
+                        "No longer recommends: {}",
+                        code(&change.crates_removed)
+                    ));
+                }
+                if !change.fields.is_empty() {
+                    details.push(format!(
+                        "Also changed: {}",
+                        change.fields.join(", ")
+                    ));
+                }
+                let (first, rest) = if markdown {
+                    ("  - ", "    ")
+                } else {
+                    (indent, "      ")
+                };
+                for detail in details {
+                    out.push_str(&wrap(&detail, width, first, rest));
+                }
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,140 @@
+//! Errors returned when parsing and loading heuristic packs.
+
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use crate::ParseDiagnostic;
+
+/// Failure to load heuristics from outside the binary
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file couldn't be read
+    Io { path: PathBuf, source: io::Error },
+    /// The file was read but isn't a valid heuristic file
+    Parse { path: PathBuf, source: ParseError },
+    /// A pack couldn't be downloaded
+    Fetch { url: String, message: String },
+    /// A downloaded pack doesn't match its published checksum
+    ChecksumMismatch { url: String, expected: String, actual: String },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, source } => {
+                write!(f, "failed to read {}: {}", path.display(), source)
+            }
+            Self::Parse { path, source } => {
+                write!(f, "failed to parse {}: {}", path.display(), source)
+            }
+            Self::Fetch { url, message } => {
+                write!(f, "failed to download {}: {}", url, message)
+            }
+            Self::ChecksumMismatch { url, expected, actual } => {
+                write!(
+                    f,
+                    "checksum mismatch for {}: expected {}, got {}",
+                    url, expected, actual
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Parse { source, .. } => Some(source),
+            Self::Fetch { .. } | Self::ChecksumMismatch { .. } => None,
+        }
+    }
+}
+
+/// Problem that makes markdown unusable as a heuristic file
+///
+/// Line numbers are 1-based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// A `---` front matter block is opened but never closed
+    UnterminatedFrontMatter { line: usize },
+    /// A `###` header has no title
+    UntitledHeuristic { line: usize },
+    /// The file defines no heuristics
+    NoHeuristics,
+    /// A structured (TOML or JSON) definition doesn't match the schema
+    InvalidDefinition(String),
+    /// Strict parsing found problems the lenient parser would skip
+    Rejected(Vec<ParseDiagnostic>),
+    /// The pack declares a file format `version` this crate can't read
+    UnsupportedVersion { found: u32, supported: u32 },
+    /// The pack declares a `min-crate-version` newer than this crate
+    CrateTooOld { required: String, current: String },
+    /// An overlay patches a heuristic, by id or title, that doesn't exist
+    UnknownHeuristic(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnterminatedFrontMatter { line } => {
+                write!(
+                    f,
+                    "line {}: front matter is never closed with '---'",
+                    line
+                )
+            }
+            Self::UntitledHeuristic { line } => {
+                write!(f, "line {}: heuristic header has no title", line)
+            }
+            Self::NoHeuristics => write!(f, "no heuristics found"),
+            Self::InvalidDefinition(message) => {
+                write!(f, "invalid definition: {}", message)
+            }
+            Self::Rejected(diagnostics) => {
+                write!(f, "{} problem(s) in strict mode", diagnostics.len())?;
+                if let Some(first) = diagnostics.first() {
+                    write!(f, ", first at {}", first)?;
+                }
+                Ok(())
+            }
+            Self::UnsupportedVersion { found, supported } => write!(
+                f,
+                "pack format version {} is not supported; this version of heuristics reads version {}",
+                found, supported
+            ),
+            Self::CrateTooOld { required, current } => {
+                write!(
+                    f,
+                    "pack needs heuristics {} or newer, but this is {}",
+                    required, current
+                )
+            }
+            Self::UnknownHeuristic(name) => {
+                write!(f, "overlay patches unknown heuristic '{}'", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Failure to combine two databases with [`crate::HeuristicDb::merge`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeError {
+    /// Both databases have a heuristic with this id
+    DuplicateId(String),
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateId(id) => {
+                write!(f, "both databases define heuristic '{}'", id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MergeError {}
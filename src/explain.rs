@@ -0,0 +1,216 @@
+//! Crate-centric digests of the pack.
+//!
+//! The pack is organized by need, so advice about one crate is spread over
+//! every heuristic recommending it or offering it as an alternative.
+//! [`HeuristicDb::explain_crate`] gathers it into one [`CrateDigest`]: what
+//! the crate is for, when to reach for it, when not to, and what to use
+//! instead.
+
+use crate::parser::Fences;
+use crate::render::{DIM, LABEL, MIN_WIDTH, TITLE, paint, wrap};
+use crate::{Alternative, Heuristic, HeuristicDb, RenderStyle};
+
+/// Everything the pack says about one crate
+#[derive(Debug, Clone)]
+pub struct CrateDigest<'a> {
+    /// The crate name as first spelled in the pack
+    pub name: String,
+    /// Descriptions from `` `name` - description `` crate list items
+    pub descriptions: Vec<String>,
+    /// Heuristics recommending the crate, in pack order
+    pub recommended_by: Vec<&'a Heuristic>,
+    /// Heuristics offering the crate as an alternative to their advice
+    pub alternative_in: Vec<&'a Heuristic>,
+    /// When to use it: the "When to use" lines and pros of the heuristics
+    /// recommending it
+    pub use_when: Vec<String>,
+    /// When to avoid it: the cons of the heuristics recommending it
+    pub avoid_when: Vec<String>,
+    /// Other crates and std types those heuristics offer, or that the
+    /// crate is offered instead of
+    pub alternatives: Vec<Alternative>,
+}
+
+impl HeuristicDb {
+    /// Gather every heuristic mentioning crate `name` into a digest
+    ///
+    /// Names are compared ignoring case, as in
+    /// [`crate_mentions`](HeuristicDb::crate_mentions). `None` if no
+    /// heuristic recommends the crate or offers it as an alternative.
+    pub fn explain_crate(&self, name: &str) -> Option<CrateDigest<'_>> {
+        let is_it = |other: &str| other.eq_ignore_ascii_case(name);
+        let offers = |h: &Heuristic| {
+            h.alternatives
+                .iter()
+                .any(|a| matches!(a, Alternative::Crate(c) if is_it(c)))
+        };
+        let recommended_by: Vec<&Heuristic> = self
+            .heuristics
+            .iter()
+            .filter(|h| h.crates.iter().any(|c| is_it(c)))
+            .collect();
+        let alternative_in: Vec<&Heuristic> =
+            self.heuristics.iter().filter(|h| offers(h)).collect();
+        let spelled = recommended_by
+            .iter()
+            .flat_map(|h| &h.crates)
+            .find(|c| is_it(c))
+            .cloned()
+            .or_else(|| {
+                alternative_in.iter().flat_map(|h| &h.alternatives).find_map(
+                    |a| match a {
+                        Alternative::Crate(c) if is_it(c) => Some(c.clone()),
+                        _ => None,
+                    },
+                )
+            })?;
+
+        let mut digest = CrateDigest {
+            name: spelled,
+            descriptions: Vec::new(),
+            recommended_by,
+            alternative_in,
+            use_when: Vec::new(),
+            avoid_when: Vec::new(),
+            alternatives: Vec::new(),
+        };
+        for heuristic in &digest.recommended_by {
+            let mut fences = Fences::default();
+            for line in
+                heuristic.content.lines().filter(|line| !fences.contains(line))
+            {
+                let item = line.trim().trim_start_matches(['-', '*']).trim();
+                if let Some(use_case) =
+                    item.strip_prefix("**When to use:**").map(str::trim)
+                {
+                    push_new(&mut digest.use_when, use_case.to_string());
+                } else if let Some((code, description)) =
+                    item.split_once(" - ")
+                    && code
+                        .trim()
+                        .strip_prefix('`')
+                        .and_then(|c| c.strip_suffix('`'))
+                        .is_some_and(is_it)
+                {
+                    push_new(
+                        &mut digest.descriptions,
+                        description.trim().to_string(),
+                    );
+                }
+            }
+            for pro in &heuristic.tradeoffs.pros {
+                push_new(&mut digest.use_when, pro.clone());
+            }
+            for con in &heuristic.tradeoffs.cons {
+                push_new(&mut digest.avoid_when, con.clone());
+            }
+        }
+
+        // What the recommending heuristics offer besides it, then what it stands in for
+        let offered = digest.recommended_by.iter().flat_map(|h| {
+            let crates =
+                h.crates.iter().map(|c| Alternative::Crate(c.clone()));
+            let std_types =
+                h.std_types.iter().map(|t| Alternative::StdType(t.clone()));
+            crates.chain(std_types).chain(h.alternatives.iter().cloned())
+        });
+        let replaced = digest.alternative_in.iter().flat_map(|h| {
+            let crates =
+                h.crates.iter().map(|c| Alternative::Crate(c.clone()));
+            crates.chain(
+                h.std_types.iter().map(|t| Alternative::StdType(t.clone())),
+            )
+        });
+        let mut alternatives = Vec::new();
+        for alternative in offered.chain(replaced) {
+            match &alternative {
+                Alternative::Crate(c) if is_it(c) => {}
+                Alternative::Heuristic(_) => {}
+                _ => push_new(&mut alternatives, alternative),
+            }
+        }
+        digest.alternatives = alternatives;
+        Some(digest)
+    }
+}
+
+impl CrateDigest<'_> {
+    /// Render the digest in `width` columns
+    ///
+    /// Sections nothing in the pack fills are left out. The markdown style
+    /// uses a heading per section.
+    pub fn render(&self, style: RenderStyle, width: usize) -> String {
+        let width = width.max(MIN_WIDTH);
+        let markdown = style == RenderStyle::Markdown;
+        let mut out = match markdown {
+            true => format!("# {}\n", self.name),
+            false => wrap(&paint(style, TITLE, &self.name), width, "", ""),
+        };
+        for description in &self.descriptions {
+            match markdown {
+                true => out.push_str(&format!(
+                    "\n{}",
+                    wrap(description, width, "", "")
+                )),
+                false => out.push_str(&wrap(description, width, "  ", "  ")),
+            }
+        }
+
+        let titles = |heuristics: &[&Heuristic]| -> Vec<String> {
+            heuristics
+                .iter()
+                .map(|h| match style {
+                    RenderStyle::Markdown => {
+                        format!("[{}](#{}): {}", h.title, h.id, h.action)
+                    }
+                    _ => {
+                        format!("{} {}", h.title, paint(style, DIM, &h.action))
+                    }
+                })
+                .collect()
+        };
+        let names: Vec<String> =
+            self.alternatives
+                .iter()
+                .map(|alternative| match alternative {
+                    Alternative::Crate(name)
+                    | Alternative::StdType(name)
+                    | Alternative::Heuristic(name) => name,
+                })
+                .map(|name| {
+                    if markdown { format!("`{}`", name) } else { name.clone() }
+                })
+                .collect();
+        let sections = [
+            ("Recommended for", titles(&self.recommended_by)),
+            ("Use it when", self.use_when.clone()),
+            ("Avoid it when", self.avoid_when.clone()),
+            ("Instead, consider", names),
+            ("Suggested instead of", titles(&self.alternative_in)),
+        ];
+        let (bullet, indent) =
+            if markdown { ("- ", "  ") } else { ("  - ", "    ") };
+        for (label, items) in
+            sections.iter().filter(|(_, items)| !items.is_empty())
+        {
+            match markdown {
+                true => out.push_str(&format!("\n## {}\n\n", label)),
+                false => out.push_str(&format!(
+                    "\n{}\n",
+                    paint(style, LABEL, &format!("{}:", label))
+                )),
+            }
+            for item in items {
+                out.push_str(&wrap(item, width, bullet, indent));
+            }
+        }
+        out
+    }
+}
+
+/// Add `item` to `items` unless it's there already
+fn push_new<T: PartialEq>(items: &mut Vec<T>, item: T) {
+    if !items.contains(&item) {
+        items.push(item);
+    }
+}
@@ -0,0 +1,619 @@
+//! Exporting heuristics for spreadsheets, static sites, and `man`.
+//!
+//! [`to_csv`] flattens every heuristic into one row, with list fields
+//! joined by `; `, and [`to_anki`] into one flash card; [`to_dot`] draws
+//! how they relate. [`to_html`] renders a standalone page from each
+//! heuristic's markdown, so nothing it says is lost ([`to_site`](crate::to_site)
+//! spreads the same over a page per heuristic), and
+//! [`Heuristic::to_roff`] does the same for a manual page, which
+//! [`to_man_pages`] writes for every heuristic. JSON is
+//! `HeuristicDb::to_json` with the `json` feature, and YAML the same
+//! document through `to_yaml` with the `yaml` feature.
+
+use std::path::PathBuf;
+
+use crate::{Alternative, Heuristic, resolve_among};
+
+/// Columns of the CSV export, in order
+const CSV_COLUMNS: [&str; 13] = [
+    "id",
+    "title",
+    "category",
+    "action",
+    "crates",
+    "std_types",
+    "tags",
+    "level",
+    "pros",
+    "cons",
+    "sources",
+    "since",
+    "updated",
+];
+
+/// One CSV row per heuristic, under a header row
+///
+/// Fields are quoted as RFC 4180 requires, so titles with commas and
+/// multi-line values survive a round trip through a spreadsheet.
+pub fn to_csv<'a>(
+    heuristics: impl IntoIterator<Item = &'a Heuristic>,
+) -> String {
+    let mut out = csv_row(CSV_COLUMNS.iter().map(|column| column.to_string()));
+    for h in heuristics {
+        let sources: Vec<&str> =
+            h.sources.iter().map(|s| s.url.as_str()).collect();
+        out.push_str(&csv_row([
+            h.id.clone(),
+            h.title.clone(),
+            h.category.clone(),
+            h.action.clone(),
+            h.crates.join("; "),
+            h.std_types.join("; "),
+            h.tags.join("; "),
+            h.level.map(|level| level.to_string()).unwrap_or_default(),
+            h.tradeoffs.pros.join("; "),
+            h.tradeoffs.cons.join("; "),
+            sources.join("; "),
+            h.since.clone().unwrap_or_default(),
+            h.updated.clone().unwrap_or_default(),
+        ]));
+    }
+    out
+}
+
+fn csv_row(fields: impl IntoIterator<Item = String>) -> String {
+    let fields: Vec<String> = fields
+        .into_iter()
+        .map(|field| match field.contains([',', '"', '\n', '\r']) {
+            true => format!("\"{}\"", field.replace('"', "\"\"")),
+            false => field,
+        })
+        .collect();
+    format!("{}\r\n", fields.join(","))
+}
+
+/// An Anki deck with a note per heuristic, for spaced repetition
+///
+/// The file is Anki's tab-separated text import: the front of each card is
+/// the heuristic's title, the back its action, the crates and std types it
+/// recommends, and its pros and cons, as HTML. Notes are keyed by
+/// heuristic id, so importing a newer export updates the cards instead of
+/// duplicating them, and tagged with the category and the heuristic's tags.
+pub fn to_anki<'a>(
+    heuristics: impl IntoIterator<Item = &'a Heuristic>,
+) -> String {
+    let mut out = String::from(
+        "#separator:tab\n#html:true\n#notetype:Basic\n#guid column:1\n#tags column:4\n",
+    );
+    for h in heuristics {
+        let code = |items: &[String]| {
+            items
+                .iter()
+                .map(|i| format!("`{}`", i))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut back = format!("**{}**\n\n", h.action);
+        for (label, value) in
+            [("Crates", code(&h.crates)), ("Std types", code(&h.std_types))]
+        {
+            if !value.is_empty() {
+                back.push_str(&format!("- **{}:** {}\n", label, value));
+            }
+        }
+        for (label, items) in
+            [("Pros", &h.tradeoffs.pros), ("Cons", &h.tradeoffs.cons)]
+        {
+            if !items.is_empty() {
+                back.push_str(&format!(
+                    "- **{}:** {}\n",
+                    label,
+                    items.join("; ")
+                ));
+            }
+        }
+        let tags = std::iter::once(crate::slug(&h.category))
+            .chain(h.tags.iter().map(|tag| tag.replace(' ', "_")));
+        let fields = [
+            h.id.clone(),
+            escape(&h.title),
+            markdown_to_html(&back),
+            tags.collect::<Vec<_>>().join(" "),
+        ];
+        let fields: Vec<String> =
+            fields.iter().map(|field| anki_field(field)).collect();
+        out.push_str(&fields.join("\t"));
+        out.push('\n');
+    }
+    out
+}
+
+/// A field of Anki's text import on one line, quoted if it has quotes
+fn anki_field(field: &str) -> String {
+    let field = field.trim_end().replace(['\t', '\r'], " ").replace('\n', " ");
+    match field.contains('"') {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field,
+    }
+}
+
+/// A Graphviz graph of the heuristics, their categories, the crates they
+/// recommend, and their references to each other
+///
+/// Each category is a cluster of its heuristics' boxes. Crates are
+/// ellipses; `**See also:**` and `[...](#id)` links are dashed edges,
+/// `**Parent:**` links solid ones from the parent, and heuristic
+/// alternatives dotted ones. References to heuristics that aren't in the
+/// graph are left out. Render it with e.g. `dot -Tsvg`.
+pub fn to_dot<'a>(
+    heuristics: impl IntoIterator<Item = &'a Heuristic>,
+) -> String {
+    let heuristics: Vec<&Heuristic> = heuristics.into_iter().collect();
+    let node = |h: &Heuristic| dot_quoted(&format!("heuristic:{}", h.id));
+    let mut out = String::from(
+        "digraph heuristics {\n  rankdir=LR;\n  node [shape=box];\n",
+    );
+
+    let mut categories: Vec<&str> = Vec::new();
+    for h in &heuristics {
+        if !categories.contains(&h.category.as_str()) {
+            categories.push(&h.category);
+        }
+    }
+    for (i, category) in categories.iter().enumerate() {
+        out.push_str(&format!(
+            "  subgraph cluster_{} {{\n    label={};\n",
+            i,
+            dot_quoted(category)
+        ));
+        for h in heuristics.iter().filter(|h| h.category == *category) {
+            out.push_str(&format!(
+                "    {} [label={}];\n",
+                node(h),
+                dot_quoted(&h.title)
+            ));
+        }
+        out.push_str("  }\n");
+    }
+
+    let mut crates: Vec<&str> = Vec::new();
+    for name in heuristics.iter().flat_map(|h| &h.crates) {
+        if !crates.contains(&name.as_str()) {
+            crates.push(name);
+            out.push_str(&format!(
+                "  {} [shape=ellipse, label={}];\n",
+                dot_quoted(&format!("crate:{}", name)),
+                dot_quoted(name)
+            ));
+        }
+    }
+    for h in &heuristics {
+        for name in &h.crates {
+            out.push_str(&format!(
+                "  {} -> {};\n",
+                node(h),
+                dot_quoted(&format!("crate:{}", name))
+            ));
+        }
+        let alternatives =
+            h.alternatives.iter().filter_map(
+                |alternative| match alternative {
+                    Alternative::Heuristic(reference) => {
+                        Some((reference, "dotted"))
+                    }
+                    _ => None,
+                },
+            );
+        let references =
+            h.related.iter().map(|id| (id, "dashed")).chain(alternatives);
+        for (reference, style) in references {
+            if let Some(other) = resolve_among(&heuristics, reference) {
+                out.push_str(&format!(
+                    "  {} -> {} [style={}];\n",
+                    node(h),
+                    node(other),
+                    style
+                ));
+            }
+        }
+        if let Some(parent) = h
+            .parent
+            .as_deref()
+            .and_then(|parent| resolve_among(&heuristics, parent))
+        {
+            out.push_str(&format!("  {} -> {};\n", node(parent), node(h)));
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// `text` as a double-quoted Graphviz id
+fn dot_quoted(text: &str) -> String {
+    format!(
+        "\"{}\"",
+        text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+    )
+}
+
+/// A standalone HTML page titled `title` with every heuristic
+///
+/// Heuristics are grouped under a heading per category, in the order
+/// given, and each is a `<section>` whose id is the heuristic's id, so
+/// links like `#need-to-cache-expensive-results` keep working.
+pub fn to_html<'a>(
+    title: &str,
+    heuristics: impl IntoIterator<Item = &'a Heuristic>,
+) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n</head>\n<body>\n<h1>{0}</h1>\n",
+        escape(title)
+    );
+    let mut category = None;
+    for heuristic in heuristics {
+        if category != Some(&heuristic.category) {
+            category = Some(&heuristic.category);
+            out.push_str(&format!(
+                "<h2>{}</h2>\n",
+                escape(&heuristic.category)
+            ));
+        }
+        out.push_str(&heuristic.to_html());
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+impl Heuristic {
+    /// The heuristic as an HTML `<section>`, rendered from its markdown
+    pub fn to_html(&self) -> String {
+        format!(
+            "<section id=\"{}\">\n{}</section>\n",
+            escape(&self.id),
+            markdown_to_html(&self.to_markdown_block())
+        )
+    }
+}
+
+impl Heuristic {
+    /// The heuristic as YAML, with the fields it has in the
+    /// [`HeuristicDb::to_yaml`](crate::HeuristicDb::to_yaml) document
+    #[cfg(feature = "yaml")]
+    pub fn to_yaml(&self) -> String {
+        crate::to_yaml(self)
+    }
+}
+
+impl Heuristic {
+    /// The heuristic as a manual page in section 7, for `man -l`
+    ///
+    /// The page is named after the heuristic's id and dated with its
+    /// `**Updated:**` or `**Since:**` field when it has one.
+    pub fn to_roff(&self) -> String {
+        self.roff_page(&self.id)
+    }
+
+    /// The manual page, named `name`
+    fn roff_page(&self, name: &str) -> String {
+        let date = self
+            .updated
+            .as_deref()
+            .or(self.since.as_deref())
+            .unwrap_or_default();
+        let mut out = format!(
+            ".TH \"{}\" 7 \"{}\" \"heuristics\" \"{}\"\n.SH NAME\n{} \\- {}\n",
+            roff_quoted(&name.to_uppercase()),
+            roff_quoted(date),
+            roff_quoted(&self.category),
+            roff_escape(name),
+            roff_escape(&self.title)
+        );
+        // The title is already in NAME; the rest of the markdown is the page
+        let block = self.to_markdown_block();
+        let body = block.split_once('\n').map_or("", |(_, body)| body);
+        out.push_str(".SH DESCRIPTION\n");
+        out.push_str(&markdown_to_roff(body));
+        out
+    }
+}
+
+/// A section 7 manual page per heuristic, as paths relative to a man
+/// directory and their contents
+///
+/// Pages are `man7/heuristic-<id>.7`, named like their file, so with the
+/// directory on `MANPATH`, `man heuristic-<id>` opens one.
+pub fn to_man_pages<'a>(
+    heuristics: impl IntoIterator<Item = &'a Heuristic>,
+) -> Vec<(PathBuf, String)> {
+    heuristics
+        .into_iter()
+        .map(|h| {
+            let name = format!("heuristic-{}", h.id);
+            (PathBuf::from(format!("man7/{}.7", name)), h.roff_page(&name))
+        })
+        .collect()
+}
+
+/// Render markdown as roff `man` macros
+fn markdown_to_roff(markdown: &str) -> String {
+    use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+
+    let mut out = String::new();
+    // Control lines must start a line of their own
+    let control = |out: &mut String, request: &str| {
+        if !out.is_empty() && !out.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push_str(request);
+        out.push('\n');
+    };
+    let mut lists = 0;
+    let mut in_code = false;
+    let mut links: Vec<String> = Vec::new();
+    for event in Parser::new_ext(
+        markdown,
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH,
+    ) {
+        match event {
+            Event::Start(tag) => match tag {
+                Tag::Paragraph if lists == 0 => control(&mut out, ".PP"),
+                Tag::Paragraph => {}
+                Tag::Heading { .. } => control(&mut out, ".SS"),
+                Tag::BlockQuote(_) => control(&mut out, ".RS 4"),
+                Tag::CodeBlock(_) => {
+                    in_code = true;
+                    control(&mut out, ".PP\n.RS 4\n.nf");
+                }
+                Tag::List(_) => {
+                    if lists > 0 {
+                        control(&mut out, ".RS 2");
+                    }
+                    lists += 1;
+                }
+                Tag::Item => control(&mut out, ".IP \\(bu 2"),
+                Tag::Table(_) => control(&mut out, ".PP\n.nf"),
+                Tag::TableCell if !out.ends_with('\n') => out.push_str(" | "),
+                Tag::Emphasis => out.push_str("\\fI"),
+                Tag::Strong => out.push_str("\\fB"),
+                Tag::Link { dest_url, .. } => links.push(dest_url.to_string()),
+                _ => {}
+            },
+            Event::End(tag) => match tag {
+                TagEnd::BlockQuote(_) => control(&mut out, ".RE"),
+                TagEnd::CodeBlock => {
+                    in_code = false;
+                    control(&mut out, ".fi\n.RE");
+                }
+                TagEnd::List(_) => {
+                    lists -= 1;
+                    if lists > 0 {
+                        control(&mut out, ".RE");
+                    }
+                }
+                TagEnd::Heading(_) | TagEnd::TableHead | TagEnd::TableRow => {
+                    out.push('\n')
+                }
+                TagEnd::Table => control(&mut out, ".fi"),
+                TagEnd::Emphasis | TagEnd::Strong => out.push_str("\\fR"),
+                // Links keep their target, as a page can't be clicked
+                TagEnd::Link => {
+                    if let Some(url) =
+                        links.pop().filter(|url| !url.starts_with('#'))
+                    {
+                        out.push_str(&format!(" <{}>", roff_escape(&url)));
+                    }
+                }
+                _ => {}
+            },
+            Event::Text(text)
+            | Event::Html(text)
+            | Event::InlineHtml(text)
+                if in_code =>
+            {
+                for line in text.lines() {
+                    out.push_str(&roff_line(line));
+                    out.push('\n');
+                }
+            }
+            Event::Text(text)
+            | Event::Html(text)
+            | Event::InlineHtml(text) => out.push_str(&roff_line(&text)),
+            Event::Code(code) => {
+                out.push_str(&format!("\\fB{}\\fR", roff_escape(&code)))
+            }
+            Event::SoftBreak => out.push('\n'),
+            Event::HardBreak => control(&mut out, ".br"),
+            Event::Rule => control(&mut out, ".PP"),
+            _ => {}
+        }
+    }
+    if !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
+/// Escape text for roff, protecting any control character it starts with
+fn roff_line(text: &str) -> String {
+    let escaped = roff_escape(text);
+    match escaped.starts_with(['.', '\'']) {
+        true => format!("\\&{}", escaped),
+        false => escaped,
+    }
+}
+
+/// Escape backslashes and hyphens, which roff would otherwise interpret
+fn roff_escape(text: &str) -> String {
+    text.replace('\\', "\\e").replace('-', "\\-")
+}
+
+/// Escape text for a double-quoted macro argument
+fn roff_quoted(text: &str) -> String {
+    roff_escape(text).replace('"', "\\(dq")
+}
+
+/// Whether a link in a pack may become an `href` or `src`: http, https,
+/// and mailto URLs, and relative ones, `#id` links included
+///
+/// Anything else, such as `javascript:` or `data:`, could run script in
+/// pages served or published from the pack.
+fn is_safe_url(url: &str) -> bool {
+    // Browsers skip control characters and spaces around and inside schemes
+    let url: String =
+        url.chars().filter(|c| !c.is_ascii_control() && *c != ' ').collect();
+    match url.split_once(':') {
+        Some((scheme, _)) if !scheme.contains(['/', '?', '#']) => {
+            ["http", "https", "mailto"]
+                .iter()
+                .any(|allowed| scheme.eq_ignore_ascii_case(allowed))
+        }
+        _ => true,
+    }
+}
+
+/// Render markdown as HTML
+///
+/// Raw HTML in the markdown is escaped rather than passed through, and
+/// links and images only keep URLs [`is_safe_url`] accepts, as packs can
+/// come from anywhere.
+fn markdown_to_html(markdown: &str) -> String {
+    use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+
+    let mut out = String::new();
+    let mut in_table_head = false;
+    // The language and text of the code block being read
+    let mut code: Option<(String, String)> = None;
+    for event in Parser::new_ext(
+        markdown,
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH,
+    ) {
+        if let (Some((_, text)), Event::Text(chunk)) = (&mut code, &event) {
+            text.push_str(chunk);
+            continue;
+        }
+        match event {
+            Event::Start(tag) => match tag {
+                Tag::Paragraph => out.push_str("<p>"),
+                Tag::Heading { level, .. } => {
+                    out.push_str(&format!("<{}>", level))
+                }
+                Tag::BlockQuote(_) => out.push_str("<blockquote>\n"),
+                Tag::CodeBlock(kind) => {
+                    let lang = match kind {
+                        CodeBlockKind::Fenced(lang) => lang
+                            .split([',', ' '])
+                            .next()
+                            .unwrap_or_default()
+                            .to_string(),
+                        CodeBlockKind::Indented => String::new(),
+                    };
+                    match lang.is_empty() {
+                        true => out.push_str("<pre><code>"),
+                        false => out.push_str(&format!(
+                            "<pre><code class=\"language-{}\">",
+                            escape(&lang)
+                        )),
+                    }
+                    code = Some((lang, String::new()));
+                }
+                Tag::List(Some(1)) => out.push_str("<ol>\n"),
+                Tag::List(Some(start)) => {
+                    out.push_str(&format!("<ol start=\"{}\">\n", start))
+                }
+                Tag::List(None) => out.push_str("<ul>\n"),
+                Tag::Item => out.push_str("<li>"),
+                Tag::Table(_) => out.push_str("<table>\n"),
+                Tag::TableHead => {
+                    in_table_head = true;
+                    out.push_str("<thead><tr>");
+                }
+                Tag::TableRow => out.push_str("<tr>"),
+                Tag::TableCell => {
+                    out.push_str(if in_table_head { "<th>" } else { "<td>" })
+                }
+                Tag::Emphasis => out.push_str("<em>"),
+                Tag::Strong => out.push_str("<strong>"),
+                Tag::Strikethrough => out.push_str("<del>"),
+                Tag::Link { dest_url, .. } if is_safe_url(&dest_url) => out
+                    .push_str(&format!("<a href=\"{}\">", escape(&dest_url))),
+                Tag::Link { .. } => out.push_str("<a>"),
+                Tag::Image { dest_url, .. } if is_safe_url(&dest_url) => out
+                    .push_str(&format!(
+                        "<img src=\"{}\" alt=\"",
+                        escape(&dest_url)
+                    )),
+                Tag::Image { .. } => out.push_str("<img alt=\""),
+                _ => {}
+            },
+            Event::End(tag) => match tag {
+                TagEnd::Paragraph => out.push_str("</p>\n"),
+                TagEnd::Heading(level) => {
+                    out.push_str(&format!("</{}>\n", level))
+                }
+                TagEnd::BlockQuote(_) => out.push_str("</blockquote>\n"),
+                TagEnd::CodeBlock => {
+                    let (lang, text) = code.take().unwrap_or_default();
+                    out.push_str(&code_to_html(&text, &lang));
+                    out.push_str("</code></pre>\n");
+                }
+                TagEnd::List(true) => out.push_str("</ol>\n"),
+                TagEnd::List(false) => out.push_str("</ul>\n"),
+                TagEnd::Item => out.push_str("</li>\n"),
+                TagEnd::Table => out.push_str("</tbody></table>\n"),
+                TagEnd::TableHead => {
+                    in_table_head = false;
+                    out.push_str("</tr></thead><tbody>\n");
+                }
+                TagEnd::TableRow => out.push_str("</tr>\n"),
+                TagEnd::TableCell => {
+                    out.push_str(if in_table_head { "</th>" } else { "</td>" })
+                }
+                TagEnd::Emphasis => out.push_str("</em>"),
+                TagEnd::Strong => out.push_str("</strong>"),
+                TagEnd::Strikethrough => out.push_str("</del>"),
+                TagEnd::Link => out.push_str("</a>"),
+                TagEnd::Image => out.push_str("\">"),
+                _ => {}
+            },
+            Event::Text(text)
+            | Event::Html(text)
+            | Event::InlineHtml(text) => out.push_str(&escape(&text)),
+            Event::Code(code) => {
+                out.push_str(&format!("<code>{}</code>", escape(&code)))
+            }
+            Event::SoftBreak => out.push('\n'),
+            Event::HardBreak => out.push_str("<br>\n"),
+            Event::Rule => out.push_str("<hr>\n"),
+            _ => {}
+        }
+    }
+    out
+}
+
+/// A code sample's text, highlighted when the `highlight` feature knows
+/// its language
+fn code_to_html(code: &str, lang: &str) -> String {
+    #[cfg(feature = "highlight")]
+    if let Some(highlighted) = crate::highlight::html(code, lang) {
+        return highlighted;
+    }
+    #[cfg(not(feature = "highlight"))]
+    let _ = lang;
+    escape(code)
+}
+
+/// Escape text for HTML element content and quoted attributes
+pub(crate) fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,225 @@
+//! Downloading heuristic packs by URL, enabled by the `fetch` feature.
+//!
+//! Every download is verified against a SHA-256 checksum before it is
+//! written to the on-disk cache, so a pack fetched once keeps loading
+//! while offline and a corrupted pack never replaces a good one. Packs
+//! aren't signed: the checksum is published next to the pack, so it proves
+//! the download is intact, not who wrote it. Pin `sha256` to a checksum
+//! from a source you trust to also rule out a tampered server.
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    HeuristicDb, LoadError, PUBLISHED_PACK_URL, Provenance, cache_dir,
+    load_heuristics_from_path, parse_file,
+};
+
+/// Download a heuristic pack and load it
+///
+/// The pack is verified against the checksum published next to it at
+/// `<url>.sha256` and cached under [`cache_dir`]. If the server can't be
+/// reached, the copy cached by an earlier download is loaded instead.
+pub fn load_heuristics_from_url(url: &str) -> Result<HeuristicDb, LoadError> {
+    let db = match fetch_pack(url, None) {
+        Ok(path) => load_heuristics_from_path(path),
+        Err(err @ LoadError::Fetch { .. }) => {
+            match cached_pack_path(url).filter(|path| path.is_file()) {
+                Some(path) => {
+                    event!(warn, url, error = %err, "download failed; loading the cached copy");
+                    load_heuristics_from_path(path)
+                }
+                None => Err(err),
+            }
+        }
+        Err(err) => Err(err),
+    }?;
+    Ok(db.with_provenance(Provenance::Url(url.to_string())))
+}
+
+/// Download a heuristic pack into the cache and return its path
+///
+/// `sha256` pins the expected checksum as hex; without it the checksum is
+/// read from `<url>.sha256`. The pack must verify and parse before it
+/// replaces the cached copy.
+pub fn fetch_pack(
+    url: &str,
+    sha256: Option<&str>,
+) -> Result<PathBuf, LoadError> {
+    let path = cached_pack_path(url).ok_or_else(|| LoadError::Fetch {
+        url: url.to_string(),
+        message: "no cache directory; set XDG_CACHE_HOME or HOME".to_string(),
+    })?;
+
+    let expected = expected_checksum(url, sha256)?;
+    let body = download(url)?;
+    let actual = sha256_hex(&body);
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        return Err(LoadError::ChecksumMismatch {
+            url: url.to_string(),
+            expected,
+            actual,
+        });
+    }
+
+    let content = String::from_utf8(body).map_err(|err| LoadError::Io {
+        path: path.clone(),
+        source: io::Error::new(io::ErrorKind::InvalidData, err),
+    })?;
+    parse_file(&path, &content)
+        .map_err(|source| LoadError::Parse { path: path.clone(), source })?;
+    write_atomically(&path, content.as_bytes())
+        .map_err(|source| LoadError::Io { path: path.clone(), source })?;
+    // Without a stamp, a cached stock pack is just never preferred
+    std::fs::write(stamp_path(&path), env!("CARGO_PKG_VERSION")).ok();
+    event!(info, url, path = %path.display(), bytes = content.len(), "cached downloaded pack");
+    Ok(path)
+}
+
+/// How the cached copy of a pack compares with the published one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackStatus {
+    /// The cached copy matches the published checksum
+    Current,
+    /// The pack has changed since it was cached
+    Stale,
+    /// Nothing has been downloaded from the URL yet
+    Missing,
+}
+
+/// Compare the cached copy of a pack with its published checksum
+///
+/// Only the checksum is downloaded, or nothing at all when `sha256` pins
+/// it. Until [`PUBLISHED_PACK_URL`] is cached, the embedded pack stands in
+/// for its cached copy, so the built-in heuristics can be stale too.
+pub fn check_pack(
+    url: &str,
+    sha256: Option<&str>,
+) -> Result<PackStatus, LoadError> {
+    let expected = expected_checksum(url, sha256)?;
+    let cached = match url == PUBLISHED_PACK_URL {
+        true => cached_published_pack_path()
+            .and_then(|path| std::fs::read(path).ok()),
+        false => {
+            cached_pack_path(url).and_then(|path| std::fs::read(path).ok())
+        }
+    };
+    let cached = match cached {
+        None if url == PUBLISHED_PACK_URL => {
+            Some(crate::BUILTIN_PACK.as_bytes().to_vec())
+        }
+        cached => cached,
+    };
+    Ok(pack_status(cached.as_deref(), &expected))
+}
+
+pub(crate) fn pack_status(
+    cached: Option<&[u8]>,
+    expected: &str,
+) -> PackStatus {
+    match cached {
+        None => PackStatus::Missing,
+        Some(bytes)
+            if sha256_hex(bytes).eq_ignore_ascii_case(expected.trim()) =>
+        {
+            PackStatus::Current
+        }
+        Some(_) => PackStatus::Stale,
+    }
+}
+
+/// Load the copy of [`PUBLISHED_PACK_URL`] cached by an earlier download
+///
+/// `None` if it was never downloaded, no longer loads, or was downloaded
+/// by another version of this crate, whose embedded pack may be newer.
+pub fn load_cached_published_pack() -> Option<HeuristicDb> {
+    let path = cached_published_pack_path()?;
+    let db = load_heuristics_from_path(path).ok()?;
+    Some(db.with_provenance(Provenance::Url(PUBLISHED_PACK_URL.to_string())))
+}
+
+/// The cached copy of [`PUBLISHED_PACK_URL`], if this version of the crate
+/// downloaded it
+///
+/// Upgrading the crate upgrades the embedded pack, so a copy downloaded
+/// before the upgrade could be older than it; it's left until the next
+/// `heuristics update` replaces it.
+fn cached_published_pack_path() -> Option<PathBuf> {
+    let path =
+        cached_pack_path(PUBLISHED_PACK_URL).filter(|path| path.is_file())?;
+    let stamp = std::fs::read_to_string(stamp_path(&path)).ok()?;
+    (stamp.trim() == env!("CARGO_PKG_VERSION")).then_some(path)
+}
+
+/// The file next to a cached pack recording which crate version cached it
+fn stamp_path(pack: &Path) -> PathBuf {
+    let mut name = pack.as_os_str().to_owned();
+    name.push(".crate-version");
+    PathBuf::from(name)
+}
+
+/// Where the pack downloaded from `url` is cached
+pub fn cached_pack_path(url: &str) -> Option<PathBuf> {
+    Some(cache_dir()?.join("packs").join(pack_file_name(url)))
+}
+
+/// Cache file name for a URL: a hash of the URL plus its file extension
+///
+/// The extension is kept so the cached copy loads in the same format.
+pub(crate) fn pack_file_name(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let extension = if path.ends_with(".toml") { "toml" } else { "md" };
+    format!("{}.{}", &sha256_hex(url.as_bytes())[..16], extension)
+}
+
+/// Lowercase hex SHA-256 digest
+pub(crate) fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// The pinned checksum, or the one published at `<url>.sha256`
+///
+/// The checksum file's first word must be the digest, the format
+/// `sha256sum` writes.
+fn expected_checksum(
+    url: &str,
+    sha256: Option<&str>,
+) -> Result<String, LoadError> {
+    match sha256 {
+        Some(expected) => Ok(expected.to_string()),
+        None => {
+            let file = download(&format!("{}.sha256", url))?;
+            Ok(String::from_utf8_lossy(&file)
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_string())
+        }
+    }
+}
+
+fn download(url: &str) -> Result<Vec<u8>, LoadError> {
+    crate::net::agent()
+        .get(url)
+        .call()
+        .and_then(|mut response| response.body_mut().read_to_vec())
+        .map_err(|err| LoadError::Fetch {
+            url: url.to_string(),
+            message: err.to_string(),
+        })
+}
+
+/// Write through a temporary file so readers never see half a pack
+fn write_atomically(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let partial =
+        path.with_extension(format!("partial-{}", std::process::id()));
+    let mut file = std::fs::File::create(&partial)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    std::fs::rename(&partial, path)
+}
@@ -0,0 +1,85 @@
+//! Content fingerprints for change detection.
+//!
+//! A fingerprint is a 64-bit FNV-1a hash of what a heuristic says, so
+//! caches and exporters can compare a stored value against the current one
+//! and skip regeneration when nothing changed. Fingerprints are stable
+//! across runs and platforms, but not a security checksum; use the pack's
+//! SHA-256 for that.
+
+use crate::{Heuristic, HeuristicDb};
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Incremental FNV-1a hasher
+///
+/// Every field is followed by a separator byte so that moving text from one
+/// field to the next changes the hash.
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Self(FNV_OFFSET)
+    }
+
+    fn write(&mut self, text: &str) -> &mut Self {
+        for byte in text.bytes().chain([0xff]) {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+        self
+    }
+}
+
+impl Heuristic {
+    /// Hash of the heuristic's content
+    ///
+    /// Covers the id, namespace, category, and every field as it would be
+    /// written back to markdown. Where the heuristic was loaded from doesn't
+    /// count, so the same heuristic read from two files hashes the same.
+    pub fn fingerprint(&self) -> u64 {
+        Fnv::new()
+            .write(&self.id)
+            .write(&self.namespace)
+            .write(&self.category)
+            .write(&self.to_markdown_block())
+            .0
+    }
+}
+
+impl HeuristicDb {
+    /// Hash of the whole database
+    ///
+    /// Changes whenever a heuristic is added, removed, reordered, or edited,
+    /// or the pack metadata or a corpus's indexing settings change.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = Fnv::new();
+        if let Some(pack) = self.pack_info() {
+            hasher.write(&pack.version.to_string());
+            for field in [&pack.name, &pack.license, &pack.min_crate_version] {
+                hasher.write(field.as_deref().unwrap_or_default());
+            }
+        }
+
+        let mut namespaces: Vec<&String> = self.corpora.keys().collect();
+        namespaces.sort();
+        for namespace in namespaces {
+            let corpus = &self.corpora[namespace];
+            hasher
+                .write(namespace)
+                .write(&corpus.stopwords.join(","))
+                .write(&corpus.boost.join(","));
+            let mut descriptions: Vec<_> =
+                corpus.category_descriptions.iter().collect();
+            descriptions.sort();
+            for (category, description) in descriptions {
+                hasher.write(category).write(description);
+            }
+        }
+
+        for heuristic in self.all() {
+            hasher.write(&format!("{:016x}", heuristic.fingerprint()));
+        }
+        hasher.0
+    }
+}
@@ -0,0 +1,72 @@
+//! Pluggable heuristic file formats.
+//!
+//! A [`HeuristicSource`] turns the text of one file into heuristics. The
+//! crate ships [`MarkdownSource`], plus [`TomlSource`] with the `toml`
+//! feature; downstream crates can implement the trait for org-mode,
+//! AsciiDoc, or their own JSON and load the result with
+//! [`HeuristicDb::from_source`].
+
+use crate::{
+    Heuristic, HeuristicDb, ParseError, ParseOptions, make_ids_unique,
+    parse_markdown_with, slug,
+};
+
+/// A format heuristics can be parsed from
+pub trait HeuristicSource {
+    /// Parse the contents of one file, in file order
+    fn parse(&self, input: &str) -> Result<Vec<Heuristic>, ParseError>;
+}
+
+/// The markdown format of the embedded pack
+///
+/// Front matter settings such as stopwords only affect how the returned
+/// heuristics were parsed; use [`parse_markdown_with`] to keep them.
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownSource {
+    options: ParseOptions,
+}
+
+impl MarkdownSource {
+    /// Parse with the given options instead of the lenient defaults
+    pub fn new(options: ParseOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl HeuristicSource for MarkdownSource {
+    fn parse(&self, input: &str) -> Result<Vec<Heuristic>, ParseError> {
+        parse_markdown_with(input, &self.options)
+            .map(|document| document.heuristics)
+    }
+}
+
+/// Structured TOML definitions, see [`crate::PackDefinition`]
+#[cfg(feature = "toml")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TomlSource;
+
+#[cfg(feature = "toml")]
+impl HeuristicSource for TomlSource {
+    fn parse(&self, input: &str) -> Result<Vec<Heuristic>, ParseError> {
+        crate::definition::parse_toml(input)
+            .map(|document| document.heuristics)
+    }
+}
+
+impl HeuristicDb {
+    /// Build a database from a file in any [`HeuristicSource`] format
+    ///
+    /// Heuristics without an id get a slug of their title; ids the source
+    /// repeats get `-2`, `-3`, ... suffixes, as in markdown.
+    pub fn from_source(
+        source: &dyn HeuristicSource,
+        input: &str,
+    ) -> Result<Self, ParseError> {
+        let mut heuristics = source.parse(input)?;
+        for heuristic in heuristics.iter_mut().filter(|h| h.id.is_empty()) {
+            heuristic.id = slug(&heuristic.title);
+        }
+        make_ids_unique(&mut heuristics);
+        Ok(Self::new(heuristics))
+    }
+}
@@ -0,0 +1,61 @@
+//! Fuzzy title matching for pickers and typo-tolerant lookups.
+//!
+//! A query matches when its characters appear in order in a title, as in
+//! fzf: "hmlk" matches "HashMap lookups". Runs of consecutive characters
+//! and characters starting a word score higher, so the closest titles come
+//! first.
+
+use crate::{Heuristic, HeuristicDb};
+
+/// Bonus for a character directly following the previous match
+const CONSECUTIVE_BONUS: usize = 5;
+
+/// Bonus for a character starting a word
+const WORD_START_BONUS: usize = 3;
+
+/// Score how well `query` fuzzily matches `text`, ignoring case and spaces
+/// in the query
+///
+/// Returns `None` unless every query character appears in `text` in order.
+/// An empty query matches everything with a score of 0.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<usize> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut start = 0;
+    let mut previous: Option<usize> = None;
+
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = start + text[start..].iter().position(|&c| c == wanted)?;
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += CONSECUTIVE_BONUS;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += WORD_START_BONUS;
+        }
+        previous = Some(found);
+        start = found + 1;
+    }
+    Some(score)
+}
+
+impl HeuristicDb {
+    /// Heuristics whose title or an alias fuzzily matches `query`, best first
+    ///
+    /// Ties keep pack order, so an empty query lists every heuristic in order.
+    pub fn fuzzy_search(&self, query: &str) -> Vec<&Heuristic> {
+        let mut scored: Vec<(usize, &Heuristic)> = self
+            .all()
+            .iter()
+            .filter_map(|h| {
+                let best = std::iter::once(&h.title)
+                    .chain(&h.aliases)
+                    .filter_map(|text| fuzzy_score(query, text))
+                    .max();
+                best.map(|score| (score, h))
+            })
+            .collect();
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        scored.into_iter().map(|(_, h)| h).collect()
+    }
+}
@@ -0,0 +1,66 @@
+//! Syntax highlighting of code samples, enabled by the `highlight` feature.
+//!
+//! Fenced code blocks are highlighted by the language of their info
+//! string, as in `` ```rust ``; blocks in a language the bundled syntaxes
+//! don't know are left to the caller to render plainly.
+
+use std::sync::OnceLock;
+
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::html::{IncludeBackground, styled_line_to_highlighted_html};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::{LinesWithEndings, as_24_bit_terminal_escaped};
+
+/// Theme for terminals, which are more often dark than not
+const TERMINAL_THEME: &str = "base16-ocean.dark";
+/// Theme for web pages, which are white unless styled otherwise
+const HTML_THEME: &str = "InspiredGitHub";
+
+/// Lines of `code` colored with 24-bit ANSI escapes, without line endings
+pub(crate) fn ansi(code: &str, lang: &str) -> Option<Vec<String>> {
+    let mut highlighter =
+        HighlightLines::new(syntax(lang)?, theme(TERMINAL_THEME));
+    LinesWithEndings::from(code)
+        .map(|line| {
+            let ranges = highlighter.highlight_line(line, syntaxes()).ok()?;
+            Some(format!(
+                "{}\x1b[0m",
+                as_24_bit_terminal_escaped(&ranges, false).trim_end()
+            ))
+        })
+        .collect()
+}
+
+/// `code` as HTML-escaped text in colored `<span>`s
+pub(crate) fn html(code: &str, lang: &str) -> Option<String> {
+    let mut highlighter =
+        HighlightLines::new(syntax(lang)?, theme(HTML_THEME));
+    LinesWithEndings::from(code)
+        .map(|line| {
+            let ranges = highlighter.highlight_line(line, syntaxes()).ok()?;
+            styled_line_to_highlighted_html(&ranges, IncludeBackground::No)
+                .ok()
+        })
+        .collect()
+}
+
+/// The syntax named by the first word of a fence's info string
+fn syntax(lang: &str) -> Option<&'static SyntaxReference> {
+    let token = lang.split([',', ' ']).next()?.trim();
+    if token.is_empty() {
+        return None;
+    }
+    syntaxes().find_syntax_by_token(token)
+}
+
+// Loading the bundled syntaxes and themes takes a while, so it's done once
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn theme(name: &str) -> &'static Theme {
+    static THEMES: OnceLock<ThemeSet> = OnceLock::new();
+    &THEMES.get_or_init(ThemeSet::load_defaults).themes[name]
+}
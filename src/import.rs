@@ -0,0 +1,478 @@
+//! Converting other cheatsheet formats into heuristics.
+//!
+//! Each format is a [`HeuristicSource`] that maps entries onto heuristic
+//! markdown as best it can and parses the result, so existing personal
+//! knowledge bases can be onboarded with [`HeuristicDb::from_source`] and
+//! saved with [`HeuristicDb::to_markdown`]. [`CsvSource`] and, with the
+//! `json` feature, `JsonSource` read this crate's own exports back.
+//!
+//! [`HeuristicDb::from_source`]: crate::HeuristicDb::from_source
+//! [`HeuristicDb::to_markdown`]: crate::HeuristicDb::to_markdown
+
+use crate::format::{HeuristicSource, MarkdownSource};
+use crate::parser::Fences;
+use crate::{
+    DEFAULT_NAMESPACE, Heuristic, HeuristicDb, Level, ParseError, Source,
+};
+
+/// A [tldr](https://tldr.sh) page
+///
+/// The page's command becomes the category. Every example becomes a
+/// heuristic titled by its description, with the command line as the action
+/// and the page's "More information" link as a source.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TldrSource;
+
+impl HeuristicSource for TldrSource {
+    fn parse(&self, input: &str) -> Result<Vec<Heuristic>, ParseError> {
+        let mut category = String::new();
+        let mut more_information = None;
+        let mut examples: Vec<(String, String)> = Vec::new();
+
+        for line in input.lines().map(str::trim) {
+            if let Some(name) = line.strip_prefix("# ") {
+                category = name.trim().to_string();
+            } else if let Some(quote) = line.strip_prefix('>') {
+                if let Some(url) =
+                    quote.trim().strip_prefix("More information:")
+                {
+                    more_information = Some(
+                        url.trim()
+                            .trim_end_matches('.')
+                            .trim_matches(['<', '>'])
+                            .to_string(),
+                    );
+                }
+            } else if let Some(example) = line.strip_prefix("- ") {
+                // Brackets mark the letters behind short options, as in "[c]reate"
+                let title = example
+                    .trim()
+                    .trim_end_matches(':')
+                    .replace(['[', ']'], "");
+                let mut chars = title.chars();
+                let title = chars
+                    .next()
+                    .map(|c| c.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default();
+                examples.push((title, String::new()));
+            } else if line.starts_with('`')
+                && let Some((_, action)) = examples.last_mut()
+                && action.is_empty()
+            {
+                action.push_str(line);
+            }
+        }
+
+        let mut markdown = format!("## {}\n\n", category);
+        for (title, action) in examples {
+            markdown.push_str(&format!("### {}\n", title));
+            if !action.is_empty() {
+                markdown.push_str(&format!("**Action:** {}\n", action));
+            }
+            if let Some(url) = &more_information {
+                markdown.push_str(&format!("- **Sources:** {}\n", url));
+            }
+            markdown.push('\n');
+        }
+        MarkdownSource::default().parse(&markdown)
+    }
+}
+
+/// A sheet in the format of [cheat](https://github.com/cheat/cheat)
+///
+/// Every `# comment` followed by commands becomes a heuristic titled by the
+/// comment, with the first command as the action and the whole snippet as
+/// an example. Sheets don't name themselves, so the category is given.
+#[derive(Debug, Clone, Default)]
+pub struct CheatSource {
+    category: String,
+}
+
+impl CheatSource {
+    /// Import into `category`, usually the sheet's file name
+    pub fn new(category: impl Into<String>) -> Self {
+        Self { category: category.into() }
+    }
+}
+
+impl HeuristicSource for CheatSource {
+    fn parse(&self, input: &str) -> Result<Vec<Heuristic>, ParseError> {
+        // Front matter holds cheat's own settings (syntax, tags)
+        let body = match input
+            .strip_prefix("---\n")
+            .and_then(|rest| rest.split_once("\n---"))
+        {
+            Some((_, body)) => body,
+            None => input,
+        };
+
+        let mut entries: Vec<(String, Vec<&str>)> = Vec::new();
+        let mut comment: Vec<&str> = Vec::new();
+        for line in body.lines() {
+            let trimmed = line.trim();
+            if let Some(text) = trimmed.strip_prefix('#') {
+                comment.push(text.trim());
+            } else if trimmed.is_empty() {
+                continue;
+            } else if !comment.is_empty() {
+                let title = std::mem::take(&mut comment).join(" ");
+                entries.push((
+                    title.trim_end_matches(':').to_string(),
+                    vec![line],
+                ));
+            } else if let Some((_, commands)) = entries.last_mut() {
+                commands.push(line);
+            }
+        }
+
+        let mut markdown = format!("## {}\n\n", self.category);
+        for (title, commands) in entries {
+            markdown.push_str(&format!(
+                "### {}\n**Action:** `{}`\n",
+                title,
+                commands[0].trim()
+            ));
+            if commands.len() > 1 {
+                markdown.push_str(&format!(
+                    "\n```sh\n{}\n```\n",
+                    commands.join("\n")
+                ));
+            }
+            markdown.push('\n');
+        }
+        MarkdownSource::default().parse(&markdown)
+    }
+}
+
+/// Free-form markdown notes
+///
+/// Every heading with text under it becomes a heuristic: the first line of
+/// the text is the action and the rest is kept as content. A heading with
+/// only subheadings under it becomes their category; headings with no
+/// such parent fall into the given category.
+#[derive(Debug, Clone, Default)]
+pub struct NotesSource {
+    category: String,
+}
+
+impl NotesSource {
+    /// Import notes without a grouping heading into `category`
+    pub fn new(category: impl Into<String>) -> Self {
+        Self { category: category.into() }
+    }
+}
+
+/// A heading of a notes file with the lines up to the next heading
+struct NoteSection<'a> {
+    level: usize,
+    title: &'a str,
+    body: Vec<&'a str>,
+}
+
+impl HeuristicSource for NotesSource {
+    fn parse(&self, input: &str) -> Result<Vec<Heuristic>, ParseError> {
+        let mut sections: Vec<NoteSection> = Vec::new();
+        let mut fences = Fences::default();
+        for line in input.lines() {
+            let level = line.chars().take_while(|&c| c == '#').count();
+            let heading = (!fences.contains(line) && (1..=6).contains(&level))
+                .then(|| line[level..].strip_prefix(' '))
+                .flatten();
+            match (heading, sections.last_mut()) {
+                (Some(title), _) => sections.push(NoteSection {
+                    level,
+                    title: title.trim(),
+                    body: Vec::new(),
+                }),
+                (None, Some(section)) => section.body.push(line),
+                (None, None) => {}
+            }
+        }
+
+        let mut markdown = String::new();
+        let mut category = None;
+        for (index, section) in sections.iter().enumerate() {
+            let Some(first) =
+                section.body.iter().position(|line| !line.trim().is_empty())
+            else {
+                continue;
+            };
+            // The nearest shallower heading groups this one
+            let parent = sections[..index]
+                .iter()
+                .rev()
+                .find(|s| s.level < section.level)
+                .map(|s| s.title);
+            let parent = parent.unwrap_or(&self.category);
+            if category != Some(parent) {
+                category = Some(parent);
+                markdown.push_str(&format!("## {}\n\n", parent));
+            }
+
+            markdown.push_str(&format!("### {}\n", section.title));
+            let action = section.body[first].trim();
+            let rest = if action.starts_with("```")
+                || action.starts_with("~~~")
+                || action.starts_with('|')
+            {
+                &section.body[first..]
+            } else {
+                let action = action.trim_start_matches(['-', '*', '>', ' ']);
+                markdown.push_str(&format!("**Action:** {}\n", action));
+                &section.body[first + 1..]
+            };
+            markdown.push_str(&format!("\n{}\n\n", rest.join("\n").trim()));
+        }
+        MarkdownSource::default().parse(&markdown)
+    }
+}
+
+/// The spreadsheet [`to_csv`](crate::to_csv) writes
+///
+/// Columns are found by their names in the header row, so they can be
+/// reordered, and columns of other names are ignored. `crates`,
+/// `std_types`, `tags`, `pros`, `cons`, and `sources` (as URLs) hold
+/// `;`-separated lists. Every row needs a `title`; rows without a
+/// `category` go into the given one.
+#[derive(Debug, Clone)]
+pub struct CsvSource {
+    category: String,
+}
+
+impl CsvSource {
+    /// Import rows without a category into `category`
+    pub fn new(category: impl Into<String>) -> Self {
+        Self { category: category.into() }
+    }
+}
+
+impl HeuristicSource for CsvSource {
+    fn parse(&self, input: &str) -> Result<Vec<Heuristic>, ParseError> {
+        let mut records = csv_records(input).into_iter();
+        let header = records.next().ok_or(ParseError::NoHeuristics)?;
+        let mut heuristics = Vec::new();
+        for (row, record) in records.enumerate() {
+            let field = |name: &str| {
+                let value = header
+                    .iter()
+                    .position(|column| column.trim() == name)
+                    .and_then(|i| record.get(i));
+                value
+                    .map(|value| value.trim().to_string())
+                    .filter(|value| !value.is_empty())
+            };
+            let list = |name: &str| -> Vec<String> {
+                let value = field(name).unwrap_or_default();
+                value
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(String::from)
+                    .collect()
+            };
+            let Some(title) = field("title") else {
+                return Err(ParseError::InvalidDefinition(format!(
+                    "row {} has no title",
+                    row + 2
+                )));
+            };
+            let mut heuristic = Heuristic {
+                id: field("id").unwrap_or_default(),
+                title,
+                category: field("category")
+                    .unwrap_or_else(|| self.category.clone()),
+                action: field("action").unwrap_or_default(),
+                crates: list("crates"),
+                std_types: list("std_types"),
+                tags: list("tags"),
+                level: field("level").as_deref().and_then(Level::parse),
+                sources: list("sources")
+                    .into_iter()
+                    .map(|url| Source { title: url.clone(), url })
+                    .collect(),
+                since: field("since"),
+                updated: field("updated"),
+                namespace: DEFAULT_NAMESPACE.to_string(),
+                ..Default::default()
+            };
+            heuristic.tradeoffs.pros = list("pros");
+            heuristic.tradeoffs.cons = list("cons");
+            heuristics.push(heuristic);
+        }
+        round_trip(heuristics)
+    }
+}
+
+/// Records of RFC 4180 CSV, whose quoted fields may hold commas, quotes,
+/// and line breaks; blank lines are skipped
+fn csv_records(input: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => field.push(c),
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => record.push(std::mem::take(&mut field)),
+            (false, '\r') if chars.peek() == Some(&'\n') => {}
+            (false, '\n') => {
+                record.push(std::mem::take(&mut field));
+                if record.iter().any(|field| !field.is_empty()) {
+                    records.push(std::mem::take(&mut record));
+                }
+                record.clear();
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    record.push(field);
+    if record.iter().any(|field| !field.is_empty()) {
+        records.push(record);
+    }
+    records
+}
+
+/// Heuristics as JSON: a snapshot [`HeuristicDb::to_json`] wrote, the
+/// document `heuristics export --format json` prints, or an array of
+/// heuristic objects
+///
+/// Heuristics whose `content` is their whole markdown block, as in both
+/// documents, are parsed from it, so nothing the block says is lost.
+/// Others are built from `id`, `title`, `category`, `action`, `aliases`,
+/// `crates`, `std_types`, `tags`, `level`, `pros` and `cons` (or
+/// `tradeoffs.pros` and `tradeoffs.cons`), `sources` (URLs, or objects with
+/// a `title` and `url`), `since`, `updated`, `parent`, `related`, and
+/// `content` as notes, all optional but `title`. Heuristics without a
+/// `category` go into the given one.
+///
+/// [`HeuristicDb::to_json`]: crate::HeuristicDb::to_json
+#[cfg(feature = "json")]
+#[derive(Debug, Clone)]
+pub struct JsonSource {
+    category: String,
+}
+
+#[cfg(feature = "json")]
+impl JsonSource {
+    /// Import heuristics without a category into `category`
+    pub fn new(category: impl Into<String>) -> Self {
+        Self { category: category.into() }
+    }
+}
+
+#[cfg(feature = "json")]
+impl HeuristicSource for JsonSource {
+    fn parse(&self, input: &str) -> Result<Vec<Heuristic>, ParseError> {
+        use serde_json::Value;
+
+        let invalid = |message: String| ParseError::InvalidDefinition(message);
+        let document: Value = serde_json::from_str(input)
+            .map_err(|err| invalid(err.to_string()))?;
+        let items = match &document {
+            Value::Array(items) => items,
+            _ => match document.get("heuristics").or_else(|| document.get("results")) {
+                Some(Value::Array(items)) => items,
+                _ => return Err(invalid("expected an array of heuristics, or one under `heuristics` or `results`".to_string())),
+            },
+        };
+
+        let mut markdown = String::new();
+        for (index, item) in items.iter().enumerate() {
+            let text = |value: Option<&Value>| {
+                value
+                    .and_then(Value::as_str)
+                    .map(str::trim)
+                    .filter(|text| !text.is_empty())
+                    .map(String::from)
+            };
+            let field = |name: &str| text(item.get(name));
+            let list = |value: Option<&Value>| -> Vec<String> {
+                let items = value
+                    .and_then(Value::as_array)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                items.iter().filter_map(|item| text(Some(item))).collect()
+            };
+            // Blocks are joined, so one without a category would join the one before
+            let category =
+                field("category").unwrap_or_else(|| self.category.clone());
+            let content = field("content").unwrap_or_default();
+            if content.starts_with("###") {
+                markdown
+                    .push_str(&format!("## {}\n\n{}\n\n", category, content));
+                continue;
+            }
+
+            let Some(title) = field("title") else {
+                return Err(invalid(format!(
+                    "heuristic {} has no title",
+                    index + 1
+                )));
+            };
+            let tradeoffs = item.get("tradeoffs");
+            let sources = item
+                .get("sources")
+                .and_then(Value::as_array)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let mut heuristic = Heuristic {
+                id: field("id").unwrap_or_default(),
+                title,
+                category,
+                action: field("action").unwrap_or_default(),
+                aliases: list(item.get("aliases")),
+                crates: list(item.get("crates")),
+                std_types: list(item.get("std_types")),
+                tags: list(item.get("tags")),
+                level: field("level").as_deref().and_then(Level::parse),
+                sources: sources
+                    .iter()
+                    .filter_map(|source| {
+                        let url = text(Some(source))
+                            .or_else(|| text(source.get("url")))?;
+                        Some(Source {
+                            title: text(source.get("title"))
+                                .unwrap_or_else(|| url.clone()),
+                            url,
+                        })
+                    })
+                    .collect(),
+                since: field("since"),
+                updated: field("updated"),
+                parent: field("parent"),
+                related: list(item.get("related")),
+                content,
+                namespace: DEFAULT_NAMESPACE.to_string(),
+                ..Default::default()
+            };
+            heuristic.tradeoffs.pros = list(
+                item.get("pros")
+                    .or_else(|| tradeoffs.and_then(|t| t.get("pros"))),
+            );
+            heuristic.tradeoffs.cons = list(
+                item.get("cons")
+                    .or_else(|| tradeoffs.and_then(|t| t.get("cons"))),
+            );
+            markdown
+                .push_str(&HeuristicDb::new(vec![heuristic]).to_markdown());
+            markdown.push('\n');
+        }
+        MarkdownSource::default().parse(&markdown)
+    }
+}
+
+/// Heuristics built from fields, as the writer writes and the parser reads
+/// them, which fills in what parsing derives, like keywords
+fn round_trip(
+    heuristics: Vec<Heuristic>,
+) -> Result<Vec<Heuristic>, ParseError> {
+    MarkdownSource::default()
+        .parse(&HeuristicDb::new(heuristics).to_markdown())
+}
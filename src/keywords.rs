@@ -0,0 +1,159 @@
+//! Keyword extraction used to index heuristics for search.
+//!
+//! The built-in dictionary covers general data-structure and systems terms.
+//! Domain-specific packs (game-dev, embedded, ...) can supply their own
+//! [`TermDictionary`] or a custom [`KeywordExtractor`] through
+//! [`ParseOptions`](crate::ParseOptions) or
+//! [`HeuristicDb::with_keywords`](crate::HeuristicDb::with_keywords).
+
+/// Finds searchable keywords in heuristic text
+pub trait KeywordExtractor: Send + Sync {
+    /// Keywords found in `text`, lowercase, in any order
+    fn extract(&self, text: &str) -> Vec<String>;
+}
+
+/// Terms the built-in dictionary recognizes
+const BUILTIN_TERMS: [&str; 49] = [
+    "hash",
+    "hashmap",
+    "hashset",
+    "btree",
+    "binary search",
+    "lookup",
+    "insert",
+    "cache",
+    "lru",
+    "ttl",
+    "bloom",
+    "filter",
+    "probabilistic",
+    "disk",
+    "persistence",
+    "wal",
+    "log",
+    "lsm",
+    "compression",
+    "distributed",
+    "shard",
+    "replicate",
+    "consensus",
+    "crdt",
+    "merkle",
+    "concurrent",
+    "lock-free",
+    "atomic",
+    "skip list",
+    "trie",
+    "prefix",
+    "autocomplete",
+    "heap",
+    "priority queue",
+    "geospatial",
+    "rtree",
+    "quadtree",
+    "rope",
+    "text",
+    "event sourcing",
+    "time-series",
+    "batch",
+    "async",
+    "append-only",
+    "performance",
+    "throughput",
+    "latency",
+    "columnar",
+    "parquet",
+];
+
+/// Extractor matching a fixed list of terms anywhere in the text
+///
+/// Terms may contain spaces ("skip list") and match case-insensitively as
+/// substrings, so "hash" also matches "hashing".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TermDictionary {
+    terms: Vec<String>,
+}
+
+impl TermDictionary {
+    /// A dictionary of exactly these terms
+    pub fn new<I, S>(terms: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            terms: terms
+                .into_iter()
+                .map(|t| t.into().to_lowercase())
+                .collect(),
+        }
+    }
+
+    /// The dictionary used for the embedded pack
+    pub fn builtin() -> Self {
+        Self::new(BUILTIN_TERMS)
+    }
+
+    /// Add terms on top of the existing ones
+    pub fn with<I, S>(mut self, terms: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        for term in terms {
+            let term = term.into().to_lowercase();
+            if !self.terms.contains(&term) {
+                self.terms.push(term);
+            }
+        }
+        self
+    }
+
+    /// The recognized terms
+    pub fn terms(&self) -> &[String] {
+        &self.terms
+    }
+}
+
+impl Default for TermDictionary {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl KeywordExtractor for TermDictionary {
+    fn extract(&self, text: &str) -> Vec<String> {
+        let lower = text.to_lowercase();
+        self.terms
+            .iter()
+            .filter(|term| lower.contains(term.as_str()))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Keywords `extractor` finds in a heuristic's full content that aren't
+/// among its `headline` keywords already
+pub(crate) fn body_keywords(
+    extractor: &dyn KeywordExtractor,
+    content: &str,
+    headline: &[String],
+) -> Vec<String> {
+    let mut found = Vec::new();
+    add_keywords(extractor, content, &mut found);
+    found.retain(|keyword| !headline.contains(keyword));
+    found
+}
+
+/// Add the keywords `extractor` finds in `text`, skipping duplicates
+pub(crate) fn add_keywords(
+    extractor: &dyn KeywordExtractor,
+    text: &str,
+    keywords: &mut Vec<String>,
+) {
+    for keyword in extractor.extract(text) {
+        if !keywords.contains(&keyword) {
+            keywords.push(keyword);
+        }
+    }
+}
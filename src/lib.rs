@@ -4,12 +4,123 @@
 //! algorithms, and architectural patterns in Rust development.
 
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Emit a `tracing` event, or nothing without the `tracing` feature
+///
+/// Fields are only evaluated when the feature is on, so values computed
+/// just for an event should be `#[cfg(feature = "tracing")]` too.
+macro_rules! event {
+    ($level:ident, $($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($arg)+)
+    };
+}
+
+mod action;
+mod book;
+mod compare;
+#[cfg(feature = "toml")]
+mod definition;
+mod diff;
+mod error;
+mod explain;
+mod export;
+#[cfg(feature = "fetch")]
+mod fetch;
+mod fingerprint;
+mod format;
+mod fuzzy;
+#[cfg(feature = "highlight")]
+mod highlight;
+mod import;
+mod keywords;
+mod merge;
+mod msrv;
+#[cfg(feature = "net")]
+pub mod net;
+mod overlay;
+mod pages;
+mod parser;
+mod render;
+mod sample;
+#[cfg(feature = "schema")]
+mod schema;
+#[cfg(feature = "server")]
+mod server;
+mod site;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod std_type;
+mod suggest;
+mod tree;
+mod validate;
+mod vault;
+#[cfg(feature = "watch")]
+mod watch;
+mod writer;
+
+pub use action::Action;
+pub use book::to_mdbook;
+pub use compare::compare;
+#[cfg(feature = "toml")]
+pub use definition::{HeuristicDefinition, PackDefinition};
+pub use diff::{HeuristicChange, PackDiff};
+pub use error::{LoadError, MergeError, ParseError};
+pub use explain::CrateDigest;
+pub use export::{to_anki, to_csv, to_dot, to_html, to_man_pages};
+#[cfg(feature = "fetch")]
+pub use fetch::{
+    PackStatus, cached_pack_path, check_pack, fetch_pack,
+    load_cached_published_pack, load_heuristics_from_url,
+};
+#[cfg(feature = "toml")]
+pub use format::TomlSource;
+pub use format::{HeuristicSource, MarkdownSource};
+pub use fuzzy::fuzzy_score;
+#[cfg(feature = "json")]
+pub use import::JsonSource;
+pub use import::{CheatSource, CsvSource, NotesSource, TldrSource};
+pub use keywords::{KeywordExtractor, TermDictionary};
+pub use merge::{MergePolicy, NearDuplicate, Similarity};
+pub use msrv::{CrateRequirement, RustVersion};
+pub use pages::{to_hugo, to_zola};
+pub use parser::{
+    Document, ParseDiagnostic, ParseOptions, parse_markdown,
+    parse_markdown_with,
+};
+use parser::{
+    extract_code_name, extract_links, make_ids_unique, parse_document,
+};
+pub use render::RenderStyle;
+pub use sample::{SampleBias, UsageLog};
+#[cfg(feature = "schema")]
+pub use schema::{pack_schema, snapshot_schema};
+#[cfg(feature = "server")]
+pub use server::serve;
+pub use site::{SEARCH_INDEX_VERSION, to_site};
+#[cfg(feature = "sqlite")]
+pub use sqlite::to_sqlite;
+pub use std_type::KnownStdType;
+pub use suggest::{Manifest, Suggestion};
+pub use tree::DecisionNode;
+pub use validate::{FileIssue, ValidationIssue, check_files};
+pub use vault::to_vault;
+#[cfg(feature = "watch")]
+pub use watch::WatchedDb;
 
 /// A single heuristic with its metadata
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Heuristic {
+    /// Stable identifier, a slug of the title unless set with `**Id:**`
+    pub id: String,
     /// The main title/question (e.g., "Need O(1) average-case lookups or inserts?")
     pub title: String,
+    /// Other phrasings of the title's question (`**Also asked as:**`)
+    pub aliases: Vec<String>,
     /// The recommended action
     pub action: String,
     /// The category this heuristic belongs to
@@ -20,279 +131,1503 @@ pub struct Heuristic {
     pub crates: Vec<String>,
     /// Standard library types mentioned
     pub std_types: Vec<String>,
-    /// Keywords for searching
+    /// Keywords for searching, from the title, action, aliases, and names
     pub keywords: Vec<String>,
+    /// Keywords found only in the rest of the content, ranked below `keywords`
+    pub body_keywords: Vec<String>,
+    /// Explicit pros and cons listed under `**Pros:**`/`**Cons:**`
+    pub tradeoffs: TradeOffs,
+    /// Citations listed under `**References:**` or `**Sources:**`
+    pub sources: Vec<Source>,
+    /// Namespace of the corpus this heuristic was loaded from
+    pub namespace: String,
+    /// Version or date the heuristic was introduced (`**Since:**`)
+    pub since: Option<String>,
+    /// Version or date the heuristic was last revised (`**Updated:**`)
+    pub updated: Option<String>,
+    /// Target environments the recommendation works in (`**Platforms:**`)
+    pub applies_to: Platforms,
+    /// Every markdown link appearing in the content
+    pub links: Vec<Link>,
+    /// Competing approaches listed under `**Alternatives:**`
+    pub alternatives: Vec<Alternative>,
+    /// Ids of the heuristics this one refers to, from `**See also:**` and
+    /// `[...](#id)` links
+    pub related: Vec<String>,
+    /// Markdown tables embedded in the content, e.g. throughput comparisons
+    pub tables: Vec<Table>,
+    /// Benchmark results backing performance claims (`**Benchmarks:**`)
+    pub benchmarks: Vec<Link>,
+    /// Memory cost of the recommendation (`**Memory:**`)
+    pub memory_overhead: Option<MemoryOverhead>,
+    /// Id or title of the parent question in the decision tree (`**Parent:**`),
+    /// or the `###` heuristic a `####` variant sits under
+    pub parent: Option<String>,
+    /// Rust version and edition requirements of recommended crates (`**MSRV:**`)
+    pub crate_requirements: Vec<CrateRequirement>,
+    /// Free-form labels from the heuristic's metadata block, indexed for search
+    pub tags: Vec<String>,
+    /// Experience the advice assumes, from the heuristic's metadata block
+    pub level: Option<Level>,
+    /// Where the heuristic was loaded from
+    pub provenance: Provenance,
+}
+
+/// Memory cost annotation, e.g. `~1.1x of data` or `48 bytes per entry`
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MemoryOverhead {
+    /// The annotation as written
+    pub text: String,
+    /// Total memory as a multiple of the raw data size (largest if a range)
+    pub factor: Option<f64>,
+    /// Bytes used per stored entry
+    pub per_entry_bytes: Option<f64>,
+}
+
+impl MemoryOverhead {
+    /// Parse the value of a `**Memory:**` annotation
+    pub fn parse(text: &str) -> Self {
+        let mut overhead =
+            Self { text: text.to_string(), ..Default::default() };
+        let lower = text.to_lowercase();
+        let words: Vec<&str> = lower.split_whitespace().collect();
+
+        for (i, word) in words.iter().enumerate() {
+            // Factors and ranges: "~1.1x", "1.1x–2x"
+            for part in word.split(['-', '–']) {
+                let number = part.trim_start_matches(['~', '<', '(']);
+                if let Some(n) = number
+                    .strip_suffix('x')
+                    .and_then(|n| n.parse::<f64>().ok())
+                {
+                    overhead.factor =
+                        Some(overhead.factor.map_or(n, |f: f64| f.max(n)));
+                }
+            }
+
+            // Sizes: "48 bytes", "1.2 bytes", "48b"
+            let number = word.trim_start_matches(['~', '<', '(']);
+            let next_is_bytes =
+                words.get(i + 1).is_some_and(|w| w.starts_with("byte"));
+            if let Ok(n) = number.parse::<f64>()
+                && next_is_bytes
+            {
+                overhead.per_entry_bytes = Some(n);
+            } else if let Some(n) =
+                number.strip_suffix('b').and_then(|n| n.parse::<f64>().ok())
+            {
+                overhead.per_entry_bytes = Some(n);
+            }
+        }
+
+        overhead
+    }
+}
+
+impl PartialOrd for MemoryOverhead {
+    /// Orders by factor, then per-entry size; unknown values sort last
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        let key = |m: &Self| {
+            (
+                m.factor.unwrap_or(f64::INFINITY),
+                m.per_entry_bytes.unwrap_or(f64::INFINITY),
+            )
+        };
+        key(self).partial_cmp(&key(other))
+    }
+}
+
+/// A markdown table, with cells as raw markdown text
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Table {
+    /// Header cells
+    pub headers: Vec<String>,
+    /// Body rows, padded or truncated to the number of headers
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Split a `| a | b |` line into trimmed cells
+    fn cells(line: &str) -> Vec<String> {
+        let line = line.trim();
+        let line = line.strip_prefix('|').unwrap_or(line);
+        let line = line.strip_suffix('|').unwrap_or(line);
+        line.split('|').map(|cell| cell.trim().to_string()).collect()
+    }
+
+    /// Whether a line is a header separator such as `|---|:--:|`
+    fn is_separator(line: &str) -> bool {
+        let cells = Self::cells(line);
+        !cells.is_empty()
+            && cells.iter().all(|c| {
+                !c.is_empty() && c.chars().all(|ch| matches!(ch, '-' | ':'))
+            })
+    }
+}
+
+/// A competing option to a heuristic's recommendation
+///
+/// Parsed from `**Alternatives:**` items: backticked names are crates (or
+/// std types when capitalized), anything else refers to another heuristic
+/// by id, title, or `[title](#id)` link.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Alternative {
+    /// Another heuristic, by id or title
+    Heuristic(String),
+    /// A crate name
+    Crate(String),
+    /// A standard library type
+    StdType(String),
+}
+
+impl Alternative {
+    /// Parse a single `**Alternatives:**` item
+    fn parse(item: &str) -> Option<Self> {
+        // Drop trailing descriptions: "`BTreeMap` - when order matters"
+        let item = item.split(" - ").next().unwrap_or(item).trim();
+        if item.is_empty() {
+            return None;
+        }
+
+        if let Some((text, url)) = extract_links(item).into_iter().next() {
+            let reference = url
+                .strip_prefix('#')
+                .filter(|a| !a.is_empty())
+                .map_or(text, str::to_string);
+            return Some(Self::Heuristic(reference));
+        }
+        if let Some(name) = extract_code_name(item) {
+            return Some(
+                if name.starts_with(|c: char| c.is_ascii_uppercase()) {
+                    Self::StdType(name)
+                } else {
+                    Self::Crate(name)
+                },
+            );
+        }
+        Some(Self::Heuristic(item.to_string()))
+    }
+}
+
+/// A markdown `[text](url)` link found in a heuristic
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Link {
+    /// The link text
+    pub text: String,
+    /// The link target
+    pub url: String,
+}
+
+impl Link {
+    /// Problem with the link that can be detected without network access
+    pub fn problem(&self) -> Option<&'static str> {
+        if self.url.is_empty() {
+            Some("empty URL")
+        } else if self.url.starts_with('#') {
+            None
+        } else if !(self.url.starts_with("https://")
+            || self.url.starts_with("http://"))
+        {
+            Some("unsupported URL scheme")
+        } else if self.url.contains(char::is_whitespace) {
+            Some("URL contains whitespace")
+        } else {
+            None
+        }
+    }
+}
+
+/// Environments a heuristic's recommendations work in
+///
+/// Parsed from a `**Platforms:** no_std, wasm, embedded, threads` line,
+/// where the listed targets are supported and `threads` marks advice that
+/// needs OS threads. `std` alone declares standard targets only. Every
+/// field is `None` when the heuristic has no annotation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Platforms {
+    /// Works without the standard library
+    pub no_std: Option<bool>,
+    /// Works on `wasm32` targets
+    pub wasm: Option<bool>,
+    /// Works on embedded/bare-metal devices
+    pub embedded: Option<bool>,
+    /// Requires OS threads
+    pub threads_required: Option<bool>,
+}
+
+/// How much experience a heuristic's advice assumes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Level {
+    Beginner,
+    Intermediate,
+    Advanced,
+}
+
+impl Level {
+    /// Parse a level name, ignoring case
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "beginner" => Some(Self::Beginner),
+            "intermediate" => Some(Self::Intermediate),
+            "advanced" => Some(Self::Advanced),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Beginner => "beginner",
+            Self::Intermediate => "intermediate",
+            Self::Advanced => "advanced",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A restricted target environment to filter heuristics by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Platform {
+    NoStd,
+    Wasm,
+    Embedded,
+}
+
+impl Platforms {
+    /// Parse the value of a `**Platforms:**` annotation
+    pub fn parse(value: &str) -> Self {
+        let targets: Vec<String> = value
+            .split(',')
+            .map(|t| {
+                t.trim().trim_matches('`').to_lowercase().replace('-', "_")
+            })
+            .collect();
+        let has = |name: &str| Some(targets.iter().any(|t| t == name));
+
+        Self {
+            no_std: has("no_std"),
+            wasm: has("wasm"),
+            embedded: has("embedded"),
+            threads_required: has("threads"),
+        }
+    }
+
+    /// Whether the advice can be followed on `platform`
+    ///
+    /// Unannotated heuristics are assumed to apply everywhere; only explicit
+    /// annotations rule a platform out.
+    pub fn supports(&self, platform: Platform) -> bool {
+        let supported = match platform {
+            Platform::NoStd => self.no_std,
+            Platform::Wasm => self.wasm,
+            Platform::Embedded => self.embedded,
+        };
+        supported != Some(false) && self.threads_required != Some(true)
+    }
+}
+
+/// Separator between the levels of a hierarchical category name
+pub const CATEGORY_SEPARATOR: char = '/';
+
+// Identity is the stable id: two heuristics are the same entry if their ids
+// match, even when one copy has been edited.
+impl PartialEq for Heuristic {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Heuristic {}
+
+impl std::hash::Hash for Heuristic {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl PartialOrd for Heuristic {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Heuristic {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.id.cmp(&other.id)
+    }
+}
+
+/// Turn text into a lowercase, dash-separated identifier
+///
+/// `"Need O(1) lookups?"` becomes `"need-o-1-lookups"`.
+pub fn slug(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// The heuristic among `heuristics` a reference names, by id, title slug,
+/// or title, like [`HeuristicDb::resolve`]
+pub(crate) fn resolve_among<'a>(
+    heuristics: &[&'a Heuristic],
+    reference: &str,
+) -> Option<&'a Heuristic> {
+    let reference = reference.trim();
+    let slugged = slug(reference);
+    let mut candidates = heuristics.iter().copied();
+    candidates
+        .clone()
+        .find(|h| h.id == reference)
+        .or_else(|| candidates.clone().find(|h| h.id == slugged))
+        .or_else(|| {
+            candidates.find(|h| h.title.eq_ignore_ascii_case(reference))
+        })
+}
+
+impl Heuristic {
+    /// The category split into its hierarchy levels
+    ///
+    /// A category header such as `## Data Structures / Probabilistic` yields
+    /// `["Data Structures", "Probabilistic"]`.
+    pub fn category_path(&self) -> Vec<&str> {
+        category_path(&self.category)
+    }
+
+    /// Whether the heuristic is in `category` or one of its subcategories
+    pub fn in_category(&self, category: &str) -> bool {
+        let prefix = category_path(category);
+        let path = self.category_path();
+        !prefix.is_empty()
+            && path.len() >= prefix.len()
+            && path.iter().zip(&prefix).all(|(level, want)| {
+                level.to_lowercase() == want.to_lowercase()
+            })
+    }
+}
+
+fn category_path(category: &str) -> Vec<&str> {
+    category
+        .split(CATEGORY_SEPARATOR)
+        .map(str::trim)
+        .filter(|level| !level.is_empty())
+        .collect()
+}
+
+/// A category with the description written under its `##` header
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Category {
+    /// The category name as written in the header
+    pub name: String,
+    /// The paragraph following the header, empty if there is none
+    pub description: String,
+    /// Number of heuristics filed directly under the category
+    pub heuristic_count: usize,
+}
+
+impl Category {
+    /// The name split into its hierarchy levels
+    pub fn path(&self) -> Vec<&str> {
+        category_path(&self.name)
+    }
+}
+
+/// A crate with the heuristics that recommend it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CrateMention {
+    /// The crate name as first spelled in the pack
+    pub name: String,
+    /// Ids of the heuristics listing the crate or offering it as an
+    /// alternative, in pack order
+    pub heuristics: Vec<String>,
+}
+
+/// A reference backing up the claims made by a heuristic
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Source {
+    /// Human-readable title of the reference
+    pub title: String,
+    /// Where the reference can be found
+    pub url: String,
+}
+
+/// Where a heuristic was loaded from
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Provenance {
+    /// Parsed from text held in memory
+    #[default]
+    Memory,
+    /// The pack embedded in the crate
+    Builtin,
+    /// A heuristic file on disk
+    File(PathBuf),
+    /// A pack downloaded from a URL
+    Url(String),
+}
+
+impl std::fmt::Display for Provenance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Provenance::Memory => write!(f, "memory"),
+            Provenance::Builtin => write!(f, "built-in"),
+            Provenance::File(path) => write!(f, "{}", path.display()),
+            Provenance::Url(url) => write!(f, "{}", url),
+        }
+    }
+}
+
+impl Provenance {
+    /// Whether this is the stock pack, embedded or refreshed from [`PUBLISHED_PACK_URL`]
+    pub fn is_stock(&self) -> bool {
+        match self {
+            Provenance::Builtin => true,
+            Provenance::Url(url) => url == PUBLISHED_PACK_URL,
+            _ => false,
+        }
+    }
+}
+
+/// Advantages and drawbacks of following a heuristic
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TradeOffs {
+    /// Reasons to follow the recommendation
+    pub pros: Vec<String>,
+    /// Costs or pitfalls of following the recommendation
+    pub cons: Vec<String>,
+}
+
+impl TradeOffs {
+    /// Returns true if neither pros nor cons were listed
+    pub fn is_empty(&self) -> bool {
+        self.pros.is_empty() && self.cons.is_empty()
+    }
+}
+
+/// Namespace given to heuristics whose corpus declares none
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+/// Index weight of terms a corpus declares as boosted
+const BOOST_WEIGHT: usize = 3;
+
+/// Index weight of terms from a heuristic's headline fields relative to
+/// terms found only in its body
+const HEADLINE_WEIGHT: usize = 2;
+
+/// How much each kind of keyword match adds to a search score
+///
+/// Both are multiplied by the weight a term was indexed with, so a
+/// headline keyword still outranks one found only in the body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScoreWeights {
+    /// A query keyword equal to an indexed term
+    pub exact: usize,
+    /// A query keyword contained in an indexed term, or containing one
+    pub partial: usize,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        Self { exact: 2, partial: 1 }
+    }
+}
+
+/// Constraints every search result must meet besides matching keywords
+///
+/// Unset fields don't constrain anything, so the default filter lets
+/// every heuristic through.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchFilter {
+    /// A category, or any leading part of its path as in [`HeuristicDb::by_category`]
+    pub category: Option<String>,
+    /// A crate the heuristic recommends, ignoring case
+    pub crate_name: Option<String>,
+    /// A standard library type in any form [`KnownStdType`] parses
+    pub std_type: Option<String>,
+    /// A tag from the heuristic's metadata block, ignoring case
+    pub tag: Option<String>,
+}
+
+impl SearchFilter {
+    /// Whether `heuristic` meets every constraint
+    pub fn matches(&self, heuristic: &Heuristic) -> bool {
+        self.category
+            .as_ref()
+            .is_none_or(|category| heuristic.in_category(category))
+            && self.crate_name.as_ref().is_none_or(|name| {
+                heuristic.crates.iter().any(|c| c.eq_ignore_ascii_case(name))
+            })
+            && self.std_type.as_ref().is_none_or(|ty| {
+                heuristic
+                    .known_std_types()
+                    .contains(&KnownStdType::from(ty.as_str()))
+            })
+            && self.tag.as_ref().is_none_or(|tag| {
+                heuristic.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+            })
+    }
+}
+
+/// Per-corpus settings: indexing overrides declared in a file's front
+/// matter, plus the category descriptions found in its body
+///
+/// ```text
+/// ---
+/// namespace: actors
+/// stopwords: message, system
+/// boost: actor, mailbox
+/// ---
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Corpus {
+    /// Namespace shared by every heuristic in the corpus
+    pub namespace: String,
+    /// Terms that are never indexed for this corpus
+    pub stopwords: Vec<String>,
+    /// Terms that rank higher when matched in this corpus
+    pub boost: Vec<String>,
+    /// First paragraph under each `##` header, keyed by category name
+    pub category_descriptions: HashMap<String, String>,
+}
+
+impl Default for Corpus {
+    fn default() -> Self {
+        Self {
+            namespace: DEFAULT_NAMESPACE.to_string(),
+            stopwords: Vec::new(),
+            boost: Vec::new(),
+            category_descriptions: HashMap::new(),
+        }
+    }
+}
+
+impl Corpus {
+    /// Fold in the settings of another file declaring the same namespace
+    fn absorb(&mut self, other: Corpus) {
+        for term in other.stopwords {
+            if !self.stopwords.contains(&term) {
+                self.stopwords.push(term);
+            }
+        }
+        for term in other.boost {
+            if !self.boost.contains(&term) {
+                self.boost.push(term);
+            }
+        }
+        for (category, description) in other.category_descriptions {
+            self.category_descriptions.entry(category).or_insert(description);
+        }
+    }
+}
+
+/// Version of the heuristic file format this crate reads
+///
+/// Packs declaring another `version` in their front matter are refused, so
+/// a pack written for a future format fails loudly instead of loading
+/// half-understood.
+pub const PACK_SCHEMA_VERSION: u32 = 1;
+
+/// File-level metadata declared in a pack's front matter
+///
+/// ```text
+/// ---
+/// version: 1
+/// pack-name: team-heuristics
+/// license: MIT
+/// min-crate-version: 0.2.0
+/// ---
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PackInfo {
+    /// File format version, [`PACK_SCHEMA_VERSION`] when not declared
+    pub version: u32,
+    /// Human-readable name of the pack
+    pub name: Option<String>,
+    /// License the pack's content is published under
+    pub license: Option<String>,
+    /// Oldest version of this crate that can load the pack
+    pub min_crate_version: Option<String>,
+}
+
+impl Default for PackInfo {
+    fn default() -> Self {
+        Self {
+            version: PACK_SCHEMA_VERSION,
+            name: None,
+            license: None,
+            min_crate_version: None,
+        }
+    }
+}
+
+impl PackInfo {
+    /// Refuse packs this build can't read correctly
+    pub(crate) fn check(&self) -> Result<(), ParseError> {
+        if self.version != PACK_SCHEMA_VERSION {
+            return Err(ParseError::UnsupportedVersion {
+                found: self.version,
+                supported: PACK_SCHEMA_VERSION,
+            });
+        }
+        if let Some(required) = &self.min_crate_version {
+            let current = env!("CARGO_PKG_VERSION");
+            let newer = match (
+                RustVersion::parse(required),
+                RustVersion::parse(current),
+            ) {
+                (Some(required), Some(current)) => required > current,
+                _ => true,
+            };
+            if newer {
+                return Err(ParseError::CrateTooOld {
+                    required: required.clone(),
+                    current: current.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Owned, index-free contents of a [`HeuristicDb`]
+///
+/// With the `serde` feature this is the serializable form of a database;
+/// converting it back with `HeuristicDb::from` rebuilds the search index.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DbSnapshot {
+    /// Every heuristic, in database order
+    pub heuristics: Vec<Heuristic>,
+    /// Indexing overrides of the corpora the heuristics came from
+    pub corpora: Vec<Corpus>,
+    /// Front matter metadata of the pack, if it declared any
+    pub pack: Option<PackInfo>,
+}
+
+/// Version of the JSON document `HeuristicDb::to_json` writes with the `json`
+/// feature
+///
+/// The document is a [`DbSnapshot`] under a `schema` version, with fields
+/// named as in the Rust types:
+///
+/// ```json
+/// {
+///   "schema": 1,
+///   "pack": { "version": 1, "name": "...", "license": "...", "min_crate_version": null },
+///   "heuristics": [{ "id": "...", "title": "...", "category": "...", "action": "...", ... }],
+///   "corpora": [{ "namespace": "...", "stopwords": [], "boost": [] }]
+/// }
+/// ```
+///
+/// `pack` is `null` when the pack declared no front matter. The version is
+/// bumped whenever a field is removed, renamed, or changes type; new fields
+/// keep it, so readers should ignore fields they don't know.
+pub const SNAPSHOT_SCHEMA_VERSION: u32 = 1;
+
+/// A borrowed [`DbSnapshot`] under its schema version
+#[cfg(any(feature = "json", feature = "yaml"))]
+#[derive(serde::Serialize)]
+struct SnapshotDocument<'a> {
+    schema: u32,
+    pack: Option<&'a PackInfo>,
+    heuristics: &'a [Heuristic],
+    corpora: Vec<&'a Corpus>,
+}
+
+/// `value` as YAML, laid out like its JSON
+///
+/// Enum variants are written as `Variant: value` maps, as the JSON has
+/// them, instead of `!Variant value` tags many YAML readers refuse.
+#[cfg(feature = "yaml")]
+pub(crate) fn to_yaml(value: &impl serde::Serialize) -> String {
+    let mut value =
+        serde_yaml::to_value(value).expect("heuristics serialize to YAML");
+    untag(&mut value);
+    serde_yaml::to_string(&value).expect("YAML values serialize")
+}
+
+#[cfg(feature = "yaml")]
+fn untag(value: &mut serde_yaml::Value) {
+    use serde_yaml::Value;
+    match value {
+        Value::Tagged(tagged) => {
+            let variant =
+                tagged.tag.to_string().trim_start_matches('!').to_string();
+            let mut inner = std::mem::take(&mut tagged.value);
+            untag(&mut inner);
+            *value = Value::Mapping(
+                [(Value::String(variant), inner)].into_iter().collect(),
+            );
+        }
+        Value::Sequence(items) => items.iter_mut().for_each(untag),
+        Value::Mapping(fields) => fields.values_mut().for_each(untag),
+        _ => {}
+    }
+}
+
+/// A [`DbSnapshot`] read from JSON, which may predate the `schema` field
+#[cfg(feature = "json")]
+#[derive(serde::Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+struct JsonSnapshot {
+    #[serde(default = "snapshot_schema_version")]
+    schema: u32,
+    #[serde(flatten)]
+    snapshot: DbSnapshot,
+}
+
+#[cfg(feature = "json")]
+fn snapshot_schema_version() -> u32 {
+    SNAPSHOT_SCHEMA_VERSION
+}
+
+#[cfg(feature = "json")]
+impl JsonSnapshot {
+    /// The snapshot, unless it was written for a newer schema
+    fn into_snapshot(self) -> Result<DbSnapshot, ParseError> {
+        if self.schema > SNAPSHOT_SCHEMA_VERSION {
+            return Err(ParseError::UnsupportedVersion {
+                found: self.schema,
+                supported: SNAPSHOT_SCHEMA_VERSION,
+            });
+        }
+        Ok(self.snapshot)
+    }
+}
+
+impl From<DbSnapshot> for HeuristicDb {
+    fn from(snapshot: DbSnapshot) -> Self {
+        Self::with_corpora(snapshot.heuristics, snapshot.corpora)
+            .with_pack(snapshot.pack)
+    }
 }
 
 /// Database of searchable heuristics
 pub struct HeuristicDb {
     heuristics: Vec<Heuristic>,
-    /// Inverted index: lowercase keyword -> heuristic indices
-    index: HashMap<String, Vec<usize>>,
+    /// Indexing overrides, keyed by namespace
+    corpora: HashMap<String, Corpus>,
+    /// Inverted index: lowercase keyword -> (heuristic index, weight)
+    index: HashMap<String, Vec<(usize, usize)>>,
+    /// Front matter metadata of the pack the database was loaded from
+    pack: Option<PackInfo>,
 }
 
 impl HeuristicDb {
     /// Create a new database from parsed heuristics
     pub fn new(heuristics: Vec<Heuristic>) -> Self {
-        let mut index: HashMap<String, Vec<usize>> = HashMap::new();
+        Self::with_corpora(heuristics, Vec::new())
+    }
 
-        for (idx, heuristic) in heuristics.iter().enumerate() {
-            // Index all keywords
-            for keyword in &heuristic.keywords {
-                index.entry(keyword.to_lowercase())
-                    .or_default()
-                    .push(idx);
+    /// Create a database, first adding the keywords `extractor` finds in
+    /// each heuristic's title, aliases, and action, and, at a lower rank,
+    /// the rest of its content
+    ///
+    /// Useful for heuristics built in code or parsed with another
+    /// dictionary; markdown can instead be parsed with
+    /// [`ParseOptions::with_keywords`].
+    pub fn with_keywords(
+        mut heuristics: Vec<Heuristic>,
+        extractor: &dyn KeywordExtractor,
+    ) -> Self {
+        for heuristic in &mut heuristics {
+            let texts = [&heuristic.title, &heuristic.action]
+                .into_iter()
+                .chain(&heuristic.aliases);
+            let mut keywords = std::mem::take(&mut heuristic.keywords);
+            for text in texts {
+                keywords::add_keywords(extractor, text, &mut keywords);
             }
-
-            // Index crate names
-            for crate_name in &heuristic.crates {
-                index.entry(crate_name.to_lowercase())
-                    .or_default()
-                    .push(idx);
+            for keyword in keywords::body_keywords(
+                extractor,
+                &heuristic.content,
+                &keywords,
+            ) {
+                if !heuristic.body_keywords.contains(&keyword) {
+                    heuristic.body_keywords.push(keyword);
+                }
             }
+            heuristic
+                .body_keywords
+                .retain(|keyword| !keywords.contains(keyword));
+            heuristic.keywords = keywords;
+        }
+        Self::new(heuristics)
+    }
 
-            // Index std types
-            for std_type in &heuristic.std_types {
-                index.entry(std_type.to_lowercase())
+    /// Create a database whose heuristics are indexed with the stopwords and
+    /// boosted terms of the corpus matching their namespace
+    pub fn with_corpora(
+        mut heuristics: Vec<Heuristic>,
+        corpora: Vec<Corpus>,
+    ) -> Self {
+        for heuristic in heuristics.iter_mut().filter(|h| h.id.is_empty()) {
+            heuristic.id = slug(&heuristic.title);
+        }
+
+        let corpora: HashMap<String, Corpus> = corpora
+            .into_iter()
+            .map(|corpus| (corpus.namespace.clone(), corpus))
+            .collect();
+        let default_corpus = Corpus::default();
+        let mut index: HashMap<String, Vec<(usize, usize)>> = HashMap::new();
+
+        for (idx, heuristic) in heuristics.iter().enumerate() {
+            let corpus =
+                corpora.get(&heuristic.namespace).unwrap_or(&default_corpus);
+            let mut add_weighted = |term: &str, field_weight: usize| {
+                let term = term.to_lowercase();
+                if corpus.stopwords.contains(&term) {
+                    return;
+                }
+                let weight = if corpus.boost.contains(&term) {
+                    BOOST_WEIGHT
+                } else {
+                    1
+                };
+                index
+                    .entry(term)
                     .or_default()
-                    .push(idx);
-            }
+                    .push((idx, weight * field_weight));
+            };
+            let mut add = |term: &str| add_weighted(term, HEADLINE_WEIGHT);
+
+            // Index all keywords, crate names and std types
+            heuristic
+                .keywords
+                .iter()
+                .chain(&heuristic.crates)
+                .chain(&heuristic.std_types)
+                .for_each(|term| add(term));
 
             // Index category
-            index.entry(heuristic.category.to_lowercase())
-                .or_default()
-                .push(idx);
+            add(&heuristic.category);
+
+            // Index whole phrasings, so any word in them matches partially
+            heuristic.aliases.iter().for_each(|alias| add(alias));
+
+            // Index boosted terms even when the keyword dictionary misses them
+            let text = format!("{} {}", heuristic.title, heuristic.action)
+                .to_lowercase();
+            for term in &corpus.boost {
+                if text.contains(term.as_str())
+                    && !heuristic.keywords.contains(term)
+                {
+                    add(term);
+                }
+            }
+
+            // Advice buried in the body is findable, but ranks below the headline
+            heuristic
+                .body_keywords
+                .iter()
+                .for_each(|term| add_weighted(term, 1));
         }
 
-        Self { heuristics, index }
+        event!(
+            debug,
+            heuristics = heuristics.len(),
+            terms = index.len(),
+            postings = index.values().map(Vec::len).sum::<usize>(),
+            "built search index"
+        );
+        Self { heuristics, corpora, index, pack: None }
+    }
+
+    /// Attach the metadata of the pack the heuristics came from
+    pub(crate) fn with_pack(mut self, pack: Option<PackInfo>) -> Self {
+        self.pack = pack;
+        self
+    }
+
+    /// Metadata the pack declared in its front matter, if any
+    ///
+    /// For merged databases this is the first side's pack that declared
+    /// any; for a directory, the first file's.
+    pub fn pack_info(&self) -> Option<&PackInfo> {
+        self.pack.as_ref()
+    }
+
+    /// Copy the database contents into a serializable snapshot
+    pub fn snapshot(&self) -> DbSnapshot {
+        let mut corpora: Vec<Corpus> =
+            self.corpora.values().cloned().collect();
+        corpora.sort_by(|a, b| a.namespace.cmp(&b.namespace));
+        DbSnapshot {
+            heuristics: self.heuristics.clone(),
+            corpora,
+            pack: self.pack.clone(),
+        }
+    }
+
+    /// Get the indexing overrides of a namespace, if it declared any
+    pub fn corpus(&self, namespace: &str) -> Option<&Corpus> {
+        self.corpora.get(namespace)
     }
 
     /// Search for heuristics by keywords
     /// Returns heuristics ranked by number of keyword matches
     pub fn search(&self, keywords: &[&str]) -> Vec<&Heuristic> {
+        self.search_scored(keywords)
+            .into_iter()
+            .map(|(heuristic, _score)| heuristic)
+            .collect()
+    }
+
+    /// Like [`HeuristicDb::search`], with each heuristic's relevance score
+    ///
+    /// Scores only compare results of the same query; higher is better.
+    pub fn search_scored(
+        &self,
+        keywords: &[&str],
+    ) -> Vec<(&Heuristic, usize)> {
+        self.search_weighted(keywords, ScoreWeights::default())
+    }
+
+    /// Like [`HeuristicDb::search_scored`], scoring matches with `weights`
+    pub fn search_weighted(
+        &self,
+        keywords: &[&str],
+        weights: ScoreWeights,
+    ) -> Vec<(&Heuristic, usize)> {
+        self.search_filtered(keywords, weights, &SearchFilter::default())
+    }
+
+    /// Like [`HeuristicDb::search_weighted`], keeping only heuristics
+    /// that `filter` matches
+    ///
+    /// Scores don't depend on the filter, so the heuristics it keeps rank
+    /// the same as in an unfiltered search.
+    pub fn search_filtered(
+        &self,
+        keywords: &[&str],
+        weights: ScoreWeights,
+        filter: &SearchFilter,
+    ) -> Vec<(&Heuristic, usize)> {
+        #[cfg(feature = "tracing")]
+        let started = std::time::Instant::now();
         let mut scores: HashMap<usize, usize> = HashMap::new();
 
         for keyword in keywords {
             let normalized = keyword.to_lowercase();
 
             // Exact matches
-            if let Some(indices) = self.index.get(&normalized) {
-                for &idx in indices {
-                    *scores.entry(idx).or_default() += 2;
+            if let Some(postings) = self.index.get(&normalized) {
+                for &(idx, weight) in postings {
+                    *scores.entry(idx).or_default() += weights.exact * weight;
                 }
             }
 
             // Partial matches
-            for (indexed_keyword, indices) in &self.index {
-                if indexed_keyword.contains(&normalized) || normalized.contains(indexed_keyword) {
-                    for &idx in indices {
-                        *scores.entry(idx).or_default() += 1;
+            for (indexed_keyword, postings) in &self.index {
+                if indexed_keyword.contains(&normalized)
+                    || normalized.contains(indexed_keyword)
+                {
+                    for &(idx, weight) in postings {
+                        *scores.entry(idx).or_default() +=
+                            weights.partial * weight;
                     }
                 }
             }
         }
 
         // Sort by score (descending)
-        let mut results: Vec<(usize, usize)> = scores.into_iter().collect();
-        results.sort_by(|a, b| b.1.cmp(&a.1));
+        let mut results: Vec<(usize, usize)> = scores
+            .into_iter()
+            .filter(|&(idx, _)| filter.matches(&self.heuristics[idx]))
+            .collect();
+        // Ties keep document order so output is deterministic
+        results.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        event!(debug, ?keywords, results = results.len(), elapsed = ?started.elapsed(), "searched");
 
-        results.into_iter()
-            .map(|(idx, _score)| &self.heuristics[idx])
+        results
+            .into_iter()
+            .map(|(idx, score)| (&self.heuristics[idx], score))
             .collect()
     }
 
-    /// Get all heuristics in a category
+    /// Get all heuristics in a category, including its subcategories
+    ///
+    /// `category` may be any leading part of a hierarchical category path,
+    /// so `"Data Structures"` matches `"Data Structures / Probabilistic"`.
     pub fn by_category(&self, category: &str) -> Vec<&Heuristic> {
-        self.heuristics
-            .iter()
-            .filter(|h| h.category.to_lowercase() == category.to_lowercase())
-            .collect()
+        self.heuristics.iter().filter(|h| h.in_category(category)).collect()
     }
 
     /// Get all unique categories
     pub fn categories(&self) -> Vec<String> {
-        let mut cats: Vec<String> = self.heuristics
-            .iter()
-            .map(|h| h.category.clone())
-            .collect();
+        let mut cats: Vec<String> =
+            self.heuristics.iter().map(|h| h.category.clone()).collect();
         cats.sort();
         cats.dedup();
         cats
     }
 
+    /// Get every category with its description and size, sorted by name
+    pub fn category_info(&self) -> Vec<Category> {
+        let mut categories: Vec<Category> = Vec::new();
+        for heuristic in &self.heuristics {
+            if let Some(category) =
+                categories.iter_mut().find(|c| c.name == heuristic.category)
+            {
+                category.heuristic_count += 1;
+                continue;
+            }
+            let description = self
+                .corpora
+                .get(&heuristic.namespace)
+                .and_then(|corpus| {
+                    corpus.category_descriptions.get(&heuristic.category)
+                })
+                .cloned()
+                .unwrap_or_default();
+            categories.push(Category {
+                name: heuristic.category.clone(),
+                description,
+                heuristic_count: 1,
+            });
+        }
+        categories.sort_by(|a, b| a.name.cmp(&b.name));
+        categories
+    }
+
+    /// Every crate mentioned by a heuristic, sorted by name
+    ///
+    /// Names are compared ignoring case, so `Moka` and `moka` are one crate.
+    pub fn crate_mentions(&self) -> Vec<CrateMention> {
+        let mut mentions: Vec<CrateMention> = Vec::new();
+        for heuristic in &self.heuristics {
+            let alternatives =
+                heuristic.alternatives.iter().filter_map(|alternative| {
+                    match alternative {
+                        Alternative::Crate(name) => Some(name),
+                        _ => None,
+                    }
+                });
+            for name in heuristic.crates.iter().chain(alternatives) {
+                match mentions
+                    .iter_mut()
+                    .find(|m| m.name.eq_ignore_ascii_case(name))
+                {
+                    Some(mention)
+                        if mention.heuristics.last()
+                            == Some(&heuristic.id) => {}
+                    Some(mention) => {
+                        mention.heuristics.push(heuristic.id.clone())
+                    }
+                    None => mentions.push(CrateMention {
+                        name: name.clone(),
+                        heuristics: vec![heuristic.id.clone()],
+                    }),
+                }
+            }
+        }
+        mentions.sort_by_key(|m| m.name.to_lowercase());
+        mentions
+    }
+
+    /// Get a heuristic by its id
+    pub fn get(&self, id: &str) -> Option<&Heuristic> {
+        self.heuristics.iter().find(|h| h.id == id)
+    }
+
+    /// Find the heuristic a reference points to, by id or title
+    pub fn resolve(&self, reference: &str) -> Option<&Heuristic> {
+        let reference = reference.trim();
+        self.get(reference).or_else(|| self.get(&slug(reference))).or_else(
+            || {
+                self.heuristics
+                    .iter()
+                    .find(|h| h.title.eq_ignore_ascii_case(reference))
+            },
+        )
+    }
+
+    /// Heuristics covering the alternatives listed by `heuristic`
+    ///
+    /// Heuristic references resolve directly; crate and std type
+    /// alternatives pull in every heuristic recommending them.
+    pub fn alternatives_of(&self, heuristic: &Heuristic) -> Vec<&Heuristic> {
+        let mut found: Vec<&Heuristic> = Vec::new();
+        for alternative in &heuristic.alternatives {
+            let matches: Vec<&Heuristic> = match alternative {
+                Alternative::Heuristic(reference) => {
+                    self.resolve(reference).into_iter().collect()
+                }
+                Alternative::Crate(name) => self
+                    .heuristics
+                    .iter()
+                    .filter(|h| {
+                        h.crates.iter().any(|c| c.eq_ignore_ascii_case(name))
+                    })
+                    .collect(),
+                Alternative::StdType(name) => {
+                    let wanted = KnownStdType::from(name.as_str());
+                    self.heuristics
+                        .iter()
+                        .filter(|h| h.known_std_types().contains(&wanted))
+                        .collect()
+                }
+            };
+            for h in matches {
+                if h.id != heuristic.id && !found.contains(&h) {
+                    found.push(h);
+                }
+            }
+        }
+        found
+    }
+
+    /// Heuristics `heuristic` refers to, in the order it lists them
+    pub fn related_to(&self, heuristic: &Heuristic) -> Vec<&Heuristic> {
+        heuristic.related.iter().filter_map(|id| self.get(id)).collect()
+    }
+
+    /// Heuristics with a memory overhead annotation, cheapest first
+    pub fn by_memory_overhead(&self) -> Vec<&Heuristic> {
+        let mut annotated: Vec<&Heuristic> = self
+            .heuristics
+            .iter()
+            .filter(|h| h.memory_overhead.is_some())
+            .collect();
+        annotated.sort_by(|a, b| {
+            a.memory_overhead
+                .partial_cmp(&b.memory_overhead)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        annotated
+    }
+
     /// Get all heuristics
     pub fn all(&self) -> &[Heuristic] {
         &self.heuristics
     }
 }
 
+/// Where the embedded pack is published; `heuristics update` refreshes it from here
+pub const PUBLISHED_PACK_URL: &str =
+    "https://raw.githubusercontent.com/cryptopatrick/heuristics/main/base.md";
+
+const BUILTIN_PACK: &str = include_str!("../base.md");
+
+/// Directory for data that can be downloaded or rebuilt, such as the packs
+/// `heuristics update` fetches
+///
+/// `$XDG_CACHE_HOME/heuristics`, falling back to `~/.cache/heuristics`.
+pub fn cache_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .map(|home| PathBuf::from(home).join(".cache"))
+        })?;
+    Some(base.join("heuristics"))
+}
+
 /// Parse the base.md file and build the heuristic database
 pub fn load_heuristics() -> HeuristicDb {
-    let content = include_str!("../base.md");
-    let heuristics = parse_markdown(content);
-    HeuristicDb::new(heuristics)
+    #[cfg(feature = "tracing")]
+    let started = std::time::Instant::now();
+    let db = HeuristicDb::from(parse_document(BUILTIN_PACK))
+        .with_provenance(Provenance::Builtin);
+    event!(info, heuristics = db.heuristics.len(), elapsed = ?started.elapsed(), "loaded the built-in pack");
+    db
 }
 
-/// Parse markdown content into heuristics
-fn parse_markdown(content: &str) -> Vec<Heuristic> {
-    let mut heuristics = Vec::new();
-    let mut current_category = String::new();
-    let mut current_title = String::new();
-    let mut current_action = String::new();
-    let mut current_content = String::new();
-    let mut current_crates = Vec::new();
-    let mut current_std_types = Vec::new();
-    let mut current_keywords = Vec::new();
-
-    let mut in_heuristic = false;
-
-    for line in content.lines() {
-        // Category headers (## ...)
-        if let Some(cat) = line.strip_prefix("## ") {
-            // Save previous heuristic if exists
-            if in_heuristic && !current_title.is_empty() {
-                heuristics.push(Heuristic {
-                    title: current_title.clone(),
-                    action: current_action.clone(),
-                    category: current_category.clone(),
-                    content: current_content.trim().to_string(),
-                    crates: current_crates.clone(),
-                    std_types: current_std_types.clone(),
-                    keywords: current_keywords.clone(),
-                });
-            }
+/// The newest stock pack available offline
+///
+/// With the `fetch` feature, a copy of [`PUBLISHED_PACK_URL`] downloaded
+/// by `heuristics update` with this version of the crate replaces the
+/// embedded pack; otherwise this is [`load_heuristics`].
+pub fn load_latest_heuristics() -> HeuristicDb {
+    #[cfg(feature = "fetch")]
+    if let Some(db) = load_cached_published_pack() {
+        return db;
+    }
+    load_heuristics()
+}
 
-            current_category = cat.trim().to_string();
-            in_heuristic = false;
-            current_title.clear();
-            current_action.clear();
-            current_content.clear();
-            current_crates.clear();
-            current_std_types.clear();
-            current_keywords.clear();
-            continue;
-        }
-
-        // Heuristic headers (### Need ...)
-        if let Some(title) = line.strip_prefix("### ") {
-            // Save previous heuristic if exists
-            if in_heuristic && !current_title.is_empty() {
-                heuristics.push(Heuristic {
-                    title: current_title.clone(),
-                    action: current_action.clone(),
-                    category: current_category.clone(),
-                    content: current_content.trim().to_string(),
-                    crates: current_crates.clone(),
-                    std_types: current_std_types.clone(),
-                    keywords: current_keywords.clone(),
-                });
-            }
+/// Load a database from a heuristic file on disk
+///
+/// Markdown files use the same format as the embedded pack, front matter
+/// included, so teams can maintain their own heuristics outside the crate.
+/// With the `toml` feature, `.toml` files are read as structured
+/// definitions instead.
+pub fn load_heuristics_from_path(
+    path: impl AsRef<Path>,
+) -> Result<HeuristicDb, LoadError> {
+    let path = path.as_ref();
+    #[cfg(feature = "tracing")]
+    let started = std::time::Instant::now();
+    let content = std::fs::read_to_string(path).map_err(|source| {
+        LoadError::Io { path: path.to_path_buf(), source }
+    })?;
+    let document = parse_file(path, &content).map_err(|source| {
+        LoadError::Parse { path: path.to_path_buf(), source }
+    })?;
+    let db = HeuristicDb::from(document)
+        .with_provenance(Provenance::File(path.to_path_buf()));
+    event!(info, path = %path.display(), heuristics = db.heuristics.len(), elapsed = ?started.elapsed(), "loaded pack file");
+    Ok(db)
+}
 
-            current_title = title.trim().to_string();
-            current_content = line.to_string() + "\n";
-            in_heuristic = true;
-            current_action.clear();
-            current_crates.clear();
-            current_std_types.clear();
-            current_keywords.clear();
+/// Parse a heuristic file in the format its extension names
+fn parse_file(path: &Path, content: &str) -> Result<Document, ParseError> {
+    #[cfg(feature = "toml")]
+    if path.extension().is_some_and(|ext| ext == "toml") {
+        return definition::parse_toml(content);
+    }
+    let _ = path;
+    parse_markdown(content)
+}
 
-            // Extract keywords from title
-            extract_keywords(&current_title, &mut current_keywords);
-            continue;
-        }
+/// Whether a directory entry is a heuristic file this build can read
+fn is_heuristic_file(path: &Path) -> bool {
+    let extension = path.extension().and_then(|ext| ext.to_str());
+    path.is_file()
+        && (extension == Some("md")
+            || (cfg!(feature = "toml") && extension == Some("toml")))
+}
 
-        if in_heuristic {
-            current_content.push_str(line);
-            current_content.push('\n');
+/// The heuristic files in a directory, in name order
+fn heuristic_files(dir: &Path) -> Result<Vec<PathBuf>, LoadError> {
+    let io_error = |source| LoadError::Io { path: dir.to_path_buf(), source };
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(io_error)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()
+        .map_err(io_error)?;
+    files.retain(|file| is_heuristic_file(file));
+    files.sort();
+    Ok(files)
+}
 
-            // Extract action
-            if let Some(action) = line.strip_prefix("**Action:**") {
-                current_action = action.trim().to_string();
-                extract_keywords(&current_action, &mut current_keywords);
+/// Load every `*.md` file (and `*.toml` with the `toml` feature) in a
+/// directory into one database
+///
+/// Files are read in name order, one topic per file. A file that can't be
+/// read or parsed is skipped and reported in the returned list, so one bad
+/// contribution doesn't hide the rest; only an unreadable directory fails
+/// the whole load. Ids repeated across files get `-2`, `-3`, ... suffixes.
+pub fn load_heuristics_from_dir(
+    path: impl AsRef<Path>,
+) -> Result<(HeuristicDb, Vec<LoadError>), LoadError> {
+    let files = heuristic_files(path.as_ref())?;
+    let mut heuristics = Vec::new();
+    let mut corpora: Vec<Corpus> = Vec::new();
+    let mut errors = Vec::new();
+    let mut pack = None;
+    for file in files {
+        let content = match std::fs::read_to_string(&file) {
+            Ok(content) => content,
+            Err(source) => {
+                errors.push(LoadError::Io { path: file, source });
+                continue;
             }
-
-            // Extract crates
-            if line.contains("- **Crates:**") {
-                // Next lines contain crate info
-            } else if line.trim().starts_with("- `") && line.contains("` -") {
-                if let Some(crate_name) = extract_crate_name(line) {
-                    current_crates.push(crate_name.clone());
-                    current_keywords.push(crate_name);
-                }
+        };
+        let document = match parse_file(&file, &content) {
+            Ok(document) => document,
+            Err(source) => {
+                errors.push(LoadError::Parse { path: file, source });
+                continue;
             }
+        };
+        event!(debug, path = %file.display(), heuristics = document.heuristics.len(), "parsed pack file");
+        heuristics.extend(document.heuristics.into_iter().map(
+            |mut heuristic| {
+                heuristic.provenance = Provenance::File(file.clone());
+                heuristic
+            },
+        ));
+        pack = pack.or(document.pack);
+        match corpora
+            .iter_mut()
+            .find(|c| c.namespace == document.corpus.namespace)
+        {
+            Some(corpus) => corpus.absorb(document.corpus),
+            None => corpora.push(document.corpus),
+        }
+    }
 
-            // Extract std types
-            if line.contains("- **Std types:**") {
-                if let Some(types) = line.split("**Std types:**").nth(1) {
-                    for part in types.split(',') {
-                        if let Some(type_name) = extract_code_name(part.trim()) {
-                            current_std_types.push(type_name.clone());
-                            current_keywords.push(type_name);
-                        }
-                    }
-                }
-            }
+    event!(
+        info,
+        path = %path.as_ref().display(),
+        heuristics = heuristics.len(),
+        skipped = errors.len(),
+        "loaded pack directory"
+    );
+    make_ids_unique(&mut heuristics);
+    Ok((
+        HeuristicDb::with_corpora(heuristics, corpora).with_pack(pack),
+        errors,
+    ))
+}
 
-            // Extract keywords from various patterns
-            if line.contains("**When to use:**") {
-                if let Some(use_case) = line.split("**When to use:**").nth(1) {
-                    extract_keywords(use_case, &mut current_keywords);
+impl HeuristicDb {
+    /// The stock pack with heuristic files layered on top
+    ///
+    /// The stock pack is the one [`load_latest_heuristics`] picks. Each path is a file or a directory of files, applied in order; a
+    /// heuristic whose id is already loaded replaces the earlier one, so
+    /// teams can override built-in advice as well as add to it. Every
+    /// heuristic records in [`Heuristic::provenance`] where it came from.
+    /// Unlike [`load_heuristics_from_dir`], any file that fails to load
+    /// fails the whole load.
+    pub fn load_default_with_extras(
+        paths: &[PathBuf],
+    ) -> Result<Self, LoadError> {
+        let mut db = load_latest_heuristics();
+        for path in paths {
+            let extra = if path.is_dir() {
+                let (extra, mut errors) = load_heuristics_from_dir(path)?;
+                if !errors.is_empty() {
+                    return Err(errors.remove(0));
                 }
-            }
+                extra
+            } else {
+                load_heuristics_from_path(path)?
+            };
+            db = db
+                .merge(extra, MergePolicy::PreferTheirs)
+                .expect("preferring theirs never conflicts");
         }
+        event!(
+            debug,
+            extras = paths.len(),
+            heuristics = db.heuristics.len(),
+            "merged extra packs"
+        );
+        Ok(db)
     }
 
-    // Save last heuristic
-    if in_heuristic && !current_title.is_empty() {
-        heuristics.push(Heuristic {
-            title: current_title,
-            action: current_action,
-            category: current_category,
-            content: current_content.trim().to_string(),
-            crates: current_crates,
-            std_types: current_std_types,
-            keywords: current_keywords,
-        });
+    /// Mark every heuristic as loaded from `provenance`
+    pub(crate) fn with_provenance(mut self, provenance: Provenance) -> Self {
+        for heuristic in &mut self.heuristics {
+            heuristic.provenance = provenance.clone();
+        }
+        self
     }
 
-    heuristics
-}
+    /// Build a database from heuristic markdown held in memory
+    ///
+    /// Accepts the same format as the embedded pack, front matter included.
+    /// Diagnostics are dropped; use [`parse_markdown`] to see them.
+    pub fn from_markdown(content: &str) -> Result<Self, ParseError> {
+        parse_markdown(content).map(Self::from)
+    }
 
-fn extract_crate_name(line: &str) -> Option<String> {
-    line.trim()
-        .strip_prefix("- `")?
-        .split('`')
-        .next()
-        .map(|s| s.to_string())
-}
+    /// Build a database from a JSON-serialized [`DbSnapshot`]
+    ///
+    /// This is the inverse of [`HeuristicDb::to_json`] and of serializing
+    /// [`HeuristicDb::snapshot`], so a prebuilt bundle can be loaded without
+    /// parsing markdown. Documents of a newer [`SNAPSHOT_SCHEMA_VERSION`]
+    /// are refused.
+    #[cfg(feature = "json")]
+    pub fn from_json(content: &str) -> Result<Self, ParseError> {
+        serde_json::from_str::<JsonSnapshot>(content)
+            .map_err(|e| ParseError::InvalidDefinition(e.to_string()))?
+            .into_snapshot()
+            .map(Self::from)
+    }
 
-fn extract_code_name(text: &str) -> Option<String> {
-    text.trim()
-        .strip_prefix('`')?
-        .strip_suffix('`')
-        .map(|s| s.to_string())
-}
+    /// Like [`HeuristicDb::from_json`], reading the snapshot from `reader`
+    ///
+    /// Malformed JSON is reported as [`std::io::ErrorKind::InvalidData`]
+    /// wrapping a [`ParseError`].
+    #[cfg(feature = "json")]
+    pub fn from_reader(reader: impl std::io::Read) -> std::io::Result<Self> {
+        let invalid = |error: ParseError| {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, error)
+        };
+        match serde_json::from_reader::<_, JsonSnapshot>(reader) {
+            Ok(snapshot) => {
+                snapshot.into_snapshot().map(Self::from).map_err(invalid)
+            }
+            Err(e) if e.is_io() => Err(e.into()),
+            Err(e) => {
+                Err(invalid(ParseError::InvalidDefinition(e.to_string())))
+            }
+        }
+    }
+
+    /// Every heuristic and the pack metadata as a JSON document
+    ///
+    /// This is the interchange format for web and WASM bundles and other
+    /// tools, laid out as [`SNAPSHOT_SCHEMA_VERSION`] describes; load it
+    /// back with [`HeuristicDb::from_json`].
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.document())
+            .expect("snapshots serialize to JSON")
+    }
+
+    /// Like [`HeuristicDb::to_json`], writing the document to `writer`
+    #[cfg(feature = "json")]
+    pub fn to_writer_json(
+        &self,
+        writer: impl std::io::Write,
+    ) -> std::io::Result<()> {
+        serde_json::to_writer(writer, &self.document())
+            .map_err(std::io::Error::from)
+    }
 
-fn extract_keywords(text: &str, keywords: &mut Vec<String>) {
-    // Extract technical terms (simplified version)
-    let terms = [
-        "hash", "hashmap", "hashset", "btree", "binary search", "lookup", "insert",
-        "cache", "lru", "ttl", "bloom", "filter", "probabilistic",
-        "disk", "persistence", "wal", "log", "lsm", "compression",
-        "distributed", "shard", "replicate", "consensus", "crdt", "merkle",
-        "concurrent", "lock-free", "atomic", "skip list",
-        "trie", "prefix", "autocomplete", "heap", "priority queue",
-        "geospatial", "rtree", "quadtree", "rope", "text",
-        "event sourcing", "time-series", "batch", "async", "append-only",
-        "performance", "throughput", "latency", "columnar", "parquet",
-    ];
+    /// The [`HeuristicDb::to_json`] document as YAML
+    #[cfg(feature = "yaml")]
+    pub fn to_yaml(&self) -> String {
+        to_yaml(&self.document())
+    }
 
-    let lower = text.to_lowercase();
-    for term in terms {
-        if lower.contains(term) && !keywords.contains(&term.to_string()) {
-            keywords.push(term.to_string());
+    #[cfg(any(feature = "json", feature = "yaml"))]
+    fn document(&self) -> SnapshotDocument<'_> {
+        let mut corpora: Vec<&Corpus> = self.corpora.values().collect();
+        corpora.sort_by(|a, b| a.namespace.cmp(&b.namespace));
+        SnapshotDocument {
+            schema: SNAPSHOT_SCHEMA_VERSION,
+            pack: self.pack.as_ref(),
+            heuristics: &self.heuristics,
+            corpora,
         }
     }
+
+    /// Build a database from a TOML heuristic definition
+    ///
+    /// See [`PackDefinition`] for the schema.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(content: &str) -> Result<Self, ParseError> {
+        definition::parse_toml(content).map(Self::from)
+    }
 }
 
 #[cfg(test)]
@@ -312,6 +1647,958 @@ mod tests {
         assert!(!results.is_empty(), "Should find HashMap-related heuristics");
     }
 
+    #[test]
+    fn test_parse_tradeoffs() {
+        let md = "## Cat\n### Need X?\n**Action:** Do Y.\n\n- **Pros:** Fast\n- **Cons:**\n  - Unordered\n  - Memory hungry\n- **When to use:** Always\n";
+        let heuristics = parse_document(md).heuristics;
+        assert_eq!(heuristics[0].tradeoffs.pros, vec!["Fast"]);
+        assert_eq!(
+            heuristics[0].tradeoffs.cons,
+            vec!["Unordered", "Memory hungry"]
+        );
+    }
+
+    #[test]
+    fn test_parse_sources() {
+        let md = "## Cat\n### Need X?\n- **References:** [Docs](https://docs.rs/x)\n- **Sources:**\n  - [Paper](https://example.com/paper.pdf)\n  - https://example.com/blog\n";
+        let sources = &parse_document(md).heuristics[0].sources;
+        let urls: Vec<_> = sources.iter().map(|s| s.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://docs.rs/x",
+                "https://example.com/paper.pdf",
+                "https://example.com/blog"
+            ]
+        );
+        assert_eq!(sources[0].title, "Docs");
+    }
+
+    #[test]
+    fn test_corpus_front_matter() {
+        let md = "---\nnamespace: actors\nstopwords: [cache]\nboost: actor\n---\n## Actors\n### Need an actor cache?\n**Action:** Give each actor its own LRU cache.\n";
+        let document = parse_document(md);
+        assert_eq!(document.corpus.namespace, "actors");
+        assert_eq!(document.heuristics[0].namespace, "actors");
+
+        let db = HeuristicDb::with_corpora(
+            document.heuristics,
+            vec![document.corpus],
+        );
+        assert!(
+            db.search(&["cache"]).is_empty(),
+            "Stopwords should not be indexed"
+        );
+        assert_eq!(
+            db.search(&["actor"]).len(),
+            1,
+            "Boosted terms should be indexed"
+        );
+    }
+
+    #[test]
+    fn test_boost_ranks_higher() {
+        let mk = |namespace: &str| Heuristic {
+            title: "Need a cache?".into(),
+            keywords: vec!["cache".into()],
+            namespace: namespace.into(),
+            ..Default::default()
+        };
+        let boosted = Corpus {
+            namespace: "b".into(),
+            boost: vec!["cache".into()],
+            ..Default::default()
+        };
+        let db =
+            HeuristicDb::with_corpora(vec![mk("a"), mk("b")], vec![boosted]);
+        assert_eq!(db.search(&["cache"])[0].namespace, "b");
+    }
+
+    #[test]
+    fn test_score_weights() {
+        let mk = |title: &str, keyword: &str| Heuristic {
+            title: title.into(),
+            category: "Views".into(),
+            keywords: vec![keyword.into()],
+            ..Default::default()
+        };
+        let db = HeuristicDb::new(vec![
+            mk("Need a cache?", "cache"),
+            mk("Need a cached view?", "cached"),
+        ]);
+        let scores = |weights| {
+            db.search_weighted(&["cache"], weights)
+                .iter()
+                .map(|&(_, score)| score)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(scores(ScoreWeights::default()), vec![6, 2]);
+        assert_eq!(scores(ScoreWeights { exact: 0, partial: 1 }), vec![2, 2]);
+    }
+
+    #[test]
+    fn test_search_filter() {
+        let md = "## Caching / In-Memory\n### Need a cache?\n---\ntags: [async]\n---\n**Action:** Use `moka`.\n- **Std types:** `std::collections::HashMap`\n\n## Storage\n### Need a disk cache?\n**Action:** Persist it.\n- **Crates:**\n  - `sled` - Embedded database\n";
+        let db = HeuristicDb::from_markdown(md).unwrap();
+        let ids = |filter: SearchFilter| {
+            db.search_filtered(&["cache"], ScoreWeights::default(), &filter)
+                .iter()
+                .map(|(h, _)| h.id.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(SearchFilter::default()).len(), 2);
+        assert_eq!(
+            ids(SearchFilter {
+                category: Some("caching".into()),
+                ..Default::default()
+            }),
+            ["need-a-cache"]
+        );
+        assert_eq!(
+            ids(SearchFilter {
+                crate_name: Some("Sled".into()),
+                ..Default::default()
+            }),
+            ["need-a-disk-cache"]
+        );
+        assert_eq!(
+            ids(SearchFilter {
+                std_type: Some("HashMap<K, V>".into()),
+                ..Default::default()
+            }),
+            ["need-a-cache"]
+        );
+        assert_eq!(
+            ids(SearchFilter {
+                tag: Some("ASYNC".into()),
+                ..Default::default()
+            }),
+            ["need-a-cache"]
+        );
+        assert!(
+            ids(SearchFilter {
+                tag: Some("async".into()),
+                crate_name: Some("sled".into()),
+                ..Default::default()
+            })
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_render_terminal_markdown() {
+        let md = "## Cat\n### Need *styled* text?\n**Action:** Use `Cow`.\n\n##### Steps\n\n1. Read the **docs** at [docs.rs](https://docs.rs), then _try_ it\n2. See [the map](#need-a-map)\n";
+        let h = &parse_document(md).heuristics[0];
+        let plain = h.render_full(RenderStyle::Plain, 30);
+        assert!(plain.contains("   Steps\n\n   1. Read the docs at docs.rs\n      <https://docs.rs>, then\n      try it\n   2. See the map\n"), "{}", plain);
+
+        let ansi = h.render_full(RenderStyle::Ansi, 30);
+        assert!(
+            ansi.contains("\x1b[1mdocs\x1b[22m")
+                && ansi.contains("\x1b[3mtry\x1b[23m")
+        );
+        assert!(ansi.contains("Use \x1b[33mCow\x1b[0m."));
+        assert!(
+            ansi.contains("   1. Read the \x1b[1mdocs\x1b[22m at docs.rs\n"),
+            "Escape codes take no room: {}",
+            ansi
+        );
+    }
+
+    #[cfg(feature = "highlight")]
+    #[test]
+    fn test_highlight_code_samples() {
+        let md = "## Cat\n### Need a sample?\n**Action:** Copy it.\n\n```rust\nlet x = 1 < 2;\n```\n\n```\nplain <text>\n```\n";
+        let h = &parse_document(md).heuristics[0];
+        let ansi = h.render_full(RenderStyle::Ansi, 80);
+        assert!(
+            ansi.contains("     \x1b[38;2;") && ansi.contains("plain <text>"),
+            "{}",
+            ansi
+        );
+        assert!(
+            !h.render_full(RenderStyle::Plain, 80).contains('\x1b'),
+            "Plain output stays plain"
+        );
+
+        let html = h.to_html();
+        assert!(
+            html.contains("<pre><code class=\"language-rust\"><span style=\""),
+            "{}",
+            html
+        );
+        assert!(html.contains("&lt;"), "Highlighted code is still escaped");
+        assert!(html.contains("<pre><code>plain &lt;text&gt;\n</code></pre>"));
+    }
+
+    #[test]
+    fn test_explain_crate() {
+        let md = "## Cat\n### Need a cache?\n**Action:** Cache it.\n\n- **Crates:**\n  - `moka` - Concurrent cache\n  - `lru` - Simple LRU\n\
+                  - **Std types:** `HashMap`\n- **When to use:** Slow lookups\n- **Pros:** Fast\n- **Cons:** Stale data\n\
+                  ### Need a map?\n**Action:** Use a map.\n\n- **Alternatives:** `moka`\n- **Std types:** `BTreeMap`\n";
+        let db = HeuristicDb::new(parse_document(md).heuristics);
+
+        let digest = db.explain_crate("MOKA").unwrap();
+        assert_eq!(digest.name, "moka");
+        assert_eq!(digest.descriptions, ["Concurrent cache"]);
+        assert_eq!(digest.recommended_by[0].id, "need-a-cache");
+        assert_eq!(digest.alternative_in[0].id, "need-a-map");
+        assert_eq!(digest.use_when, ["Slow lookups", "Fast"]);
+        assert_eq!(digest.avoid_when, ["Stale data"]);
+        let expected = ["lru", "HashMap", "BTreeMap"];
+        let names: Vec<&str> = digest
+            .alternatives
+            .iter()
+            .map(|a| match a {
+                Alternative::Crate(name)
+                | Alternative::StdType(name)
+                | Alternative::Heuristic(name) => name.as_str(),
+            })
+            .collect();
+        assert_eq!(names, expected);
+        assert!(
+            digest
+                .render(RenderStyle::Plain, 80)
+                .contains("Avoid it when:\n  - Stale data")
+        );
+        assert!(db.explain_crate("dashmap").is_none());
+    }
+
+    #[test]
+    fn test_parse_since_updated() {
+        let md = "## Cat\n### Need X?\n**Since:** 0.1.0\n- **Updated:** 2025-11-17\n### Need Y?\n";
+        let heuristics = parse_document(md).heuristics;
+        assert_eq!(heuristics[0].since.as_deref(), Some("0.1.0"));
+        assert_eq!(heuristics[0].updated.as_deref(), Some("2025-11-17"));
+        assert_eq!(heuristics[1].since, None);
+    }
+
+    #[test]
+    fn test_parse_platforms() {
+        let md = "## Cat\n### Need X?\n- **Platforms:** no_std, `wasm`\n### Need Y?\n- **Platforms:** threads\n### Need Z?\n";
+        let heuristics = parse_document(md).heuristics;
+        let [x, y, z] = [0, 1, 2].map(|i| heuristics[i].applies_to);
+
+        assert_eq!(x.no_std, Some(true));
+        assert_eq!(x.embedded, Some(false));
+        assert!(x.supports(Platform::Wasm) && !x.supports(Platform::Embedded));
+        assert!(
+            !y.supports(Platform::NoStd),
+            "Threaded advice can't apply to no_std"
+        );
+        assert!(z.supports(Platform::NoStd) && z == Platforms::default());
+    }
+
+    #[test]
+    fn test_links_collected() {
+        let md = "## Cat\n### Need X?\n**Action:** See [the docs](https://docs.rs/x).\n- **References:** [Paper](ftp://example.com)\n";
+        let links = &parse_document(md).heuristics[0].links;
+        assert_eq!(links.len(), 2);
+        assert_eq!(
+            links[0],
+            Link { text: "the docs".into(), url: "https://docs.rs/x".into() }
+        );
+        assert_eq!(links[0].problem(), None);
+        assert_eq!(links[1].problem(), Some("unsupported URL scheme"));
+    }
+
+    #[test]
+    fn test_ids() {
+        let md = "## Cat\n### Need O(1) lookups?\n### Need O(1) lookups?\n### Renamed title\n**Id:** Stable Id\n";
+        let ids: Vec<_> =
+            parse_document(md).heuristics.into_iter().map(|h| h.id).collect();
+        assert_eq!(
+            ids,
+            vec!["need-o-1-lookups", "need-o-1-lookups-2", "stable-id"]
+        );
+    }
+
+    #[test]
+    fn test_parse_alternatives() {
+        let md = "## Cat\n### Need X?\n- **Alternatives:** `BTreeMap`, `indexmap`\n  - [Need Y?](#need-y)\n  - Need Z? - when Z matters\n";
+        let alternatives = &parse_document(md).heuristics[0].alternatives;
+        assert_eq!(
+            alternatives,
+            &vec![
+                Alternative::StdType("BTreeMap".into()),
+                Alternative::Crate("indexmap".into()),
+                Alternative::Heuristic("need-y".into()),
+                Alternative::Heuristic("Need Z?".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_tables() {
+        let md = "## Cat\n### Need X?\n\n| Codec | Speed |\n|-------|:-----:|\n| zstd | Fast |\n| lz4 |\n\n```\n| not | a table |\n|---|---|\n```\n";
+        let tables = &parse_document(md).heuristics[0].tables;
+        assert_eq!(tables.len(), 1, "Tables inside code fences are ignored");
+        assert_eq!(tables[0].headers, vec!["Codec", "Speed"]);
+        assert_eq!(
+            tables[0].rows,
+            vec![vec!["zstd", "Fast"], vec!["lz4", ""]]
+        );
+    }
+
+    #[test]
+    fn test_parse_memory_overhead() {
+        let factor = MemoryOverhead::parse("~1.1x–2x of data");
+        assert_eq!(factor.factor, Some(2.0));
+        assert_eq!(factor.per_entry_bytes, None);
+
+        let bytes = MemoryOverhead::parse("per-entry 48 bytes");
+        assert_eq!(bytes.per_entry_bytes, Some(48.0));
+        assert!(bytes > factor, "Unknown factors sort last");
+
+        let md = "## Cat\n### Need X?\n- **Memory:** ~1.2 bytes per entry\n";
+        let overhead =
+            parse_document(md).heuristics[0].memory_overhead.clone().unwrap();
+        assert_eq!(overhead.per_entry_bytes, Some(1.2));
+    }
+
+    #[test]
+    fn test_parse_parent_builds_tree() {
+        let md = "## Cat\n### Need lookups?\n**Action:** Pick a map.\n\n### Ordered?\n**Action:** Use BTreeMap.\n**Parent:** Need lookups?\n\n### Orphan?\n- **Parent:** missing\n";
+        let db = HeuristicDb::new(parse_document(md).heuristics);
+        let ordered = db.get("ordered").unwrap();
+        assert_eq!(
+            db.parent_of(ordered).map(|p| p.id.as_str()),
+            Some("need-lookups")
+        );
+        assert_eq!(
+            db.children_of(db.get("need-lookups").unwrap()),
+            vec![ordered]
+        );
+        assert_eq!(
+            db.decision_root().children().len(),
+            2,
+            "Unresolved parents sit at the root"
+        );
+    }
+
+    #[test]
+    fn test_parse_crate_requirements() {
+        let md = "## Cat\n### Need X?\n- **MSRV:** `moka` 1.70, `lru` (edition 2024), `old` 1.60 (edition 2018)\n";
+        let h = &parse_document(md).heuristics[0];
+        assert_eq!(h.crate_requirements.len(), 3);
+        assert_eq!(h.crate_requirements[1].name, "lru");
+        assert_eq!(h.crate_requirements[2].msrv, RustVersion::parse("1.60"));
+        assert_eq!(
+            h.msrv(),
+            RustVersion::parse("1.85"),
+            "Edition 2024 needs Rust 1.85"
+        );
+        assert!(!h.supports_rust(RustVersion::parse("1.70").unwrap()));
+    }
+
+    #[test]
+    fn test_parse_benchmarks() {
+        let md = "## Cat\n### Need X?\n- **Benchmarks:** [inline](https://a.example)\n  - [nested](https://b.example)\n- **Cons:** [not a benchmark](https://c.example)\n";
+        let h = &parse_document(md).heuristics[0];
+        let urls: Vec<&str> =
+            h.benchmarks.iter().map(|l| l.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://a.example", "https://b.example"]);
+    }
+
+    #[test]
+    fn test_parse_category_descriptions() {
+        let md = "## Caching\n\nKeeping results around.\nAcross two lines.\n\nSecond paragraph.\n\n### Need X?\n**Action:** Do X.\n\n## Bare\n### Need Y?\n";
+        let corpus = parse_document(md).corpus;
+        assert_eq!(
+            corpus.category_descriptions["Caching"],
+            "Keeping results around. Across two lines."
+        );
+        assert!(!corpus.category_descriptions.contains_key("Bare"));
+    }
+
+    #[test]
+    fn test_parse_aliases() {
+        let md = "## Cat\n### Need X?\n- **Also asked as:** \"How do I memoize results?\", “Why recompute?”\n";
+        let h = &parse_document(md).heuristics[0];
+        assert_eq!(
+            h.aliases,
+            vec!["How do I memoize results?", "Why recompute?"]
+        );
+
+        let db = HeuristicDb::new(vec![h.clone()]);
+        assert_eq!(
+            db.search(&["memoize"]).len(),
+            1,
+            "Alias words are searchable"
+        );
+    }
+
+    #[test]
+    fn test_structured_action() {
+        let md = "## Cat\n### Need A?\n**Action:** Use a binary heap.\n- **Std types:** `std::collections::BinaryHeap`\n\n### Need B?\n**Action:** Use `moka` for TTLs.\n- **Crates:**\n  - `moka` - Cache\n\n### Need C?\n**Action:** Prefer B-trees (or LSM-trees).\n\n### Need D?\n**Action:** Merkle tree.\n";
+        let actions: Vec<Action> = parse_document(md)
+            .heuristics
+            .iter()
+            .map(|h| h.structured_action())
+            .collect();
+        assert!(
+            matches!(&actions[0], Action::UseStdType { ty, .. } if ty == "std::collections::BinaryHeap")
+        );
+        assert!(
+            matches!(&actions[1], Action::UseCrate { name, .. } if name == "moka")
+        );
+        assert!(
+            matches!(&actions[2], Action::ApplyPattern { pattern, .. } if pattern == "B-trees")
+        );
+        assert_eq!(actions[3], Action::Freeform("Merkle tree.".to_string()));
+        assert_eq!(actions[2].text(), "Prefer B-trees (or LSM-trees).");
+
+        // Lowercasing `İ` adds a byte; the pattern is still cut from the original
+        let h = &parse_document(
+            "## Cat\n### Need E?\n**Action:** Use İİİ caches, mostly.\n",
+        )
+        .heuristics[0];
+        assert!(
+            matches!(h.structured_action(), Action::ApplyPattern { pattern, .. } if pattern == "İİİ caches")
+        );
+    }
+
+    #[test]
+    fn test_from_markdown_errors() {
+        assert!(HeuristicDb::from_markdown("## Cat\n### Need X?\n").is_ok());
+        assert_eq!(
+            HeuristicDb::from_markdown(
+                "\n---\nnamespace: a\n## Cat\n### Need X?\n"
+            )
+            .err(),
+            Some(ParseError::UnterminatedFrontMatter { line: 2 })
+        );
+        assert_eq!(
+            HeuristicDb::from_markdown("## Cat\n### Need X?\n###\n").err(),
+            Some(ParseError::UntitledHeuristic { line: 3 })
+        );
+        assert_eq!(
+            HeuristicDb::from_markdown("#### Not a heuristic\n").err(),
+            Some(ParseError::NoHeuristics)
+        );
+    }
+
+    #[test]
+    fn test_parse_diagnostics() {
+        let md = "---\nnamespace: a\nflavour: x\n---\n### Orphan cache?\n**Action:** Do it.\n## Cat\n### Need a cache?\n- **Crate:** `typo`\n- **MSRV:** 1.70\n### Need a cache?\n**Action:** Again.\n";
+        let document = parse_markdown(md).unwrap();
+        let found: Vec<(usize, &str)> = document
+            .diagnostics
+            .iter()
+            .map(|d| (d.line, d.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (3, "unknown front matter key 'flavour' ignored"),
+                (5, "'Orphan cache?' is not under a '##' category header"),
+                (8, "'Need a cache?' has no **Action:** line"),
+                (9, "unknown label '**Crate:**' kept as text"),
+                (10, "MSRV item '1.70' isn't `crate` VERSION; skipped"),
+                (
+                    11,
+                    "duplicate id 'need-a-cache' renamed to 'need-a-cache-2'"
+                ),
+            ]
+        );
+        assert_eq!(
+            document.heuristics.len(),
+            3,
+            "Problems don't drop heuristics"
+        );
+    }
+
+    #[test]
+    fn test_strict_parsing() {
+        let md = "## Cat\n### Need X?\n**Action:** Use a cache.\n\n### Need Y?\n- **When to use:** Rarely\n";
+        assert_eq!(
+            parse_markdown(md).unwrap().heuristics.len(),
+            2,
+            "Lenient mode keeps everything"
+        );
+
+        let Err(ParseError::Rejected(diagnostics)) =
+            parse_markdown_with(md, &ParseOptions::strict())
+        else {
+            panic!(
+                "Strict mode should reject a heuristic without an action or keywords"
+            );
+        };
+        let lines: Vec<usize> = diagnostics.iter().map(|d| d.line).collect();
+        assert_eq!(lines, vec![5, 5]);
+    }
+
+    #[test]
+    fn test_custom_keyword_dictionary() {
+        let md = "## Game Dev\n### Need to update many entities per frame?\n**Action:** Use an ECS.\n";
+        let builtin = HeuristicDb::from(parse_markdown(md).unwrap());
+        assert!(
+            builtin.search(&["ecs"]).is_empty(),
+            "The built-in dictionary has no game-dev terms"
+        );
+
+        let options = ParseOptions::default().with_keywords(
+            TermDictionary::builtin().with(["ECS", "entities"]),
+        );
+        let document = parse_markdown_with(md, &options).unwrap();
+        assert_eq!(document.heuristics[0].keywords, vec!["entities", "ecs"]);
+
+        let rebuilt = HeuristicDb::with_keywords(
+            parse_markdown(md).unwrap().heuristics,
+            &TermDictionary::new(["frame"]),
+        );
+        assert_eq!(rebuilt.search(&["frame"]).len(), 1);
+    }
+
+    #[test]
+    fn test_crate_and_std_type_formatting_variations() {
+        let md = "## Cat\n### Need a map?\n**Action:** Use a map.\n\n- **Crates:** `ahash`,\n  `indexmap`\n- **Std types:**\n    - `std::collections::HashMap` (default)\n    - `BTreeMap` - when order matters, unlike `HashSet`\n\n* **Crates:**\n\n  * `hashbrown` - see `RawTable`\n\n```rust\n- **Crates:** `not_a_crate`\n```\n**Std types:** `Vec`, `VecDeque`\n";
+        let heuristic = &parse_document(md).heuristics[0];
+        assert_eq!(heuristic.crates, ["ahash", "indexmap", "hashbrown"]);
+        assert_eq!(
+            heuristic.std_types,
+            ["std::collections::HashMap", "BTreeMap", "Vec", "VecDeque"]
+        );
+        assert!(heuristic.keywords.contains(&"indexmap".to_string()));
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let db = load_heuristics();
+        assert_eq!(
+            db.fingerprint(),
+            load_heuristics().fingerprint(),
+            "Fingerprints are deterministic"
+        );
+        let fingerprints: std::collections::HashSet<u64> =
+            db.all().iter().map(Heuristic::fingerprint).collect();
+        assert_eq!(fingerprints.len(), db.all().len());
+
+        let mut heuristics = db.all().to_vec();
+        heuristics[0].provenance = Provenance::File(PathBuf::from("copy.md"));
+        assert_eq!(
+            heuristics[0].fingerprint(),
+            db.all()[0].fingerprint(),
+            "Provenance is not content"
+        );
+        heuristics[1].action.push_str(" Measure first.");
+        assert_ne!(heuristics[1].fingerprint(), db.all()[1].fingerprint());
+        let edited =
+            HeuristicDb::new(heuristics).with_pack(db.pack_info().cloned());
+        assert_ne!(edited.fingerprint(), db.fingerprint());
+
+        let mut reordered = db.all().to_vec();
+        reordered.swap(0, 1);
+        let reordered =
+            HeuristicDb::new(reordered).with_pack(db.pack_info().cloned());
+        assert_ne!(
+            reordered.fingerprint(),
+            HeuristicDb::new(db.all().to_vec())
+                .with_pack(db.pack_info().cloned())
+                .fingerprint()
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_search() {
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        assert_eq!(fuzzy_score("xyz", "Need a cache?"), None);
+        assert!(
+            fuzzy_score("cache", "Need to cache results?")
+                > fuzzy_score("cache", "Need concurrent access here?")
+        );
+        assert!(
+            fuzzy_score("nc", "Need cache") > fuzzy_score("nc", "Once"),
+            "Word starts rank higher"
+        );
+
+        let db = load_heuristics();
+        assert_eq!(db.fuzzy_search("")[0].id, db.all()[0].id);
+        assert_eq!(
+            db.fuzzy_search("compress data")[0].id,
+            "need-to-compress-data-aggressively"
+        );
+    }
+
+    #[test]
+    fn test_markdown_round_trip() {
+        let db = load_heuristics();
+        let written = db.to_markdown();
+        let reparsed = parse_markdown(&written).unwrap();
+        assert!(reparsed.diagnostics.is_empty(), "{:?}", reparsed.diagnostics);
+        let reloaded = HeuristicDb::from(reparsed);
+
+        // Written output is canonical: writing it again changes nothing
+        assert_eq!(reloaded.to_markdown(), written);
+        assert_eq!(reloaded.pack_info(), db.pack_info());
+        assert_eq!(reloaded.category_info(), db.category_info());
+        for (before, after) in db.all().iter().zip(reloaded.all()) {
+            let fields = |h: &Heuristic| {
+                let mut keywords = h.keywords.clone();
+                keywords.sort();
+                (
+                    h.id.clone(),
+                    h.title.clone(),
+                    h.action.clone(),
+                    h.aliases.clone(),
+                    h.crates.clone(),
+                    h.std_types.clone(),
+                    keywords,
+                )
+            };
+            assert_eq!(fields(before), fields(after));
+            let structured = |h: &Heuristic| {
+                (
+                    h.tradeoffs.clone(),
+                    h.sources.clone(),
+                    h.alternatives.clone(),
+                    h.benchmarks.clone(),
+                    h.parent.clone(),
+                    h.tables.clone(),
+                )
+            };
+            assert_eq!(structured(before), structured(after));
+            assert_eq!(
+                (
+                    &before.memory_overhead,
+                    &before.crate_requirements,
+                    before.applies_to,
+                    &before.since
+                ),
+                (
+                    &after.memory_overhead,
+                    &after.crate_requirements,
+                    after.applies_to,
+                    &after.since
+                )
+            );
+        }
+        assert_eq!(reloaded.all().len(), db.all().len());
+
+        // Programmatic edits survive the trip
+        let mut heuristics = db.all().to_vec();
+        heuristics[0].action = "Use `indexmap`.".to_string();
+        heuristics[0].crates.push("indexmap".to_string());
+        let edited = HeuristicDb::from(
+            parse_markdown(&HeuristicDb::new(heuristics).to_markdown())
+                .unwrap(),
+        );
+        assert_eq!(edited.all()[0].action, "Use `indexmap`.");
+        assert!(edited.all()[0].crates.contains(&"indexmap".to_string()));
+    }
+
+    #[test]
+    fn test_pack_info() {
+        let builtin = load_heuristics();
+        let info = builtin
+            .pack_info()
+            .expect("The embedded pack declares its metadata");
+        assert_eq!(
+            (info.version, info.name.as_deref(), info.license.as_deref()),
+            (1, Some("heuristics"), Some("MIT"))
+        );
+
+        let md = |header: &str| {
+            format!(
+                "---\n{}\n---\n## Caching\n### Need a cache?\n**Action:** Use `moka`.\n",
+                header
+            )
+        };
+        let team =
+            parse_markdown(&md("pack-name: team\nmin-crate-version: 0.1.0"))
+                .unwrap();
+        assert!(
+            team.diagnostics.is_empty(),
+            "Pack keys are known front matter: {:?}",
+            team.diagnostics
+        );
+        let info = team.pack.clone().unwrap();
+        assert_eq!(
+            (info.version, info.name.as_deref()),
+            (PACK_SCHEMA_VERSION, Some("team"))
+        );
+        assert!(
+            HeuristicDb::from(parse_markdown(&md("namespace: x")).unwrap())
+                .pack_info()
+                .is_none()
+        );
+
+        let merged = HeuristicDb::from(team)
+            .merge(load_heuristics(), MergePolicy::PreferOurs)
+            .unwrap();
+        assert_eq!(merged.pack_info().unwrap().name.as_deref(), Some("team"));
+
+        assert_eq!(
+            parse_markdown(&md("version: 2")).err(),
+            Some(ParseError::UnsupportedVersion {
+                found: 2,
+                supported: PACK_SCHEMA_VERSION
+            })
+        );
+        let error =
+            parse_markdown(&md("min-crate-version: 99.0.0")).unwrap_err();
+        assert!(
+            error.to_string().contains("needs heuristics 99.0.0 or newer"),
+            "{}",
+            error
+        );
+
+        #[cfg(feature = "toml")]
+        {
+            let toml = "version = 1\npack-name = \"team\"\n[[heuristic]]\ntitle = \"Need a cache?\"\n";
+            assert_eq!(
+                HeuristicDb::from_toml(toml)
+                    .unwrap()
+                    .pack_info()
+                    .unwrap()
+                    .name
+                    .as_deref(),
+                Some("team")
+            );
+            let future = HeuristicDb::from_toml(
+                &toml.replace("version = 1", "version = 3"),
+            );
+            assert!(matches!(
+                future,
+                Err(ParseError::UnsupportedVersion { found: 3, .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_code_fences_are_verbatim() {
+        let code = "````markdown\n## Not a category\n```\n### Not a heuristic\n- `fake` - not a crate\n**Action:** Not the action.\n````";
+        let md = format!(
+            "## Cat\n### Need a cache sample?\n**Action:** Copy it.\n\n{}\n~~~\n### Still code\n~~~\n\n### Next cache?\n**Action:** Go on.\n",
+            code
+        );
+        let document = parse_markdown(&md).unwrap();
+
+        let titles: Vec<&str> =
+            document.heuristics.iter().map(|h| h.title.as_str()).collect();
+        assert_eq!(titles, ["Need a cache sample?", "Next cache?"]);
+        let sample = &document.heuristics[0];
+        assert_eq!(sample.category, "Cat");
+        assert_eq!(sample.action, "Copy it.");
+        assert!(sample.crates.is_empty());
+        assert!(
+            sample.content.contains(code)
+                && sample.content.ends_with("### Still code\n~~~")
+        );
+        assert!(document.diagnostics.is_empty(), "{:?}", document.diagnostics);
+    }
+
+    #[test]
+    fn test_custom_source_format() {
+        /// One heuristic per `title => action` line
+        struct Arrows;
+
+        impl HeuristicSource for Arrows {
+            fn parse(
+                &self,
+                input: &str,
+            ) -> Result<Vec<Heuristic>, ParseError> {
+                let heuristics: Vec<Heuristic> = input
+                    .lines()
+                    .filter_map(|line| line.split_once("=>"))
+                    .map(|(title, action)| Heuristic {
+                        title: title.trim().to_string(),
+                        action: action.trim().to_string(),
+                        keywords: vec!["arrow".into()],
+                        ..Default::default()
+                    })
+                    .collect();
+                if heuristics.is_empty() {
+                    Err(ParseError::NoHeuristics)
+                } else {
+                    Ok(heuristics)
+                }
+            }
+        }
+
+        let db = HeuristicDb::from_source(
+            &Arrows,
+            "Need a map? => Use HashMap\nNeed a map? => Use BTreeMap\n",
+        )
+        .unwrap();
+        let ids: Vec<&str> =
+            db.search(&["arrow"]).iter().map(|h| h.id.as_str()).collect();
+        assert_eq!(ids, ["need-a-map", "need-a-map-2"]);
+        assert_eq!(
+            HeuristicDb::from_source(&Arrows, "").err(),
+            Some(ParseError::NoHeuristics)
+        );
+
+        let markdown = HeuristicDb::from_source(
+            &MarkdownSource::default(),
+            include_str!("../base.md"),
+        )
+        .unwrap();
+        assert_eq!(markdown.all(), load_heuristics().all());
+        let strict = MarkdownSource::new(ParseOptions::strict());
+        assert!(matches!(
+            strict.parse("## Cat\n### Untitled action?\n"),
+            Err(ParseError::Rejected(_))
+        ));
+    }
+
+    #[test]
+    fn test_sub_heuristics() {
+        let md = "## Caching\n### Need a cache?\n**Action:** Pick an eviction policy.\n\n#### LRU cache\n**Action:** Use `lru`.\n\n#### TTL cache\n**Action:** Use `moka`.\n**Parent:** need-key-value-lookups\n\n## Other\n#### Orphan cache\n**Action:** Nothing.\n";
+        let document = parse_markdown(md).unwrap();
+        let [parent, lru, ttl, orphan] = &document.heuristics[..] else {
+            panic!("expected four heuristics")
+        };
+
+        assert!(
+            !parent.content.contains("LRU"),
+            "Variants are not part of the parent's content"
+        );
+        assert_eq!(
+            (lru.category.as_str(), lru.parent.as_deref()),
+            ("Caching", Some("need-a-cache"))
+        );
+        assert_eq!(
+            ttl.parent.as_deref(),
+            Some("need-key-value-lookups"),
+            "An explicit parent wins"
+        );
+        assert_eq!(orphan.parent, None);
+        assert_eq!(document.diagnostics.len(), 1);
+        assert!(
+            document.diagnostics[0]
+                .message
+                .contains("without a '###' heuristic")
+        );
+
+        let db = HeuristicDb::from(document);
+        let children: Vec<&str> = db
+            .children_of(db.get("need-a-cache").unwrap())
+            .iter()
+            .map(|h| h.id.as_str())
+            .collect();
+        assert_eq!(children, ["lru-cache"]);
+    }
+
+    #[cfg(feature = "fetch")]
+    #[test]
+    fn test_pack_cache_names() {
+        assert_eq!(
+            fetch::sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        let name =
+            fetch::pack_file_name("https://example.com/packs/team.toml?v=2");
+        assert!(name.ends_with(".toml") && name.len() == 21);
+        assert_ne!(
+            name,
+            fetch::pack_file_name("https://example.com/packs/team.toml?v=3")
+        );
+        assert!(
+            fetch::pack_file_name("https://example.com/pack").ends_with(".md")
+        );
+    }
+
+    #[cfg(feature = "fetch")]
+    #[test]
+    fn test_pack_status() {
+        let digest = fetch::sha256_hex(BUILTIN_PACK.as_bytes());
+        assert_eq!(
+            fetch::pack_status(
+                Some(BUILTIN_PACK.as_bytes()),
+                &digest.to_uppercase()
+            ),
+            PackStatus::Current
+        );
+        assert_eq!(
+            fetch::pack_status(Some(b"## Old\n"), &digest),
+            PackStatus::Stale
+        );
+        assert_eq!(fetch::pack_status(None, &digest), PackStatus::Missing);
+        assert!(
+            Provenance::Url(PUBLISHED_PACK_URL.into()).is_stock()
+                && !Provenance::Url("https://example.com".into()).is_stock()
+        );
+    }
+
+    #[cfg(feature = "watch")]
+    #[test]
+    fn test_watch_reloads_on_change() {
+        use std::time::Duration;
+
+        let path = std::env::temp_dir()
+            .join(format!("heuristics-watch-{}.md", std::process::id()));
+        std::fs::write(
+            &path,
+            "## Caching\n### Need a cache?\n**Action:** Use `moka`.\n",
+        )
+        .unwrap();
+        let (reloads, reloaded) = std::sync::mpsc::channel();
+        let watched = HeuristicDb::watch(&path, move |result| {
+            reloads.send(result.is_ok()).unwrap()
+        })
+        .unwrap();
+        assert_eq!(watched.current().all().len(), 1);
+        let before = watched.current();
+
+        std::fs::write(&path, "## Caching\n").unwrap();
+        assert_eq!(reloaded.recv_timeout(Duration::from_secs(5)), Ok(false));
+        assert_eq!(
+            watched.current().all().len(),
+            1,
+            "A broken file keeps the last good database"
+        );
+
+        std::fs::write(&path, "## Caching\n### Need a cache?\n**Action:** Use `moka`.\n\n### Need a bigger cache?\n**Action:** Use `foyer`.\n").unwrap();
+        while reloaded.recv_timeout(Duration::from_secs(5)) != Ok(true) {}
+        assert_eq!(watched.current().all().len(), 2);
+        assert_eq!(
+            before.all().len(),
+            1,
+            "Earlier snapshots are unaffected by reloads"
+        );
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn test_serve_pages() {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || serve(load_heuristics(), listener));
+        let get = |path: &str| {
+            let mut stream = std::net::TcpStream::connect(address).unwrap();
+            write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n", path).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+
+        let page = get("/heuristic/need-to-cache-expensive-results");
+        assert!(page.starts_with("HTTP/1.1 200"), "{}", page);
+        assert!(
+            page.contains("<section id=\"need-to-cache-expensive-results\">")
+        );
+        assert!(
+            get("/search?q=lru+cache").contains(
+                "href=\"/heuristic/need-to-cache-expensive-results\""
+            )
+        );
+        assert!(get("/").contains(
+            "/category?name=Concurrency%20%26%20Lock-Free%20Heuristics"
+        ));
+        assert!(
+            get("/category?name=Concurrency%20%26%20Lock-Free%20Heuristics")
+                .starts_with("HTTP/1.1 200")
+        );
+        assert!(
+            get("/heuristic/no-such-heuristic").starts_with("HTTP/1.1 404")
+        );
+    }
+
     #[test]
     fn test_categories() {
         let db = load_heuristics();
@@ -1,6 +1,30 @@
-use clap::{Parser, Subcommand};
+use clap::parser::ValueSource;
+use clap::{
+    ArgAction, Args, CommandFactory, FromArgMatches, Parser, Subcommand,
+    ValueEnum,
+};
 use colored::*;
-use heuristics::{load_heuristics, Heuristic};
+use heuristics::{
+    Action, Alternative, CheatSource, CsvSource, Heuristic, HeuristicDb,
+    HeuristicSource, Level, Manifest, MergePolicy, NotesSource, ParseError,
+    ParseOptions, Platform, Provenance, RenderStyle, RustVersion, SampleBias,
+    ScoreWeights, SearchFilter, TldrSource, UsageLog, ValidationIssue,
+    check_files, compare, parse_markdown_with, to_anki, to_csv, to_dot,
+    to_html, to_hugo, to_man_pages, to_mdbook, to_site, to_vault, to_zola,
+};
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Version of the `--format json` output schema.
+///
+/// Bumped whenever a field is removed, renamed, or changes type. Adding new
+/// fields is not a breaking change and keeps the current version.
+const JSON_SCHEMA_VERSION: u32 = 1;
 
 #[derive(Parser)]
 #[command(name = "heuristics")]
@@ -8,6 +32,135 @@ use heuristics::{load_heuristics, Heuristic};
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Output format
+    #[arg(long, global = true, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    /// Plain, deterministic output for CI and containers: no color, pager,
+    /// clipboard, network access, config file discovery, or search history
+    #[arg(long, global = true)]
+    minimal: bool,
+
+    /// Language of messages, e.g. `de`; defaults to LC_ALL, LC_MESSAGES, or
+    /// LANG, except with --minimal
+    #[arg(long, global = true, value_name = "CODE")]
+    lang: Option<String>,
+
+    /// When to color text output; `auto` colors only terminals and honors
+    /// NO_COLOR and CLICOLOR_FORCE
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto, value_name = "WHEN")]
+    color: ColorChoice,
+
+    /// Same as `--color never`
+    #[arg(long, global = true, conflicts_with = "color")]
+    no_color: bool,
+
+    /// Log what was loaded and how long parsing and searching took to
+    /// stderr; repeat for more detail
+    // Short only, so `categories --verbose` keeps meaning more detail
+    #[arg(short = 'v', global = true, action = ArgAction::Count)]
+    verbosity: u8,
+
+    /// Hide heuristics whose advice doesn't work without std
+    #[arg(long, global = true)]
+    no_std: bool,
+
+    /// Hide heuristics whose advice doesn't work on wasm32
+    #[arg(long, global = true)]
+    wasm: bool,
+
+    /// Hide heuristics whose advice doesn't work on embedded targets
+    #[arg(long, global = true)]
+    embedded: bool,
+
+    /// Hide heuristics whose memory overhead exceeds this multiple of the data size
+    #[arg(long, global = true, value_name = "FACTOR")]
+    max_overhead: Option<f64>,
+
+    /// Hide heuristics recommending crates that need a newer Rust than this
+    #[arg(long, global = true, value_name = "VERSION", value_parser = parse_rust_version)]
+    msrv: Option<RustVersion>,
+
+    /// Load heuristics from this file or directory on top of the built-in
+    /// ones; repeatable, later paths override earlier ones
+    #[arg(long, global = true, value_name = "PATH")]
+    extra: Vec<PathBuf>,
+}
+
+impl Cli {
+    /// Platforms every shown heuristic must support
+    fn platforms(&self) -> Vec<Platform> {
+        [
+            (self.no_std, Platform::NoStd),
+            (self.wasm, Platform::Wasm),
+            (self.embedded, Platform::Embedded),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, platform)| enabled.then_some(platform))
+        .collect()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// Human-readable, colored text
+    Text,
+    /// Versioned JSON for scripts and editor integrations
+    Json,
+    /// The JSON document as YAML
+    Yaml,
+    /// Markdown in the heuristic file format, for notes and bots
+    Markdown,
+    /// One spreadsheet row per heuristic (`export` only)
+    Csv,
+    /// A standalone web page (`export` only)
+    Html,
+    /// An Anki deck to import as flash cards (`export` only)
+    Anki,
+    /// An mdBook with a chapter per category (`export --out` only)
+    Mdbook,
+    /// A manual page per heuristic under `man7/` (`export --out` only)
+    Man,
+    /// An Obsidian vault with a note per heuristic and crate (`export --out`
+    /// only)
+    Obsidian,
+    /// Hugo content with YAML front matter (`export --out` only)
+    Hugo,
+    /// Zola content with TOML front matter (`export --out` only)
+    Zola,
+    /// An SQLite database with full-text search (`export -o` only)
+    Sqlite,
+    /// A Graphviz graph of how heuristics and crates relate (`export` only)
+    Dot,
+    /// A prebuilt search index and the JSON it indexes, for web frontends
+    /// (`export --out` only)
+    SearchIndex,
+}
+
+impl Format {
+    /// Whether the output is a versioned data document
+    fn is_structured(self) -> bool {
+        matches!(self, Format::Json | Format::Yaml)
+    }
+
+    /// Whether only `heuristics export` writes this format
+    fn is_export_only(self) -> bool {
+        matches!(
+            self,
+            Format::Csv
+                | Format::Html
+                | Format::Anki
+                | Format::Mdbook
+                | Format::Man
+                | Format::Obsidian
+                | Format::Hugo
+                | Format::Zola
+                | Format::Sqlite
+                | Format::Dot
+                | Format::SearchIndex
+        )
+    }
 }
 
 #[derive(Subcommand)]
@@ -15,119 +168,4860 @@ enum Commands {
     /// Search for heuristics by keywords
     Search {
         /// Keywords to search for
+        #[arg(required_unless_present = "again")]
         keywords: Vec<String>,
 
+        /// Re-run search number N from `heuristics history`
+        #[arg(long, value_name = "N", conflicts_with = "keywords")]
+        again: Option<usize>,
+
         /// Maximum number of results to show
         #[arg(short, long, default_value = "5")]
         limit: usize,
+
+        /// Print the sources cited by each result
+        #[arg(long)]
+        citations: bool,
+
+        /// Print links to benchmarks backing each result
+        #[arg(long)]
+        benchmarks: bool,
+
+        /// Only heuristics in this category or its subcategories
+        #[arg(long)]
+        category: Option<String>,
+
+        /// Only heuristics recommending this crate
+        #[arg(long = "crate", value_name = "NAME")]
+        crate_name: Option<String>,
+
+        /// Only heuristics mentioning this std type, e.g. HashMap or std::collections::BTreeMap
+        #[arg(long, value_name = "TYPE")]
+        std_type: Option<String>,
+
+        /// Only heuristics with this tag
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Afterwards, choose results by number to show in full
+        #[arg(short, long, conflicts_with_all = ["output", "count"])]
+        select: bool,
+
+        #[command(flatten)]
+        script: ScriptOutput,
+    },
+
+    /// Find heuristics containing an exact phrase, or a regex with --regex
+    Grep {
+        /// Text to look for in the full content of each heuristic
+        pattern: String,
+
+        /// Treat the pattern as a regular expression
+        #[arg(short = 'E', long)]
+        regex: bool,
+
+        /// Match regardless of case
+        #[arg(short, long)]
+        ignore_case: bool,
+
+        /// Lines of context to print around each match
+        #[arg(short = 'C', long, default_value = "1", value_name = "LINES")]
+        context: usize,
+    },
+
+    /// Write a new heuristic in $EDITOR and add it to your own pack
+    Add {
+        /// Category to file the heuristic under
+        #[arg(long, default_value = "My Heuristics")]
+        category: String,
+
+        /// The question the heuristic answers, e.g. "Need a bounded cache?"
+        #[arg(long)]
+        title: Option<String>,
+    },
+
+    /// Change a heuristic in $EDITOR; built-in ones are copied into your own pack
+    Edit {
+        /// Id or title of the heuristic
+        heuristic: String,
+    },
+
+    /// Attach a note to a heuristic, or list its notes; `show` prints them
+    Note {
+        /// Id or title of the heuristic
+        heuristic: String,
+
+        /// The note to add; without one, the heuristic's notes are listed
+        text: Option<String>,
+
+        /// Delete every note on the heuristic
+        #[arg(long, conflicts_with = "text")]
+        clear: bool,
+    },
+
+    /// List past searches, most recent first
+    History {
+        /// Maximum number of searches to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+
+        /// Forget every past search
+        #[arg(long)]
+        clear: bool,
     },
 
     /// List all categories
-    Categories,
+    Categories {
+        /// Show each category's description and number of heuristics
+        #[arg(long)]
+        verbose: bool,
+    },
 
     /// Show all heuristics in a category
     Category {
         /// Category name
         name: String,
+
+        /// Print the sources cited by each result
+        #[arg(long)]
+        citations: bool,
+
+        /// Print links to benchmarks backing each result
+        #[arg(long)]
+        benchmarks: bool,
+
+        #[command(flatten)]
+        script: ScriptOutput,
     },
 
     /// List all heuristics
+    List {
+        /// Order of the list (default: pack order)
+        #[arg(long, value_enum)]
+        sort: Option<ListOrder>,
+
+        /// Show the list in sections
+        #[arg(long, value_enum)]
+        group_by: Option<ListGroup>,
+
+        #[command(flatten)]
+        script: ScriptOutput,
+    },
+
+    /// List every crate the heuristics recommend
+    Crates {
+        /// Order of the list
+        #[arg(long, value_enum, default_value_t = CrateOrder::Name)]
+        sort: CrateOrder,
+
+        /// Show only the crate names and counts
+        #[arg(short, long)]
+        quiet: bool,
+    },
+
+    /// Choose a heuristic by fuzzy-matching its title, then show it
+    Pick {
+        /// Initial query; refine it at the prompt
+        query: Option<String>,
+
+        /// Print only the chosen heuristic's id, for use in scripts
+        #[arg(long)]
+        print_id: bool,
+    },
+
+    /// Answer yes/no questions down the decision tree to a recommendation
+    Tree {
+        /// Start with the questions of this category
+        #[arg(long)]
+        category: Option<String>,
+
+        /// Print only the recommended heuristic's id, for use in scripts
+        #[arg(long)]
+        print_id: bool,
+    },
+
+    /// Explore interactively: search, show, and bookmark without reloading
+    Repl {
+        /// Maximum number of search results to list
+        #[arg(short, long, default_value = "5")]
+        limit: usize,
+
+        /// Wrap prose of shown heuristics at this many columns [default: terminal width, up to 100]
+        #[arg(long)]
+        width: Option<usize>,
+    },
+
+    /// Show today's tip: one heuristic a day, none repeated until every
+    /// one has been shown
+    Daily {
+        /// Print nothing if today's tip was already shown, e.g. from a shell profile
+        #[arg(long)]
+        once: bool,
+    },
+
+    /// Show a randomly chosen heuristic
+    Random {
+        /// Only choose from this category
+        #[arg(long)]
+        category: Option<String>,
+
+        /// Choose the same heuristic all day (UTC), for a daily tip in a shell profile
+        #[arg(long)]
+        seed_by_date: bool,
+    },
+
+    /// Show the complete content of one heuristic
+    Show {
+        /// Id or title of the heuristic
+        heuristic: String,
+
+        /// Wrap prose at this many columns [default: terminal width, up to 100]
+        #[arg(long)]
+        width: Option<usize>,
+
+        /// Also open the docs.rs page of the first crate it recommends
+        #[arg(long)]
+        open: bool,
+    },
+
+    /// Summarize everything the heuristics say about a crate
+    Explain {
+        /// Crate name, e.g. dashmap
+        name: String,
+
+        /// Wrap prose at this many columns [default: terminal width, up to 100]
+        #[arg(long)]
+        width: Option<usize>,
+    },
+
+    /// Open the docs.rs page of a crate the heuristics recommend
+    OpenDocs {
+        /// Crate name, as mentioned by a heuristic
+        name: String,
+
+        /// Also open its crates.io page
+        #[arg(long)]
+        crates_io: bool,
+
+        /// Print the URLs instead of opening them
+        #[arg(long)]
+        print: bool,
+    },
+
+    /// Compare heuristics side by side
+    Compare {
+        /// Ids, titles, or names like `hashmap` for each heuristic to compare
+        #[arg(num_args = 2.., required = true)]
+        terms: Vec<String>,
+
+        /// Fit the table into this many columns
+        #[arg(long, default_value = "100")]
+        width: usize,
+    },
+
+    /// Show which heuristics, actions, and crate recommendations changed
+    /// between two packs; like diff, exits 1 when they differ and 2 on errors
+    Diff {
+        /// The older pack file
+        old: PathBuf,
+
+        /// The newer pack file (default: the stock pack, embedded or updated)
+        new: Option<PathBuf>,
+
+        /// Wrap prose at this many columns [default: terminal width, up to 100]
+        #[arg(long)]
+        width: Option<usize>,
+    },
+
+    /// Suggest heuristics to read based on a crate's dependencies and features
+    Suggest {
+        /// The crate's manifest
+        #[arg(default_value = "Cargo.toml")]
+        manifest: PathBuf,
+
+        /// Maximum number of suggestions to show
+        #[arg(short, long, default_value = "10")]
+        limit: usize,
+    },
+
+    /// Print a man page in roff: the CLI's own, or one for a heuristic
+    ///
+    /// View it with `heuristics man | man -l -`.
+    Man {
+        /// Id or title of the heuristic; the CLI's page without one
+        heuristic: Option<String>,
+    },
+
+    /// Check heuristics for problems: the loaded ones, or a pack you're writing
+    Doctor {
+        /// A heuristic file or directory to check instead, against the
+        /// built-in heuristics it may refer to
+        path: Option<PathBuf>,
+
+        /// Also request every link to find dead ones (needs the `net` feature)
+        #[arg(long)]
+        check_links: bool,
+    },
+
+    /// Check a heuristic file every time it's saved, for pack authors (needs the `watch` feature)
+    Watch {
+        /// The heuristic file being edited
+        file: PathBuf,
+    },
+
+    /// Print a JSON Schema of TOML heuristic files, to validate generated packs with (needs the `schema` feature)
+    Schema {
+        /// Describe JSON database snapshots instead
+        #[arg(long)]
+        snapshot: bool,
+    },
+
+    /// Serve a web UI with search, categories, and a page per heuristic (needs the `server` feature)
+    Serve {
+        /// Port to listen on
+        #[arg(long, default_value = "7878")]
+        port: u16,
+
+        /// Address to listen on; 0.0.0.0 makes the UI reachable from other machines
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+    },
+
+    /// Download the latest pack into the local cache, where it replaces the built-in one (needs the `fetch` feature)
+    Update {
+        /// URL of the pack, verified against the checksum at `<URL>.sha256`
+        #[arg(default_value = heuristics::PUBLISHED_PACK_URL)]
+        url: String,
+
+        /// Expected SHA-256 of the pack, instead of downloading `<URL>.sha256`;
+        /// packs aren't signed, so pin one from a source you trust
+        #[arg(long, value_name = "HEX")]
+        sha256: Option<String>,
+
+        /// Only report whether the cached pack is out of date; exits 1 if it is
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Write the heuristics out as JSON, YAML, markdown, CSV, or HTML, chosen with --format
+    Export {
+        /// Only export this category
+        #[arg(long)]
+        category: Option<String>,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Write a multi-file export into this directory: with html, a
+        /// static site with client-side search; with mdbook, a book; with
+        /// man, a page per heuristic under `man7/`; with obsidian, a vault;
+        /// with hugo or zola, pages under `content/heuristics/`; with
+        /// search-index, the index and the JSON it covers
+        #[arg(long, value_name = "DIR", conflicts_with = "output")]
+        out: Option<PathBuf>,
+    },
+
+    /// Convert a cheatsheet, or a JSON or CSV export, into heuristic markdown
+    Import {
+        /// Format of the file (default: json or csv, from its extension)
+        #[arg(long, value_enum)]
+        from: Option<ImportFormat>,
+
+        /// File to convert
+        path: PathBuf,
+
+        /// Category for entries the file doesn't group (default: the file name)
+        #[arg(long)]
+        category: Option<String>,
+
+        /// Add the heuristics to this pack file instead of printing them,
+        /// replacing any with the same id
+        #[arg(long, value_name = "PACK")]
+        into: Option<PathBuf>,
+    },
+
+    /// Show or reset what heuristics stores on disk
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+
+    /// Save searches under a name and run them as `heuristics NAME`
+    Alias {
+        #[command(subcommand)]
+        action: AliasAction,
+    },
+
+    /// A saved search, by its alias
+    #[command(external_subcommand)]
+    Saved(Vec<String>),
+}
+
+#[derive(Subcommand)]
+enum CacheAction {
+    /// List each stored file or directory with its size
+    Status,
+
+    /// Delete downloaded packs, the search history, the daily tip log, and view counts
+    Clear {
+        /// Also delete bookmarks and notes; your own pack is always kept
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Print the cache directory, for scripts
+    Path {
+        /// Print the data directory instead, where history, bookmarks, notes, and your pack live
+        #[arg(long)]
+        data: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum AliasAction {
+    /// Save a query: keywords plus `category:`, `crate:`, `std-type:`, or `tag:` filters
+    Set {
+        /// Name to run it by
+        name: String,
+
+        /// The query, e.g. 'category:"Data Structures" lookup insert'
+        query: String,
+    },
+
+    /// Forget a saved query
+    Remove { name: String },
+
+    /// List the saved queries
     List,
 }
 
-fn main() {
-    let cli = Cli::parse();
-    let db = load_heuristics();
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ColorChoice {
+    /// Color when stdout is a terminal and NO_COLOR is unset
+    Auto,
+    /// Always color, even when piped
+    Always,
+    /// Never color
+    Never,
+}
+
+/// Options for consuming a list of heuristics from shell scripts
+#[derive(Args)]
+struct ScriptOutput {
+    /// One result per line: just its id, or id, category, title, and action separated by tabs
+    #[arg(long, value_enum, value_name = "MODE")]
+    output: Option<LineOutput>,
+
+    /// Print only the number of heuristics found
+    #[arg(long, conflicts_with = "output")]
+    count: bool,
+
+    /// Leave out headers, footers, and hints around the results
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Exit with status 1 when nothing is found
+    #[arg(long)]
+    fail_if_empty: bool,
+}
+
+impl ScriptOutput {
+    /// Exit status for a command that found `count` heuristics
+    fn status(&self, count: usize) -> ExitCode {
+        if self.fail_if_empty && count == 0 {
+            ExitCode::FAILURE
+        } else {
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum LineOutput {
+    /// Heuristic ids
+    Ids,
+    /// Tab-separated id, category, title, and action
+    Tsv,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum CrateOrder {
+    /// Alphabetically
+    Name,
+    /// Most mentioned first
+    Count,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ListOrder {
+    /// Alphabetically by title
+    Title,
+    /// Alphabetically by category, then in pack order
+    Category,
+    /// Most recommended crates first
+    CrateCount,
+    /// Most recently updated first, by `**Updated:**` or `**Since:**`
+    RecentlyUpdated,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ListGroup {
+    /// One section per category
+    Category,
+    /// One section per recommended crate; a heuristic may appear in several
+    Crate,
+    /// One section per experience level
+    Level,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ImportFormat {
+    /// A tldr page
+    Tldr,
+    /// A cheat sheet: `# comment` lines followed by commands
+    Cheat,
+    /// Free-form markdown notes
+    Notes,
+    /// A snapshot or `export --format json` document (needs the `json` feature)
+    Json,
+    /// A spreadsheet in the columns of `export --format csv`
+    Csv,
+}
+
+fn main() -> ExitCode {
+    let mut matches = Cli::command().get_matches();
+    let mut cli =
+        Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    init_logging(&cli);
+    // --minimal output doesn't depend on the environment's locale
+    let lang = cli
+        .lang
+        .clone()
+        .or_else(|| if cli.minimal { None } else { language_from_env() });
+    if let Some(lang) = lang
+        && !set_language(&lang)
+        && cli.lang.is_some()
+    {
+        let known: Vec<&str> =
+            CATALOGS.iter().map(|(code, _)| *code).collect();
+        eprintln!(
+            "{}",
+            format!(
+                "Unknown language {}; choose one of {}",
+                lang,
+                known.join(", ")
+            )
+            .red()
+        );
+        return ExitCode::from(2);
+    }
+    // --minimal promises no config file discovery
+    let config =
+        if cli.minimal { Ok(Config::default()) } else { Config::load() };
+    let config = match config {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err.red());
+            return ExitCode::FAILURE;
+        }
+    };
+    // A saved query runs as the search it stands for
+    if let Commands::Saved(words) = &cli.command {
+        let args: Vec<String> = std::env::args().collect();
+        let Some(expanded) = expand_alias(&config, &args, words) else {
+            eprintln!(
+                "{}",
+                message("unknown-alias", &[("name", &words[0])]).red()
+            );
+            eprintln!("{}", message("unknown-alias-hint", &[]));
+            return ExitCode::from(2);
+        };
+        tracing::info!(alias = %words[0], "expanded saved query");
+        matches = Cli::command().get_matches_from(expanded);
+        cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    }
+    // Flags given on the command line win over the config file
+    let given =
+        |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+    if !given("format")
+        && let Some(format) = config.format
+    {
+        cli.format = format;
+    }
+    if !given("color")
+        && !cli.no_color
+        && let Some(color) = config.color
+    {
+        cli.color = color;
+    }
+    cli.extra.splice(0..0, config.extra.iter().cloned());
+    let limit_given = matches.subcommand().is_some_and(|(name, command)| {
+        matches!(name, "search" | "repl")
+            && command.value_source("limit") == Some(ValueSource::CommandLine)
+    });
+    // Markdown goes to files and bots; commands without a markdown form print plain text
+    if cli.minimal
+        || cli.no_color
+        || cli.color == ColorChoice::Never
+        || cli.format == Format::Markdown
+    {
+        colored::control::set_override(false);
+    } else if cli.color == ColorChoice::Always {
+        colored::control::set_override(true);
+    }
+    if cli.format.is_export_only()
+        && !matches!(cli.command, Commands::Export { .. })
+    {
+        eprintln!("{}", "csv, html, anki, mdbook, man, obsidian, hugo, zola, sqlite, dot, and search-index output are only available for `heuristics export`.".red());
+        return ExitCode::from(2);
+    }
+    let bullet = if cli.minimal { "-" } else { "•" };
+    // The user's own pack comes first, so --extra files can override it
+    let mut extras: Vec<PathBuf> = user_pack_path()
+        .filter(|path| !cli.minimal && path.exists())
+        .into_iter()
+        .collect();
+    extras.extend(cli.extra.iter().cloned());
+    let db = match HeuristicDb::load_default_with_extras(&extras) {
+        Ok(db) => db,
+        Err(err) => {
+            eprintln!("{}", err.to_string().red());
+            return ExitCode::FAILURE;
+        }
+    };
+    let platforms = cli.platforms();
+    // Commands naming a category look past the configured ones
+    let names_category = matches!(
+        &cli.command,
+        Commands::Category { .. }
+            | Commands::Search { category: Some(_), .. }
+            | Commands::Random { category: Some(_), .. }
+            | Commands::Tree { category: Some(_), .. }
+            | Commands::Export { category: Some(_), .. }
+    );
+    let scope: Option<HashSet<&str>> =
+        (!config.categories.is_empty() && !names_category).then(|| {
+            config
+                .categories
+                .iter()
+                .flat_map(|c| db.by_category(c))
+                .map(|h| h.id.as_str())
+                .collect()
+        });
+    if scope.is_some() {
+        for category in
+            config.categories.iter().filter(|c| db.by_category(c).is_empty())
+        {
+            eprintln!(
+                "{}",
+                format!(
+                    "The config file names no known category: {}",
+                    category
+                )
+                .yellow()
+            );
+        }
+    }
+    let shows = |h: &Heuristic| {
+        let overhead = h.memory_overhead.as_ref().and_then(|m| m.factor);
+        platforms.iter().all(|&p| h.applies_to.supports(p))
+            && cli
+                .max_overhead
+                .is_none_or(|max| overhead.is_none_or(|f| f <= max))
+            && cli.msrv.is_none_or(|version| h.supports_rust(version))
+            && scope.as_ref().is_none_or(|ids| ids.contains(h.id.as_str()))
+    };
+
+    // --minimal output doesn't depend on the terminal
+    let prose_width = if cli.minimal {
+        DEFAULT_WIDTH
+    } else {
+        terminal_width().min(MAX_PROSE_WIDTH)
+    };
+
+    // --minimal leaves out the user's own data
+    let notes = |id: &str| if cli.minimal { Vec::new() } else { notes_on(id) };
 
     match cli.command {
-        Commands::Search { keywords, limit } => {
-            let keyword_refs: Vec<&str> = keywords.iter().map(|s| s.as_str()).collect();
-            let results = db.search(&keyword_refs);
+        Commands::Search {
+            keywords,
+            again,
+            limit,
+            citations,
+            benchmarks,
+            category,
+            crate_name,
+            std_type,
+            tag,
+            select,
+            script,
+        } => {
+            if select && cli.format != Format::Text {
+                eprintln!("{}", "--select needs text output.".red());
+                return ExitCode::from(2);
+            }
+            let keywords = match again {
+                Some(n) => match load_history()
+                    .into_iter()
+                    .rev()
+                    .nth(n.saturating_sub(1))
+                {
+                    Some(keywords) if n > 0 => keywords,
+                    _ => {
+                        eprintln!(
+                            "{}",
+                            format!("No search number {} in the history.", n)
+                                .red()
+                        );
+                        return ExitCode::FAILURE;
+                    }
+                },
+                None => keywords,
+            };
+            // --minimal promises no files outside the ones given
+            if !cli.minimal {
+                record_search(&keywords);
+            }
+            let limit = if limit_given {
+                limit
+            } else {
+                config.limit.unwrap_or(limit)
+            };
+            let keyword_refs: Vec<&str> =
+                keywords.iter().map(|s| s.as_str()).collect();
+            let filter = SearchFilter { category, crate_name, std_type, tag };
+            let mut scored =
+                db.search_filtered(&keyword_refs, config.weights, &filter);
+            scored.retain(|(h, _)| shows(h));
+            let results: Vec<&Heuristic> =
+                scored.iter().map(|&(h, _)| h).collect();
+            let status = script.status(results.len());
+
+            if script.count {
+                println!("{}", results.len());
+                return status;
+            }
+            if let Some(output) = script.output {
+                print_lines(output, results.into_iter().take(limit));
+                return status;
+            }
+
+            if cli.format.is_structured() {
+                let shown: Vec<Value> = scored
+                    .iter()
+                    .take(limit)
+                    .map(|&(h, score)| {
+                        let mut result = heuristic_json(h);
+                        result["score"] = json!(score);
+                        result
+                    })
+                    .collect();
+                print_document(
+                    cli.format,
+                    json!({
+                        "command": "search",
+                        "query": keywords,
+                        "total": results.len(),
+                        "results": shown,
+                    }),
+                );
+                return status;
+            }
+
+            if cli.format == Format::Markdown {
+                print_markdown(results.into_iter().take(limit));
+                return status;
+            }
 
             if results.is_empty() {
-                println!("{}", "No heuristics found matching your keywords.".yellow());
-                println!("\nTry broader terms like: hash, cache, distributed, concurrent, etc.");
-                return;
+                if !script.quiet {
+                    println!("{}", message("search-none", &[]).yellow());
+                    println!("\n{}", message("search-hint", &[]));
+                }
+                return status;
             }
 
-            println!("{}", format!("Found {} heuristic(s):\n", results.len()).green().bold());
+            if !script.quiet {
+                println!(
+                    "{}\n",
+                    message("search-found", &[("count", &results.len())])
+                        .green()
+                        .bold()
+                );
+            }
 
             for (i, heuristic) in results.iter().take(limit).enumerate() {
-                print_heuristic(heuristic, i + 1);
+                print_heuristic(heuristic, i + 1, citations, benchmarks);
             }
 
-            if results.len() > limit {
+            if results.len() > limit && !script.quiet {
                 println!(
                     "\n{}",
-                    format!("... and {} more. Use --limit to show more results.", results.len() - limit)
-                        .dimmed()
+                    message(
+                        "search-more",
+                        &[("count", &(results.len() - limit))]
+                    )
+                    .dimmed()
                 );
             }
+
+            if select {
+                let shown = &results[..results.len().min(limit)];
+                for (i, heuristic) in
+                    select_results(shown).into_iter().enumerate()
+                {
+                    if i > 0 {
+                        let rule = if cli.minimal { "-" } else { "─" };
+                        println!(
+                            "\n{}\n",
+                            rule.repeat(prose_width.min(40)).dimmed()
+                        );
+                    }
+                    show_heuristic(
+                        &db,
+                        heuristic,
+                        &notes(&heuristic.id),
+                        "search",
+                        prose_width,
+                        cli.format,
+                        bullet,
+                    );
+                }
+            }
         }
 
-        Commands::Categories => {
-            let categories = db.categories();
-            println!("{}\n", "Available categories:".green().bold());
+        Commands::Grep { pattern, regex, ignore_case, context } => {
+            let source =
+                if regex { pattern.clone() } else { regex::escape(&pattern) };
+            let matcher = match regex::RegexBuilder::new(&source)
+                .case_insensitive(ignore_case)
+                .build()
+            {
+                Ok(matcher) => matcher,
+                Err(err) => {
+                    eprintln!("{}", format!("Invalid pattern: {}", err).red());
+                    return ExitCode::from(2);
+                }
+            };
+            // Line numbers count from the heuristic's header
+            let results: Vec<(&Heuristic, Vec<usize>)> = db
+                .all()
+                .iter()
+                .filter(|h| shows(h))
+                .filter_map(|h| {
+                    let lines: Vec<usize> = h
+                        .content
+                        .lines()
+                        .enumerate()
+                        .filter(|(_, line)| matcher.is_match(line))
+                        .map(|(i, _)| i)
+                        .collect();
+                    (!lines.is_empty()).then_some((h, lines))
+                })
+                .collect();
 
-            for cat in categories {
-                println!("  • {}", cat.cyan());
+            if cli.format.is_structured() {
+                let results: Vec<Value> = results
+                    .iter()
+                    .map(|(h, lines)| {
+                        let content: Vec<&str> = h.content.lines().collect();
+                        let matches: Vec<Value> =
+                            lines.iter().map(|&i| json!({ "line": i + 1, "text": content[i] })).collect();
+                        json!({ "id": h.id, "title": h.title, "category": h.category, "matches": matches })
+                    })
+                    .collect();
+                print_document(
+                    cli.format,
+                    json!({
+                        "command": "grep",
+                        "pattern": pattern,
+                        "regex": regex,
+                        "ignore_case": ignore_case,
+                        "results": results,
+                    }),
+                );
+                return ExitCode::SUCCESS;
             }
 
-            println!("\n{}", "Use 'heuristics category <name>' to see heuristics in a category.".dimmed());
+            if results.is_empty() {
+                println!(
+                    "{}",
+                    format!("No heuristics contain '{}'.", pattern).yellow()
+                );
+                return ExitCode::SUCCESS;
+            }
+            for (heuristic, lines) in &results {
+                println!(
+                    "{} {}",
+                    heuristic.title.cyan().bold(),
+                    format!("({})", heuristic.id).dimmed()
+                );
+                print_matches(&heuristic.content, lines, &matcher, context);
+                println!();
+            }
         }
 
-        Commands::Category { name } => {
-            let results = db.by_category(&name);
+        Commands::Add { category, title } => {
+            let Some(pack) = user_pack_path() else {
+                eprintln!(
+                    "{}",
+                    "No data directory; set XDG_DATA_HOME or HOME.".red()
+                );
+                return ExitCode::FAILURE;
+            };
+            let template = heuristic_template(
+                &category,
+                title.as_deref().unwrap_or("Need ...?"),
+            );
+            let Some(entry) = edit_heuristic(&template, |entry| {
+                let document =
+                    parse_markdown_with(entry, &ParseOptions::strict())
+                        .map_err(parse_problems)?;
+                match document
+                    .heuristics
+                    .iter()
+                    .find(|h| db.get(&h.id).is_some())
+                {
+                    Some(taken) => Err(format!(
+                        "a heuristic with id '{}' already exists",
+                        taken.id
+                    )),
+                    None => Ok(()),
+                }
+            }) else {
+                return ExitCode::FAILURE;
+            };
 
-            if results.is_empty() {
-                println!("{}", format!("No category found: {}", name).red());
-                println!("\nUse 'heuristics categories' to see available categories.");
-                return;
+            let mut content =
+                std::fs::read_to_string(&pack).unwrap_or_default();
+            if !content.is_empty() {
+                content.push_str(if content.ends_with('\n') {
+                    "\n"
+                } else {
+                    "\n\n"
+                });
+            }
+            content.push_str(&entry);
+            let written = pack
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::write(&pack, content));
+            if let Err(err) = written {
+                eprintln!(
+                    "{}",
+                    format!("Could not write {}: {}", pack.display(), err)
+                        .red()
+                );
+                return ExitCode::FAILURE;
             }
+            println!("{}", format!("Added to {}", pack.display()).green());
+        }
 
-            println!("{}\n", format!("Heuristics in category '{}':", name).green().bold());
+        Commands::Edit { heuristic } => {
+            let Some(found) = db.resolve(&heuristic) else {
+                eprintln!(
+                    "{}",
+                    format!("No heuristic found: {}", heuristic).red()
+                );
+                return ExitCode::FAILURE;
+            };
+            let Some(pack) = user_pack_path() else {
+                eprintln!(
+                    "{}",
+                    "No data directory; set XDG_DATA_HOME or HOME.".red()
+                );
+                return ExitCode::FAILURE;
+            };
+            let ours = if pack.exists() {
+                heuristics::load_heuristics_from_path(&pack)
+            } else {
+                Ok(HeuristicDb::new(Vec::new()))
+            };
+            let ours = match ours {
+                Ok(ours) => ours,
+                Err(err) => {
+                    eprintln!("{}", err.to_string().red());
+                    return ExitCode::FAILURE;
+                }
+            };
 
-            for (i, heuristic) in results.iter().enumerate() {
-                print_heuristic(heuristic, i + 1);
+            let draft = format!(
+                "{} Lines starting with {0} are removed. Keep the **Id:** line if you retitle it. -->\n## {}\n\n{}",
+                TEMPLATE_COMMENT,
+                found.category,
+                found.render_full(RenderStyle::Markdown, 0)
+            );
+            let Some(entry) = edit_heuristic(&draft, |entry| {
+                let document =
+                    parse_markdown_with(entry, &ParseOptions::strict())
+                        .map_err(parse_problems)?;
+                match document.heuristics.as_slice() {
+                    [edited] if edited.id == found.id => Ok(()),
+                    [edited] => Err(format!(
+                        "the id changed to '{}'; add `**Id:** {}` under the title to keep it",
+                        edited.id, found.id
+                    )),
+                    _ => Err("write exactly one heuristic".to_string()),
+                }
+            }) else {
+                return ExitCode::FAILURE;
+            };
+
+            // The edited copy replaces the pack's own, or overrides the built-in one by id
+            let edited = HeuristicDb::from_markdown(&entry)
+                .expect("checked by the editor loop");
+            let content = ours
+                .merge(edited, MergePolicy::PreferTheirs)
+                .expect("preferring theirs never conflicts")
+                .to_markdown();
+            let written = pack
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::write(&pack, content));
+            if let Err(err) = written {
+                eprintln!(
+                    "{}",
+                    format!("Could not write {}: {}", pack.display(), err)
+                        .red()
+                );
+                return ExitCode::FAILURE;
+            }
+
+            let layers: Vec<PathBuf> = std::iter::once(pack.clone())
+                .chain(cli.extra.iter().cloned())
+                .collect();
+            let reloaded = match HeuristicDb::load_default_with_extras(&layers)
+            {
+                Ok(reloaded) => reloaded,
+                Err(err) => {
+                    eprintln!("{}", err.to_string().red());
+                    return ExitCode::FAILURE;
+                }
+            };
+            println!("{}", format!("Saved to {}", pack.display()).green());
+            // --extra files load after the user's pack and win over it
+            if let Some(shadowing) = reloaded
+                .get(&found.id)
+                .filter(|h| h.provenance != Provenance::File(pack.clone()))
+            {
+                eprintln!(
+                    "{}",
+                    format!(
+                        "{} still overrides this heuristic.",
+                        shadowing.provenance
+                    )
+                    .yellow()
+                );
             }
         }
 
-        Commands::List => {
-            let all = db.all();
-            println!("{}\n", format!("All {} heuristics:", all.len()).green().bold());
+        Commands::Note { heuristic, text, clear } => {
+            let Some(found) = db.resolve(&heuristic) else {
+                eprintln!(
+                    "{}",
+                    format!("No heuristic found: {}", heuristic).red()
+                );
+                return ExitCode::FAILURE;
+            };
+
+            if clear || text.is_some() {
+                let mut notes = load_notes();
+                match text {
+                    // One note per line in the file
+                    Some(text) => notes.push((
+                        found.id.clone(),
+                        text.split_whitespace().collect::<Vec<_>>().join(" "),
+                    )),
+                    None => notes.retain(|(id, _)| *id != found.id),
+                }
+                if let Err(err) = save_notes(&notes) {
+                    eprintln!(
+                        "{}",
+                        format!("Could not save notes: {}", err).red()
+                    );
+                    return ExitCode::FAILURE;
+                }
+                return ExitCode::SUCCESS;
+            }
 
-            for (i, heuristic) in all.iter().enumerate() {
-                println!("{}. {} ({})",
-                    format!("{:3}", i + 1).dimmed(),
-                    heuristic.title.cyan(),
-                    heuristic.category.yellow()
+            let notes = notes_on(&found.id);
+            if cli.format.is_structured() {
+                print_document(
+                    cli.format,
+                    json!({ "command": "note", "id": found.id, "notes": notes }),
                 );
+            } else if notes.is_empty() {
+                println!(
+                    "{}",
+                    format!("No notes on '{}'.", found.title).yellow()
+                );
+            } else {
+                for note in notes {
+                    println!("  {} {}", bullet, note);
+                }
             }
         }
-    }
-}
 
-fn print_heuristic(heuristic: &Heuristic, index: usize) {
-    println!("{}", format!("{}. {}", index, heuristic.title).cyan().bold());
+        Commands::History { limit, clear } => {
+            if clear {
+                if let Some(path) = history_path()
+                    && let Err(err) = std::fs::remove_file(&path)
+                    && err.kind() != std::io::ErrorKind::NotFound
+                {
+                    eprintln!(
+                        "{}",
+                        format!(
+                            "Could not remove {}: {}",
+                            path.display(),
+                            err
+                        )
+                        .red()
+                    );
+                    return ExitCode::FAILURE;
+                }
+                return ExitCode::SUCCESS;
+            }
 
-    if !heuristic.action.is_empty() {
-        println!("   {} {}", "Action:".green().bold(), heuristic.action);
-    }
+            let history = load_history();
+            let recent: Vec<(usize, &Vec<String>)> = history
+                .iter()
+                .rev()
+                .enumerate()
+                .map(|(i, k)| (i + 1, k))
+                .take(limit)
+                .collect();
+            if cli.format.is_structured() {
+                let entries: Vec<Value> = recent.iter().map(|(n, keywords)| json!({ "number": n, "query": keywords })).collect();
+                print_document(
+                    cli.format,
+                    json!({ "command": "history", "entries": entries }),
+                );
+                return ExitCode::SUCCESS;
+            }
+            if recent.is_empty() {
+                println!("{}", message("history-none", &[]).yellow());
+                return ExitCode::SUCCESS;
+            }
+            for (n, keywords) in recent {
+                println!(
+                    "{} {}",
+                    format!("{:3}", n).dimmed(),
+                    keywords.join(" ").cyan()
+                );
+            }
+        }
 
-    if !heuristic.crates.is_empty() {
-        println!("   {} {}",
-            "Crates:".green().bold(),
-            heuristic.crates.join(", ").yellow()
-        );
-    }
+        Commands::Categories { verbose } => {
+            let categories = db.category_info();
 
-    if !heuristic.std_types.is_empty() {
-        println!("   {} {}",
-            "Std types:".green().bold(),
-            heuristic.std_types.join(", ").yellow()
-        );
-    }
+            if cli.format.is_structured() {
+                let names: Vec<&str> =
+                    categories.iter().map(|c| c.name.as_str()).collect();
+                let details: Vec<Value> = categories
+                    .iter()
+                    .map(|c| json!({ "name": c.name, "description": c.description, "heuristic_count": c.heuristic_count }))
+                    .collect();
+                print_document(
+                    cli.format,
+                    json!({ "command": "categories", "categories": names, "details": details }),
+                );
+                return ExitCode::SUCCESS;
+            }
 
-    println!("   {} {}", "Category:".green().bold(), heuristic.category.dimmed());
-    println!();
+            if cli.format == Format::Markdown {
+                for category in &categories {
+                    let path = category.path();
+                    let indent = "  ".repeat(path.len().saturating_sub(1));
+                    match category.description.is_empty() {
+                        true => println!(
+                            "{}- {}",
+                            indent,
+                            path.last().unwrap_or(&"")
+                        ),
+                        false => println!(
+                            "{}- {}: {}",
+                            indent,
+                            path.last().unwrap_or(&""),
+                            category.description
+                        ),
+                    }
+                }
+                return ExitCode::SUCCESS;
+            }
+
+            println!("{}\n", "Available categories:".green().bold());
+
+            // Print hierarchical categories as an indented tree
+            let mut previous: Vec<&str> = Vec::new();
+            for category in &categories {
+                let path = category.path();
+                let shared = path
+                    .iter()
+                    .zip(&previous)
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                for (depth, level) in path.iter().enumerate().skip(shared) {
+                    let indent = "  ".repeat(depth + 1);
+                    if verbose && depth + 1 == path.len() {
+                        println!(
+                            "{}{} {} {}",
+                            indent,
+                            bullet,
+                            level.cyan(),
+                            format!("({})", category.heuristic_count).dimmed()
+                        );
+                        if !category.description.is_empty() {
+                            println!(
+                                "{}  {}",
+                                indent,
+                                category.description.dimmed()
+                            );
+                        }
+                    } else {
+                        println!("{}{} {}", indent, bullet, level.cyan());
+                    }
+                }
+                previous = path;
+            }
+
+            println!("\n{}", message("categories-hint", &[]).dimmed());
+        }
+
+        Commands::Category { name, citations, benchmarks, script } => {
+            let mut results = db.by_category(&name);
+            results.retain(|h| shows(h));
+            let status = script.status(results.len());
+
+            if script.count {
+                println!("{}", results.len());
+                return status;
+            }
+            if let Some(output) = script.output {
+                print_lines(output, results.into_iter());
+                return status;
+            }
+
+            if cli.format.is_structured() {
+                let shown: Vec<Value> =
+                    results.iter().map(|h| heuristic_json(h)).collect();
+                print_document(
+                    cli.format,
+                    json!({ "command": "category", "category": name, "results": shown }),
+                );
+                return status;
+            }
+
+            if cli.format == Format::Markdown {
+                print_markdown(results.into_iter());
+                return status;
+            }
+
+            if results.is_empty() {
+                if !script.quiet {
+                    println!(
+                        "{}",
+                        message("category-none", &[("name", &name)]).red()
+                    );
+                    println!("\n{}", message("category-hint", &[]));
+                }
+                return status;
+            }
+
+            if !script.quiet {
+                println!(
+                    "{}\n",
+                    message("category-header", &[("name", &name)])
+                        .green()
+                        .bold()
+                );
+            }
+
+            for (i, heuristic) in results.iter().enumerate() {
+                print_heuristic(heuristic, i + 1, citations, benchmarks);
+            }
+        }
+
+        Commands::List { sort, group_by, script } => {
+            let mut all: Vec<&Heuristic> =
+                db.all().iter().filter(|h| shows(h)).collect();
+            if let Some(sort) = sort {
+                sort_heuristics(&mut all, sort);
+            }
+            let groups =
+                group_by.map(|group_by| group_heuristics(&all, group_by));
+            let status = script.status(all.len());
+
+            if script.count {
+                println!("{}", all.len());
+                return status;
+            }
+            if let Some(output) = script.output {
+                print_lines(output, all.into_iter());
+                return status;
+            }
+
+            if cli.format.is_structured() {
+                let shown: Vec<Value> =
+                    all.iter().map(|h| heuristic_json(h)).collect();
+                let mut document = json!({
+                    "command": "list",
+                    "fingerprint": format!("{:016x}", db.fingerprint()),
+                    "results": shown,
+                });
+                if let Some(groups) = &groups {
+                    let groups: Vec<Value> = groups
+                        .iter()
+                        .map(|(name, heuristics)| {
+                            let ids: Vec<&str> = heuristics
+                                .iter()
+                                .map(|h| h.id.as_str())
+                                .collect();
+                            json!({ "name": name, "ids": ids })
+                        })
+                        .collect();
+                    document["groups"] = json!(groups);
+                }
+                print_document(cli.format, document);
+                return status;
+            }
+
+            if cli.format == Format::Markdown {
+                print_markdown(all.into_iter());
+                return status;
+            }
+
+            if !script.quiet {
+                println!(
+                    "{}\n",
+                    message("list-header", &[("count", &all.len())])
+                        .green()
+                        .bold()
+                );
+            }
+
+            let Some(groups) = groups else {
+                for (i, heuristic) in all.iter().enumerate() {
+                    println!(
+                        "{}. {} ({})",
+                        format!("{:3}", i + 1).dimmed(),
+                        heuristic.title.cyan(),
+                        heuristic.category.yellow()
+                    );
+                }
+                return status;
+            };
+            for (n, (name, heuristics)) in groups.iter().enumerate() {
+                if n > 0 {
+                    println!();
+                }
+                println!(
+                    "{} {}",
+                    name.yellow().bold(),
+                    format!("({})", heuristics.len()).dimmed()
+                );
+                for (i, heuristic) in heuristics.iter().enumerate() {
+                    println!(
+                        "{}. {}",
+                        format!("{:3}", i + 1).dimmed(),
+                        heuristic.title.cyan()
+                    );
+                }
+            }
+            return status;
+        }
+
+        Commands::Crates { sort, quiet } => {
+            // Only count heuristics the platform filters let through
+            let mut crates: Vec<(String, Vec<&Heuristic>)> = db
+                .crate_mentions()
+                .into_iter()
+                .map(|mention| {
+                    let shown: Vec<&Heuristic> = mention
+                        .heuristics
+                        .iter()
+                        .filter_map(|id| db.get(id))
+                        .filter(|h| shows(h))
+                        .collect();
+                    (mention.name, shown)
+                })
+                .filter(|(_, heuristics)| !heuristics.is_empty())
+                .collect();
+            if sort == CrateOrder::Count {
+                crates.sort_by_key(|(_, heuristics)| {
+                    std::cmp::Reverse(heuristics.len())
+                });
+            }
+
+            if cli.format.is_structured() {
+                let shown: Vec<Value> = crates
+                    .iter()
+                    .map(|(name, heuristics)| {
+                        let mentions: Vec<Value> =
+                            heuristics.iter().map(|h| json!({ "id": h.id, "title": h.title })).collect();
+                        json!({ "name": name, "count": heuristics.len(), "heuristics": mentions })
+                    })
+                    .collect();
+                print_document(
+                    cli.format,
+                    json!({ "command": "crates", "crates": shown }),
+                );
+                return ExitCode::SUCCESS;
+            }
+
+            if cli.format == Format::Markdown {
+                for (name, heuristics) in &crates {
+                    let titles: Vec<&str> =
+                        heuristics.iter().map(|h| h.title.as_str()).collect();
+                    println!(
+                        "- **{}** ({}): {}",
+                        name,
+                        heuristics.len(),
+                        titles.join(", ")
+                    );
+                }
+                return ExitCode::SUCCESS;
+            }
+
+            println!(
+                "{}\n",
+                message("crates-header", &[("count", &crates.len())])
+                    .green()
+                    .bold()
+            );
+            for (name, heuristics) in &crates {
+                println!(
+                    "  {} {} {}",
+                    bullet,
+                    name.cyan(),
+                    format!("({})", heuristics.len()).dimmed()
+                );
+                if !quiet {
+                    for heuristic in heuristics {
+                        println!("      {}", heuristic.title.dimmed());
+                    }
+                }
+            }
+        }
+
+        Commands::Repl { limit, width } => {
+            let width = width.unwrap_or(prose_width);
+            let limit = if limit_given {
+                limit
+            } else {
+                config.limit.unwrap_or(limit)
+            };
+            repl(
+                &db,
+                &shows,
+                config.weights,
+                limit,
+                width,
+                !cli.minimal,
+                bullet,
+            );
+        }
+
+        Commands::Show { heuristic, width, open } => {
+            let Some(found) = db.resolve(&heuristic) else {
+                eprintln!(
+                    "{}",
+                    format!("No heuristic found: {}", heuristic).red()
+                );
+                return ExitCode::FAILURE;
+            };
+            show_heuristic(
+                &db,
+                found,
+                &notes(&found.id),
+                "show",
+                width.unwrap_or(prose_width),
+                cli.format,
+                bullet,
+            );
+            if !cli.minimal {
+                record_view(found);
+            }
+            if open {
+                let Some(name) = found.crates.first() else {
+                    eprintln!(
+                        "{}",
+                        "This heuristic recommends no crate to open.".yellow()
+                    );
+                    return ExitCode::FAILURE;
+                };
+                // Structured output stays a single document on stdout
+                if let Err(err) = open_urls(
+                    &docs_urls(name, false),
+                    cli.minimal || cli.format != Format::Text,
+                ) {
+                    eprintln!(
+                        "{}",
+                        format!("Could not open a browser: {}", err).red()
+                    );
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+
+        Commands::Explain { name, width } => {
+            let Some(digest) = db.explain_crate(&name) else {
+                eprintln!(
+                    "{}",
+                    format!("No heuristic mentions the crate {}", name).red()
+                );
+                eprintln!(
+                    "\nUse 'heuristics crates' to see the crates the heuristics recommend."
+                );
+                return ExitCode::FAILURE;
+            };
+
+            if cli.format.is_structured() {
+                let summary = |heuristics: &[&Heuristic]| -> Vec<Value> {
+                    heuristics.iter().map(|h| json!({ "id": h.id, "title": h.title, "action": h.action })).collect()
+                };
+                let alternatives: Vec<Value> =
+                    digest.alternatives.iter().map(alternative_json).collect();
+                print_document(
+                    cli.format,
+                    json!({
+                        "command": "explain",
+                        "crate": digest.name,
+                        "descriptions": digest.descriptions,
+                        "recommended_by": summary(&digest.recommended_by),
+                        "use_when": digest.use_when,
+                        "avoid_when": digest.avoid_when,
+                        "alternatives": alternatives,
+                        "alternative_in": summary(&digest.alternative_in),
+                    }),
+                );
+                return ExitCode::SUCCESS;
+            }
+
+            let style = match cli.format {
+                Format::Markdown => RenderStyle::Markdown,
+                _ if colored::control::SHOULD_COLORIZE.should_colorize() => {
+                    RenderStyle::Ansi
+                }
+                _ => RenderStyle::Plain,
+            };
+            print!("{}", digest.render(style, width.unwrap_or(prose_width)));
+        }
+
+        Commands::OpenDocs { name, crates_io, print } => {
+            let mentions = db.crate_mentions();
+            let Some(mention) =
+                mentions.iter().find(|m| m.name.eq_ignore_ascii_case(&name))
+            else {
+                eprintln!(
+                    "{}",
+                    format!("No heuristic mentions a crate named {}", name)
+                        .red()
+                );
+                return ExitCode::FAILURE;
+            };
+            let urls = docs_urls(&mention.name, crates_io);
+            if cli.format.is_structured() {
+                print_document(
+                    cli.format,
+                    json!({ "command": "open-docs", "crate": mention.name, "urls": urls }),
+                );
+                return ExitCode::SUCCESS;
+            }
+            // --minimal never starts other programs, so the URLs are printed
+            if let Err(err) = open_urls(&urls, print || cli.minimal) {
+                eprintln!(
+                    "{}",
+                    format!("Could not open a browser: {}", err).red()
+                );
+                return ExitCode::FAILURE;
+            }
+        }
+
+        Commands::Compare { terms, width } => {
+            let mut found: Vec<&Heuristic> = Vec::new();
+            for term in &terms {
+                match resolve_term(&db, term, &found) {
+                    Some(heuristic) => found.push(heuristic),
+                    None => {
+                        eprintln!(
+                            "{}",
+                            format!("No heuristic found: {}", term).red()
+                        );
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+
+            if cli.format.is_structured() {
+                let results: Vec<Value> = found
+                    .iter()
+                    .map(|h| {
+                        let mut result = heuristic_json(h);
+                        result["complexity"] = json!(h.complexity());
+                        result
+                    })
+                    .collect();
+                print_document(
+                    cli.format,
+                    json!({ "command": "compare", "query": terms, "results": results }),
+                );
+                return ExitCode::SUCCESS;
+            }
+
+            let style = match cli.format {
+                Format::Markdown => RenderStyle::Markdown,
+                _ if colored::control::SHOULD_COLORIZE.should_colorize() => {
+                    RenderStyle::Ansi
+                }
+                _ => RenderStyle::Plain,
+            };
+            print!("{}", compare(&found, style, width));
+        }
+
+        Commands::Diff { old, new, width } => {
+            let load = |path: &PathBuf| {
+                heuristics::load_heuristics_from_path(path)
+                    .inspect_err(|err| eprintln!("{}", err.to_string().red()))
+            };
+            let Ok(older) = load(&old) else {
+                return ExitCode::from(2);
+            };
+            // Not `db`, whose user pack and extras would all show up as added
+            let newer = match new.as_ref().map(load) {
+                Some(Ok(newer)) => newer,
+                Some(Err(_)) => return ExitCode::from(2),
+                None => heuristics::load_latest_heuristics(),
+            };
+            let diff = older.diff(&newer);
+
+            if cli.format.is_structured() {
+                let ids = |heuristics: &[&Heuristic]| -> Vec<String> {
+                    heuristics.iter().map(|h| h.id.clone()).collect()
+                };
+                let changed: Vec<Value> = diff
+                    .changed
+                    .iter()
+                    .map(|change| {
+                        json!({
+                            "id": change.new.id,
+                            "action": change.action_changed.then(|| json!({ "old": change.old.action, "new": change.new.action })),
+                            "crates_added": change.crates_added,
+                            "crates_removed": change.crates_removed,
+                            "fields": change.fields,
+                        })
+                    })
+                    .collect();
+                print_document(
+                    cli.format,
+                    json!({
+                        "command": "diff",
+                        "added": ids(&diff.added),
+                        "removed": ids(&diff.removed),
+                        "changed": changed,
+                    }),
+                );
+            } else if diff.is_empty() {
+                println!("No heuristics changed.");
+            } else {
+                let style = match cli.format {
+                    Format::Markdown => RenderStyle::Markdown,
+                    _ if colored::control::SHOULD_COLORIZE
+                        .should_colorize() =>
+                    {
+                        RenderStyle::Ansi
+                    }
+                    _ => RenderStyle::Plain,
+                };
+                print!("{}", diff.render(style, width.unwrap_or(prose_width)));
+            }
+            if !diff.is_empty() {
+                return ExitCode::FAILURE;
+            }
+        }
+
+        Commands::Pick { query, print_id } => {
+            let Some(picked) = pick(&db, query.unwrap_or_default()) else {
+                return ExitCode::FAILURE;
+            };
+            if print_id {
+                println!("{}", picked.id);
+            } else {
+                show_heuristic(
+                    &db,
+                    picked,
+                    &notes(&picked.id),
+                    "pick",
+                    prose_width,
+                    cli.format,
+                    bullet,
+                );
+            }
+        }
+
+        Commands::Tree { category, print_id } => {
+            let Some(found) = walk_tree(&db, category.as_deref(), &shows)
+            else {
+                return ExitCode::FAILURE;
+            };
+            if print_id {
+                println!("{}", found.id);
+            } else {
+                show_heuristic(
+                    &db,
+                    found,
+                    &notes(&found.id),
+                    "tree",
+                    prose_width,
+                    cli.format,
+                    bullet,
+                );
+            }
+        }
+
+        Commands::Daily { once } => {
+            let today = days_since_epoch();
+            let shown: Vec<&Heuristic> =
+                db.all().iter().filter(|h| shows(h)).collect();
+            // --minimal keeps no log, so every day starts a fresh cycle
+            let log = if cli.minimal { Vec::new() } else { load_daily() };
+            let already = log
+                .iter()
+                .rev()
+                .find(|(day, _)| *day == today)
+                .and_then(|(_, id)| db.get(id));
+            if once && already.is_some() {
+                return ExitCode::SUCCESS;
+            }
+            let seen = seen_this_cycle(&log, &shown);
+            let usage =
+                if cli.minimal { UsageLog::new() } else { load_usage() };
+            let chosen = already.or_else(|| {
+                let shuffled = db.sample_seeded(
+                    db.all().len(),
+                    SampleBias::UnderViewed(&usage),
+                    today,
+                );
+                let unseen = shuffled.iter().find(|h| {
+                    shown.contains(h) && !seen.contains(&h.id.as_str())
+                });
+                unseen
+                    .or_else(|| shuffled.iter().find(|h| shown.contains(h)))
+                    .copied()
+            });
+            let Some(chosen) = chosen else {
+                eprintln!("{}", "No heuristics to choose from.".red());
+                return ExitCode::FAILURE;
+            };
+            if !cli.minimal {
+                if already.is_none() {
+                    record_daily(today, &chosen.id);
+                }
+                record_view(chosen);
+            }
+            let log = if cli.minimal { Vec::new() } else { load_daily() };
+            let streak = (0..)
+                .take_while(|&back| {
+                    log.iter().any(|(day, _)| *day + back == today)
+                })
+                .count()
+                .max(1);
+            let number = seen_this_cycle(&log, &shown).len().max(1);
+
+            if cli.format.is_structured() {
+                print_document(
+                    cli.format,
+                    json!({
+                        "command": "daily",
+                        "result": heuristic_json(chosen),
+                        "cycle": { "shown": number, "total": shown.len() },
+                        "streak": streak,
+                    }),
+                );
+            } else if cli.format == Format::Markdown {
+                print_markdown(std::iter::once(chosen));
+            } else {
+                let args: [(&str, &dyn std::fmt::Display); 3] = [
+                    ("number", &number),
+                    ("total", &shown.len()),
+                    ("streak", &streak),
+                ];
+                println!(
+                    "{}\n",
+                    message("daily-header", &args).green().bold()
+                );
+                print_heuristic(chosen, number, false, false);
+            }
+        }
+
+        Commands::Random { category, seed_by_date } => {
+            let candidates = match &category {
+                Some(name) => db.by_category(name),
+                None => db.all().iter().collect(),
+            };
+            if let Some(name) = &category
+                && candidates.is_empty()
+            {
+                eprintln!("{}", format!("No category found: {}", name).red());
+                return ExitCode::FAILURE;
+            }
+            // A weighted shuffle of the whole database, so the first candidate in it is a weighted pick.
+            // The date-seeded pick stays uniform, since views recorded during the day would change it.
+            let usage =
+                if cli.minimal { UsageLog::new() } else { load_usage() };
+            let shuffled = match seed_by_date {
+                true => db.sample_seeded(
+                    db.all().len(),
+                    SampleBias::Uniform,
+                    days_since_epoch(),
+                ),
+                false => {
+                    db.sample(db.all().len(), SampleBias::UnderViewed(&usage))
+                }
+            };
+            let Some(chosen) = shuffled
+                .into_iter()
+                .find(|h| shows(h) && candidates.contains(h))
+            else {
+                eprintln!("{}", "No heuristics to choose from.".red());
+                return ExitCode::FAILURE;
+            };
+            if !cli.minimal {
+                record_view(chosen);
+            }
+
+            if cli.format.is_structured() {
+                print_document(
+                    cli.format,
+                    json!({ "command": "random", "result": heuristic_json(chosen) }),
+                );
+            } else if cli.format == Format::Markdown {
+                print_markdown(std::iter::once(chosen));
+            } else {
+                let heading = if seed_by_date {
+                    "random-daily"
+                } else {
+                    "random-header"
+                };
+                println!("{}\n", message(heading, &[]).green().bold());
+                print_heuristic(chosen, 1, false, false);
+            }
+        }
+
+        Commands::Suggest { manifest: path, limit } => {
+            let manifest = match std::fs::read_to_string(&path) {
+                Ok(text) => Manifest::parse(&text),
+                Err(err) => {
+                    eprintln!(
+                        "{}",
+                        format!("Could not read {}: {}", path.display(), err)
+                            .red()
+                    );
+                    return ExitCode::FAILURE;
+                }
+            };
+            let mut suggestions = db.suggest(&manifest);
+            suggestions.retain(|s| shows(s.heuristic));
+            suggestions.truncate(limit);
+
+            if cli.format.is_structured() {
+                let results: Vec<Value> = suggestions
+                    .iter()
+                    .map(|s| {
+                        let mut result = heuristic_json(s.heuristic);
+                        result["because"] = json!(s.because);
+                        result["score"] = json!(s.score);
+                        result
+                    })
+                    .collect();
+                print_document(
+                    cli.format,
+                    json!({
+                        "command": "suggest",
+                        "manifest": path.display().to_string(),
+                        "dependencies": manifest.dependencies,
+                        "features": manifest.features,
+                        "results": results,
+                    }),
+                );
+                return ExitCode::SUCCESS;
+            }
+
+            if cli.format == Format::Markdown {
+                print_markdown(suggestions.iter().map(|s| s.heuristic));
+                return ExitCode::SUCCESS;
+            }
+
+            if suggestions.is_empty() {
+                println!(
+                    "{}",
+                    format!(
+                        "No heuristics relate to the dependencies of {}.",
+                        path.display()
+                    )
+                    .yellow()
+                );
+                return ExitCode::SUCCESS;
+            }
+
+            println!(
+                "{}\n",
+                format!("Reading list for {}:", path.display()).green().bold()
+            );
+            for (i, suggestion) in suggestions.iter().enumerate() {
+                println!(
+                    "{}",
+                    format!("Because of {}", suggestion.because.join(", "))
+                        .dimmed()
+                );
+                print_heuristic(suggestion.heuristic, i + 1, false, false);
+            }
+        }
+
+        Commands::Cache { action } => match action {
+            CacheAction::Status => {
+                let stored: Vec<(&str, Option<PathBuf>, (usize, u64))> =
+                    stored_paths()
+                        .into_iter()
+                        .map(|(name, path, _)| {
+                            let usage =
+                                path.as_deref().map_or((0, 0), disk_usage);
+                            (name, path, usage)
+                        })
+                        .collect();
+                if cli.format.is_structured() {
+                    let locations: Vec<Value> = stored
+                        .iter()
+                        .map(|(name, path, (files, bytes))| json!({ "name": name, "path": path, "files": files, "bytes": bytes }))
+                        .collect();
+                    print_document(
+                        cli.format,
+                        json!({ "command": "cache", "locations": locations }),
+                    );
+                    return ExitCode::SUCCESS;
+                }
+                for (name, path, (files, bytes)) in &stored {
+                    let Some(path) = path else {
+                        println!(
+                            "{:<18} {}",
+                            name,
+                            "no directory; set HOME".dimmed()
+                        );
+                        continue;
+                    };
+                    let usage = match files {
+                        0 => "none".to_string(),
+                        1 => format_bytes(*bytes),
+                        _ => format!(
+                            "{} in {} files",
+                            format_bytes(*bytes),
+                            files
+                        ),
+                    };
+                    println!(
+                        "{:<18} {:<20} {}",
+                        name.cyan(),
+                        usage,
+                        path.display().to_string().dimmed()
+                    );
+                }
+            }
+            CacheAction::Clear { all } => {
+                let mut cleared = 0;
+                for (name, path, kind) in stored_paths() {
+                    let Some(path) = path.filter(|path| path.exists()) else {
+                        continue;
+                    };
+                    if kind == Stored::Kept
+                        || (kind == Stored::UserData && !all)
+                    {
+                        continue;
+                    }
+                    let removed = if path.is_dir() {
+                        std::fs::remove_dir_all(&path)
+                    } else {
+                        std::fs::remove_file(&path)
+                    };
+                    if let Err(err) = removed {
+                        eprintln!(
+                            "{}",
+                            format!(
+                                "Could not delete {}: {}",
+                                path.display(),
+                                err
+                            )
+                            .red()
+                        );
+                        return ExitCode::FAILURE;
+                    }
+                    println!(
+                        "Deleted {} {}",
+                        name,
+                        format!("({})", path.display()).dimmed()
+                    );
+                    cleared += 1;
+                }
+                if cleared == 0 {
+                    println!("{}", "Nothing to delete.".yellow());
+                }
+            }
+            CacheAction::Path { data } => {
+                let dir =
+                    if data { data_dir() } else { heuristics::cache_dir() };
+                let Some(dir) = dir else {
+                    eprintln!(
+                        "{}",
+                        "No home directory; set HOME or the XDG_* variables."
+                            .red()
+                    );
+                    return ExitCode::FAILURE;
+                };
+                println!("{}", dir.display());
+            }
+        },
+
+        Commands::Alias { action } => {
+            let result = match action {
+                AliasAction::Set { name, query } => {
+                    let builtin = Cli::command().get_subcommands().any(|c| {
+                        c.get_name() == name
+                            || c.get_all_aliases().any(|alias| alias == name)
+                    });
+                    if builtin || name == "help" {
+                        eprintln!(
+                            "{}",
+                            format!("{} is a built-in command.", name).red()
+                        );
+                        return ExitCode::from(2);
+                    }
+                    if let Err(err) = parse_query(&query) {
+                        eprintln!(
+                            "{}",
+                            format!("Invalid query: {}", err).red()
+                        );
+                        return ExitCode::from(2);
+                    }
+                    save_alias(&name, Some(&query))
+                }
+                AliasAction::Remove { name } => {
+                    if !config.aliases.iter().any(|(alias, _)| *alias == name)
+                    {
+                        eprintln!(
+                            "{}",
+                            format!("No alias named {}", name).red()
+                        );
+                        return ExitCode::FAILURE;
+                    }
+                    save_alias(&name, None)
+                }
+                AliasAction::List => {
+                    if cli.format.is_structured() {
+                        let aliases: Vec<Value> =
+                            config.aliases.iter().map(|(name, query)| json!({ "name": name, "query": query })).collect();
+                        print_document(
+                            cli.format,
+                            json!({ "command": "alias", "aliases": aliases }),
+                        );
+                    } else if config.aliases.is_empty() {
+                        println!("{}", "No aliases yet; save one with `heuristics alias set NAME QUERY`.".yellow());
+                    } else {
+                        for (name, query) in &config.aliases {
+                            println!("{} {}", name.cyan().bold(), query);
+                        }
+                    }
+                    return ExitCode::SUCCESS;
+                }
+            };
+            match result {
+                Ok(path) => println!(
+                    "{}",
+                    format!("Saved to {}", path.display()).green()
+                ),
+                Err(err) => {
+                    eprintln!("{}", err.red());
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+
+        // Expanded into a search before the database was loaded
+        Commands::Saved(_) => unreachable!("saved queries run as searches"),
+
+        Commands::Man { heuristic } => {
+            let Some(reference) = heuristic else {
+                let mut page = Vec::new();
+                if let Err(err) =
+                    clap_mangen::Man::new(Cli::command()).render(&mut page)
+                {
+                    eprintln!(
+                        "{}",
+                        format!("Could not render the man page: {}", err)
+                            .red()
+                    );
+                    return ExitCode::FAILURE;
+                }
+                print!("{}", String::from_utf8_lossy(&page));
+                return ExitCode::SUCCESS;
+            };
+            let Some(found) = db.resolve(&reference) else {
+                eprintln!(
+                    "{}",
+                    format!("No heuristic found: {}", reference).red()
+                );
+                return ExitCode::FAILURE;
+            };
+            print!("{}", found.to_roff());
+        }
+
+        Commands::Doctor { path, check_links } => {
+            if check_links && (cli.minimal || !cfg!(feature = "net")) {
+                eprintln!("{}", "--check-links needs network access: build with the `net` feature and drop --minimal.".red());
+                return ExitCode::from(2);
+            }
+
+            let (pack, file_issues) =
+                match path.as_ref().map(check_files).transpose() {
+                    Ok(Some((pack, issues))) => (Some(pack), issues),
+                    Ok(None) => (None, Vec::new()),
+                    Err(err) => {
+                        eprintln!("{}", err.to_string().red());
+                        return ExitCode::FAILURE;
+                    }
+                };
+            // A pack may refer to built-in heuristics, but only its own are checked
+            let ids: Option<HashSet<String>> = pack
+                .as_ref()
+                .map(|pack| pack.all().iter().map(|h| h.id.clone()).collect());
+            let merged = pack.map(|pack| {
+                heuristics::load_latest_heuristics()
+                    .merge(pack, MergePolicy::PreferTheirs)
+                    .expect("preferring theirs never conflicts")
+            });
+            let db = merged.as_ref().unwrap_or(&db);
+            let checks = |h: &Heuristic| {
+                ids.as_ref().is_none_or(|ids| ids.contains(&h.id))
+            };
+
+            // Each problem is (heuristic, offending link if any, description)
+            let mut problems: Vec<(&Heuristic, Option<&str>, String)> =
+                Vec::new();
+            for (id, issue) in db.validate() {
+                // Parsing a pack already reported what each heuristic lacks
+                if ids.is_some()
+                    && !matches!(
+                        issue,
+                        ValidationIssue::UnresolvedReference(_)
+                    )
+                {
+                    continue;
+                }
+                if let Some(heuristic) = db.get(&id).filter(|h| checks(h)) {
+                    problems.push((heuristic, None, issue.to_string()));
+                }
+            }
+
+            let mut checked: HashMap<&str, Option<String>> = HashMap::new();
+            for heuristic in db.all().iter().filter(|h| checks(h)) {
+                for link in &heuristic.links {
+                    let problem = match link.problem() {
+                        Some(problem) => Some(problem.to_string()),
+                        None if check_links && !link.url.starts_with('#') => {
+                            checked
+                                .entry(link.url.as_str())
+                                .or_insert_with(|| check_remote(&link.url))
+                                .clone()
+                        }
+                        None => None,
+                    };
+                    if let Some(problem) = problem {
+                        problems.push((heuristic, Some(&link.url), problem));
+                    }
+                }
+            }
+
+            if cli.format.is_structured() {
+                let mut issues: Vec<Value> = file_issues
+                    .iter()
+                    .map(|issue| json!({ "path": issue.path, "line": issue.line, "problem": issue.message }))
+                    .collect();
+                issues.extend(
+                    problems
+                        .iter()
+                        .map(|(h, url, problem)| json!({ "id": h.id, "heuristic": h.title, "url": url, "problem": problem })),
+                );
+                print_document(
+                    cli.format,
+                    json!({ "command": "doctor", "path": path, "issues": issues }),
+                );
+            } else {
+                let checked: Vec<&Heuristic> =
+                    db.all().iter().filter(|h| checks(h)).collect();
+                let links: usize = checked.iter().map(|h| h.links.len()).sum();
+                let place = path.as_ref().map_or(String::new(), |path| {
+                    format!(" in {}", path.display())
+                });
+                println!(
+                    "{}",
+                    format!(
+                        "Checked {} heuristic(s) and {} link(s){}.",
+                        checked.len(),
+                        links,
+                        place
+                    )
+                    .green()
+                    .bold()
+                );
+                for issue in &file_issues {
+                    println!("  {} {}", bullet, issue.to_string().red());
+                }
+                for (heuristic, url, problem) in &problems {
+                    match url {
+                        Some(url) => println!(
+                            "  {} {}: {} ({})",
+                            bullet,
+                            heuristic.title.cyan(),
+                            url,
+                            problem.red()
+                        ),
+                        None => println!(
+                            "  {} {}: {}",
+                            bullet,
+                            heuristic.title.cyan(),
+                            problem.red()
+                        ),
+                    }
+                }
+                if problems.is_empty() && file_issues.is_empty() {
+                    println!("No problems found.");
+                }
+            }
+
+            if !problems.is_empty() || !file_issues.is_empty() {
+                return ExitCode::FAILURE;
+            }
+        }
+
+        Commands::Watch { file } => {
+            if !cfg!(feature = "watch") {
+                eprintln!("{}", "watch needs the `watch` feature.".red());
+                return ExitCode::from(2);
+            }
+            if !file.is_file() {
+                eprintln!(
+                    "{}",
+                    format!("Not a file: {}", file.display()).red()
+                );
+                return ExitCode::FAILURE;
+            }
+            let format = cli.format;
+            if !format.is_structured() {
+                eprintln!(
+                    "{}",
+                    format!(
+                        "Watching {}; press Ctrl-C to stop.",
+                        file.display()
+                    )
+                    .dimmed()
+                );
+            }
+            let path = file.clone();
+            if let Err(err) =
+                watch_pack(&file, move || report_pack(&path, format))
+            {
+                eprintln!("{}", err.red());
+                return ExitCode::FAILURE;
+            }
+        }
+
+        Commands::Schema { snapshot } => {
+            if !cfg!(feature = "schema") {
+                eprintln!("{}", "schema needs the `schema` feature.".red());
+                return ExitCode::from(2);
+            }
+            match json_schema(snapshot) {
+                Ok(schema) => print!("{}", schema),
+                Err(err) => {
+                    eprintln!("{}", err.red());
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+
+        Commands::Serve { port, host } => {
+            if !cfg!(feature = "server") {
+                eprintln!("{}", "serve needs the `server` feature.".red());
+                return ExitCode::from(2);
+            }
+            let listener =
+                match std::net::TcpListener::bind((host.as_str(), port)) {
+                    Ok(listener) => listener,
+                    Err(err) => {
+                        eprintln!(
+                            "{}",
+                            format!(
+                                "Could not listen on {}:{}: {}",
+                                host, port, err
+                            )
+                            .red()
+                        );
+                        return ExitCode::FAILURE;
+                    }
+                };
+            println!(
+                "{}",
+                format!(
+                    "Serving {} heuristic(s) at http://{}:{}/",
+                    db.all().len(),
+                    host,
+                    port
+                )
+                .green()
+                .bold()
+            );
+            if let Err(err) = serve_ui(db, listener) {
+                eprintln!("{}", err.red());
+                return ExitCode::FAILURE;
+            }
+        }
+
+        Commands::Update { url, sha256, check } => {
+            if cli.minimal || !cfg!(feature = "fetch") {
+                eprintln!("{}", "update needs network access: build with the `fetch` feature and drop --minimal.".red());
+                return ExitCode::from(2);
+            }
+
+            if check {
+                let status = match check_pack(&url, sha256.as_deref()) {
+                    Ok(status) => status,
+                    Err(err) => {
+                        eprintln!("{}", err.red());
+                        return ExitCode::FAILURE;
+                    }
+                };
+                if cli.format.is_structured() {
+                    print_document(
+                        cli.format,
+                        json!({ "command": "update", "url": url, "status": status }),
+                    );
+                } else {
+                    match status {
+                        "current" => println!(
+                            "{}",
+                            format!("Up to date with {}", url).green().bold()
+                        ),
+                        "stale" => println!(
+                            "{}",
+                            format!("An update is available from {}", url)
+                                .yellow()
+                                .bold()
+                        ),
+                        _ => println!(
+                            "{}",
+                            format!("Nothing downloaded from {} yet", url)
+                                .yellow()
+                                .bold()
+                        ),
+                    }
+                }
+                return if status == "current" {
+                    ExitCode::SUCCESS
+                } else {
+                    ExitCode::FAILURE
+                };
+            }
+
+            let (path, count) = match update_pack(&url, sha256.as_deref()) {
+                Ok(updated) => updated,
+                Err(err) => {
+                    eprintln!("{}", err.red());
+                    return ExitCode::FAILURE;
+                }
+            };
+            if cli.format.is_structured() {
+                print_document(
+                    cli.format,
+                    json!({ "command": "update", "url": url, "path": path, "heuristics": count }),
+                );
+            } else {
+                println!(
+                    "{}",
+                    format!("Cached {} heuristic(s) from {}", count, url)
+                        .green()
+                        .bold()
+                );
+                println!("  {} {}", bullet, path);
+            }
+        }
+
+        Commands::Export { category, output, out } => {
+            let mut heuristics = match &category {
+                Some(name) => db.by_category(name),
+                None => db.all().iter().collect(),
+            };
+            heuristics.retain(|h| shows(h));
+            if heuristics.is_empty() {
+                eprintln!("{}", "No heuristics to export.".red());
+                return ExitCode::FAILURE;
+            }
+
+            if let Some(dir) = out {
+                let title = db
+                    .pack_info()
+                    .and_then(|pack| pack.name.as_deref())
+                    .unwrap_or("Heuristics");
+                let files = match cli.format {
+                    Format::Html => to_site(title, heuristics),
+                    Format::Mdbook => to_mdbook(title, heuristics),
+                    Format::Man => to_man_pages(heuristics),
+                    Format::Obsidian => to_vault(heuristics),
+                    Format::Hugo => to_hugo(title, heuristics),
+                    Format::Zola => to_zola(title, heuristics),
+                    // Results in the content document are in the index's `ids` order
+                    Format::SearchIndex => vec![
+                        (
+                            PathBuf::from("search-index.json"),
+                            HeuristicDb::new(
+                                heuristics.iter().copied().cloned().collect(),
+                            )
+                            .search_index_json(),
+                        ),
+                        (
+                            PathBuf::from("heuristics.json"),
+                            export_document(Format::Json, &db, &heuristics),
+                        ),
+                    ],
+                    _ => {
+                        eprintln!("{}", "--out writes a directory; use it with --format html, mdbook, man, obsidian, hugo, zola, or search-index.".red());
+                        return ExitCode::from(2);
+                    }
+                };
+                return match write_files(&dir, &files) {
+                    Ok(()) => ExitCode::SUCCESS,
+                    Err(err) => {
+                        eprintln!("{}", err.red());
+                        ExitCode::FAILURE
+                    }
+                };
+            }
+
+            if cli.format == Format::Sqlite {
+                if !cfg!(feature = "sqlite") {
+                    eprintln!(
+                        "{}",
+                        "sqlite export needs the `sqlite` feature.".red()
+                    );
+                    return ExitCode::from(2);
+                }
+                let Some(path) = output else {
+                    eprintln!("{}", "An SQLite database can't go to stdout; write it with -o FILE.".red());
+                    return ExitCode::from(2);
+                };
+                return match export_sqlite(heuristics, &path) {
+                    Ok(()) => ExitCode::SUCCESS,
+                    Err(err) => {
+                        eprintln!(
+                            "{}",
+                            format!(
+                                "Could not write {}: {}",
+                                path.display(),
+                                err
+                            )
+                            .red()
+                        );
+                        ExitCode::FAILURE
+                    }
+                };
+            }
+
+            let exported = match cli.format {
+                Format::Text => {
+                    eprintln!("{}", "Choose what to export with --format json, yaml, markdown, csv, html, anki, mdbook, man, obsidian, hugo, zola, sqlite, dot, or search-index.".red());
+                    return ExitCode::from(2);
+                }
+                Format::Json | Format::Yaml => {
+                    export_document(cli.format, &db, &heuristics)
+                }
+                Format::Markdown => {
+                    HeuristicDb::new(heuristics.into_iter().cloned().collect())
+                        .to_markdown()
+                }
+                Format::Csv => to_csv(heuristics),
+                Format::Anki => to_anki(heuristics),
+                Format::Dot => to_dot(heuristics),
+                Format::Mdbook
+                | Format::Man
+                | Format::Obsidian
+                | Format::Hugo
+                | Format::Zola
+                | Format::SearchIndex => {
+                    eprintln!(
+                        "{}",
+                        "This export is a directory; write it with --out DIR."
+                            .red()
+                    );
+                    return ExitCode::from(2);
+                }
+                Format::Sqlite => unreachable!("written above"),
+                Format::Html => {
+                    let title = db
+                        .pack_info()
+                        .and_then(|pack| pack.name.as_deref())
+                        .unwrap_or("Heuristics");
+                    to_html(title, heuristics)
+                }
+            };
+
+            match output {
+                Some(path) => {
+                    if let Err(err) = std::fs::write(&path, exported) {
+                        eprintln!(
+                            "{}",
+                            format!(
+                                "Could not write {}: {}",
+                                path.display(),
+                                err
+                            )
+                            .red()
+                        );
+                        return ExitCode::FAILURE;
+                    }
+                }
+                None => print!("{}", exported),
+            }
+        }
+
+        Commands::Import { from, path, category, into } => {
+            let extension = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(str::to_ascii_lowercase);
+            let from = match (from, extension.as_deref()) {
+                (Some(from), _) => from,
+                (None, Some("json")) => ImportFormat::Json,
+                (None, Some("csv")) => ImportFormat::Csv,
+                (None, _) => {
+                    eprintln!("{}", "Choose the file's format with --from tldr, cheat, notes, json, or csv.".red());
+                    return ExitCode::from(2);
+                }
+            };
+            if from == ImportFormat::Json && !cfg!(feature = "json") {
+                eprintln!("{}", "json import needs the `json` feature.".red());
+                return ExitCode::from(2);
+            }
+            let content = match std::fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(err) => {
+                    eprintln!(
+                        "{}",
+                        format!("{}: {}", path.display(), err).red()
+                    );
+                    return ExitCode::FAILURE;
+                }
+            };
+            let category = category
+                .or_else(|| {
+                    path.file_stem()
+                        .map(|stem| stem.to_string_lossy().into_owned())
+                })
+                .unwrap_or_default();
+            let source: Box<dyn HeuristicSource> = match from {
+                ImportFormat::Tldr => Box::new(TldrSource),
+                ImportFormat::Cheat => Box::new(CheatSource::new(category)),
+                ImportFormat::Notes => Box::new(NotesSource::new(category)),
+                ImportFormat::Json => json_source(category),
+                ImportFormat::Csv => Box::new(CsvSource::new(category)),
+            };
+            let imported =
+                match HeuristicDb::from_source(source.as_ref(), &content) {
+                    Ok(imported) => imported,
+                    Err(err) => {
+                        eprintln!(
+                            "{}",
+                            format!("{}: {}", path.display(), err).red()
+                        );
+                        return ExitCode::FAILURE;
+                    }
+                };
+
+            if let Some(pack) = into {
+                let ours = if pack.exists() {
+                    heuristics::load_heuristics_from_path(&pack)
+                } else {
+                    Ok(HeuristicDb::new(Vec::new()))
+                };
+                let ours = match ours {
+                    Ok(ours) => ours,
+                    Err(err) => {
+                        eprintln!("{}", err.to_string().red());
+                        return ExitCode::FAILURE;
+                    }
+                };
+                let count = imported.all().len();
+                let content = ours
+                    .merge(imported, MergePolicy::PreferTheirs)
+                    .expect("preferring theirs never conflicts")
+                    .to_markdown();
+                let written = pack
+                    .parent()
+                    .map_or(Ok(()), std::fs::create_dir_all)
+                    .and_then(|_| std::fs::write(&pack, content));
+                if let Err(err) = written {
+                    eprintln!(
+                        "{}",
+                        format!("Could not write {}: {}", pack.display(), err)
+                            .red()
+                    );
+                    return ExitCode::FAILURE;
+                }
+                if cli.format.is_structured() {
+                    print_document(
+                        cli.format,
+                        json!({ "command": "import", "path": pack, "heuristics": count }),
+                    );
+                } else {
+                    println!(
+                        "{}",
+                        format!(
+                            "Imported {} heuristic(s) into {}",
+                            count,
+                            pack.display()
+                        )
+                        .green()
+                    );
+                }
+            } else if cli.format.is_structured() {
+                let converted: Vec<Value> =
+                    imported.all().iter().map(heuristic_json).collect();
+                print_document(
+                    cli.format,
+                    json!({
+                        "command": "import",
+                        "markdown": imported.to_markdown(),
+                        "results": converted,
+                    }),
+                );
+            } else {
+                print!("{}", imported.to_markdown());
+            }
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// The heuristic a `compare` term stands for, other than those in `taken`
+///
+/// An id or title picks that heuristic. A name like `hashmap` picks the
+/// search match listing a crate or std type of that name earliest, as the
+/// first one listed is the main recommendation, falling back to the best
+/// search match, then the best fuzzy title match.
+fn resolve_term<'a>(
+    db: &'a HeuristicDb,
+    term: &str,
+    taken: &[&Heuristic],
+) -> Option<&'a Heuristic> {
+    if let Some(heuristic) = db.resolve(term) {
+        return Some(heuristic);
+    }
+    // Position of the name among the heuristic's crates or std types
+    let listed_at = |h: &Heuristic| {
+        let position = |names: &[String]| {
+            names.iter().position(|name| {
+                let last = name.rsplit("::").next().unwrap_or(name);
+                last.eq_ignore_ascii_case(term)
+                    || name.eq_ignore_ascii_case(term)
+            })
+        };
+        position(&h.crates).into_iter().chain(position(&h.std_types)).min()
+    };
+    let free = |h: &&Heuristic| !taken.iter().any(|t| t.id == h.id);
+    let matches = db.search(&[term]);
+    matches
+        .iter()
+        .copied()
+        .filter(free)
+        .filter_map(|h| listed_at(h).map(|position| (position, h)))
+        .min_by_key(|&(position, _)| position)
+        .map(|(_, h)| h)
+        .or_else(|| matches.iter().copied().find(free))
+        .or_else(|| db.fuzzy_search(term).into_iter().find(free))
+}
+
+/// Print a heuristic in full with the user's notes on it, followed by the
+/// heuristics it points to
+fn show_heuristic(
+    db: &HeuristicDb,
+    found: &Heuristic,
+    notes: &[String],
+    command: &str,
+    width: usize,
+    format: Format,
+    bullet: &str,
+) {
+    let mut related = db.related_to(found);
+    for alternative in db.alternatives_of(found) {
+        if !related.contains(&alternative) {
+            related.push(alternative);
+        }
+    }
+
+    if format.is_structured() {
+        let mut result = heuristic_json(found);
+        result["content"] = json!(found.content);
+        let related: Vec<Value> = related
+            .iter()
+            .map(|h| json!({ "id": h.id, "title": h.title }))
+            .collect();
+        print_document(
+            format,
+            json!({ "command": command, "result": result, "notes": notes, "related": related }),
+        );
+        return;
+    }
+
+    if format == Format::Markdown {
+        print!("{}", found.render_full(RenderStyle::Markdown, width));
+        if !notes.is_empty() {
+            println!("\n**Notes:**");
+            for note in notes {
+                println!("- {}", note);
+            }
+        }
+        if !related.is_empty() {
+            println!("\n**Related:**");
+            for h in related {
+                println!("- [{}](#{})", h.title, h.id);
+            }
+        }
+        return;
+    }
+
+    let style = if colored::control::SHOULD_COLORIZE.should_colorize() {
+        RenderStyle::Ansi
+    } else {
+        RenderStyle::Plain
+    };
+    print!("{}", found.render_full(style, width));
+    if !notes.is_empty() {
+        println!("\n{}", message("label-notes", &[]).green().bold());
+        for note in notes {
+            println!("  {} {}", bullet, note);
+        }
+    }
+    if !related.is_empty() {
+        println!("\n{}", message("label-related", &[]).green().bold());
+        for h in related {
+            println!(
+                "  {} {} {}",
+                bullet,
+                h.title.cyan(),
+                format!("({})", h.id).dimmed()
+            );
+        }
+    }
+}
+
+/// Message catalogs by language code, in Fluent's `key = text` syntax with
+/// `{ $name }` placeholders; the first is the fallback for missing keys
+const CATALOGS: [(&str, &str); 2] = [
+    ("en", include_str!("../locales/en.ftl")),
+    ("de", include_str!("../locales/de.ftl")),
+];
+
+/// The catalog messages are shown from, once chosen
+static CATALOG: OnceLock<&str> = OnceLock::new();
+
+/// Language code from the locale variables, e.g. `de` for `de_DE.UTF-8`
+///
+/// Like `setlocale`, the first variable set and non-empty wins.
+fn language_from_env() -> Option<String> {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())?;
+    let code = locale
+        .split(['_', '.', '@', '-'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    Some(code)
+}
+
+/// Show messages in the language with this code; false if there's no
+/// catalog for it, leaving English
+fn set_language(code: &str) -> bool {
+    let code = code.split(['_', '.', '@', '-']).next().unwrap_or_default();
+    match CATALOGS.iter().find(|(known, _)| known.eq_ignore_ascii_case(code)) {
+        Some((_, catalog)) => CATALOG.set(catalog).is_ok(),
+        None => false,
+    }
+}
+
+/// The message `key` in the chosen language, with `{ $name }` placeholders
+/// filled in from `args`
+fn message(key: &str, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+    let lookup = |catalog: &str| {
+        catalog
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .find(|(name, _)| name.trim() == key)
+            .map(|(_, text)| text.trim().to_string())
+    };
+    let mut text = CATALOG
+        .get()
+        .and_then(|catalog| lookup(catalog))
+        .or_else(|| lookup(CATALOGS[0].1))
+        .unwrap_or_else(|| key.to_string());
+    for (name, value) in args {
+        text = text.replace(&format!("{{ ${} }}", name), &value.to_string());
+    }
+    text
+}
+
+/// Send log events to stderr: info with `-v`, debug with `-vv`, and
+/// trace with more
+///
+/// Without `-v` nothing is logged. `--color auto` colors events only
+/// when stderr is a terminal.
+fn init_logging(cli: &Cli) {
+    let level = match cli.verbosity {
+        0 => return,
+        1 => tracing::Level::INFO,
+        2 => tracing::Level::DEBUG,
+        _ => tracing::Level::TRACE,
+    };
+    let ansi = match cli.color {
+        _ if cli.minimal || cli.no_color => false,
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            std::io::stderr().is_terminal()
+                && std::env::var_os("NO_COLOR").is_none()
+        }
+    };
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(std::io::stderr)
+        .with_ansi(ansi)
+        .with_target(false)
+        .init();
+}
+
+/// Columns to wrap to when the terminal's width is unknown
+const DEFAULT_WIDTH: usize = 80;
+
+/// Widest prose is wrapped to by default, as long lines are hard to read
+const MAX_PROSE_WIDTH: usize = 100;
+
+/// Width of the terminal on stdout, else `$COLUMNS`, else [`DEFAULT_WIDTH`]
+fn terminal_width() -> usize {
+    terminal_size::terminal_size()
+        .map(|(terminal_size::Width(width), _)| usize::from(width))
+        .or_else(|| std::env::var("COLUMNS").ok()?.parse().ok())
+        .filter(|&width| width > 0)
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+/// Number of candidates the picker lists at a time
+const PICK_LIMIT: usize = 10;
+
+/// The current UTC day, as a seed that changes once a day
+fn days_since_epoch() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+        / 86_400
+}
+
+/// Let the user narrow the heuristics down by fuzzy title and choose one
+///
+/// Reads queries from stdin and writes the menu to stderr, so stdout only
+/// carries the result. A number picks that candidate and an empty line the
+/// first; anything else is a new query. Returns `None` at end of input.
+fn pick(db: &HeuristicDb, mut query: String) -> Option<&Heuristic> {
+    let stdin = std::io::stdin();
+    loop {
+        let candidates: Vec<&Heuristic> =
+            db.fuzzy_search(&query).into_iter().take(PICK_LIMIT).collect();
+        if candidates.is_empty() {
+            eprintln!("{}", format!("No titles match '{}'", query).yellow());
+        }
+        for (i, heuristic) in candidates.iter().enumerate() {
+            eprintln!(
+                "{} {}",
+                format!("{:2}.", i + 1).dimmed(),
+                heuristic.title.cyan()
+            );
+        }
+        eprint!("{} ", "pick>".green().bold());
+
+        let mut line = String::new();
+        if stdin.read_line(&mut line).ok()? == 0 {
+            eprintln!();
+            return None;
+        }
+        let line = line.trim();
+        match line.parse::<usize>() {
+            Ok(n) if (1..=candidates.len()).contains(&n) => {
+                return Some(candidates[n - 1]);
+            }
+            _ if line.is_empty() && !candidates.is_empty() => {
+                return Some(candidates[0]);
+            }
+            _ => query = line.to_string(),
+        }
+    }
+}
+
+/// Ask which of the listed `results` to show in full
+///
+/// Takes numbers separated by spaces or commas, ranges like `2-4`, or
+/// `all`; an empty line or end of input selects nothing. Asks again after
+/// a number that wasn't listed.
+fn select_results<'a>(results: &[&'a Heuristic]) -> Vec<&'a Heuristic> {
+    let stdin = std::io::stdin();
+    loop {
+        eprint!("\n{} ", message("select-prompt", &[]).green().bold());
+        let mut line = String::new();
+        if stdin.read_line(&mut line).unwrap_or(0) == 0 {
+            eprintln!();
+            return Vec::new();
+        }
+        match parse_selection(&line, results.len()) {
+            Ok(chosen) => {
+                return chosen.into_iter().map(|n| results[n - 1]).collect();
+            }
+            Err(problem) => eprintln!("{}", problem.yellow()),
+        }
+    }
+}
+
+/// The 1-based result numbers a selection names, in the order given
+fn parse_selection(line: &str, count: usize) -> Result<Vec<usize>, String> {
+    if line.trim().eq_ignore_ascii_case("all") {
+        return Ok((1..=count).collect());
+    }
+    let number = |text: &str| match text.trim().parse::<usize>() {
+        Ok(n) if (1..=count).contains(&n) => Ok(n),
+        _ => Err(message(
+            "select-invalid",
+            &[("count", &count), ("text", &text.trim())],
+        )),
+    };
+    let mut chosen = Vec::new();
+    for part in line
+        .split([',', ' ', '\t', '\n', '\r'])
+        .filter(|part| !part.is_empty())
+    {
+        let range = match part.split_once('-') {
+            Some((start, end)) => number(start)?..=number(end)?,
+            None => number(part)?..=number(part)?,
+        };
+        for n in range {
+            if !chosen.contains(&n) {
+                chosen.push(n);
+            }
+        }
+    }
+    Ok(chosen)
+}
+
+/// Commands of the `repl` prompt with their usage, for `help` and completion
+const REPL_COMMANDS: [(&str, &str); 8] = [
+    (
+        "search",
+        "search KEYWORDS...   list the heuristics matching the keywords",
+    ),
+    ("show", "show N|ID            show a listed heuristic in full"),
+    ("category", "category NAME        list the heuristics of a category"),
+    ("categories", "categories           list the categories"),
+    (
+        "bookmark",
+        "bookmark [N|ID]      bookmark a heuristic, or list the bookmarks",
+    ),
+    ("unbookmark", "unbookmark N|ID      remove a bookmark"),
+    ("help", "help                 list these commands"),
+    ("quit", "quit                 leave, as does Ctrl-D"),
+];
+
+/// Answer `repl` commands until the user quits
+///
+/// The database stays loaded between commands. Listings are numbered, so
+/// `show 2` shows the second heuristic of the last one. With `persist`,
+/// searches go to the search history and bookmarks are kept across
+/// sessions, as is the prompt history when built with `readline`.
+fn repl<'a>(
+    db: &'a HeuristicDb,
+    shows: &dyn Fn(&Heuristic) -> bool,
+    weights: ScoreWeights,
+    limit: usize,
+    width: usize,
+    persist: bool,
+    bullet: &str,
+) {
+    let mut prompt = Prompt::new(db, persist);
+    let mut bookmarks = if persist { load_bookmarks() } else { Vec::new() };
+    let mut listed: Vec<&Heuristic> = Vec::new();
+    let list = |heuristics: Vec<&'a Heuristic>,
+                limit: usize|
+     -> Vec<&'a Heuristic> {
+        for (i, heuristic) in heuristics.iter().take(limit).enumerate() {
+            print_heuristic(heuristic, i + 1, false, false);
+        }
+        if heuristics.len() > limit {
+            println!(
+                "{}",
+                format!("... and {} more.", heuristics.len() - limit).dimmed()
+            );
+        }
+        heuristics.into_iter().take(limit).collect()
+    };
+    // A number picks from the last listing
+    let choose = |listed: &[&'a Heuristic],
+                  argument: &str|
+     -> Option<&'a Heuristic> {
+        let chosen = match argument.parse::<usize>() {
+            Ok(n) if (1..=listed.len()).contains(&n) => Some(listed[n - 1]),
+            _ => db.resolve(argument),
+        };
+        if chosen.is_none() {
+            eprintln!("{}", format!("No heuristic found: {}", argument).red());
+        }
+        chosen
+    };
+
+    eprintln!("{}", message("repl-hint", &[]).dimmed());
+    while let Some(line) = prompt.read() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((&command, rest)) = words.split_first() else { continue };
+        let argument = rest.join(" ");
+        match command {
+            _ if rest.is_empty()
+                && matches!(
+                    command,
+                    "search" | "show" | "category" | "unbookmark"
+                ) =>
+            {
+                let usage = REPL_COMMANDS
+                    .iter()
+                    .find(|(name, _)| *name == command)
+                    .map_or("", |(_, usage)| usage);
+                eprintln!("{}", format!("Usage: {}", usage).yellow());
+            }
+            "search" => {
+                if persist {
+                    record_search(
+                        &rest
+                            .iter()
+                            .map(|word| word.to_string())
+                            .collect::<Vec<_>>(),
+                    );
+                }
+                let found: Vec<&Heuristic> = db
+                    .search_weighted(rest, weights)
+                    .into_iter()
+                    .map(|(h, _)| h)
+                    .filter(|h| shows(h))
+                    .collect();
+                if found.is_empty() {
+                    println!("{}", message("search-none", &[]).yellow());
+                }
+                listed = list(found, limit);
+            }
+            "show" => {
+                if let Some(found) = choose(&listed, &argument) {
+                    let notes =
+                        if persist { notes_on(&found.id) } else { Vec::new() };
+                    show_heuristic(
+                        db,
+                        found,
+                        &notes,
+                        "show",
+                        width,
+                        Format::Text,
+                        bullet,
+                    );
+                }
+            }
+            "category" => {
+                let found: Vec<&Heuristic> = db
+                    .by_category(&argument)
+                    .into_iter()
+                    .filter(|h| shows(h))
+                    .collect();
+                // Like `show`, a miss keeps the last list to pick from
+                if found.is_empty() {
+                    eprintln!(
+                        "{}",
+                        format!("No category found: {}", argument).red()
+                    );
+                    continue;
+                }
+                listed = list(found, usize::MAX);
+            }
+            "categories" => {
+                for category in db.categories() {
+                    println!("  {} {}", bullet, category.cyan());
+                }
+            }
+            "bookmark" if rest.is_empty() => {
+                if bookmarks.is_empty() {
+                    println!(
+                        "{}",
+                        "No bookmarks yet; add one with `bookmark N`."
+                            .yellow()
+                    );
+                }
+                listed = list(
+                    bookmarks.iter().filter_map(|id| db.get(id)).collect(),
+                    usize::MAX,
+                );
+            }
+            "bookmark" | "unbookmark" => {
+                let Some(found) = choose(&listed, &argument) else { continue };
+                bookmarks.retain(|id| *id != found.id);
+                if command == "bookmark" {
+                    bookmarks.push(found.id.clone());
+                }
+                if persist && let Err(err) = save_bookmarks(&bookmarks) {
+                    eprintln!(
+                        "{}",
+                        format!("Could not save bookmarks: {}", err).red()
+                    );
+                }
+                let done = if command == "bookmark" {
+                    "Bookmarked"
+                } else {
+                    "Removed the bookmark on"
+                };
+                println!("{}", format!("{} {}", done, found.title).green());
+            }
+            "help" => {
+                for (_, usage) in REPL_COMMANDS {
+                    println!("  {}", usage);
+                }
+            }
+            "quit" | "exit" => break,
+            _ => eprintln!(
+                "{}",
+                format!(
+                    "Unknown command `{}`; type `help` for the commands.",
+                    command
+                )
+                .red()
+            ),
+        }
+    }
+    prompt.save();
+}
+
+/// Reads lines at the `repl` prompt
+///
+/// Built with the `readline` feature, lines can be edited, Tab completes
+/// commands, ids, and categories, and the prompt history is kept in the
+/// data directory.
+#[cfg(feature = "readline")]
+struct Prompt {
+    editor: Option<
+        rustyline::Editor<ReplHelper, rustyline::history::DefaultHistory>,
+    >,
+    history: Option<PathBuf>,
+}
+
+#[cfg(feature = "readline")]
+impl Prompt {
+    fn new(db: &HeuristicDb, persist: bool) -> Self {
+        let history =
+            data_dir().filter(|_| persist).map(|dir| dir.join("repl_history"));
+        let editor = rustyline::Editor::new().ok().map(
+            |mut editor: rustyline::Editor<ReplHelper, _>| {
+                let ids = db.all().iter().map(|h| h.id.clone()).collect();
+                editor.set_helper(Some(ReplHelper {
+                    ids,
+                    categories: db.categories(),
+                }));
+                if let Some(history) = &history {
+                    editor.load_history(history).ok();
+                }
+                editor
+            },
+        );
+        Self { editor, history }
+    }
+
+    /// The next line, or `None` at end of input
+    fn read(&mut self) -> Option<String> {
+        let Some(editor) = &mut self.editor else { return read_prompt_line() };
+        loop {
+            match editor.readline("heuristics> ") {
+                Ok(line) => {
+                    editor.add_history_entry(line.as_str()).ok();
+                    return Some(line);
+                }
+                // Ctrl-C abandons the line, not the session
+                Err(rustyline::error::ReadlineError::Interrupted) => continue,
+                Err(_) => return None,
+            }
+        }
+    }
+
+    fn save(&mut self) {
+        if let (Some(editor), Some(history)) =
+            (&mut self.editor, &self.history)
+        {
+            history.parent().map(std::fs::create_dir_all);
+            editor.save_history(history).ok();
+        }
+    }
+}
+
+/// Tab completion of `repl` commands and their arguments
+#[cfg(feature = "readline")]
+struct ReplHelper {
+    ids: Vec<String>,
+    categories: Vec<String>,
+}
+
+#[cfg(feature = "readline")]
+impl rustyline::completion::Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let line = &line[..pos];
+        let matching = |candidates: &mut dyn Iterator<Item = &str>,
+                        partial: &str|
+         -> Vec<String> {
+            let partial = partial.to_lowercase();
+            candidates
+                .filter(|c| c.to_lowercase().starts_with(&partial))
+                .map(String::from)
+                .collect()
+        };
+        let Some((command, argument)) = line.split_once(' ') else {
+            return Ok((
+                0,
+                matching(
+                    &mut REPL_COMMANDS.iter().map(|(name, _)| *name),
+                    line,
+                ),
+            ));
+        };
+        let start = command.len() + 1;
+        Ok(match command {
+            // Category names have spaces, so the whole argument is completed
+            "category" => (
+                start,
+                matching(
+                    &mut self.categories.iter().map(String::as_str),
+                    argument,
+                ),
+            ),
+            "show" | "bookmark" | "unbookmark" => (
+                start,
+                matching(&mut self.ids.iter().map(String::as_str), argument),
+            ),
+            _ => (pos, Vec::new()),
+        })
+    }
+}
+
+#[cfg(feature = "readline")]
+impl rustyline::hint::Hinter for ReplHelper {
+    type Hint = String;
+}
+
+#[cfg(feature = "readline")]
+impl rustyline::highlight::Highlighter for ReplHelper {}
+
+#[cfg(feature = "readline")]
+impl rustyline::validate::Validator for ReplHelper {}
+
+#[cfg(feature = "readline")]
+impl rustyline::Helper for ReplHelper {}
+
+#[cfg(not(feature = "readline"))]
+struct Prompt;
+
+#[cfg(not(feature = "readline"))]
+impl Prompt {
+    fn new(_db: &HeuristicDb, _persist: bool) -> Self {
+        Self
+    }
+
+    /// The next line, or `None` at end of input
+    fn read(&mut self) -> Option<String> {
+        read_prompt_line()
+    }
+
+    fn save(&mut self) {}
+}
+
+/// Prompt on stderr and read a line from stdin, as the pickers do
+fn read_prompt_line() -> Option<String> {
+    eprint!("{} ", "heuristics>".green().bold());
+    let mut line = String::new();
+    if std::io::stdin().read_line(&mut line).ok()? == 0 {
+        eprintln!();
+        return None;
+    }
+    Some(line)
+}
+
+/// Defaults from the config file; command-line flags win over them
+#[derive(Default)]
+struct Config {
+    /// Results per search
+    limit: Option<usize>,
+    color: Option<ColorChoice>,
+    format: Option<Format>,
+    /// Packs loaded before any `--extra` ones
+    extra: Vec<PathBuf>,
+    /// Only show heuristics in these categories, unless a command names one
+    categories: Vec<String>,
+    weights: ScoreWeights,
+    /// Saved queries by name, in file order
+    aliases: Vec<(String, String)>,
+}
+
+/// `$XDG_CONFIG_HOME/heuristics/config.toml`, falling back to
+/// `~/.config/heuristics/config.toml`
+fn config_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .map(|home| PathBuf::from(home).join(".config"))
+        })?;
+    Some(base.join("heuristics").join("config.toml"))
+}
+
+impl Config {
+    /// Read the config file; a missing one means no defaults
+    fn load() -> Result<Self, String> {
+        let Some(path) = config_path() else { return Ok(Self::default()) };
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Self::default());
+            }
+            Err(err) => {
+                return Err(format!(
+                    "Could not read {}: {}",
+                    path.display(),
+                    err
+                ));
+            }
+        };
+        tracing::info!(path = %path.display(), "read config file");
+        let dir = path.parent().unwrap_or(Path::new("."));
+        Self::parse(&text, dir).map_err(|(line, problem)| {
+            format!("{}:{}: {}", path.display(), line, problem)
+        })
+    }
+
+    /// Read the keys the CLI understands from the subset of TOML a config
+    /// needs: strings, integers, and one-line string arrays
+    ///
+    /// Relative `extra` paths are relative to `dir`. Errors carry the line
+    /// number. Like `Manifest::parse`, this works without the `toml` feature.
+    fn parse(text: &str, dir: &Path) -> Result<Self, (usize, String)> {
+        let mut config = Config::default();
+        let mut table = String::new();
+        for (number, line) in text.lines().enumerate() {
+            let number = number + 1;
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(header) =
+                line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']'))
+            {
+                table = header.trim().to_string();
+                if table != "weights" && table != "aliases" {
+                    return Err((
+                        number,
+                        format!("unknown table [{}]", table),
+                    ));
+                }
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err((number, "expected `key = value`".to_string()));
+            };
+            let (key, value) = (key.trim(), value.trim());
+            let set = match (table.as_str(), key) {
+                ("", "limit") => toml_integer(value).map(|limit| config.limit = Some(limit)),
+                ("", "color") => toml_choice(value).map(|color| config.color = Some(color)),
+                ("", "format") => match toml_choice::<Format>(value) {
+                    Ok(format) if format.is_export_only() => {
+                        Err("csv, html, anki, mdbook, man, obsidian, hugo, zola, sqlite, dot, and search-index are only for `heuristics export`".to_string())
+                    }
+                    format => format.map(|format| config.format = Some(format)),
+                },
+                ("", "extra") => toml_strings(value).map(|paths| {
+                    config.extra = paths.iter().map(|path| expand_path(path, dir)).collect();
+                }),
+                ("", "categories") => toml_strings(value).map(|categories| config.categories = categories),
+                ("weights", "exact") => toml_integer(value).map(|weight| config.weights.exact = weight),
+                ("weights", "partial") => toml_integer(value).map(|weight| config.weights.partial = weight),
+                ("aliases", name) => toml_string(value).and_then(|query| {
+                    parse_query(&query)?;
+                    config.aliases.retain(|(alias, _)| alias != name);
+                    config.aliases.push((name.to_string(), query));
+                    Ok(())
+                }),
+                _ => Err("unknown key".to_string()),
+            };
+            set.map_err(|problem| (number, format!("{}: {}", key, problem)))?;
+        }
+        Ok(config)
+    }
+}
+
+/// Keywords of a saved query, and the `search` flags for its filters
+///
+/// Words are split on whitespace except inside double quotes, so
+/// `category:"Data Structures"` is one filter.
+fn parse_query(query: &str) -> Result<Vec<String>, String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+    for c in query.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                words.push(std::mem::take(&mut word))
+            }
+            c => word.push(c),
+        }
+    }
+    if quoted {
+        return Err("unclosed quote".to_string());
+    }
+    words.push(word);
+
+    let mut keywords = Vec::new();
+    let mut flags = Vec::new();
+    for word in words.into_iter().filter(|word| !word.is_empty()) {
+        match word.split_once(':') {
+            Some((
+                key @ ("category" | "crate" | "std-type" | "tag"),
+                value,
+            )) => {
+                flags.extend([format!("--{}", key), value.to_string()]);
+            }
+            _ => keywords.push(word),
+        }
+    }
+    if keywords.is_empty() {
+        return Err("a query needs at least one keyword".to_string());
+    }
+    Ok(keywords.into_iter().chain(flags).collect())
+}
+
+/// The command line with a saved query's name replaced by its search
+///
+/// `words` are the arguments from the alias on, so any extra keywords or
+/// flags after the name are kept. `None` if no alias has that name.
+fn expand_alias(
+    config: &Config,
+    args: &[String],
+    words: &[String],
+) -> Option<Vec<String>> {
+    let (_, query) =
+        config.aliases.iter().find(|(name, _)| *name == words[0])?;
+    let at = args.len() - words.len();
+    let search = parse_query(query).ok()?;
+    let expanded = args[..at]
+        .iter()
+        .cloned()
+        .chain(std::iter::once("search".to_string()))
+        .chain(search);
+    Some(expanded.chain(words[1..].iter().cloned()).collect())
+}
+
+/// Set or, with no query, remove an alias in the config file's
+/// `[aliases]` table, leaving the rest of the file as it was
+fn save_alias(name: &str, query: Option<&str>) -> Result<PathBuf, String> {
+    let path = config_path()
+        .ok_or("No config directory; set XDG_CONFIG_HOME or HOME")?;
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        || name.is_empty()
+    {
+        return Err(format!(
+            "Alias names may only use letters, digits, `-`, and `_`: {}",
+            name
+        ));
+    }
+    let entry = match query {
+        // TOML literal strings need no escapes, so quotes in the query stay readable
+        Some(query) if !query.contains('\'') => {
+            Some(format!("{} = '{}'", name, query))
+        }
+        Some(query) => Some(format!(
+            "{} = \"{}\"",
+            name,
+            query.replace('\\', "\\\\").replace('"', "\\\"")
+        )),
+        None => None,
+    };
+
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            String::new()
+        }
+        Err(err) => {
+            return Err(format!("Could not read {}: {}", path.display(), err));
+        }
+    };
+    let mut lines: Vec<String> = text.lines().map(String::from).collect();
+    let table_of = |line: &str| {
+        let line = strip_comment(line).trim();
+        line.strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+            .map(|table| table.trim().to_string())
+    };
+    let start = lines
+        .iter()
+        .position(|line| table_of(line).as_deref() == Some("aliases"));
+    match start {
+        Some(start) => {
+            let end = lines[start + 1..]
+                .iter()
+                .position(|line| table_of(line).is_some())
+                .map_or(lines.len(), |i| start + 1 + i);
+            let existing = (start + 1..end).find(|&i| {
+                strip_comment(&lines[i])
+                    .split_once('=')
+                    .is_some_and(|(key, _)| key.trim() == name)
+            });
+            match (existing, entry) {
+                (Some(i), Some(entry)) => lines[i] = entry,
+                (Some(i), None) => {
+                    lines.remove(i);
+                }
+                (None, Some(entry)) => {
+                    // After the table's last entry, before any blank lines
+                    let last = (start..end)
+                        .rev()
+                        .find(|&i| !lines[i].trim().is_empty())
+                        .unwrap_or(start);
+                    lines.insert(last + 1, entry);
+                }
+                (None, None) => {}
+            }
+        }
+        None => {
+            if let Some(entry) = entry {
+                if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                    lines.push(String::new());
+                }
+                lines.extend(["[aliases]".to_string(), entry]);
+            }
+        }
+    }
+
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, lines.join("\n") + "\n"));
+    written.map_err(|err| {
+        format!("Could not write {}: {}", path.display(), err)
+    })?;
+    Ok(path)
+}
+
+/// `line` without a trailing `# comment`, leaving `#` inside strings alone
+fn strip_comment(line: &str) -> &str {
+    unquoted(line).find(|&(_, c)| c == '#').map_or(line, |(i, _)| &line[..i])
+}
+
+/// The characters of `line` outside quoted strings, with their positions
+fn unquoted(line: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut quote = None;
+    let mut escaped = false;
+    line.char_indices().filter(move |&(_, c)| {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            // Only basic strings have escapes
+            Some('"') if c == '\\' => escaped = true,
+            Some(open) if c == open => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None => return true,
+        }
+        false
+    })
+}
+
+/// A basic `"..."` string with its escapes resolved, or a literal `'...'`
+/// string as written
+fn toml_string(value: &str) -> Result<String, String> {
+    let value = value.trim();
+    let unquoted_error =
+        || format!("expected a quoted string, found `{}`", value);
+    if let Some(literal) =
+        value.strip_prefix('\'').and_then(|rest| rest.strip_suffix('\''))
+    {
+        return Ok(literal.to_string());
+    }
+    let basic = value
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .filter(|_| value.len() > 1)
+        .ok_or_else(unquoted_error)?;
+    let mut out = String::with_capacity(basic.len());
+    let mut chars = basic.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        let unescaped = match chars.next() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some(size @ ('u' | 'U')) => {
+                let digits: String = chars
+                    .by_ref()
+                    .take(if size == 'u' { 4 } else { 8 })
+                    .collect();
+                u32::from_str_radix(&digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| {
+                        format!("invalid escape `\\{}{}`", size, digits)
+                    })?
+            }
+            Some(other) => {
+                return Err(format!("invalid escape `\\{}`", other));
+            }
+            None => return Err(unquoted_error()),
+        };
+        out.push(unescaped);
+    }
+    Ok(out)
+}
+
+/// A string array, or a single string
+fn toml_strings(value: &str) -> Result<Vec<String>, String> {
+    let Some(items) = value.strip_prefix('[') else {
+        return Ok(vec![toml_string(value)?]);
+    };
+    let items =
+        items.strip_suffix(']').ok_or("arrays must close on the same line")?;
+    let commas: Vec<usize> =
+        unquoted(items).filter(|&(_, c)| c == ',').map(|(i, _)| i).collect();
+    let starts = std::iter::once(0).chain(commas.iter().map(|i| i + 1));
+    let ends = commas.iter().copied().chain(std::iter::once(items.len()));
+    starts
+        .zip(ends)
+        .map(|(start, end)| &items[start..end])
+        .filter(|item| !item.trim().is_empty())
+        .map(toml_string)
+        .collect()
+}
+
+fn toml_integer(value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("expected a whole number, found `{}`", value))
+}
+
+fn toml_choice<T: ValueEnum>(value: &str) -> Result<T, String> {
+    let value = toml_string(value)?;
+    T::from_str(&value, true).map_err(|_| {
+        let choices: Vec<String> = T::value_variants()
+            .iter()
+            .filter_map(|v| v.to_possible_value())
+            .map(|v| v.get_name().to_string())
+            .collect();
+        format!("expected one of {}, found `{}`", choices.join(", "), value)
+    })
+}
+
+/// A configured path, with `~/` for the home directory and relative paths
+/// resolved against `dir`
+fn expand_path(path: &str, dir: &Path) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => dir.join(path),
+    }
+}
+
+/// Searches kept in the history file
+const HISTORY_LIMIT: usize = 1000;
+
+/// Directory for the user's own data: search history and notes
+///
+/// `$XDG_DATA_HOME/heuristics`, falling back to `~/.local/share/heuristics`.
+fn data_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .map(|home| PathBuf::from(home).join(".local").join("share"))
+        })?;
+    Some(base.join("heuristics"))
+}
+
+/// File recording past searches, one per line with keywords separated by tabs
+fn history_path() -> Option<PathBuf> {
+    Some(data_dir()?.join("history"))
+}
+
+/// The user's own heuristic pack, loaded on top of the built-in one and
+/// written by `heuristics add`
+fn user_pack_path() -> Option<PathBuf> {
+    Some(data_dir()?.join("pack.md"))
+}
+
+/// Lines of the `add` template starting with this are instructions, removed
+/// before the heuristic is checked
+const TEMPLATE_COMMENT: &str = "<!--";
+
+/// The skeleton `heuristics add` opens in the editor
+fn heuristic_template(category: &str, title: &str) -> String {
+    format!(
+        "{c} Describe one rule of thumb; lines starting with {c} are removed. -->\n\
+         {c} Title it with the question it answers. Delete the fields you don't need. -->\n\
+         ## {category}\n\n\
+         ### {title}\n\
+         **Action:** \n\n\
+         - **Crates:** \n\
+         - **When to use:** \n\
+         - **Pros:** \n\
+         - **Cons:** \n",
+        c = TEMPLATE_COMMENT,
+    )
+}
+
+/// Open `text` in the user's editor until `check` accepts the result
+///
+/// Template comment lines are removed from the result. Returns `None` if
+/// the editor fails, the text is left unchanged, or the user gives up
+/// fixing what `check` rejects; the draft is kept in that last case.
+fn edit_heuristic(
+    text: &str,
+    check: impl Fn(&str) -> Result<(), String>,
+) -> Option<String> {
+    let dir = data_dir().unwrap_or_else(std::env::temp_dir);
+    let draft = dir.join("draft.md");
+    if let Err(err) = std::fs::create_dir_all(&dir)
+        .and_then(|_| std::fs::write(&draft, text))
+    {
+        eprintln!(
+            "{}",
+            format!("Could not write {}: {}", draft.display(), err).red()
+        );
+        return None;
+    }
+
+    loop {
+        if let Err(err) = open_editor(&draft) {
+            eprintln!(
+                "{}",
+                format!("Could not run the editor: {}", err).red()
+            );
+            return None;
+        }
+        let edited = std::fs::read_to_string(&draft).unwrap_or_default();
+        if edited.trim() == text.trim() {
+            std::fs::remove_file(&draft).ok();
+            eprintln!("{}", "Nothing changed; nothing saved.".yellow());
+            return None;
+        }
+        let kept: Vec<&str> = edited
+            .lines()
+            .filter(|line| !line.trim_start().starts_with(TEMPLATE_COMMENT))
+            .collect();
+        let entry = format!("{}\n", kept.join("\n").trim());
+
+        match check(&entry) {
+            Ok(()) => {
+                std::fs::remove_file(&draft).ok();
+                return Some(entry);
+            }
+            Err(problem) => eprintln!("{}", problem.red()),
+        }
+        eprint!("{} ", "Edit again? [Y/n]".cyan());
+        let mut answer = String::new();
+        let read = std::io::stdin().read_line(&mut answer).unwrap_or(0);
+        if read == 0 || answer.trim().eq_ignore_ascii_case("n") {
+            if read == 0 {
+                eprintln!();
+            }
+            eprintln!(
+                "{}",
+                format!("Draft kept at {}", draft.display()).yellow()
+            );
+            return None;
+        }
+    }
+}
+
+/// A parse error with every strict-mode problem on its own line
+fn parse_problems(err: ParseError) -> String {
+    match err {
+        ParseError::Rejected(diagnostics) => diagnostics
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        err => err.to_string(),
+    }
+}
+
+/// The docs.rs page of a crate, and its crates.io page if asked for
+fn docs_urls(name: &str, crates_io: bool) -> Vec<String> {
+    let mut urls = vec![format!("https://docs.rs/{}", name)];
+    if crates_io {
+        urls.push(format!("https://crates.io/crates/{}", name));
+    }
+    urls
+}
+
+/// Open each URL in `$BROWSER` or the platform's opener, or just print it
+fn open_urls(urls: &[String], print: bool) -> std::io::Result<()> {
+    for url in urls {
+        if print {
+            println!("{}", url);
+            continue;
+        }
+        let browser = std::env::var("BROWSER")
+            .ok()
+            .filter(|browser| !browser.trim().is_empty());
+        let mut command = match browser.as_deref() {
+            // Browsers are often configured with arguments, as in `firefox --new-tab`
+            Some(browser) => {
+                let mut words = browser.split_whitespace();
+                let mut command =
+                    Command::new(words.next().unwrap_or_default());
+                command.args(words);
+                command
+            }
+            None if cfg!(target_os = "macos") => Command::new("open"),
+            None if cfg!(windows) => {
+                let mut command = Command::new("cmd");
+                command.args(["/C", "start", ""]);
+                command
+            }
+            None => Command::new("xdg-open"),
+        };
+        let program = command.get_program().to_string_lossy().into_owned();
+        let status = command.arg(url).status()?;
+        if !status.success() {
+            return Err(std::io::Error::other(format!(
+                "{} exited with {}",
+                program, status
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Run `$VISUAL` or `$EDITOR` (default `vi`) on `path` and wait for it
+fn open_editor(path: &std::path::Path) -> std::io::Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // Editors are often configured with arguments, as in `code --wait`
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let status = Command::new(program).args(words).arg(path).status()?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(std::io::Error::other(format!(
+            "{} exited with {}",
+            program, status
+        ))),
+    }
+}
+
+/// File holding notes on heuristics, one per line after the heuristic's id
+/// and a tab
+fn notes_path() -> Option<PathBuf> {
+    Some(data_dir()?.join("notes"))
+}
+
+/// File listing bookmarked heuristics from `repl`, one id per line
+fn bookmarks_path() -> Option<PathBuf> {
+    Some(data_dir()?.join("bookmarks"))
+}
+
+/// Ids of the bookmarked heuristics, oldest first
+fn load_bookmarks() -> Vec<String> {
+    let Some(text) =
+        bookmarks_path().and_then(|path| std::fs::read_to_string(path).ok())
+    else {
+        return Vec::new();
+    };
+    text.lines().filter(|line| !line.is_empty()).map(String::from).collect()
+}
+
+/// Replace every bookmark with `bookmarks`
+fn save_bookmarks(bookmarks: &[String]) -> std::io::Result<()> {
+    let path = bookmarks_path().ok_or_else(|| {
+        std::io::Error::other("no data directory; set XDG_DATA_HOME or HOME")
+    })?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(
+        path,
+        bookmarks.iter().map(|id| format!("{}\n", id)).collect::<String>(),
+    )
+}
+
+/// Every note as (heuristic id, note), oldest first
+fn load_notes() -> Vec<(String, String)> {
+    let Some(text) =
+        notes_path().and_then(|path| std::fs::read_to_string(path).ok())
+    else {
+        return Vec::new();
+    };
+    text.lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(id, note)| (id.to_string(), note.to_string()))
+        .collect()
+}
+
+/// The notes on one heuristic, oldest first
+fn notes_on(id: &str) -> Vec<String> {
+    load_notes()
+        .into_iter()
+        .filter(|(on, _)| on == id)
+        .map(|(_, note)| note)
+        .collect()
+}
+
+/// Replace every note with `notes`
+fn save_notes(notes: &[(String, String)]) -> std::io::Result<()> {
+    let path = notes_path().ok_or_else(|| {
+        std::io::Error::other("no data directory; set XDG_DATA_HOME or HOME")
+    })?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let lines: Vec<String> =
+        notes.iter().map(|(id, note)| format!("{}\t{}\n", id, note)).collect();
+    std::fs::write(path, lines.concat())
+}
+
+/// File recording the daily tips, one line per day: the day as counted by
+/// [`days_since_epoch`], a tab, and the id shown
+fn daily_path() -> Option<PathBuf> {
+    Some(data_dir()?.join("daily"))
+}
+
+/// Daily tips shown so far, oldest first
+fn load_daily() -> Vec<(u64, String)> {
+    let Some(text) =
+        daily_path().and_then(|path| std::fs::read_to_string(path).ok())
+    else {
+        return Vec::new();
+    };
+    text.lines()
+        .filter_map(|line| line.split_once('\t'))
+        .filter_map(|(day, id)| Some((day.parse().ok()?, id.to_string())))
+        .collect()
+}
+
+/// Ids of the daily tips of the current cycle, in the order shown; once
+/// every one of `shown` has been, that cycle is complete
+///
+/// Counting cycles from the whole log, rather than clearing it, keeps the
+/// streak and copes with heuristics being added to or removed from the pack.
+fn seen_this_cycle<'a>(
+    log: &'a [(u64, String)],
+    shown: &[&Heuristic],
+) -> Vec<&'a str> {
+    let mut seen: Vec<&str> = Vec::new();
+    for (_, id) in log {
+        // A tip after a complete cycle starts the next one
+        if seen.len() == shown.len() {
+            seen.clear();
+        }
+        if shown.iter().any(|h| h.id == *id) && !seen.contains(&id.as_str()) {
+            seen.push(id);
+        }
+    }
+    seen
+}
+
+/// Add today's tip to the daily log
+///
+/// Like the search history, failing to write it doesn't fail the command.
+fn record_daily(day: u64, id: &str) {
+    let Some(path) = daily_path() else { return };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).ok();
+    }
+    let appended =
+        std::fs::OpenOptions::new().create(true).append(true).open(&path);
+    if let Ok(mut file) = appended {
+        use std::io::Write;
+        writeln!(file, "{}\t{}", day, id).ok();
+    }
+}
+
+/// File counting how often each heuristic has been shown, one line per
+/// heuristic: the count, a tab, and its id
+fn usage_path() -> Option<PathBuf> {
+    Some(data_dir()?.join("usage"))
+}
+
+/// How often each heuristic has been shown, so `random` and `daily` can
+/// favour the ones seen least
+fn load_usage() -> UsageLog {
+    usage_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .map(|text| UsageLog::parse(&text))
+        .unwrap_or_default()
+}
+
+/// Count one more view of `heuristic` in the usage log
+///
+/// Like the search history, failing to write it doesn't fail the command.
+fn record_view(heuristic: &Heuristic) {
+    let Some(path) = usage_path() else { return };
+    let mut usage = load_usage();
+    usage.record(heuristic);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).ok();
+    }
+    std::fs::write(&path, usage.to_string()).ok();
+}
+
+/// What `cache clear` does with a stored file
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Stored {
+    /// Downloaded or recorded along the way; always cleared
+    Cache,
+    /// Saved on purpose; cleared only with `--all`
+    UserData,
+    /// Written by hand; never cleared
+    Kept,
+}
+
+/// Everything the CLI stores on disk, by name, with what clearing does to it
+fn stored_paths() -> Vec<(&'static str, Option<PathBuf>, Stored)> {
+    vec![
+        ("Downloaded packs", heuristics::cache_dir(), Stored::Cache),
+        ("Search history", history_path(), Stored::Cache),
+        ("Daily tips", daily_path(), Stored::Cache),
+        ("View counts", usage_path(), Stored::Cache),
+        ("Bookmarks", bookmarks_path(), Stored::UserData),
+        ("Notes", notes_path(), Stored::UserData),
+        ("Your pack", user_pack_path(), Stored::Kept),
+    ]
+}
+
+/// What `heuristics export --format json` writes: every field of each
+/// heuristic, its content included
+fn export_document(
+    format: Format,
+    db: &HeuristicDb,
+    heuristics: &[&Heuristic],
+) -> String {
+    let results: Vec<Value> = heuristics
+        .iter()
+        .map(|h| {
+            let mut result = heuristic_json(h);
+            result["content"] = json!(h.content);
+            result
+        })
+        .collect();
+    document(
+        format,
+        json!({
+            "command": "export",
+            "fingerprint": format!("{:016x}", db.fingerprint()),
+            "results": results,
+        }),
+    )
+}
+
+/// Write an export's files under `dir`, creating directories as needed
+fn write_files(dir: &Path, files: &[(PathBuf, String)]) -> Result<(), String> {
+    for (path, contents) in files {
+        let path = dir.join(path);
+        let written = match path.parent() {
+            Some(parent) => std::fs::create_dir_all(parent)
+                .and_then(|()| std::fs::write(&path, contents)),
+            None => std::fs::write(&path, contents),
+        };
+        written.map_err(|err| {
+            format!("Could not write {}: {}", path.display(), err)
+        })?;
+    }
+    Ok(())
+}
+
+/// Number of files under `path` and their total size in bytes
+fn disk_usage(path: &Path) -> (usize, u64) {
+    let Ok(metadata) = std::fs::metadata(path) else { return (0, 0) };
+    if !metadata.is_dir() {
+        return (1, metadata.len());
+    }
+    let entries = std::fs::read_dir(path).into_iter().flatten().flatten();
+    entries
+        .map(|entry| disk_usage(&entry.path()))
+        .fold((0, 0), |(files, bytes), (f, b)| (files + f, bytes + b))
+}
+
+/// A size in bytes, KiB, or MiB
+fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{} B", bytes),
+        1024..1_048_576 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+/// Past searches, oldest first
+fn load_history() -> Vec<Vec<String>> {
+    let Some(text) =
+        history_path().and_then(|path| std::fs::read_to_string(path).ok())
+    else {
+        return Vec::new();
+    };
+    text.lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.split('\t').map(String::from).collect())
+        .collect()
+}
+
+/// Add a search to the history, unless it repeats the last one
+///
+/// History is a convenience, so failing to write it doesn't fail the search.
+fn record_search(keywords: &[String]) {
+    let Some(path) = history_path() else { return };
+    let mut history = load_history();
+    if keywords.is_empty()
+        || history.last().is_some_and(|last| last == keywords)
+    {
+        return;
+    }
+    history
+        .push(keywords.iter().map(|k| k.replace(['\t', '\n'], " ")).collect());
+    let skip = history.len().saturating_sub(HISTORY_LIMIT);
+    let lines: Vec<String> = history[skip..]
+        .iter()
+        .map(|keywords| keywords.join("\t") + "\n")
+        .collect();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).ok();
+    }
+    std::fs::write(&path, lines.concat()).ok();
+}
+
+/// An answer to a decision-tree question
+enum Answer {
+    Yes,
+    No,
+    Back,
+}
+
+/// Ask a yes/no question on stderr until the answer is understood
+///
+/// `b` answers [`Answer::Back`]. Returns `None` on `q` or at end of input.
+fn ask(question: &str) -> Option<Answer> {
+    let stdin = std::io::stdin();
+    loop {
+        eprint!("{} {} ", question.cyan(), "[y/n/b/q]".dimmed());
+        let mut line = String::new();
+        if stdin.read_line(&mut line).ok()? == 0 {
+            eprintln!();
+            return None;
+        }
+        match line.trim().to_lowercase().as_str() {
+            "y" | "yes" => return Some(Answer::Yes),
+            "n" | "no" => return Some(Answer::No),
+            "b" | "back" => return Some(Answer::Back),
+            "q" | "quit" => return None,
+            _ => eprintln!("{}", "Answer y (yes), n (no), b (back to the previous question), or q (quit).".yellow()),
+        }
+    }
+}
+
+/// Walk the decision tree by asking about each follow-up question in turn
+///
+/// At the top, the user first picks a category unless `category` names
+/// one. Answering yes descends into a question; when no follow-up fits,
+/// the question's own advice is the recommendation.
+fn walk_tree<'a>(
+    db: &'a HeuristicDb,
+    category: Option<&str>,
+    shows: &dyn Fn(&Heuristic) -> bool,
+) -> Option<&'a Heuristic> {
+    let roots: Vec<&Heuristic> = db
+        .decision_root()
+        .children()
+        .iter()
+        .filter_map(|node| node.heuristic())
+        .filter(|h| shows(h))
+        .collect();
+    let mut categories: Vec<&str> = Vec::new();
+    for heuristic in &roots {
+        if !categories.contains(&heuristic.category.as_str()) {
+            categories.push(&heuristic.category);
+        }
+    }
+
+    let given = category.is_some();
+    let mut chosen = category.map(|name| name.to_string());
+    // Each level holds its questions and the one being asked
+    let mut levels: Vec<(Vec<&Heuristic>, usize)> = Vec::new();
+    loop {
+        let Some(category) = &chosen else {
+            let mut index = 0;
+            while index < categories.len() {
+                match ask(&format!("Is it about {}?", categories[index]))? {
+                    Answer::Yes => break,
+                    Answer::No => index += 1,
+                    Answer::Back => index = index.saturating_sub(1),
+                }
+            }
+            let Some(category) = categories.get(index) else {
+                eprintln!(
+                    "{}",
+                    "That's every category; try `heuristics search` instead."
+                        .yellow()
+                );
+                return None;
+            };
+            chosen = Some(category.to_string());
+            continue;
+        };
+
+        if levels.is_empty() {
+            let in_category: Vec<&Heuristic> = db
+                .by_category(category)
+                .into_iter()
+                .filter(|h| roots.contains(h))
+                .collect();
+            if in_category.is_empty() {
+                eprintln!(
+                    "{}",
+                    format!("No questions in category: {}", category).red()
+                );
+                return None;
+            }
+            levels.push((in_category, 0));
+        }
+
+        let (questions, index) = levels.last_mut()?;
+        let Some(&question) = questions.get(*index) else {
+            // No follow-up fits: the parent's own advice applies
+            levels.pop();
+            match levels.last() {
+                Some((questions, index)) => return Some(questions[*index]),
+                None => {
+                    eprintln!("{}", "None of the questions fit; try `heuristics search` instead.".yellow());
+                    return None;
+                }
+            }
+        };
+        match ask(&question.title)? {
+            Answer::Yes => {
+                let children: Vec<&Heuristic> = db
+                    .children_of(question)
+                    .into_iter()
+                    .filter(|h| shows(h))
+                    .collect();
+                if children.is_empty() {
+                    return Some(question);
+                }
+                eprintln!("{} {}", "→".dimmed(), question.action.dimmed());
+                levels.push((children, 0));
+            }
+            Answer::No => *index += 1,
+            Answer::Back if *index > 0 => *index -= 1,
+            Answer::Back => {
+                levels.pop();
+                if levels.is_empty() && !given {
+                    chosen = None;
+                }
+            }
+        }
+    }
+}
+
+fn parse_rust_version(text: &str) -> Result<RustVersion, String> {
+    RustVersion::parse(text).ok_or_else(|| {
+        format!("expected a Rust version like 1.70, got '{}'", text)
+    })
+}
+
+fn print_heuristic(
+    heuristic: &Heuristic,
+    index: usize,
+    citations: bool,
+    benchmarks: bool,
+) {
+    let style = if colored::control::SHOULD_COLORIZE.should_colorize() {
+        RenderStyle::Ansi
+    } else {
+        RenderStyle::Plain
+    };
+    print!(
+        "{}{}",
+        format!("{}. ", index).cyan().bold(),
+        heuristic.render(style)
+    );
+    if !heuristic.provenance.is_stock() {
+        println!(
+            "   {}",
+            message("label-from", &[("source", &heuristic.provenance)])
+                .dimmed()
+        );
+    }
+
+    if citations && !heuristic.sources.is_empty() {
+        println!("   {}", message("label-sources", &[]).green().bold());
+        for source in &heuristic.sources {
+            println!("     - {} {}", source.title, source.url.dimmed());
+        }
+    }
+
+    if benchmarks && !heuristic.benchmarks.is_empty() {
+        println!("   {}", message("label-benchmarks", &[]).green().bold());
+        for link in &heuristic.benchmarks {
+            println!("     - {} {}", link.text, link.url.dimmed());
+        }
+    }
+
+    println!();
+}
+
+/// Print the `matched` lines of `content` with `context` lines around them,
+/// grep style: `:` after the number of a match, `-` after context, and
+/// `--` between separate stretches
+fn print_matches(
+    content: &str,
+    matched: &[usize],
+    matcher: &regex::Regex,
+    context: usize,
+) {
+    let lines: Vec<&str> = content.lines().collect();
+    let shown: std::collections::BTreeSet<usize> = matched
+        .iter()
+        .flat_map(|&i| {
+            i.saturating_sub(context)..=(i + context).min(lines.len() - 1)
+        })
+        .collect();
+    let mut previous: Option<usize> = None;
+    for n in shown {
+        if previous.is_some_and(|previous| n > previous + 1) {
+            println!("  {}", "--".dimmed());
+        }
+        let (separator, text) = match matched.contains(&n) {
+            true => (":", highlight(lines[n], matcher)),
+            false => ("-", lines[n].to_string()),
+        };
+        println!(
+            "  {}{} {}",
+            format!("{:>3}", n + 1).dimmed(),
+            separator.dimmed(),
+            text
+        );
+        previous = Some(n);
+    }
+}
+
+/// `line` with every match of `matcher` highlighted
+fn highlight(line: &str, matcher: &regex::Regex) -> String {
+    let mut out = String::new();
+    let mut end = 0;
+    for found in matcher.find_iter(line) {
+        out.push_str(&line[end..found.start()]);
+        out.push_str(&found.as_str().red().bold().to_string());
+        end = found.end();
+    }
+    out.push_str(&line[end..]);
+    out
+}
+
+/// Print one heuristic per line for shell scripts
+///
+/// Tabs and line breaks inside fields become spaces so every line splits
+/// into the same columns.
+fn print_lines<'a>(
+    output: LineOutput,
+    heuristics: impl Iterator<Item = &'a Heuristic>,
+) {
+    let field = |text: &str| {
+        text.split(['\t', '\n', '\r']).collect::<Vec<_>>().join(" ")
+    };
+    for h in heuristics {
+        match output {
+            LineOutput::Ids => println!("{}", h.id),
+            LineOutput::Tsv => println!(
+                "{}\t{}\t{}\t{}",
+                h.id,
+                field(&h.category),
+                field(&h.title),
+                field(&h.action)
+            ),
+        }
+    }
+}
+
+/// Order heuristics for `list --sort`, keeping pack order among equals
+fn sort_heuristics(heuristics: &mut [&Heuristic], order: ListOrder) {
+    match order {
+        ListOrder::Title => heuristics.sort_by_key(|h| h.title.to_lowercase()),
+        ListOrder::Category => {
+            heuristics.sort_by_key(|h| h.category.to_lowercase())
+        }
+        ListOrder::CrateCount => {
+            heuristics.sort_by_key(|h| std::cmp::Reverse(h.crates.len()))
+        }
+        // Undated heuristics go last
+        ListOrder::RecentlyUpdated => {
+            heuristics.sort_by_key(|h| std::cmp::Reverse(revision(h)))
+        }
+    }
+}
+
+/// When a heuristic was last revised, as numbers to compare
+///
+/// Both dates (`2025-11-17`) and versions (`0.1.0`) compare correctly
+/// part by part, unlike their text. Parts that aren't numbers are skipped.
+fn revision(heuristic: &Heuristic) -> Option<Vec<u64>> {
+    let text = heuristic.updated.as_deref().or(heuristic.since.as_deref())?;
+    Some(
+        text.split(|c: char| !c.is_ascii_digit())
+            .filter_map(|part| part.parse().ok())
+            .collect(),
+    )
+}
+
+/// Sections for `list --group-by`, each keeping the list's order
+fn group_heuristics<'a>(
+    heuristics: &[&'a Heuristic],
+    group_by: ListGroup,
+) -> Vec<(String, Vec<&'a Heuristic>)> {
+    let mut groups: Vec<(String, Vec<&Heuristic>)> = Vec::new();
+    let mut add = |name: &str, heuristic| match groups
+        .iter_mut()
+        .find(|(group, _)| group.eq_ignore_ascii_case(name))
+    {
+        Some((_, members)) => members.push(heuristic),
+        None => groups.push((name.to_string(), vec![heuristic])),
+    };
+    for &heuristic in heuristics {
+        match group_by {
+            ListGroup::Category => add(&heuristic.category, heuristic),
+            ListGroup::Crate if heuristic.crates.is_empty() => {
+                add("No crate", heuristic)
+            }
+            ListGroup::Crate => {
+                heuristic.crates.iter().for_each(|name| add(name, heuristic))
+            }
+            ListGroup::Level => add(
+                &heuristic.level.map_or("unspecified".to_string(), |level| {
+                    level.to_string()
+                }),
+                heuristic,
+            ),
+        }
+    }
+    // Categories keep the list's order; crates and levels have their own
+    match group_by {
+        ListGroup::Category => {}
+        ListGroup::Crate => groups.sort_by_key(|(name, _)| {
+            (name == "No crate", name.to_lowercase())
+        }),
+        ListGroup::Level => groups.sort_by_key(|(name, _)| {
+            (Level::parse(name).is_none(), Level::parse(name))
+        }),
+    }
+    groups
+}
+
+/// Print heuristics as markdown summaries, one block each
+fn print_markdown<'a>(heuristics: impl Iterator<Item = &'a Heuristic>) {
+    let blocks: Vec<String> =
+        heuristics.map(|h| h.render(RenderStyle::Markdown)).collect();
+    print!("{}", blocks.join("\n"));
+}
+
+/// Print a data document tagged with the output schema version
+fn print_document(format: Format, document: Value) {
+    print!("{}", self::document(format, document));
+}
+
+/// A data document tagged with the output schema version, as JSON or YAML
+fn document(format: Format, mut document: Value) -> String {
+    if let Value::Object(fields) = &mut document {
+        fields.insert("schema".to_string(), json!(JSON_SCHEMA_VERSION));
+    }
+    match format {
+        Format::Yaml => yaml(&document, 0),
+        _ => format!(
+            "{}\n",
+            serde_json::to_string_pretty(&document).unwrap_or_default()
+        ),
+    }
+}
+
+/// Write a JSON value as block-style YAML
+///
+/// Strings are written as JSON strings, which YAML reads as double-quoted
+/// scalars, so no value needs YAML-specific escaping.
+fn yaml(value: &Value, indent: usize) -> String {
+    let pad = "  ".repeat(indent);
+    let mut out = String::new();
+    match value {
+        Value::Object(fields) if !fields.is_empty() => {
+            for (key, value) in fields {
+                match value {
+                    Value::Object(inner) if !inner.is_empty() => {
+                        out.push_str(&format!(
+                            "{}{}:\n{}",
+                            pad,
+                            key,
+                            yaml(value, indent + 1)
+                        ))
+                    }
+                    Value::Array(items) if !items.is_empty() => out.push_str(
+                        &format!("{}{}:\n{}", pad, key, yaml(value, indent)),
+                    ),
+                    _ => out.push_str(&format!(
+                        "{}{}: {}\n",
+                        pad,
+                        key,
+                        yaml(value, 0).trim_end()
+                    )),
+                }
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for item in items {
+                // The first line of a nested block shares the `- ` line
+                let nested = yaml(item, indent + 1);
+                out.push_str(&format!(
+                    "{}- {}\n",
+                    pad,
+                    nested.trim_start().trim_end_matches('\n')
+                ));
+            }
+        }
+        Value::Object(_) => out.push_str("{}\n"),
+        Value::Array(_) => out.push_str("[]\n"),
+        scalar => out.push_str(&format!("{}\n", scalar)),
+    }
+    out
+}
+
+/// JSON for an alternative, keyed by what it refers to
+fn alternative_json(alternative: &Alternative) -> Value {
+    match alternative {
+        Alternative::Heuristic(reference) => json!({ "heuristic": reference }),
+        Alternative::Crate(name) => json!({ "crate": name }),
+        Alternative::StdType(name) => json!({ "std_type": name }),
+    }
+}
+
+/// The stable JSON representation of a heuristic
+///
+/// Fields are mapped explicitly so library refactors cannot change the
+/// output schema by accident.
+fn heuristic_json(heuristic: &Heuristic) -> Value {
+    json!({
+        "id": heuristic.id,
+        "title": heuristic.title,
+        "aliases": heuristic.aliases,
+        "action": heuristic.action,
+        "structured_action": match heuristic.structured_action() {
+            Action::UseStdType { ty, .. } => json!({ "kind": "std_type", "name": ty }),
+            Action::UseCrate { name, .. } => json!({ "kind": "crate", "name": name }),
+            Action::ApplyPattern { pattern, .. } => json!({ "kind": "pattern", "name": pattern }),
+            Action::Freeform(_) => json!({ "kind": "freeform" }),
+        },
+        "category": heuristic.category,
+        "namespace": heuristic.namespace,
+        "crates": heuristic.crates,
+        "std_types": heuristic.std_types,
+        "keywords": heuristic.keywords,
+        "body_keywords": heuristic.body_keywords,
+        "tradeoffs": {
+            "pros": heuristic.tradeoffs.pros,
+            "cons": heuristic.tradeoffs.cons,
+        },
+        "sources": heuristic.sources.iter()
+            .map(|s| json!({ "title": s.title, "url": s.url }))
+            .collect::<Vec<_>>(),
+        "since": heuristic.since,
+        "updated": heuristic.updated,
+        "alternatives": heuristic.alternatives.iter().map(alternative_json).collect::<Vec<_>>(),
+        "tables": heuristic.tables.iter()
+            .map(|t| json!({ "headers": t.headers, "rows": t.rows }))
+            .collect::<Vec<_>>(),
+        "parent": heuristic.parent,
+        "related": heuristic.related,
+        "tags": heuristic.tags,
+        "level": heuristic.level.map(|level| level.to_string()),
+        "provenance": heuristic.provenance.to_string(),
+        "fingerprint": format!("{:016x}", heuristic.fingerprint()),
+        "benchmarks": heuristic.benchmarks.iter()
+            .map(|l| json!({ "text": l.text, "url": l.url }))
+            .collect::<Vec<_>>(),
+        "memory_overhead": heuristic.memory_overhead.as_ref().map(|m| json!({
+            "text": m.text,
+            "factor": m.factor,
+            "per_entry_bytes": m.per_entry_bytes,
+        })),
+        "crate_requirements": heuristic.crate_requirements.iter()
+            .map(|r| json!({
+                "name": r.name,
+                "msrv": r.msrv.map(|v| v.to_string()),
+                "edition": r.edition,
+            }))
+            .collect::<Vec<_>>(),
+        "applies_to": {
+            "no_std": heuristic.applies_to.no_std,
+            "wasm": heuristic.applies_to.wasm,
+            "embedded": heuristic.applies_to.embedded,
+            "threads_required": heuristic.applies_to.threads_required,
+        },
+        "content": heuristic.content,
+    })
+}
+
+/// Request a link and describe why it is dead, if it is
+#[cfg(feature = "net")]
+fn check_remote(url: &str) -> Option<String> {
+    use heuristics::net::{LinkStatus, check_link};
+
+    match check_link(url) {
+        LinkStatus::Ok => None,
+        LinkStatus::Broken(code) => Some(format!("HTTP {}", code)),
+        LinkStatus::Unreachable(reason) => Some(reason),
+    }
+}
+
+#[cfg(not(feature = "net"))]
+fn check_remote(_url: &str) -> Option<String> {
+    None
+}
+
+/// Check the pack at `path` and print its problems and index statistics,
+/// one line of JSON per check for structured output
+fn report_pack(path: &Path, format: Format) {
+    let started = std::time::Instant::now();
+    let (pack, issues) = match check_files(path) {
+        Ok(checked) => checked,
+        Err(err) => {
+            eprintln!("{}", err.to_string().red());
+            return;
+        }
+    };
+    let elapsed = started.elapsed();
+    let stats = json!({
+        "heuristics": pack.all().len(),
+        "categories": pack.categories().len(),
+        "crates": pack.crate_mentions().len(),
+        "terms": pack.all().iter().flat_map(|h| h.keywords.iter().chain(&h.body_keywords)).collect::<HashSet<_>>().len(),
+        "parse_ms": elapsed.as_millis() as u64,
+    });
+
+    if format.is_structured() {
+        let problems: Vec<Value> =
+            issues.iter().map(|issue| json!({ "line": issue.line, "problem": issue.message })).collect();
+        let report = json!({
+            "command": "watch",
+            "path": path.display().to_string(),
+            "stats": stats,
+            "issues": problems,
+            "schema": JSON_SCHEMA_VERSION,
+        });
+        match format {
+            Format::Yaml => print!("---\n{}", yaml(&report, 0)),
+            _ => println!("{}", report),
+        }
+        return;
+    }
+
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        % 86_400;
+    let clock = format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+    let summary = format!(
+        "{} heuristic(s) in {} categories, {} crates, {} index terms; parsed in {} ms",
+        stats["heuristics"],
+        stats["categories"],
+        stats["crates"],
+        stats["terms"],
+        stats["parse_ms"]
+    );
+    println!("{} {}", format!("[{}]", clock).dimmed(), summary);
+    if issues.is_empty() {
+        println!("  {}", "No problems found.".green());
+    }
+    for issue in &issues {
+        match issue.line {
+            Some(line) => println!(
+                "  {} {}",
+                format!("line {}:", line).yellow(),
+                issue.message
+            ),
+            None => println!("  {}", issue.message.yellow()),
+        }
+    }
+    use std::io::Write;
+    std::io::stdout().flush().ok();
+}
+
+/// Call `on_change` once watching the file at `path` has started, so no
+/// change is missed, and again each time it changes until the process is
+/// stopped
+#[cfg(feature = "watch")]
+fn watch_pack(
+    path: &Path,
+    on_change: impl FnMut() + Send + 'static,
+) -> Result<(), String> {
+    let on_change = std::sync::Arc::new(std::sync::Mutex::new(on_change));
+    let changed = std::sync::Arc::clone(&on_change);
+    let _watched =
+        HeuristicDb::watch(path, move |_| (changed.lock().unwrap())())
+            .map_err(|err| err.to_string())?;
+    (on_change.lock().unwrap())();
+    loop {
+        std::thread::park();
+    }
+}
+
+#[cfg(not(feature = "watch"))]
+fn watch_pack(
+    _path: &Path,
+    _on_change: impl FnMut() + Send + 'static,
+) -> Result<(), String> {
+    Err("built without the `watch` feature".to_string())
+}
+
+/// The JSON import format
+#[cfg(feature = "json")]
+fn json_source(category: String) -> Box<dyn HeuristicSource> {
+    Box::new(heuristics::JsonSource::new(category))
+}
+
+#[cfg(not(feature = "json"))]
+fn json_source(_category: String) -> Box<dyn HeuristicSource> {
+    unreachable!("json import is refused without the `json` feature")
+}
+
+/// Write the heuristics into a new SQLite database
+#[cfg(feature = "sqlite")]
+fn export_sqlite(
+    heuristics: Vec<&Heuristic>,
+    path: &Path,
+) -> Result<(), String> {
+    heuristics::to_sqlite(heuristics, path).map_err(|err| err.to_string())
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn export_sqlite(
+    _heuristics: Vec<&Heuristic>,
+    _path: &Path,
+) -> Result<(), String> {
+    Err("built without the `sqlite` feature".to_string())
+}
+
+/// JSON Schema of TOML heuristic files, or of JSON snapshots
+#[cfg(feature = "schema")]
+fn json_schema(snapshot: bool) -> Result<String, String> {
+    Ok(if snapshot {
+        heuristics::snapshot_schema()
+    } else {
+        heuristics::pack_schema()
+    })
+}
+
+#[cfg(not(feature = "schema"))]
+fn json_schema(_snapshot: bool) -> Result<String, String> {
+    Err("built without the `schema` feature".to_string())
+}
+
+/// Run the web UI until the process is stopped
+#[cfg(feature = "server")]
+fn serve_ui(
+    db: HeuristicDb,
+    listener: std::net::TcpListener,
+) -> Result<(), String> {
+    heuristics::serve(db, listener).map_err(|err| err.to_string())
+}
+
+#[cfg(not(feature = "server"))]
+fn serve_ui(
+    _db: HeuristicDb,
+    _listener: std::net::TcpListener,
+) -> Result<(), String> {
+    Err("built without the `server` feature".to_string())
+}
+
+/// Download and cache a pack, returning its cached path and size
+#[cfg(feature = "fetch")]
+fn update_pack(
+    url: &str,
+    sha256: Option<&str>,
+) -> Result<(String, usize), String> {
+    let path =
+        heuristics::fetch_pack(url, sha256).map_err(|err| err.to_string())?;
+    let db = heuristics::load_heuristics_from_path(&path)
+        .map_err(|err| err.to_string())?;
+    Ok((path.display().to_string(), db.all().len()))
+}
+
+#[cfg(not(feature = "fetch"))]
+fn update_pack(
+    _url: &str,
+    _sha256: Option<&str>,
+) -> Result<(String, usize), String> {
+    Err("built without the `fetch` feature".to_string())
+}
+
+/// Whether the cached pack is "current", "stale", or "missing"
+#[cfg(feature = "fetch")]
+fn check_pack(
+    url: &str,
+    sha256: Option<&str>,
+) -> Result<&'static str, String> {
+    Ok(
+        match heuristics::check_pack(url, sha256)
+            .map_err(|err| err.to_string())?
+        {
+            heuristics::PackStatus::Current => "current",
+            heuristics::PackStatus::Stale => "stale",
+            heuristics::PackStatus::Missing => "missing",
+        },
+    )
+}
+
+#[cfg(not(feature = "fetch"))]
+fn check_pack(
+    _url: &str,
+    _sha256: Option<&str>,
+) -> Result<&'static str, String> {
+    Err("built without the `fetch` feature".to_string())
 }
@@ -0,0 +1,170 @@
+//! Combining heuristic databases, e.g. a team pack over the built-in one.
+
+use std::collections::HashSet;
+use std::collections::hash_map::Entry;
+
+use crate::{Corpus, Heuristic, HeuristicDb, MergeError, slug};
+
+/// Share of keywords two heuristics must have in common to count as the
+/// same advice (intersection over union)
+const NEAR_DUPLICATE_OVERLAP: f64 = 0.9;
+
+/// What [`HeuristicDb::merge`] does when both databases have the same id
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Keep the heuristic already in `self`
+    #[default]
+    PreferOurs,
+    /// Replace it with the one from `other`, keeping its position
+    PreferTheirs,
+    /// Fail the merge
+    Error,
+    /// Fold heuristics with the same id or [near-duplicate](HeuristicDb::near_duplicates)
+    /// advice into the one in `self`, adding the other's crates, sources,
+    /// and other list metadata
+    Combine,
+}
+
+/// Why two heuristics look like the same advice
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Similarity {
+    /// Their titles reduce to the same slug
+    SameTitle,
+    /// This share of their keywords is shared (intersection over union)
+    KeywordOverlap(f64),
+}
+
+/// A heuristic in another database that repeats one in this database
+#[derive(Debug, Clone, PartialEq)]
+pub struct NearDuplicate {
+    /// Id of the heuristic in this database
+    pub ours: String,
+    /// Id of the heuristic in the other database
+    pub theirs: String,
+    /// What makes them look alike
+    pub similarity: Similarity,
+}
+
+/// Whether two heuristics with different ids give the same advice
+fn similarity(ours: &Heuristic, theirs: &Heuristic) -> Option<Similarity> {
+    if slug(&ours.title) == slug(&theirs.title) {
+        return Some(Similarity::SameTitle);
+    }
+    let keywords = |h: &Heuristic| {
+        h.keywords.iter().map(|k| k.to_lowercase()).collect::<HashSet<_>>()
+    };
+    let (a, b) = (keywords(ours), keywords(theirs));
+    let union = a.union(&b).count();
+    let overlap = if union == 0 {
+        0.0
+    } else {
+        a.intersection(&b).count() as f64 / union as f64
+    };
+    (overlap > NEAR_DUPLICATE_OVERLAP)
+        .then_some(Similarity::KeywordOverlap(overlap))
+}
+
+impl Heuristic {
+    /// Add the list metadata of a duplicate, keeping this heuristic's text
+    pub(crate) fn absorb(&mut self, other: Heuristic) {
+        fn extend<T: PartialEq>(ours: &mut Vec<T>, theirs: Vec<T>) {
+            for item in theirs {
+                if !ours.contains(&item) {
+                    ours.push(item);
+                }
+            }
+        }
+        extend(&mut self.aliases, other.aliases);
+        extend(&mut self.crates, other.crates);
+        extend(&mut self.std_types, other.std_types);
+        extend(&mut self.keywords, other.keywords);
+        extend(&mut self.body_keywords, other.body_keywords);
+        self.body_keywords.retain(|keyword| !self.keywords.contains(keyword));
+        extend(&mut self.tradeoffs.pros, other.tradeoffs.pros);
+        extend(&mut self.tradeoffs.cons, other.tradeoffs.cons);
+        extend(&mut self.sources, other.sources);
+        extend(&mut self.alternatives, other.alternatives);
+        extend(&mut self.related, other.related);
+        extend(&mut self.tags, other.tags);
+        extend(&mut self.benchmarks, other.benchmarks);
+        extend(&mut self.crate_requirements, other.crate_requirements);
+        extend(&mut self.links, other.links);
+    }
+}
+
+impl HeuristicDb {
+    /// Combine two databases and rebuild the search index
+    ///
+    /// Heuristics from `other` are appended after those of `self`;
+    /// duplicate ids (and, with [`MergePolicy::Combine`], near-duplicate
+    /// heuristics) are resolved by `policy`. Corpus settings of the same
+    /// namespace are combined, with the preferred side's category
+    /// descriptions winning.
+    pub fn merge(
+        self,
+        other: HeuristicDb,
+        policy: MergePolicy,
+    ) -> Result<HeuristicDb, MergeError> {
+        let pack = self.pack.or(other.pack);
+        let mut heuristics = self.heuristics;
+        for theirs in other.heuristics {
+            if policy == MergePolicy::Combine
+                && let Some(ours) = heuristics.iter_mut().find(|ours| {
+                    ours.id == theirs.id || similarity(ours, &theirs).is_some()
+                })
+            {
+                ours.absorb(theirs);
+                continue;
+            }
+            match heuristics.iter_mut().find(|ours| ours.id == theirs.id) {
+                None => heuristics.push(theirs),
+                Some(_) if policy == MergePolicy::PreferOurs => {}
+                Some(ours) if policy == MergePolicy::PreferTheirs => {
+                    *ours = theirs
+                }
+                Some(_) => return Err(MergeError::DuplicateId(theirs.id)),
+            }
+        }
+
+        let (mut preferred, other_corpora) = match policy {
+            MergePolicy::PreferTheirs => (other.corpora, self.corpora),
+            _ => (self.corpora, other.corpora),
+        };
+        for (namespace, corpus) in other_corpora {
+            match preferred.entry(namespace) {
+                Entry::Occupied(mut existing) => {
+                    existing.get_mut().absorb(corpus)
+                }
+                Entry::Vacant(slot) => {
+                    slot.insert(corpus);
+                }
+            }
+        }
+
+        let mut corpora: Vec<Corpus> = preferred.into_values().collect();
+        corpora.sort_by(|a, b| a.namespace.cmp(&b.namespace));
+        Ok(HeuristicDb::with_corpora(heuristics, corpora).with_pack(pack))
+    }
+
+    /// Heuristics in `other` that repeat advice already in this database
+    ///
+    /// A pair matches when the titles reduce to the same slug or more than
+    /// 90% of their keywords are shared. Pairs with the same id are left
+    /// out, since [`HeuristicDb::merge`] already resolves those by policy.
+    /// Useful for reviewing a pack before merging it.
+    pub fn near_duplicates(&self, other: &HeuristicDb) -> Vec<NearDuplicate> {
+        let mut duplicates = Vec::new();
+        for theirs in other.all() {
+            for ours in self.all().iter().filter(|ours| ours.id != theirs.id) {
+                if let Some(similarity) = similarity(ours, theirs) {
+                    duplicates.push(NearDuplicate {
+                        ours: ours.id.clone(),
+                        theirs: theirs.id.clone(),
+                        similarity,
+                    });
+                }
+            }
+        }
+        duplicates
+    }
+}
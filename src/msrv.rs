@@ -0,0 +1,122 @@
+//! Rust toolchain requirements of recommended crates.
+
+use std::fmt;
+
+use crate::Heuristic;
+
+/// A Rust release such as `1.70` or `1.70.1`
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RustVersion {
+    /// Major version, always 1 so far
+    pub major: u32,
+    /// Minor version, the release number
+    pub minor: u32,
+    /// Patch release, 0 when not given
+    pub patch: u32,
+}
+
+impl RustVersion {
+    /// Parse `1.70`, `1.70.1`, or `v1.70`
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim().trim_start_matches('v');
+        let mut parts = text.split('.').map(|p| p.parse::<u32>().ok());
+        let major = parts.next()??;
+        let minor = parts.next()??;
+        let patch = parts.next().unwrap_or(Some(0))?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self { major, minor, patch })
+    }
+
+    /// First Rust release supporting an edition
+    pub fn for_edition(edition: &str) -> Option<Self> {
+        let minor = match edition.trim() {
+            "2015" => 0,
+            "2018" => 31,
+            "2021" => 56,
+            "2024" => 85,
+            _ => return None,
+        };
+        Some(Self { major: 1, minor, patch: 0 })
+    }
+}
+
+impl fmt::Display for RustVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.patch == 0 {
+            write!(f, "{}.{}", self.major, self.minor)
+        } else {
+            write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+        }
+    }
+}
+
+/// Toolchain requirements of one recommended crate (`**MSRV:**`)
+///
+/// Written as comma-separated items like `` `moka` 1.70 `` or
+/// `` `lru` 1.65 (edition 2021) ``.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CrateRequirement {
+    /// The crate name
+    pub name: String,
+    /// Minimum supported Rust version
+    pub msrv: Option<RustVersion>,
+    /// Rust edition the crate is written in
+    pub edition: Option<String>,
+}
+
+impl CrateRequirement {
+    /// Parse one `**MSRV:**` item
+    pub(crate) fn parse(item: &str) -> Option<Self> {
+        let item = item.trim();
+        let (name, rest) = match item.strip_prefix('`') {
+            Some(quoted) => quoted.split_once('`')?,
+            None => item.split_once(' ')?,
+        };
+        let mut requirement =
+            Self { name: name.trim().to_string(), ..Default::default() };
+        if requirement.name.is_empty() {
+            return None;
+        }
+
+        let rest = rest.replace(['(', ')'], " ");
+        let mut words = rest.split_whitespace();
+        while let Some(word) = words.next() {
+            if word.eq_ignore_ascii_case("edition") {
+                requirement.edition = words.next().map(|e| e.to_string());
+            } else if let Some(version) = RustVersion::parse(word) {
+                requirement.msrv = Some(version);
+            }
+        }
+        Some(requirement)
+    }
+
+    /// The oldest Rust release that builds the crate
+    ///
+    /// Accounts for the edition when no explicit MSRV is given, or when the
+    /// edition needs a newer compiler than the stated MSRV.
+    pub fn effective_msrv(&self) -> Option<RustVersion> {
+        let edition =
+            self.edition.as_deref().and_then(RustVersion::for_edition);
+        self.msrv.max(edition)
+    }
+}
+
+impl Heuristic {
+    /// The newest Rust release required by any recommended crate
+    pub fn msrv(&self) -> Option<RustVersion> {
+        self.crate_requirements.iter().filter_map(|r| r.effective_msrv()).max()
+    }
+
+    /// Whether every recommended crate builds on the given toolchain
+    pub fn supports_rust(&self, version: RustVersion) -> bool {
+        self.msrv().is_none_or(|msrv| msrv <= version)
+    }
+}
@@ -0,0 +1,37 @@
+//! Network access, enabled by the `net` feature.
+
+use std::time::Duration;
+
+/// Outcome of checking whether a link is reachable
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkStatus {
+    /// The server answered with a success status
+    Ok,
+    /// The server answered with an error status code
+    Broken(u16),
+    /// The request failed before a status was received
+    Unreachable(String),
+}
+
+pub(crate) fn agent() -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(10)))
+        .build()
+        .into()
+}
+
+/// Check whether a URL is reachable
+pub fn check_link(url: &str) -> LinkStatus {
+    let agent = agent();
+    let result = match agent.head(url).call() {
+        // Some servers reject HEAD requests; retry with GET before reporting
+        Err(ureq::Error::StatusCode(403 | 405)) => agent.get(url).call(),
+        result => result,
+    };
+
+    match result {
+        Ok(_) => LinkStatus::Ok,
+        Err(ureq::Error::StatusCode(code)) => LinkStatus::Broken(code),
+        Err(err) => LinkStatus::Unreachable(err.to_string()),
+    }
+}
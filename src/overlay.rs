@@ -0,0 +1,93 @@
+//! Overlay files that patch heuristics in place.
+//!
+//! An overlay uses the heuristic file format, but each `###` header names
+//! an existing heuristic by id or title instead of adding a new one:
+//!
+//! ```text
+//! ### need-to-cache-expensive-results
+//! **Action:** Use our `cachekit` wrapper around `moka`.
+//! - **Crates:** `cachekit`
+//!
+//! We standardized on cachekit in 2024; see the platform wiki.
+//! ```
+//!
+//! An action, parent, memory, level, or `**Updated:**` annotation replaces
+//! the original; crates, std types, aliases, tags, trade-offs, sources,
+//! alternatives, and benchmarks are added to it; any other text is appended
+//! as a note.
+
+use std::path::Path;
+
+use crate::parser::{check_markdown, parse_document, split_prose};
+use crate::{HeuristicDb, LoadError, ParseError};
+
+impl HeuristicDb {
+    /// Apply the overlay file at `path` on top of this database
+    ///
+    /// Teams can annotate the built-in advice ("we use X instead") without
+    /// forking it: `load_heuristics().with_overlay("overlay.md")?`.
+    pub fn with_overlay(
+        self,
+        path: impl AsRef<Path>,
+    ) -> Result<Self, LoadError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|source| {
+            LoadError::Io { path: path.to_path_buf(), source }
+        })?;
+        self.apply_overlay(&content).map_err(|source| LoadError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Apply overlay markdown held in memory, see [`HeuristicDb::with_overlay`]
+    ///
+    /// Fails if a header names no heuristic in the database.
+    pub fn apply_overlay(self, content: &str) -> Result<Self, ParseError> {
+        check_markdown(content)?;
+        let patches = parse_document(content).heuristics;
+        let HeuristicDb { mut heuristics, corpora, pack, .. } = self;
+
+        for patch in patches {
+            let Some(target) = heuristics
+                .iter_mut()
+                .find(|h| h.id == patch.id || h.title == patch.title)
+            else {
+                return Err(ParseError::UnknownHeuristic(patch.title));
+            };
+            let (notes, _) = split_prose(&patch.content);
+
+            if !patch.action.is_empty() {
+                target.content =
+                    replace_action(&target.content, &patch.action);
+                target.action = patch.action.clone();
+            }
+            target.parent = patch.parent.clone().or(target.parent.take());
+            target.memory_overhead = patch
+                .memory_overhead
+                .clone()
+                .or(target.memory_overhead.take());
+            target.updated = patch.updated.clone().or(target.updated.take());
+            target.level = patch.level.or(target.level);
+            target.absorb(patch);
+            if !notes.is_empty() {
+                target.content = format!("{}\n\n{}", target.content, notes);
+            }
+        }
+
+        Ok(Self::with_corpora(heuristics, corpora.into_values().collect())
+            .with_pack(pack))
+    }
+}
+
+/// Swap the `**Action:**` line of a heuristic's content, adding one after
+/// the header if it had none
+fn replace_action(content: &str, action: &str) -> String {
+    let line = format!("**Action:** {}", action);
+    let mut lines: Vec<&str> = content.lines().collect();
+    match lines.iter().position(|l| l.starts_with("**Action:**")) {
+        Some(index) => lines[index] = &line,
+        None => lines.insert(1.min(lines.len()), &line),
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,150 @@
+//! Content files for adding a pack to a Hugo or Zola site.
+//!
+//! [`to_hugo`] and [`to_zola`] write the same section, a page per
+//! heuristic under `content/heuristics/`, each with the front matter its
+//! generator reads: title, slug, description, dates, categories, and tags.
+//! Merging the directory into a site's root adds the pack to its docs.
+
+use std::path::PathBuf;
+
+use crate::Heuristic;
+use crate::site::json_string;
+
+/// Hugo content for every heuristic, as paths relative to the site's root
+/// and their contents
+///
+/// Pages have YAML front matter with `categories` and `tags`, Hugo's
+/// default taxonomies, and a `weight` keeping them in pack order.
+pub fn to_hugo<'a>(
+    title: &str,
+    heuristics: impl IntoIterator<Item = &'a Heuristic>,
+) -> Vec<(PathBuf, String)> {
+    pages(
+        heuristics,
+        format!("---\ntitle: {}\n---\n", json_string(title)),
+        "lastmod",
+        |fields, taxonomies| {
+            let lines: Vec<String> = fields
+                .iter()
+                .chain(taxonomies)
+                .map(|(key, value)| format!("{}: {}\n", key, value))
+                .collect();
+            format!("---\n{}---\n", lines.concat())
+        },
+    )
+}
+
+/// Zola content for every heuristic, as paths relative to the site's root
+/// and their contents
+///
+/// Pages have TOML front matter, with categories and tags under
+/// `[taxonomies]`; the site's `config.toml` has to declare both. The
+/// section sorts its pages by `weight`, which keeps them in pack order.
+pub fn to_zola<'a>(
+    title: &str,
+    heuristics: impl IntoIterator<Item = &'a Heuristic>,
+) -> Vec<(PathBuf, String)> {
+    pages(
+        heuristics,
+        format!(
+            "+++\ntitle = {}\nsort_by = \"weight\"\n+++\n",
+            json_string(title)
+        ),
+        "updated",
+        |fields, taxonomies| {
+            let lines: Vec<String> = fields
+                .iter()
+                .map(|(key, value)| format!("{} = {}\n", key, value))
+                .collect();
+            let taxonomies: Vec<String> = taxonomies
+                .iter()
+                .map(|(key, value)| format!("{} = {}\n", key, value))
+                .collect();
+            format!(
+                "+++\n{}\n[taxonomies]\n{}+++\n",
+                lines.concat(),
+                taxonomies.concat()
+            )
+        },
+    )
+}
+
+/// The section index and a page per heuristic, with front matter written
+/// by `front_matter` from the page's fields and taxonomies, both as
+/// values YAML and TOML read alike, and the last revision's date under
+/// `updated_key`
+fn pages<'a>(
+    heuristics: impl IntoIterator<Item = &'a Heuristic>,
+    section: String,
+    updated_key: &str,
+    front_matter: impl Fn(&[(&str, String)], &[(&str, String)]) -> String,
+) -> Vec<(PathBuf, String)> {
+    let heuristics: Vec<&Heuristic> = heuristics.into_iter().collect();
+    let mut files =
+        vec![(PathBuf::from("content/heuristics/_index.md"), section)];
+    for (i, heuristic) in heuristics.iter().enumerate() {
+        let mut fields = vec![
+            ("title", json_string(&heuristic.title)),
+            ("slug", json_string(&heuristic.id)),
+            ("description", json_string(&heuristic.action)),
+            ("weight", (i + 1).to_string()),
+        ];
+        // Versions aren't dates, so only dated annotations make it in
+        let since = heuristic.since.as_deref().filter(|since| is_date(since));
+        let updated =
+            heuristic.updated.as_deref().filter(|updated| is_date(updated));
+        if let Some(date) = since.or(updated) {
+            fields.push(("date", date.to_string()));
+        }
+        if let Some(updated) = updated {
+            fields.push((updated_key, updated.to_string()));
+        }
+        let list = |items: &[String]| {
+            let items: Vec<String> =
+                items.iter().map(|item| json_string(item)).collect();
+            format!("[{}]", items.join(", "))
+        };
+        let taxonomies = [
+            ("categories", list(std::slice::from_ref(&heuristic.category))),
+            ("tags", list(&heuristic.tags)),
+        ];
+
+        let block = heuristic.to_markdown_block();
+        let rest = block.split_once('\n').map_or("", |(_, rest)| rest);
+        let rest = rest
+            .strip_prefix("---\n")
+            .and_then(|meta| meta.split_once("---\n"))
+            .map_or(rest, |(_, rest)| rest);
+        let mut body: String = rest
+            .lines()
+            .filter(|line| !line.starts_with("**Id:** "))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        // Pages are served at `heuristics/<id>/`
+        for id in heuristic
+            .related
+            .iter()
+            .filter(|id| heuristics.iter().any(|h| h.id == **id))
+        {
+            body = body
+                .replace(&format!("](#{})", id), &format!("](../{}/)", id));
+        }
+        let path =
+            PathBuf::from(format!("content/heuristics/{}.md", heuristic.id));
+        files.push((
+            path,
+            format!("{}\n{}", front_matter(&fields, &taxonomies), body),
+        ));
+    }
+    files
+}
+
+/// Whether `text` is a `YYYY-MM-DD` date, which both generators read as one
+fn is_date(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    bytes.len() == 10
+        && bytes.iter().enumerate().all(|(i, b)| match i {
+            4 | 7 => *b == b'-',
+            _ => b.is_ascii_digit(),
+        })
+}
@@ -0,0 +1,1188 @@
+//! Markdown parsing of heuristic files.
+//!
+//! The parser is lenient: anything it can't make sense of stays in the
+//! heuristic's content and is reported as a [`ParseDiagnostic`] instead of
+//! failing the whole file. Only problems that would make the result
+//! misleading, such as an unterminated front matter block, are errors.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+use crate::keywords::{
+    KeywordExtractor, TermDictionary, add_keywords, body_keywords,
+};
+use crate::{
+    Alternative, Corpus, CrateRequirement, Heuristic, HeuristicDb, Level,
+    Link, MemoryOverhead, PackInfo, ParseError, Platforms, Source, Table,
+    slug,
+};
+
+/// A problem in a heuristic file that didn't stop it from loading
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDiagnostic {
+    /// 1-based line number in the file
+    pub line: usize,
+    /// What is wrong and what the parser did about it
+    pub message: String,
+}
+
+impl fmt::Display for ParseDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Heuristics parsed from one file, plus its corpus settings and any
+/// problems found along the way
+#[derive(Debug, Clone, Default)]
+pub struct Document {
+    /// Settings from the front matter and category descriptions
+    pub corpus: Corpus,
+    /// The heuristics, in file order
+    pub heuristics: Vec<Heuristic>,
+    /// Lines that were skipped or only partly understood
+    pub diagnostics: Vec<ParseDiagnostic>,
+    /// Pack metadata, if the front matter declared any
+    pub pack: Option<PackInfo>,
+}
+
+impl From<Document> for HeuristicDb {
+    fn from(document: Document) -> Self {
+        Self::with_corpora(document.heuristics, vec![document.corpus])
+            .with_pack(document.pack)
+    }
+}
+
+/// How [`parse_markdown_with`] reads a file
+#[derive(Clone)]
+pub struct ParseOptions {
+    /// Reject the file if anything produces a diagnostic, including
+    /// heuristics missing an action, a category, or searchable keywords
+    ///
+    /// Off by default, which keeps the best-effort behavior the embedded
+    /// pack and casual packs rely on; pack authors can turn it on in CI.
+    pub strict: bool,
+    /// Finds keywords in titles, actions, aliases, and "When to use" lines,
+    /// and lower-ranked ones in the rest of the content
+    pub keywords: Arc<dyn KeywordExtractor>,
+}
+
+impl ParseOptions {
+    /// Options for strict parsing
+    pub fn strict() -> Self {
+        Self { strict: true, ..Self::default() }
+    }
+
+    /// Use a custom keyword extractor, e.g. a domain [`TermDictionary`]
+    pub fn with_keywords(
+        mut self,
+        extractor: impl KeywordExtractor + 'static,
+    ) -> Self {
+        self.keywords = Arc::new(extractor);
+        self
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self { strict: false, keywords: Arc::new(TermDictionary::builtin()) }
+    }
+}
+
+impl fmt::Debug for ParseOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParseOptions")
+            .field("strict", &self.strict)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Parse a markdown heuristic file, reporting problems with line numbers
+///
+/// Returns an error if the file can't be used at all; otherwise the
+/// document lists every line the parser skipped or only partly understood
+/// in [`Document::diagnostics`].
+pub fn parse_markdown(content: &str) -> Result<Document, ParseError> {
+    parse_markdown_with(content, &ParseOptions::default())
+}
+
+/// Like [`parse_markdown`], with explicit options
+///
+/// In strict mode any diagnostic fails the parse with
+/// [`ParseError::Rejected`].
+pub fn parse_markdown_with(
+    content: &str,
+    options: &ParseOptions,
+) -> Result<Document, ParseError> {
+    check_markdown(content)?;
+    let document = parse_document_with(content, options.keywords.as_ref());
+    if let Some(pack) = &document.pack {
+        pack.check()?;
+    }
+    if options.strict && !document.diagnostics.is_empty() {
+        return Err(ParseError::Rejected(document.diagnostics));
+    }
+    Ok(document)
+}
+
+/// Reject markdown the lenient parser would silently misread
+pub(crate) fn check_markdown(content: &str) -> Result<(), ParseError> {
+    let mut lines = content
+        .lines()
+        .enumerate()
+        .skip_while(|(_, line)| line.trim().is_empty())
+        .peekable();
+    if let Some(&(start, line)) = lines.peek()
+        && line.trim_end() == "---"
+        && !lines.clone().skip(1).any(|(_, line)| line.trim_end() == "---")
+    {
+        return Err(ParseError::UnterminatedFrontMatter { line: start + 1 });
+    }
+
+    let mut found = false;
+    let mut fences = Fences::default();
+    for (number, line) in content.lines().enumerate() {
+        if fences.contains(line) {
+            continue;
+        }
+        if let Some(title) = line
+            .strip_prefix("###")
+            .filter(|rest| rest.is_empty() || rest.starts_with(' '))
+        {
+            if title.trim().is_empty() {
+                return Err(ParseError::UntitledHeuristic {
+                    line: number + 1,
+                });
+            }
+            found = true;
+        }
+    }
+    if found { Ok(()) } else { Err(ParseError::NoHeuristics) }
+}
+
+/// Tracks fenced code blocks (`` ``` `` or `~~~`) while reading line by line
+///
+/// Lines inside a fence are code samples: they are kept verbatim and never
+/// read as headers or metadata.
+#[derive(Default)]
+pub(crate) struct Fences {
+    /// Marker character and length of the open fence
+    open: Option<(char, usize)>,
+}
+
+impl Fences {
+    /// Whether a code block is open after the lines seen so far
+    pub(crate) fn is_open(&self) -> bool {
+        self.open.is_some()
+    }
+
+    /// Whether the next line belongs to a code block, its fences included
+    pub(crate) fn contains(&mut self, line: &str) -> bool {
+        let trimmed = line.trim_start();
+        let marker =
+            trimmed.chars().next().filter(|c| *c == '`' || *c == '~').map(
+                |c| {
+                    let len = trimmed.chars().take_while(|&d| d == c).count();
+                    (c, len, &trimmed[len..])
+                },
+            );
+
+        match (self.open, marker) {
+            (Some((c, len)), Some((d, n, rest)))
+                if c == d && n >= len && rest.trim().is_empty() =>
+            {
+                self.open = None;
+                true
+            }
+            (Some(_), _) => true,
+            (None, Some((c, len, rest)))
+                if len >= 3 && !(c == '`' && rest.contains('`')) =>
+            {
+                self.open = Some((c, len));
+                true
+            }
+            (None, _) => false,
+        }
+    }
+}
+
+/// A `key: value` front matter line and its line number
+struct FrontMatterField {
+    line: usize,
+    key: String,
+    value: String,
+}
+
+/// Split an optional `---`-delimited front matter block off the content
+///
+/// Returns the fields and the remaining markdown body, which is always a
+/// suffix of `content`.
+fn split_front_matter(content: &str) -> (Vec<FrontMatterField>, &str) {
+    let trimmed = content.trim_start();
+    let Some(rest) = trimmed
+        .strip_prefix("---\n")
+        .or_else(|| trimmed.strip_prefix("---\r\n"))
+    else {
+        return (Vec::new(), content);
+    };
+    let Some(end) = rest.find("\n---") else {
+        return (Vec::new(), content);
+    };
+
+    // Lines skipped before the block, plus the opening `---`
+    let first_line =
+        content[..content.len() - rest.len()].matches('\n').count() + 1;
+    let fields = rest[..end]
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            line.split_once(':')
+                .map(|(key, value)| (first_line + i, key, value))
+        })
+        .map(|(line, key, value)| FrontMatterField {
+            line,
+            key: key.trim().to_lowercase(),
+            value: value.trim().to_string(),
+        })
+        .collect();
+    let body = rest[end + 4..].split_once('\n').map_or("", |(_, body)| body);
+
+    (fields, body)
+}
+
+/// Parse a front matter list value such as `a, b` or `[a, b]`
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(',')
+        .map(|item| item.trim().trim_matches('"').to_lowercase())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Apply the fields of the metadata block under a heuristic's header
+fn apply_metadata(
+    heuristic: &mut Heuristic,
+    fields: &[FrontMatterField],
+    diagnostics: &mut Vec<ParseDiagnostic>,
+) {
+    for field in fields {
+        let value = field.value.trim_matches(['"', '\'']);
+        let mut report = |message: String| {
+            diagnostics.push(ParseDiagnostic { line: field.line, message })
+        };
+        match field.key.as_str() {
+            "id" => heuristic.id = slug(value),
+            "tags" => {
+                for tag in parse_list(&field.value) {
+                    if !heuristic.keywords.contains(&tag) {
+                        heuristic.keywords.push(tag.clone());
+                    }
+                    if !heuristic.tags.contains(&tag) {
+                        heuristic.tags.push(tag);
+                    }
+                }
+            }
+            "level" => match Level::parse(value) {
+                Some(level) => heuristic.level = Some(level),
+                None => report(format!(
+                    "level '{}' is not beginner, intermediate, or advanced; ignored",
+                    value
+                )),
+            },
+            "since" => heuristic.since = Some(value.to_string()),
+            "updated" => heuristic.updated = Some(value.to_string()),
+            "parent" => heuristic.parent = Some(value.to_string()),
+            key => report(format!("unknown metadata key '{}' ignored", key)),
+        }
+    }
+}
+
+/// Build corpus settings from front matter fields
+fn parse_corpus(
+    fields: &[FrontMatterField],
+    diagnostics: &mut Vec<ParseDiagnostic>,
+) -> Corpus {
+    let mut corpus = Corpus::default();
+    for field in fields {
+        let value = &field.value;
+        match field.key.as_str() {
+            "namespace" => {
+                corpus.namespace = value.trim_matches('"').to_string()
+            }
+            "stopwords" => corpus.stopwords = parse_list(value),
+            "boost" => corpus.boost = parse_list(value),
+            key if PACK_KEYS.contains(&key) => {}
+            key => diagnostics.push(ParseDiagnostic {
+                line: field.line,
+                message: format!("unknown front matter key '{}' ignored", key),
+            }),
+        }
+    }
+    corpus
+}
+
+/// Front matter keys describing the pack rather than its corpus
+const PACK_KEYS: [&str; 4] =
+    ["version", "pack-name", "license", "min-crate-version"];
+
+/// Build pack metadata from front matter fields, if any are present
+fn parse_pack_info(
+    fields: &[FrontMatterField],
+    diagnostics: &mut Vec<ParseDiagnostic>,
+) -> Option<PackInfo> {
+    let mut pack: Option<PackInfo> = None;
+    for field in
+        fields.iter().filter(|field| PACK_KEYS.contains(&field.key.as_str()))
+    {
+        let pack = pack.get_or_insert_with(PackInfo::default);
+        let value = field.value.trim_matches('"').to_string();
+        match field.key.as_str() {
+            // `1` or `1.0`; an unreadable version can't be compatible, and 0 never is
+            "version" => {
+                pack.version = value
+                    .split('.')
+                    .next()
+                    .and_then(|major| major.parse().ok())
+                    .unwrap_or(0);
+                if pack.version == 0 {
+                    let message = format!(
+                        "pack version '{}' is not a positive number",
+                        value
+                    );
+                    diagnostics
+                        .push(ParseDiagnostic { line: field.line, message });
+                }
+            }
+            "pack-name" => pack.name = Some(value),
+            "license" => pack.license = Some(value),
+            _ => pack.min_crate_version = Some(value),
+        }
+    }
+    pack
+}
+
+/// Metadata list that indented bullet lines currently belong to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    None,
+    Pros,
+    Cons,
+    Sources,
+    Alternatives,
+    Benchmarks,
+    Related,
+}
+
+/// Single-line `**Label:**` annotations the parser understands or
+/// deliberately leaves in the content
+const KNOWN_LABELS: [&str; 15] = [
+    "Action",
+    "Id",
+    "Since",
+    "Updated",
+    "Also asked as",
+    "Parent",
+    "Memory",
+    "Memory overhead",
+    "MSRV",
+    "Platforms",
+    "Crates",
+    "Std types",
+    "When to use",
+    "Example",
+    "Implementation",
+];
+
+/// Labels that open a metadata list, either inline or as nested bullets
+const SECTION_LABELS: [(&str, Section); 7] = [
+    ("**Pros:**", Section::Pros),
+    ("**Cons:**", Section::Cons),
+    ("**References:**", Section::Sources),
+    ("**Sources:**", Section::Sources),
+    ("**Alternatives:**", Section::Alternatives),
+    ("**Benchmarks:**", Section::Benchmarks),
+    ("**See also:**", Section::Related),
+];
+
+/// Parse markdown content with the built-in keyword dictionary
+pub(crate) fn parse_document(content: &str) -> Document {
+    parse_document_with(content, &TermDictionary::builtin())
+}
+
+/// Parse markdown content into heuristics, never failing
+fn parse_document_with(
+    content: &str,
+    extractor: &dyn KeywordExtractor,
+) -> Document {
+    let mut diagnostics = Vec::new();
+    let (front_matter, body) = split_front_matter(content);
+    let mut corpus = parse_corpus(&front_matter, &mut diagnostics);
+    let pack = parse_pack_info(&front_matter, &mut diagnostics);
+    // Line numbers count from the top of the file, front matter included
+    let offset = content[..content.len() - body.len()].matches('\n').count();
+
+    let mut heuristics = Vec::new();
+    let mut header_lines = Vec::new();
+    let mut current_category = String::new();
+    // The heuristic being read, its header line, and whether it is a `####` child
+    let mut current: Option<(Heuristic, usize, bool)> = None;
+    let mut section = Section::None;
+    // Lines between a category header and its first heuristic
+    let mut intro: Vec<&str> = Vec::new();
+    // Index of the last `###` heuristic, and `(child, parent)` index pairs
+    let mut top: Option<usize> = None;
+    let mut children: Vec<(usize, Option<usize>)> = Vec::new();
+
+    let mut finish =
+        |current: Option<(Heuristic, usize, bool)>,
+         diagnostics: &mut Vec<ParseDiagnostic>| {
+            let Some((mut heuristic, line, sub)) = current else { return };
+            let (crates, std_types) = extract_named_code(&heuristic.content);
+            for name in crates.iter().chain(&std_types) {
+                if !heuristic.keywords.contains(name) {
+                    heuristic.keywords.push(name.clone());
+                }
+            }
+            heuristic.crates = crates;
+            heuristic.std_types = std_types;
+            heuristic.body_keywords = body_keywords(
+                extractor,
+                &heuristic.content,
+                &heuristic.keywords,
+            );
+            match finish_heuristic(heuristic) {
+                Some(mut heuristic) => {
+                    // Links to `#id` anchors refer to other heuristics in the pack
+                    for link in &heuristic.links {
+                        if let Some(anchor) = link.url.strip_prefix('#')
+                            && !heuristic.related.iter().any(|r| r == anchor)
+                        {
+                            heuristic.related.push(anchor.to_string());
+                        }
+                    }
+                    if heuristic.action.is_empty() {
+                        let message = format!(
+                            "'{}' has no **Action:** line",
+                            heuristic.title
+                        );
+                        diagnostics.push(ParseDiagnostic { line, message });
+                    }
+                    if heuristic.keywords.is_empty()
+                        && heuristic.body_keywords.is_empty()
+                    {
+                        let message = format!(
+                            "'{}' has no searchable keywords",
+                            heuristic.title
+                        );
+                        diagnostics.push(ParseDiagnostic { line, message });
+                    }
+                    if heuristic.category.is_empty() {
+                        let message = format!(
+                            "'{}' is not under a '##' category header",
+                            heuristic.title
+                        );
+                        diagnostics.push(ParseDiagnostic { line, message });
+                    }
+                    if sub {
+                        children.push((heuristics.len(), top));
+                    } else {
+                        top = Some(heuristics.len());
+                    }
+                    heuristics.push(heuristic);
+                    header_lines.push(line);
+                }
+                None => diagnostics.push(ParseDiagnostic {
+                    line,
+                    message: "heuristic without a title skipped".into(),
+                }),
+            }
+        };
+
+    let lines: Vec<&str> = body.lines().collect();
+    // Closing delimiter and fields of the metadata block being read
+    let mut metadata: Option<(&str, Vec<FrontMatterField>)> = None;
+    let mut after_header = false;
+    let mut fences = Fences::default();
+    for (i, &line) in lines.iter().enumerate() {
+        let number = offset + i + 1;
+
+        // Metadata blocks sit directly under a header, fenced by `---` or `+++`
+        if let Some((close, fields)) = metadata.as_mut() {
+            if line.trim_end() == *close {
+                if let Some((heuristic, _, _)) = current.as_mut() {
+                    apply_metadata(heuristic, fields, &mut diagnostics);
+                }
+                metadata = None;
+            } else if let Some((key, value)) =
+                line.split_once(if *close == "+++" { '=' } else { ':' })
+            {
+                let (key, value) =
+                    (key.trim().to_lowercase(), value.trim().to_string());
+                fields.push(FrontMatterField { line: number, key, value });
+            }
+            continue;
+        }
+        let delimiter = line.trim_end();
+        if std::mem::take(&mut after_header)
+            && matches!(delimiter, "---" | "+++")
+            && lines[i + 1..]
+                .iter()
+                .take_while(|l| !l.starts_with('#'))
+                .any(|l| l.trim_end() == delimiter)
+        {
+            metadata = Some((delimiter, Vec::new()));
+            continue;
+        }
+
+        // Code samples are content, whatever they look like
+        if fences.contains(line) {
+            match current.as_mut() {
+                Some((heuristic, _, _)) => {
+                    heuristic.content.push_str(line);
+                    heuristic.content.push('\n');
+                }
+                None => intro.push(line),
+            }
+            continue;
+        }
+
+        // Category headers (## ...)
+        if let Some(cat) = line.strip_prefix("## ") {
+            // Save previous heuristic if exists
+            finish(current.take(), &mut diagnostics);
+            add_category_description(
+                &mut corpus,
+                &current_category,
+                &mut intro,
+            );
+            current_category = cat.trim().to_string();
+            continue;
+        }
+
+        // Heuristic headers (### Need ...) and their variants (#### LRU)
+        let header = line.strip_prefix("### ").map(|title| (title, false));
+        if let Some((title, sub)) = header
+            .or_else(|| line.strip_prefix("#### ").map(|title| (title, true)))
+        {
+            // Save previous heuristic if exists
+            finish(current.take(), &mut diagnostics);
+            add_category_description(
+                &mut corpus,
+                &current_category,
+                &mut intro,
+            );
+
+            let mut heuristic = Heuristic {
+                id: slug(title),
+                title: title.trim().to_string(),
+                category: current_category.clone(),
+                content: line.to_string() + "\n",
+                namespace: corpus.namespace.clone(),
+                ..Default::default()
+            };
+
+            // Extract keywords from title
+            add_keywords(extractor, &heuristic.title, &mut heuristic.keywords);
+            current = Some((heuristic, number, sub));
+            section = Section::None;
+            after_header = true;
+            continue;
+        }
+
+        if let Some((heuristic, _, _)) = current.as_mut() {
+            heuristic.content.push_str(line);
+            heuristic.content.push('\n');
+            let mut report = |message: String| {
+                diagnostics.push(ParseDiagnostic { line: number, message })
+            };
+            parse_line(heuristic, line, &mut section, extractor, &mut report);
+        } else {
+            intro.push(line);
+        }
+    }
+
+    // Save last heuristic
+    finish(current.take(), &mut diagnostics);
+    add_category_description(&mut corpus, &current_category, &mut intro);
+
+    for (index, original) in make_ids_unique(&mut heuristics) {
+        let message = format!(
+            "duplicate id '{}' renamed to '{}'",
+            original, heuristics[index].id
+        );
+        diagnostics
+            .push(ParseDiagnostic { line: header_lines[index], message });
+    }
+
+    // Link `####` variants to the `###` heuristic they sit under
+    for (child, parent) in children {
+        let parent = parent
+            .filter(|&p| heuristics[p].category == heuristics[child].category);
+        match parent {
+            Some(parent) if heuristics[child].parent.is_none() => {
+                heuristics[child].parent = Some(heuristics[parent].id.clone());
+            }
+            Some(_) => {}
+            None => {
+                let message = format!(
+                    "'{}' is a '####' variant without a '###' heuristic above it",
+                    heuristics[child].title
+                );
+                diagnostics.push(ParseDiagnostic {
+                    line: header_lines[child],
+                    message,
+                });
+            }
+        }
+    }
+
+    for (index, reference) in resolve_related(&mut heuristics) {
+        let message = format!(
+            "'{}' refers to unknown heuristic '{}'",
+            heuristics[index].title, reference
+        );
+        diagnostics
+            .push(ParseDiagnostic { line: header_lines[index], message });
+    }
+    diagnostics.sort_by_key(|d| d.line);
+
+    Document { corpus, heuristics, diagnostics, pack }
+}
+
+/// Suffix repeated ids with `-2`, `-3`, ... in order of appearance
+///
+/// A suffix another heuristic already has as its id, say a declared
+/// `**Id:** foo-2`, is skipped for the next free one. Returns the index and
+/// original id of every renamed heuristic.
+pub(crate) fn make_ids_unique(
+    heuristics: &mut [Heuristic],
+) -> Vec<(usize, String)> {
+    let declared: HashSet<String> =
+        heuristics.iter().map(|h| h.id.clone()).collect();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut renamed = Vec::new();
+    for (index, heuristic) in heuristics.iter_mut().enumerate() {
+        if seen.insert(heuristic.id.clone()) {
+            continue;
+        }
+        let id = (2..)
+            .map(|count| format!("{}-{}", heuristic.id, count))
+            .find(|id| !declared.contains(id) && !seen.contains(id))
+            .expect("some suffix is free");
+        renamed
+            .push((index, std::mem::replace(&mut heuristic.id, id.clone())));
+        seen.insert(id);
+    }
+    renamed
+}
+
+/// Replace each heuristic's `related` references with the ids they name
+///
+/// References match an id, the slug of a title, or a title. Unknown ones
+/// are dropped and returned with the index of the heuristic listing them;
+/// references back to the heuristic itself are dropped silently.
+fn resolve_related(heuristics: &mut [Heuristic]) -> Vec<(usize, String)> {
+    let mut unresolved = Vec::new();
+    for index in 0..heuristics.len() {
+        let mut resolved: Vec<String> = Vec::new();
+        for reference in std::mem::take(&mut heuristics[index].related) {
+            let target =
+                heuristics.iter().find(|h| h.id == reference).or_else(|| {
+                    let reference_slug = slug(&reference);
+                    heuristics.iter().find(|h| {
+                        h.id == reference_slug
+                            || h.title.eq_ignore_ascii_case(reference.trim())
+                    })
+                });
+            match target {
+                Some(target) if target.id == heuristics[index].id => {}
+                Some(target) => {
+                    if !resolved.contains(&target.id) {
+                        resolved.push(target.id.clone());
+                    }
+                }
+                None => unresolved.push((index, reference)),
+            }
+        }
+        heuristics[index].related = resolved;
+    }
+    unresolved
+}
+
+/// Record the first paragraph of a category's intro as its description
+fn add_category_description(
+    corpus: &mut Corpus,
+    category: &str,
+    intro: &mut Vec<&str>,
+) {
+    let paragraph: Vec<&str> = intro
+        .drain(..)
+        .map(str::trim)
+        .skip_while(|line| line.is_empty())
+        .take_while(|line| !line.is_empty())
+        .collect();
+    if !category.is_empty()
+        && !paragraph.is_empty()
+        && !corpus.category_descriptions.contains_key(category)
+    {
+        corpus
+            .category_descriptions
+            .insert(category.to_string(), paragraph.join(" "));
+    }
+}
+
+/// Finalize a heuristic once its section ends, dropping untitled ones
+pub(crate) fn finish_heuristic(mut heuristic: Heuristic) -> Option<Heuristic> {
+    if heuristic.title.is_empty() {
+        return None;
+    }
+    heuristic.content = heuristic.content.trim().to_string();
+    heuristic.links = extract_links(&heuristic.content)
+        .into_iter()
+        .map(|(text, url)| Link { text, url })
+        .collect();
+    heuristic.tables = extract_tables(&heuristic.content);
+    Some(heuristic)
+}
+
+/// Extract metadata from a single line inside a heuristic
+///
+/// Lines that look like metadata but can't be used are passed to `report`.
+fn parse_line(
+    heuristic: &mut Heuristic,
+    line: &str,
+    section: &mut Section,
+    extractor: &dyn KeywordExtractor,
+    report: &mut dyn FnMut(String),
+) {
+    let trimmed = line.trim();
+
+    // Indented bullets continue the current metadata list
+    if *section != Section::None {
+        if line.starts_with(char::is_whitespace)
+            && let Some(item) = trimmed.strip_prefix("- ")
+        {
+            push_section_item(heuristic, *section, item.trim(), report);
+            return;
+        }
+        *section = Section::None;
+    }
+
+    // Extract action
+    if let Some(action) = line.strip_prefix("**Action:**") {
+        heuristic.action = action.trim().to_string();
+        add_keywords(extractor, &heuristic.action, &mut heuristic.keywords);
+    }
+
+    // Extract trade-offs and references, either inline or as nested bullets
+    let label = trimmed.strip_prefix("- ").unwrap_or(trimmed);
+    for (prefix, kind) in SECTION_LABELS {
+        if let Some(rest) = label.strip_prefix(prefix) {
+            let rest = rest.trim();
+            if matches!(kind, Section::Alternatives | Section::Related) {
+                // Inline alternatives and references are short names separated by commas
+                for item in
+                    rest.split(',').filter(|item| !item.trim().is_empty())
+                {
+                    push_section_item(heuristic, kind, item.trim(), report);
+                }
+            } else if !rest.is_empty() {
+                push_section_item(heuristic, kind, rest, report);
+            }
+            *section = kind;
+            return;
+        }
+    }
+
+    // Extract explicit id
+    if let Some(id) = labeled(trimmed, "Id") {
+        heuristic.id = slug(id);
+    }
+
+    // Extract freshness annotations
+    if let Some(since) = labeled(trimmed, "Since") {
+        heuristic.since = Some(since.to_string());
+    }
+    if let Some(updated) = labeled(trimmed, "Updated") {
+        heuristic.updated = Some(updated.to_string());
+    }
+
+    // Extract alternate phrasings of the question
+    if let Some(aliases) = labeled(trimmed, "Also asked as") {
+        for alias in parse_aliases(aliases) {
+            add_keywords(extractor, &alias, &mut heuristic.keywords);
+            heuristic.aliases.push(alias);
+        }
+    }
+
+    // Extract decision-tree parent
+    if let Some(parent) = labeled(trimmed, "Parent") {
+        heuristic.parent = Some(parent.to_string());
+    }
+
+    // Extract memory overhead
+    if let Some(memory) = labeled(trimmed, "Memory")
+        .or_else(|| labeled(trimmed, "Memory overhead"))
+    {
+        heuristic.memory_overhead = Some(MemoryOverhead::parse(memory));
+    }
+
+    // Extract toolchain requirements of recommended crates
+    if let Some(requirements) = labeled(trimmed, "MSRV") {
+        for item in requirements.split(',') {
+            match CrateRequirement::parse(item) {
+                Some(requirement) => {
+                    heuristic.crate_requirements.push(requirement)
+                }
+                None => report(format!(
+                    "MSRV item '{}' isn't `crate` VERSION; skipped",
+                    item.trim()
+                )),
+            }
+        }
+    }
+
+    // Extract platform applicability
+    if let Some(platforms) = labeled(trimmed, "Platforms") {
+        heuristic.applies_to = Platforms::parse(platforms);
+    }
+
+    // Extract keywords from various patterns
+    if let Some(use_case) = line.split("**When to use:**").nth(1) {
+        add_keywords(extractor, use_case, &mut heuristic.keywords);
+    }
+
+    // Flag labels that look like metadata but aren't understood, e.g. typos
+    if let Some(name) = label
+        .strip_prefix("**")
+        .and_then(|rest| rest.split_once(":**"))
+        .map(|(name, _)| name)
+        && !name.contains("**")
+        && !KNOWN_LABELS.contains(&name)
+    {
+        report(format!("unknown label '**{}:**' kept as text", name));
+    }
+}
+
+/// Split `"How do I memoize?", "Why recompute?"` into its quoted phrasings
+///
+/// A value without quotes is taken as a single phrasing.
+fn parse_aliases(value: &str) -> Vec<String> {
+    let value = value.replace(['“', '”'], "\"");
+    if !value.contains('"') {
+        return vec![value.trim().to_string()]
+            .into_iter()
+            .filter(|a| !a.is_empty())
+            .collect();
+    }
+    value
+        .split('"')
+        .skip(1)
+        .step_by(2)
+        .map(|alias| alias.trim().to_string())
+        .filter(|alias| !alias.is_empty())
+        .collect()
+}
+
+/// Labels whose values are kept in a heuristic's fields, not just its content
+const FIELD_LABELS: [&str; 19] = [
+    "Action",
+    "Id",
+    "Since",
+    "Updated",
+    "Also asked as",
+    "Parent",
+    "Memory",
+    "Memory overhead",
+    "MSRV",
+    "Platforms",
+    "Crates",
+    "Std types",
+    "Pros",
+    "Cons",
+    "References",
+    "Sources",
+    "Alternatives",
+    "Benchmarks",
+    "See also",
+];
+
+/// Split a heuristic's content into its prose and crate descriptions
+///
+/// Drops the header and every line (with its nested bullets) whose value
+/// is kept in a field, leaving usage notes, examples, and tables. Crate
+/// descriptions (`` - `lru` - Simple LRU cache ``) are returned by name,
+/// since the `crates` field keeps names only.
+pub(crate) fn split_prose(content: &str) -> (String, HashMap<String, String>) {
+    let mut prose: Vec<&str> = Vec::new();
+    let mut descriptions = HashMap::new();
+    let mut list: Option<&str> = None;
+    let mut fences = Fences::default();
+
+    for (i, line) in content.lines().enumerate() {
+        if i == 0 && line.starts_with("###") {
+            continue;
+        }
+        if fences.contains(line) {
+            list = None;
+            prose.push(line);
+            continue;
+        }
+        if let Some(label) = list
+            && line.starts_with(char::is_whitespace)
+            && !line.trim().is_empty()
+        {
+            let item = line.trim().strip_prefix("- ").unwrap_or(line.trim());
+            if label == "Crates"
+                && let Some((name, rest)) = item
+                    .strip_prefix('`')
+                    .and_then(|item| item.split_once('`'))
+                && let Some(description) = rest.trim().strip_prefix("- ")
+            {
+                descriptions
+                    .insert(name.to_string(), description.trim().to_string());
+            }
+            continue;
+        }
+
+        let trimmed = line.trim();
+        let label = trimmed
+            .strip_prefix("- ")
+            .unwrap_or(trimmed)
+            .strip_prefix("**")
+            .and_then(|rest| rest.split_once(":**"));
+        list = label
+            .map(|(label, _)| label)
+            .filter(|label| FIELD_LABELS.contains(label));
+        // Collapse the blank lines left where fields were dropped
+        if list.is_none()
+            && !(trimmed.is_empty()
+                && prose.last().is_none_or(|last| last.trim().is_empty()))
+        {
+            prose.push(line);
+        }
+    }
+
+    while prose.last().is_some_and(|line| line.trim().is_empty()) {
+        prose.pop();
+    }
+    (prose.join("\n"), descriptions)
+}
+
+/// Value of a `**Label:** value` line, optionally written as a bullet
+fn labeled<'a>(line: &'a str, label: &str) -> Option<&'a str> {
+    let line = line.strip_prefix("- ").unwrap_or(line);
+    let value = line
+        .strip_prefix("**")?
+        .strip_prefix(label)?
+        .strip_prefix(":**")?
+        .trim();
+    (!value.is_empty()).then_some(value)
+}
+
+/// Add one item of a metadata list to the matching field
+fn push_section_item(
+    heuristic: &mut Heuristic,
+    section: Section,
+    item: &str,
+    report: &mut dyn FnMut(String),
+) {
+    match section {
+        Section::Pros => heuristic.tradeoffs.pros.push(item.to_string()),
+        Section::Cons => heuristic.tradeoffs.cons.push(item.to_string()),
+        Section::Sources => {
+            let links = extract_links(item);
+            if links.is_empty() && item.starts_with("http") {
+                heuristic.sources.push(Source {
+                    title: item.to_string(),
+                    url: item.to_string(),
+                });
+            }
+            heuristic.sources.extend(
+                links.into_iter().map(|(title, url)| Source { title, url }),
+            );
+        }
+        Section::Alternatives => match Alternative::parse(item) {
+            Some(alternative) => heuristic.alternatives.push(alternative),
+            None => report(format!(
+                "alternative '{}' not understood; skipped",
+                item
+            )),
+        },
+        Section::Benchmarks => heuristic.benchmarks.extend(
+            extract_links(item)
+                .into_iter()
+                .map(|(text, url)| Link { text, url }),
+        ),
+        Section::Related => {
+            // A link names its target by anchor, or by its text if it points elsewhere
+            let reference = match extract_links(item).into_iter().next() {
+                Some((_, url)) if url.starts_with('#') => url[1..].to_string(),
+                Some((text, _)) => text,
+                None => item.trim_matches(['`', '"']).to_string(),
+            };
+            if !reference.is_empty() && !heuristic.related.contains(&reference)
+            {
+                heuristic.related.push(reference);
+            }
+        }
+        Section::None => {}
+    }
+}
+
+/// List whose items `extract_named_code` collects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CodeList {
+    Crates,
+    StdTypes,
+}
+
+/// Open list item or paragraph while walking a heuristic's markdown
+#[derive(Default)]
+struct CodeFrame {
+    /// List this block's code spans belong to, inherited by nested items
+    list: Option<CodeList>,
+    /// Whether this block holds the label itself, so every span counts
+    labeled: bool,
+    /// Whether a nested item already contributed its name
+    taken: bool,
+}
+
+/// Names listed under `**Crates:**` and `**Std types:**`
+///
+/// Walks the markdown structure rather than matching line prefixes, so the
+/// names are found whether they follow the label inline (`` `a`, `b` ``),
+/// wrap onto the next line, or sit in nested bullets at any indentation.
+/// A nested bullet contributes only its first code span, the name; the
+/// description after it may mention other types. Code blocks are skipped.
+fn extract_named_code(content: &str) -> (Vec<String>, Vec<String>) {
+    use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+
+    let mut crates = Vec::new();
+    let mut std_types = Vec::new();
+    let mut frames: Vec<CodeFrame> = Vec::new();
+    let mut label: Option<String> = None;
+    let mut in_paragraph = false;
+
+    for event in Parser::new(content) {
+        match event {
+            Event::Start(Tag::Item) => {
+                let list = frames.last().and_then(|frame| frame.list);
+                frames.push(CodeFrame { list, ..Default::default() });
+            }
+            // A paragraph outside any list can hold a label too
+            Event::Start(Tag::Paragraph) if frames.is_empty() => {
+                frames.push(CodeFrame::default());
+                in_paragraph = true;
+            }
+            Event::End(TagEnd::Paragraph) if in_paragraph => {
+                frames.pop();
+                in_paragraph = false;
+            }
+            Event::End(TagEnd::Item) => {
+                frames.pop();
+            }
+            Event::Start(Tag::Strong) => label = Some(String::new()),
+            Event::Text(text) if label.is_some() => {
+                label.iter_mut().for_each(|l| l.push_str(&text))
+            }
+            Event::End(TagEnd::Strong) => {
+                let list = match label.take().as_deref().map(str::trim) {
+                    Some("Crates:") => Some(CodeList::Crates),
+                    Some("Std types:") => Some(CodeList::StdTypes),
+                    _ => None,
+                };
+                if let (Some(list), Some(frame)) = (list, frames.last_mut()) {
+                    *frame = CodeFrame {
+                        list: Some(list),
+                        labeled: true,
+                        taken: false,
+                    };
+                }
+            }
+            Event::Code(code) => {
+                let Some(frame) = frames.last_mut() else { continue };
+                let Some(list) = frame.list else { continue };
+                if !frame.labeled && std::mem::replace(&mut frame.taken, true)
+                {
+                    continue;
+                }
+                let names = if list == CodeList::Crates {
+                    &mut crates
+                } else {
+                    &mut std_types
+                };
+                if !names.contains(&code.to_string()) {
+                    names.push(code.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    (crates, std_types)
+}
+
+/// Extract all `[text](url)` markdown links from a piece of text
+pub(crate) fn extract_links(text: &str) -> Vec<(String, String)> {
+    let mut links = Vec::new();
+    let mut rest = text;
+
+    while let Some(open) = rest.find('[') {
+        let after = &rest[open + 1..];
+        let Some(close) = after.find("](") else { break };
+        let target = &after[close + 2..];
+        let Some(end) = target.find(')') else { break };
+
+        links.push((
+            after[..close].to_string(),
+            target[..end].trim().to_string(),
+        ));
+        rest = &target[end + 1..];
+    }
+
+    links
+}
+
+pub(crate) fn extract_code_name(text: &str) -> Option<String> {
+    text.trim().strip_prefix('`')?.strip_suffix('`').map(|s| s.to_string())
+}
+
+/// Extract every markdown table outside of code fences
+fn extract_tables(content: &str) -> Vec<Table> {
+    let mut tables = Vec::new();
+    let mut fences = Fences::default();
+    let lines: Vec<&str> = content.lines().collect();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i].trim();
+        let in_fence = fences.contains(line);
+
+        let is_row = |l: &str| l.trim().starts_with('|');
+        if !in_fence
+            && is_row(line)
+            && i + 1 < lines.len()
+            && Table::is_separator(lines[i + 1])
+        {
+            let headers = Table::cells(line);
+            let mut rows = Vec::new();
+            i += 2;
+            while i < lines.len() && is_row(lines[i]) {
+                let mut row = Table::cells(lines[i]);
+                row.resize(headers.len(), String::new());
+                rows.push(row);
+                i += 1;
+            }
+            tables.push(Table { headers, rows });
+            continue;
+        }
+        i += 1;
+    }
+
+    tables
+}
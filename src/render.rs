@@ -0,0 +1,501 @@
+//! Text rendering of heuristics for terminals and documents.
+
+use std::fmt;
+
+use crate::parser::{Fences, split_prose};
+use crate::{Alternative, Heuristic};
+
+/// Output style for [`Heuristic::render`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderStyle {
+    /// Plain text without escape codes
+    Plain,
+    /// Text colored with ANSI escape codes for terminals
+    Ansi,
+    /// Markdown in the same shape as heuristic files
+    Markdown,
+}
+
+pub(crate) const TITLE: &str = "1;36";
+pub(crate) const LABEL: &str = "1;32";
+const VALUE: &str = "33";
+pub(crate) const DIM: &str = "2";
+
+/// Narrowest width prose is wrapped to, however small the terminal
+pub(crate) const MIN_WIDTH: usize = 20;
+
+/// Wrap `text` in an ANSI color sequence when rendering in that style
+pub(crate) fn paint(style: RenderStyle, codes: &str, text: &str) -> String {
+    match style {
+        RenderStyle::Ansi => format!("\x1b[{}m{}\x1b[0m", codes, text),
+        _ => text.to_string(),
+    }
+}
+
+impl Heuristic {
+    /// Render the title and summary fields of the heuristic
+    ///
+    /// Plain and ANSI output put the title on its own line and indent each
+    /// field below it; markdown output mirrors the heuristic file format.
+    pub fn render(&self, style: RenderStyle) -> String {
+        if style == RenderStyle::Markdown {
+            return self.render_markdown();
+        }
+        self.render_summary(style, usize::MAX)
+    }
+
+    /// The plain or ANSI summary, wrapped to `width` columns
+    fn render_summary(&self, style: RenderStyle, width: usize) -> String {
+        let mut out = wrap(&paint(style, TITLE, &self.title), width, "", "");
+        let mut field = |label: &str, value: String| {
+            out.push_str(&wrap(
+                &format!("{} {}", paint(style, LABEL, label), value),
+                width,
+                "   ",
+                "     ",
+            ));
+        };
+
+        if !self.action.is_empty() {
+            field("Action:", inline(&self.action, style));
+        }
+        if !self.crates.is_empty() {
+            field("Crates:", paint(style, VALUE, &self.crates.join(", ")));
+        }
+        if !self.std_types.is_empty() {
+            field(
+                "Std types:",
+                paint(style, VALUE, &self.std_types.join(", ")),
+            );
+        }
+        field("Category:", paint(style, DIM, &self.category));
+        if let Some(updated) = self.updated.as_ref().or(self.since.as_ref()) {
+            field("Updated:", paint(style, DIM, updated));
+        }
+
+        out
+    }
+
+    /// Render the whole heuristic: summary, lists, and prose
+    ///
+    /// Plain and ANSI output wrap prose and lists to `width` columns,
+    /// indent code samples, and align table columns. ANSI output also
+    /// shows bold, italics, and code spans; plain output drops their
+    /// markers. Markdown output is the heuristic as written back to a
+    /// heuristic file.
+    pub fn render_full(&self, style: RenderStyle, width: usize) -> String {
+        if style == RenderStyle::Markdown {
+            return self.to_markdown_block();
+        }
+        let width = width.max(MIN_WIDTH);
+        let mut out = self.render_summary(style, width);
+        if let Some(memory) = &self.memory_overhead {
+            out.push_str(&format!(
+                "   {} {}\n",
+                paint(style, LABEL, "Memory:"),
+                memory.text
+            ));
+        }
+
+        let mut list = |label: &str, items: Vec<String>| {
+            if items.is_empty() {
+                return;
+            }
+            out.push_str(&format!("   {}\n", paint(style, LABEL, label)));
+            for item in items {
+                out.push_str(&wrap(
+                    &inline(&item, style),
+                    width,
+                    "     - ",
+                    "       ",
+                ));
+            }
+        };
+        list("Pros:", self.tradeoffs.pros.clone());
+        list("Cons:", self.tradeoffs.cons.clone());
+        // Heuristic alternatives need the database to resolve; see `alternatives_of`
+        list(
+            "Alternatives:",
+            self.alternatives
+                .iter()
+                .filter_map(|a| match a {
+                    Alternative::Crate(name) | Alternative::StdType(name) => {
+                        Some(name.clone())
+                    }
+                    Alternative::Heuristic(_) => None,
+                })
+                .collect(),
+        );
+        list(
+            "Sources:",
+            self.sources
+                .iter()
+                .map(|s| format!("{} {}", s.title, s.url))
+                .collect(),
+        );
+        list(
+            "Benchmarks:",
+            self.benchmarks
+                .iter()
+                .map(|l| format!("{} {}", l.text, l.url))
+                .collect(),
+        );
+
+        let (prose, _) = split_prose(&self.content);
+        if !prose.is_empty() {
+            out.push('\n');
+            out.push_str(&render_prose(&prose, style, width));
+        }
+        out
+    }
+
+    fn render_markdown(&self) -> String {
+        let code = |items: &[String]| {
+            items
+                .iter()
+                .map(|i| format!("`{}`", i))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let mut out = format!("### {}\n", self.title);
+        if !self.action.is_empty() {
+            out.push_str(&format!("**Action:** {}\n", self.action));
+        }
+        out.push('\n');
+        if !self.crates.is_empty() {
+            out.push_str(&format!("- **Crates:** {}\n", code(&self.crates)));
+        }
+        if !self.std_types.is_empty() {
+            out.push_str(&format!(
+                "- **Std types:** {}\n",
+                code(&self.std_types)
+            ));
+        }
+        out.push_str(&format!("- **Category:** {}\n", self.category));
+        if let Some(updated) = self.updated.as_ref().or(self.since.as_ref()) {
+            out.push_str(&format!("- **Updated:** {}\n", updated));
+        }
+
+        // Benchmarks are cited as footnotes so the summary stays short
+        if !self.benchmarks.is_empty() {
+            let marks: Vec<String> = (1..=self.benchmarks.len())
+                .map(|n| format!("[^{}]", n))
+                .collect();
+            out.push_str(&format!(
+                "- **Benchmarks:** {}\n\n",
+                marks.join(" ")
+            ));
+            for (n, link) in self.benchmarks.iter().enumerate() {
+                out.push_str(&format!(
+                    "[^{}]: [{}]({})\n",
+                    n + 1,
+                    link.text,
+                    link.url
+                ));
+            }
+        }
+
+        out
+    }
+}
+
+/// Lay out markdown prose for a terminal
+fn render_prose(prose: &str, style: RenderStyle, width: usize) -> String {
+    let mut out = String::new();
+    let mut paragraph: Vec<String> = Vec::new();
+    let mut table: Vec<Vec<String>> = Vec::new();
+    let mut fences = Fences::default();
+    // The info string and lines of the open code block
+    let mut code: Option<(&str, Vec<&str>)> = None;
+
+    let flush = |out: &mut String,
+                 paragraph: &mut Vec<String>,
+                 table: &mut Vec<Vec<String>>| {
+        if !paragraph.is_empty() {
+            out.push_str(&wrap(
+                &inline(&paragraph.join(" "), style),
+                width,
+                "   ",
+                "   ",
+            ));
+            paragraph.clear();
+        }
+        if !table.is_empty() {
+            out.push_str(&render_table(table, style));
+            table.clear();
+        }
+    };
+
+    for line in prose.lines() {
+        let was_open = fences.is_open();
+        if fences.contains(line) {
+            flush(&mut out, &mut paragraph, &mut table);
+            // Fence markers are dropped; the indent and color set code apart
+            match (was_open, fences.is_open(), &mut code) {
+                (true, true, Some((_, lines))) => lines.push(line),
+                (true, false, _) => {
+                    if let Some((lang, lines)) = code.take() {
+                        out.push_str(&render_code(&lines, lang, style));
+                    }
+                }
+                _ => {
+                    code = Some((
+                        line.trim_start().trim_start_matches(['`', '~']),
+                        Vec::new(),
+                    ))
+                }
+            }
+            continue;
+        }
+
+        let trimmed = line.trim();
+        if trimmed.starts_with('|') {
+            if !paragraph.is_empty() {
+                flush(&mut out, &mut paragraph, &mut Vec::new());
+            }
+            // Styles would throw off the column widths, so cells drop them
+            let cells: Vec<String> = trimmed
+                .trim_matches('|')
+                .split('|')
+                .map(|c| inline(c.trim(), RenderStyle::Plain))
+                .collect();
+            // The `|---|` row only separates the header in markdown
+            if !cells.iter().all(|c| {
+                !c.is_empty() && c.chars().all(|ch| matches!(ch, '-' | ':'))
+            }) {
+                table.push(cells);
+            }
+            continue;
+        }
+
+        if !table.is_empty() {
+            flush(&mut out, &mut paragraph, &mut table);
+        }
+        let item = list_item(trimmed);
+        let heading = trimmed
+            .strip_prefix('#')
+            .map(|h| h.trim_start_matches('#'))
+            .and_then(|h| h.strip_prefix(' '));
+        let rule = trimmed.len() >= 3 && trimmed.chars().all(|c| c == '-')
+            || trimmed == "***";
+        if trimmed.is_empty() || item.is_some() || heading.is_some() || rule {
+            flush(&mut out, &mut paragraph, &mut table);
+        }
+        match (item, heading) {
+            (Some((marker, text)), _) => {
+                let depth = line.len() - line.trim_start().len();
+                let indent = " ".repeat(3 + depth);
+                let first = format!("{}{} ", indent, marker);
+                let rest = " ".repeat(first.chars().count());
+                out.push_str(&wrap(
+                    &inline(text, style),
+                    width,
+                    &first,
+                    &rest,
+                ));
+            }
+            (None, Some(heading)) => {
+                if !out.is_empty() && !out.ends_with("\n\n") {
+                    out.push('\n');
+                }
+                out.push_str(&format!(
+                    "   {}\n",
+                    paint(style, LABEL, &inline(heading, RenderStyle::Plain))
+                ));
+            }
+            (None, None) if trimmed.is_empty() || rule => {
+                if !out.is_empty() && !out.ends_with("\n\n") {
+                    out.push('\n');
+                }
+            }
+            (None, None) => paragraph.push(trimmed.to_string()),
+        }
+    }
+    flush(&mut out, &mut paragraph, &mut table);
+    if let Some((lang, lines)) = code {
+        out.push_str(&render_code(&lines, lang, style));
+    }
+    while out.ends_with("\n\n") {
+        out.pop();
+    }
+    out
+}
+
+/// Indent a code sample, highlighting it on terminals when the
+/// `highlight` feature knows its language
+fn render_code(lines: &[&str], lang: &str, style: RenderStyle) -> String {
+    #[cfg(feature = "highlight")]
+    if style == RenderStyle::Ansi
+        && let Some(highlighted) =
+            crate::highlight::ansi(&lines.join("\n"), lang)
+    {
+        return highlighted
+            .iter()
+            .map(|line| format!("     {}\n", line))
+            .collect();
+    }
+    #[cfg(not(feature = "highlight"))]
+    let _ = lang;
+    lines
+        .iter()
+        .map(|line| {
+            format!(
+                "{}\n",
+                format!("     {}", paint(style, VALUE, line)).trim_end()
+            )
+        })
+        .collect()
+}
+
+/// The marker and text of a bulleted (`- `, `* `) or numbered (`1. `) list item
+fn list_item(line: &str) -> Option<(&str, &str)> {
+    if let Some(text) =
+        line.strip_prefix("- ").or_else(|| line.strip_prefix("* "))
+    {
+        return Some(("-", text));
+    }
+    let digits = line.len()
+        - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let text = line[digits..]
+        .strip_prefix(". ")
+        .or_else(|| line[digits..].strip_prefix(") "))?;
+    (digits > 0).then(|| (&line[..digits + 1], text))
+}
+
+/// Apply the inline markdown of a line: emphasis, code spans, and links
+///
+/// ANSI output shows bold and italics as such and colors code; plain
+/// output keeps only the text. Links are followed by their target, except
+/// for links to other heuristics, whose ids mean nothing on a terminal.
+fn inline(text: &str, style: RenderStyle) -> String {
+    use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+
+    let ansi = style == RenderStyle::Ansi;
+    let mut out = String::new();
+    let mut links: Vec<String> = Vec::new();
+    for event in Parser::new(text) {
+        match event {
+            Event::Start(Tag::Strong) if ansi => out.push_str("\x1b[1m"),
+            Event::End(TagEnd::Strong) if ansi => out.push_str("\x1b[22m"),
+            Event::Start(Tag::Emphasis) if ansi => out.push_str("\x1b[3m"),
+            Event::End(TagEnd::Emphasis) if ansi => out.push_str("\x1b[23m"),
+            Event::Start(Tag::Link { dest_url, .. }) => {
+                links.push(dest_url.to_string())
+            }
+            Event::End(TagEnd::Link) => {
+                if let Some(url) =
+                    links.pop().filter(|url| !url.starts_with('#'))
+                {
+                    out.push_str(&format!(
+                        " {}",
+                        paint(style, DIM, &format!("<{}>", url))
+                    ));
+                }
+            }
+            Event::Text(text)
+            | Event::Html(text)
+            | Event::InlineHtml(text) => out.push_str(&text),
+            Event::Code(code) => out.push_str(&paint(style, VALUE, &code)),
+            Event::SoftBreak | Event::HardBreak => out.push(' '),
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Columns `text` takes up on a terminal, leaving out ANSI escape sequences
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+/// Align the cells of a table, header first
+fn render_table(rows: &[Vec<String>], style: RenderStyle) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|i| {
+            rows.iter()
+                .filter_map(|row| row.get(i))
+                .map(|c| c.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let line = |row: &[String]| {
+        let cells: Vec<String> = widths
+            .iter()
+            .enumerate()
+            .map(|(i, &w)| {
+                format!(
+                    "{:<w$}",
+                    row.get(i).map(String::as_str).unwrap_or(""),
+                    w = w
+                )
+            })
+            .collect();
+        cells.join("  ").trim_end().to_string()
+    };
+
+    let mut out = String::new();
+    for (i, row) in rows.iter().enumerate() {
+        if i == 0 {
+            out.push_str(&format!("   {}\n", paint(style, LABEL, &line(row))));
+            let rule: Vec<String> =
+                widths.iter().map(|&w| "-".repeat(w)).collect();
+            out.push_str(&format!(
+                "   {}\n",
+                paint(style, DIM, &rule.join("  "))
+            ));
+        } else {
+            out.push_str(&format!("   {}\n", line(row)));
+        }
+    }
+    out
+}
+
+/// Wrap `text` to `width` columns, starting with `first` and indenting
+/// continuation lines with `rest`
+///
+/// Words longer than a line, such as URLs, are never split, and ANSI
+/// escape sequences take up no room.
+pub(crate) fn wrap(
+    text: &str,
+    width: usize,
+    first: &str,
+    rest: &str,
+) -> String {
+    let mut out = String::new();
+    let mut line = first.to_string();
+    let mut empty = true;
+    for word in text.split_whitespace() {
+        if !empty && visible_width(&line) + 1 + visible_width(word) > width {
+            out.push_str(&line);
+            out.push('\n');
+            line = rest.to_string();
+            empty = true;
+        }
+        if !empty {
+            line.push(' ');
+        }
+        line.push_str(word);
+        empty = false;
+    }
+    out.push_str(&line);
+    out.push('\n');
+    out
+}
+
+impl fmt::Display for Heuristic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.render(RenderStyle::Plain).trim_end())
+    }
+}
@@ -0,0 +1,162 @@
+//! Weighted random sampling over the heuristic database.
+//!
+//! Exploration features (random tips, daily heuristics, quizzes) draw
+//! entries through [`HeuristicDb::sample`] so they can favour heuristics
+//! the user hasn't seen yet.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{Heuristic, HeuristicDb};
+
+/// How [`HeuristicDb::sample`] weights each heuristic
+#[derive(Debug, Clone, Copy)]
+pub enum SampleBias<'a> {
+    /// Every heuristic is equally likely
+    Uniform,
+    /// Heuristics with fewer recorded views in the log are more likely
+    UnderViewed(&'a UsageLog),
+    /// Spread picks evenly across categories, regardless of their size
+    ByCategoryCoverage,
+}
+
+/// Local record of how often each heuristic has been viewed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UsageLog {
+    views: HashMap<String, u32>,
+}
+
+impl UsageLog {
+    /// Create an empty usage log
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one view of a heuristic
+    pub fn record(&mut self, heuristic: &Heuristic) {
+        *self.views.entry(heuristic.id.clone()).or_default() += 1;
+    }
+
+    /// Number of recorded views of a heuristic
+    pub fn views(&self, heuristic: &Heuristic) -> u32 {
+        self.views.get(&heuristic.id).copied().unwrap_or(0)
+    }
+
+    /// Parse a log previously written with its `Display` impl
+    ///
+    /// Each line holds a view count and a heuristic id separated by a
+    /// tab. Malformed lines are skipped.
+    pub fn parse(text: &str) -> Self {
+        let mut log = Self::new();
+        for line in text.lines() {
+            if let Some((count, key)) = line.split_once('\t')
+                && let Ok(count) = count.trim().parse::<u32>()
+            {
+                *log.views.entry(key.to_string()).or_default() += count;
+            }
+        }
+        log
+    }
+}
+
+impl fmt::Display for UsageLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut entries: Vec<_> = self.views.iter().collect();
+        entries.sort();
+        for (key, count) in entries {
+            writeln!(f, "{}\t{}", count, key)?;
+        }
+        Ok(())
+    }
+}
+
+impl HeuristicDb {
+    /// Draw up to `n` distinct heuristics at random, weighted by `bias`
+    pub fn sample(&self, n: usize, bias: SampleBias<'_>) -> Vec<&Heuristic> {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default();
+        self.sample_seeded(n, bias, seed)
+    }
+
+    /// Like [`HeuristicDb::sample`], but deterministic for a given seed
+    pub fn sample_seeded(
+        &self,
+        n: usize,
+        bias: SampleBias<'_>,
+        seed: u64,
+    ) -> Vec<&Heuristic> {
+        let mut rng = SplitMix64(seed);
+        let mut category_sizes: HashMap<&str, usize> = HashMap::new();
+        for h in &self.heuristics {
+            *category_sizes.entry(h.category.as_str()).or_default() += 1;
+        }
+
+        let mut remaining: Vec<usize> = (0..self.heuristics.len()).collect();
+        let mut picked_per_category: HashMap<&str, usize> = HashMap::new();
+        let mut picked = Vec::new();
+
+        while picked.len() < n && !remaining.is_empty() {
+            let weights: Vec<f64> = remaining
+                .iter()
+                .map(|&idx| {
+                    let h = &self.heuristics[idx];
+                    match bias {
+                        SampleBias::Uniform => 1.0,
+                        SampleBias::UnderViewed(log) => {
+                            1.0 / (1.0 + log.views(h) as f64)
+                        }
+                        SampleBias::ByCategoryCoverage => {
+                            let cat = h.category.as_str();
+                            let size = category_sizes[cat] as f64;
+                            let seen = picked_per_category
+                                .get(cat)
+                                .copied()
+                                .unwrap_or(0)
+                                as f64;
+                            1.0 / (size * (1.0 + seen).powi(2))
+                        }
+                    }
+                })
+                .collect();
+
+            let total: f64 = weights.iter().sum();
+            let mut target = rng.next_f64() * total;
+            let mut choice = remaining.len() - 1;
+            for (pos, weight) in weights.iter().enumerate() {
+                if target < *weight {
+                    choice = pos;
+                    break;
+                }
+                target -= weight;
+            }
+
+            let idx = remaining.remove(choice);
+            let h = &self.heuristics[idx];
+            *picked_per_category.entry(h.category.as_str()).or_default() += 1;
+            picked.push(h);
+        }
+
+        picked
+    }
+}
+
+/// Small, dependency-free PRNG with a stable sequence per seed
+pub(crate) struct SplitMix64(pub(crate) u64);
+
+impl SplitMix64 {
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform float in `[0, 1)`
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
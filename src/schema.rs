@@ -0,0 +1,32 @@
+//! JSON Schemas of the formats programs write packs in.
+//!
+//! [`pack_schema`] describes TOML heuristic files and [`snapshot_schema`]
+//! the JSON documents `HeuristicDb::from_json` reads. Both are generated
+//! from the types loading deserializes into, so they accept what loading
+//! accepts, and producers can validate their output before shipping it.
+
+use schemars::{Schema, schema_for};
+
+use crate::JsonSnapshot;
+use crate::definition::PackDefinition;
+
+/// JSON Schema of a TOML heuristic file, which TOML-aware editors and
+/// validators read too
+pub fn pack_schema() -> String {
+    document(schema_for!(PackDefinition), "Heuristic pack")
+}
+
+/// JSON Schema of a database snapshot, as written by `HeuristicDb::to_json`
+pub fn snapshot_schema() -> String {
+    document(schema_for!(JsonSnapshot), "Heuristic database snapshot")
+}
+
+/// `schema` titled `title`, as pretty-printed JSON
+fn document(mut schema: Schema, title: &str) -> String {
+    schema.insert("title".to_string(), title.into());
+    format!(
+        "{}\n",
+        serde_json::to_string_pretty(&schema)
+            .expect("schemas serialize to JSON")
+    )
+}
@@ -0,0 +1,186 @@
+//! A small web UI over a heuristic database.
+//!
+//! [`serve`] answers with a search box and the categories at `/`, search
+//! results at `/search?q=`, a category's heuristics at `/category?name=`,
+//! and each heuristic at `/heuristic/<id>`, all rendered from the same
+//! database the CLI reads, so a team can host its knowledge base
+//! internally.
+
+use std::collections::HashMap;
+use std::net::TcpListener;
+use std::sync::Arc;
+
+use axum::Router;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Redirect, Response};
+use axum::routing::get;
+
+use crate::export::escape;
+use crate::{Heuristic, HeuristicDb};
+
+/// Serve the web UI for `db` on `listener` until the process exits
+///
+/// The server runs on its own single-threaded runtime, so callers don't
+/// need one: `serve(db, TcpListener::bind("127.0.0.1:7878")?)`.
+pub fn serve(db: HeuristicDb, listener: TcpListener) -> std::io::Result<()> {
+    listener.set_nonblocking(true)?;
+    let runtime =
+        tokio::runtime::Builder::new_current_thread().enable_io().build()?;
+    runtime.block_on(async {
+        let listener = tokio::net::TcpListener::from_std(listener)?;
+        axum::serve(listener, router(Arc::new(db))).await
+    })
+}
+
+fn router(db: Arc<HeuristicDb>) -> Router {
+    Router::new()
+        .route("/", get(index))
+        .route("/search", get(search))
+        .route("/category", get(category))
+        .route("/heuristic/{id}", get(heuristic))
+        .with_state(db)
+}
+
+type Db = State<Arc<HeuristicDb>>;
+
+type Params = Query<HashMap<String, String>>;
+
+async fn index(State(db): Db) -> Html<String> {
+    let mut body = String::from("<h1>Heuristics</h1>\n<ul>\n");
+    for category in db.categories() {
+        body.push_str(&format!(
+            "<li><a href=\"/category?name={}\">{}</a> ({})</li>\n",
+            encode(&category),
+            escape(&category),
+            db.by_category(&category).len()
+        ));
+    }
+    body.push_str("</ul>\n");
+    page("Heuristics", "", &body)
+}
+
+async fn search(State(db): Db, Query(params): Params) -> Response {
+    let query = params.get("q").map_or("", |q| q.trim());
+    if query.is_empty() {
+        return Redirect::to("/").into_response();
+    }
+    let keywords: Vec<&str> = query.split_whitespace().collect();
+    let body = format!(
+        "<h1>Results for &ldquo;{}&rdquo;</h1>\n{}",
+        escape(query),
+        listing(&db.search(&keywords))
+    );
+    page(query, query, &body).into_response()
+}
+
+async fn category(State(db): Db, Query(params): Params) -> Response {
+    let name = params.get("name").map_or("", String::as_str);
+    let heuristics = db.by_category(name);
+    if heuristics.is_empty() {
+        return not_found(&format!(
+            "No category named &ldquo;{}&rdquo;.",
+            escape(name)
+        ));
+    }
+    let mut body = format!("<h1>{}</h1>\n", escape(name));
+    let corpus = db.corpus(&heuristics[0].namespace);
+    if let Some(description) =
+        corpus.and_then(|corpus| corpus.category_descriptions.get(name))
+    {
+        body.push_str(&format!("<p>{}</p>\n", escape(description)));
+    }
+    body.push_str(&listing(&heuristics));
+    page(name, "", &body).into_response()
+}
+
+async fn heuristic(State(db): Db, Path(id): Path<String>) -> Response {
+    let Some(found) = db.get(&id) else {
+        return not_found(&format!(
+            "No heuristic with id &ldquo;{}&rdquo;.",
+            escape(&id)
+        ));
+    };
+    // In-page `#id` links point at other heuristics' pages here
+    let mut body = found.to_html().replace("href=\"#", "href=\"/heuristic/");
+    let mut related = db.related_to(found);
+    for alternative in db.alternatives_of(found) {
+        if !related.contains(&alternative) {
+            related.push(alternative);
+        }
+    }
+    if !related.is_empty() {
+        body.push_str(&format!("<h2>Related</h2>\n{}", listing(&related)));
+    }
+    body.push_str(&format!(
+        "<p><a href=\"/category?name={}\">More in {}</a></p>\n",
+        encode(&found.category),
+        escape(&found.category)
+    ));
+    page(&found.title, "", &body).into_response()
+}
+
+/// Heuristics as a list of links, each with its action
+fn listing(heuristics: &[&Heuristic]) -> String {
+    if heuristics.is_empty() {
+        return "<p>No heuristics found.</p>\n".to_string();
+    }
+    let mut out = String::from("<ul>\n");
+    for h in heuristics {
+        out.push_str(&format!(
+            "<li><a href=\"/heuristic/{}\">{}</a><br><small>{}</small></li>\n",
+            encode(&h.id),
+            escape(&h.title),
+            escape(&h.action)
+        ));
+    }
+    out.push_str("</ul>\n");
+    out
+}
+
+fn not_found(message: &str) -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        page(
+            "Not found",
+            "",
+            &format!("<h1>Not found</h1>\n<p>{}</p>\n", message),
+        ),
+    )
+        .into_response()
+}
+
+/// A complete page with the search box, which shows `query`
+fn page(title: &str, query: &str, body: &str) -> Html<String> {
+    Html(format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{} &middot; Heuristics</title>\n\
+         <style>body {{ font-family: sans-serif; max-width: 50em; margin: 0 auto; padding: 1em; }} \
+         pre {{ overflow-x: auto; background: #f4f4f4; padding: 0.5em; }} li {{ margin: 0.4em 0; }}</style>\n\
+         </head>\n<body>\n<header><a href=\"/\">Heuristics</a>\n\
+         <form action=\"/search\"><input type=\"search\" name=\"q\" value=\"{}\" placeholder=\"cache, lock-free, ...\" autofocus> \
+         <button>Search</button></form></header>\n<main>\n{}</main>\n</body>\n</html>\n",
+        escape(title),
+        escape(query),
+        body
+    ))
+}
+
+/// Percent-encode `text` for a URL path segment or query value
+fn encode(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'_'
+            | b'.'
+            | b'~' => out.push(byte as char),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
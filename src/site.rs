@@ -0,0 +1,290 @@
+//! Static HTML sites for publishing a pack, e.g. on GitHub Pages.
+//!
+//! [`to_site`] lays out the same pages `heuristics serve` does as files:
+//! an index of categories, a page per category, and a page per heuristic
+//! linking to the heuristics it refers to. Search runs in the browser
+//! against a JSON index built alongside the pages.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::export::escape;
+use crate::{
+    Alternative, Heuristic, HeuristicDb, ScoreWeights, resolve_among, slug,
+};
+
+/// Version of the document [`HeuristicDb::search_index_json`] writes,
+/// bumped whenever a field is removed, renamed, or changes type
+pub const SEARCH_INDEX_VERSION: u32 = 1;
+
+/// Filters `search-index.json` as the reader types, like `heuristics
+/// search` ranking title and action matches above the rest
+const SEARCH_JS: &str = r#"(function () {
+  var input = document.getElementById("search");
+  var results = document.getElementById("results");
+  var root = document.currentScript.dataset.root;
+  var index = null;
+  function show() {
+    var words = input.value.toLowerCase().split(/\s+/).filter(Boolean);
+    results.innerHTML = "";
+    if (!words.length || !index) return;
+    index
+      .map(function (h) {
+        var headline = (h.title + " " + h.action).toLowerCase();
+        var terms = h.terms.join(" ").toLowerCase();
+        var score = 0;
+        words.forEach(function (w) {
+          if (headline.indexOf(w) >= 0) score += 2;
+          else if (terms.indexOf(w) >= 0) score += 1;
+        });
+        return [score, h];
+      })
+      .filter(function (r) { return r[0] > 0; })
+      .sort(function (a, b) { return b[0] - a[0]; })
+      .slice(0, 20)
+      .forEach(function (r) {
+        var li = document.createElement("li");
+        var a = document.createElement("a");
+        a.href = root + "heuristic/" + r[1].id + ".html";
+        a.textContent = r[1].title;
+        var action = document.createElement("small");
+        action.textContent = r[1].action;
+        li.append(a, document.createElement("br"), action);
+        results.append(li);
+      });
+  }
+  input.addEventListener("input", function () {
+    if (index) return show();
+    fetch(root + "search-index.json")
+      .then(function (response) { return response.json(); })
+      .then(function (loaded) { index = loaded; show(); });
+  });
+})();
+"#;
+
+/// A static site with every heuristic, as paths relative to the site's
+/// root and their contents
+///
+/// The site has `index.html` listing the categories, `category/<slug>.html`
+/// and `heuristic/<id>.html` pages, and `search-index.json`, which
+/// `search.js` searches as the reader types. `[...](#id)` links, `**See
+/// also:**`, `**Parent:**`, and heuristic alternatives link to the other
+/// heuristics' pages when they're part of the site.
+pub fn to_site<'a>(
+    title: &str,
+    heuristics: impl IntoIterator<Item = &'a Heuristic>,
+) -> Vec<(PathBuf, String)> {
+    let heuristics: Vec<&Heuristic> = heuristics.into_iter().collect();
+    let by_id: HashMap<&str, &Heuristic> =
+        heuristics.iter().map(|h| (h.id.as_str(), *h)).collect();
+
+    let mut categories: Vec<(&str, Vec<&Heuristic>)> = Vec::new();
+    for &heuristic in &heuristics {
+        match categories
+            .iter_mut()
+            .find(|(name, _)| *name == heuristic.category)
+        {
+            Some((_, members)) => members.push(heuristic),
+            None => categories.push((&heuristic.category, vec![heuristic])),
+        }
+    }
+
+    let mut index = format!("<h1>{}</h1>\n<ul>\n", escape(title));
+    for (name, members) in &categories {
+        index.push_str(&format!(
+            "<li><a href=\"category/{}.html\">{}</a> ({})</li>\n",
+            slug(name),
+            escape(name),
+            members.len()
+        ));
+    }
+    index.push_str("</ul>\n");
+    let mut files =
+        vec![(PathBuf::from("index.html"), page(title, "", title, &index))];
+
+    for (name, members) in &categories {
+        let body = format!("<h1>{}</h1>\n{}", escape(name), listing(members));
+        files.push((
+            PathBuf::from(format!("category/{}.html", slug(name))),
+            page(title, "../", name, &body),
+        ));
+    }
+
+    for heuristic in &heuristics {
+        // In-page `#id` links point at other heuristics' pages here
+        let mut body = heuristic.to_html();
+        for id in heuristic
+            .related
+            .iter()
+            .filter(|id| by_id.contains_key(id.as_str()))
+        {
+            body = body.replace(
+                &format!("href=\"#{}\"", escape(id)),
+                &format!("href=\"{}.html\"", escape(id)),
+            );
+        }
+        let references =
+            heuristic.related.iter().chain(&heuristic.parent).chain(
+                heuristic.alternatives.iter().filter_map(|alternative| {
+                    match alternative {
+                        Alternative::Heuristic(reference) => Some(reference),
+                        _ => None,
+                    }
+                }),
+            );
+        let mut related: Vec<&Heuristic> = Vec::new();
+        for other in references
+            .filter_map(|reference| resolve_among(&heuristics, reference))
+        {
+            if other.id != heuristic.id
+                && !related.iter().any(|h| h.id == other.id)
+            {
+                related.push(other);
+            }
+        }
+        if !related.is_empty() {
+            body.push_str(&format!("<h2>Related</h2>\n{}", listing(&related)));
+        }
+        body.push_str(&format!(
+            "<p><a href=\"../category/{}.html\">More in {}</a></p>\n",
+            slug(&heuristic.category),
+            escape(&heuristic.category)
+        ));
+        let path = PathBuf::from(format!("heuristic/{}.html", heuristic.id));
+        files.push((path, page(title, "../", &heuristic.title, &body)));
+    }
+
+    files
+        .push((PathBuf::from("search-index.json"), search_index(&heuristics)));
+    files.push((PathBuf::from("search.js"), SEARCH_JS.to_string()));
+    files
+}
+
+impl HeuristicDb {
+    /// The search index as JSON, so web and WASM frontends can rank
+    /// results exactly as [`HeuristicDb::search`] does without indexing
+    /// the pack themselves
+    ///
+    /// ```json
+    /// {
+    ///   "version": 1,
+    ///   "weights": { "exact": 2, "partial": 1 },
+    ///   "ids": ["need-key-value-lookups", ...],
+    ///   "terms": { "cache": [[6, 3], [12, 1]], ... }
+    /// }
+    /// ```
+    ///
+    /// `ids` are the heuristics in database order, and each term maps to
+    /// `[position in ids, weight]` postings. To search, lowercase each
+    /// query word; a term equal to it adds `exact * weight` to the score of
+    /// each heuristic it lists, and every term containing it or contained
+    /// in it, itself included, adds `partial * weight`. Rank by score,
+    /// highest first, ties in `ids` order. Pair it with `to_json` for the
+    /// content.
+    pub fn search_index_json(&self) -> String {
+        let weights = ScoreWeights::default();
+        let ids: Vec<String> =
+            self.heuristics.iter().map(|h| json_string(&h.id)).collect();
+        let mut terms: Vec<(&String, &Vec<(usize, usize)>)> =
+            self.index.iter().collect();
+        terms.sort();
+        let terms: Vec<String> = terms
+            .into_iter()
+            .map(|(term, postings)| {
+                let postings: Vec<String> = postings
+                    .iter()
+                    .map(|(i, weight)| format!("[{},{}]", i, weight))
+                    .collect();
+                format!("{}:[{}]", json_string(term), postings.join(","))
+            })
+            .collect();
+        format!(
+            "{{\"version\":{},\"weights\":{{\"exact\":{},\"partial\":{}}},\"ids\":[{}],\n\"terms\":{{\n{}\n}}}}\n",
+            SEARCH_INDEX_VERSION,
+            weights.exact,
+            weights.partial,
+            ids.join(","),
+            terms.join(",\n")
+        )
+    }
+}
+
+/// Heuristics as a list of links, each with its action, from a page one
+/// directory below the root
+fn listing(heuristics: &[&Heuristic]) -> String {
+    let mut out = String::from("<ul>\n");
+    for h in heuristics {
+        out.push_str(&format!(
+            "<li><a href=\"../heuristic/{}.html\">{}</a><br><small>{}</small></li>\n",
+            escape(&h.id),
+            escape(&h.title),
+            escape(&h.action)
+        ));
+    }
+    out.push_str("</ul>\n");
+    out
+}
+
+/// A complete page of the site titled `site`, whose root is at `root`
+/// relative to the page
+fn page(site: &str, root: &str, title: &str, body: &str) -> String {
+    let title = match title == site {
+        true => escape(site),
+        false => format!("{} &middot; {}", escape(title), escape(site)),
+    };
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{title}</title>\n\
+         <style>body {{ font-family: sans-serif; max-width: 50em; margin: 0 auto; padding: 1em; }} \
+         pre {{ overflow-x: auto; background: #f4f4f4; padding: 0.5em; }} li {{ margin: 0.4em 0; }}</style>\n\
+         </head>\n<body>\n<header><a href=\"{root}index.html\">{site}</a>\n\
+         <input type=\"search\" id=\"search\" placeholder=\"cache, lock-free, ...\" aria-label=\"Search\">\n\
+         <ul id=\"results\"></ul></header>\n<main>\n{body}</main>\n\
+         <script src=\"{root}search.js\" data-root=\"{root}\"></script>\n</body>\n</html>\n",
+        site = escape(site),
+    )
+}
+
+/// What `search.js` searches: each heuristic's id, title, action,
+/// category, and the terms it's indexed under
+fn search_index(heuristics: &[&Heuristic]) -> String {
+    let entries: Vec<String> = heuristics
+        .iter()
+        .map(|h| {
+            let terms = h.keywords.iter().chain(&h.body_keywords).chain(&h.crates).chain(&h.std_types).chain(&h.tags);
+            let terms: Vec<String> = terms.map(|term| json_string(term)).collect();
+            format!(
+                "{{\"id\":{},\"title\":{},\"action\":{},\"category\":{},\"terms\":[{}]}}",
+                json_string(&h.id),
+                json_string(&h.title),
+                json_string(&h.action),
+                json_string(&h.category),
+                terms.join(",")
+            )
+        })
+        .collect();
+    format!("[\n{}\n]\n", entries.join(",\n"))
+}
+
+/// `text` as a JSON string literal, which YAML reads as a double-quoted
+/// scalar too
+pub(crate) fn json_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                out.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
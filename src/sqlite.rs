@@ -0,0 +1,153 @@
+//! SQLite databases of the pack, for running SQL over it.
+//!
+//! [`to_sqlite`] writes one table of heuristics, tables for the names and
+//! links they list, and an FTS5 index of their text:
+//!
+//! ```sql
+//! heuristics(id PRIMARY KEY, title, category, action, content, namespace, level, since, updated)
+//! crates(heuristic_id, name)
+//! std_types(heuristic_id, name)
+//! keywords(heuristic_id, keyword, weight)      -- weight 2 for headline terms, 1 for the body's
+//! tags(heuristic_id, tag)
+//! alternatives(heuristic_id, kind, name)       -- kind 'crate', 'std_type', or 'heuristic'
+//! cross_references(from_id, to_id, kind)       -- kind 'see_also', 'parent', or 'alternative'
+//! heuristics_fts(id, title, action, content)   -- FTS5, e.g. `WHERE heuristics_fts MATCH 'lock*'`
+//! ```
+
+use std::path::Path;
+
+use rusqlite::{Connection, params};
+
+use crate::{Alternative, Heuristic, resolve_among};
+
+const SCHEMA: &str = "
+CREATE TABLE heuristics (
+    id TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    category TEXT NOT NULL,
+    action TEXT NOT NULL,
+    content TEXT NOT NULL,
+    namespace TEXT NOT NULL,
+    level TEXT,
+    since TEXT,
+    updated TEXT
+);
+CREATE TABLE crates (heuristic_id TEXT NOT NULL REFERENCES heuristics (id), name TEXT NOT NULL);
+CREATE TABLE std_types (heuristic_id TEXT NOT NULL REFERENCES heuristics (id), name TEXT NOT NULL);
+CREATE TABLE keywords (heuristic_id TEXT NOT NULL REFERENCES heuristics (id), keyword TEXT NOT NULL, weight INTEGER NOT NULL);
+CREATE TABLE tags (heuristic_id TEXT NOT NULL REFERENCES heuristics (id), tag TEXT NOT NULL);
+CREATE TABLE alternatives (heuristic_id TEXT NOT NULL REFERENCES heuristics (id), kind TEXT NOT NULL, name TEXT NOT NULL);
+CREATE TABLE cross_references (
+    from_id TEXT NOT NULL REFERENCES heuristics (id),
+    to_id TEXT NOT NULL REFERENCES heuristics (id),
+    kind TEXT NOT NULL
+);
+CREATE INDEX crates_by_name ON crates (name);
+CREATE INDEX keywords_by_keyword ON keywords (keyword);
+CREATE VIRTUAL TABLE heuristics_fts USING fts5 (id UNINDEXED, title, action, content);
+";
+
+/// Write the heuristics into a new SQLite database at `path`
+///
+/// An existing file at `path` is replaced. Cross-references only link
+/// heuristics that are both exported; the ones a heuristic names but that
+/// aren't are left out. SQLite errors are reported as
+/// [`std::io::ErrorKind::Other`].
+pub fn to_sqlite<'a>(
+    heuristics: impl IntoIterator<Item = &'a Heuristic>,
+    path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let path = path.as_ref();
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    write_tables(&heuristics.into_iter().collect::<Vec<_>>(), path)
+        .map_err(std::io::Error::other)
+}
+
+fn write_tables(
+    heuristics: &[&Heuristic],
+    path: &Path,
+) -> rusqlite::Result<()> {
+    let mut conn = Connection::open(path)?;
+    let tx = conn.transaction()?;
+    tx.execute_batch(SCHEMA)?;
+    for h in heuristics {
+        tx.execute(
+            "INSERT INTO heuristics VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                h.id,
+                h.title,
+                h.category,
+                h.action,
+                h.content,
+                h.namespace,
+                h.level.map(|level| level.to_string()),
+                h.since,
+                h.updated
+            ],
+        )?;
+        tx.execute(
+            "INSERT INTO heuristics_fts VALUES (?1, ?2, ?3, ?4)",
+            params![h.id, h.title, h.action, h.content],
+        )?;
+    }
+    // Every heuristic is in before anything refers to one
+    for h in heuristics {
+        for (table, names) in
+            [("crates", &h.crates), ("std_types", &h.std_types)]
+        {
+            for name in names {
+                tx.execute(
+                    &format!("INSERT INTO {} VALUES (?1, ?2)", table),
+                    params![h.id, name],
+                )?;
+            }
+        }
+        for tag in &h.tags {
+            tx.execute(
+                "INSERT INTO tags VALUES (?1, ?2)",
+                params![h.id, tag],
+            )?;
+        }
+        let keywords = h
+            .keywords
+            .iter()
+            .map(|k| (k, 2))
+            .chain(h.body_keywords.iter().map(|k| (k, 1)));
+        for (keyword, weight) in keywords {
+            tx.execute(
+                "INSERT INTO keywords VALUES (?1, ?2, ?3)",
+                params![h.id, keyword, weight],
+            )?;
+        }
+
+        let mut references: Vec<(&str, &str)> =
+            h.related.iter().map(|id| (id.as_str(), "see_also")).collect();
+        references
+            .extend(h.parent.iter().map(|parent| (parent.as_str(), "parent")));
+        for alternative in &h.alternatives {
+            let (kind, name) = match alternative {
+                Alternative::Crate(name) => ("crate", name),
+                Alternative::StdType(name) => ("std_type", name),
+                Alternative::Heuristic(name) => {
+                    references.push((name, "alternative"));
+                    ("heuristic", name)
+                }
+            };
+            tx.execute(
+                "INSERT INTO alternatives VALUES (?1, ?2, ?3)",
+                params![h.id, kind, name],
+            )?;
+        }
+        for (reference, kind) in references {
+            if let Some(other) = resolve_among(heuristics, reference) {
+                tx.execute(
+                    "INSERT INTO cross_references VALUES (?1, ?2, ?3)",
+                    params![h.id, other.id, kind],
+                )?;
+            }
+        }
+    }
+    tx.commit()
+}
@@ -0,0 +1,133 @@
+//! Typed view of the standard library types heuristics recommend.
+
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::Heuristic;
+
+/// A standard library type mentioned by a heuristic
+///
+/// Parsing accepts the forms used in heuristic files, such as `HashMap`,
+/// `std::collections::HashMap`, `HashMap<K, V>` or `Vec::binary_search`.
+/// Types without a dedicated variant are kept as [`KnownStdType::Other`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum KnownStdType {
+    Vec,
+    VecDeque,
+    LinkedList,
+    HashMap,
+    HashSet,
+    BTreeMap,
+    BTreeSet,
+    BinaryHeap,
+    String,
+    Box,
+    Rc,
+    Arc,
+    Cell,
+    RefCell,
+    Mutex,
+    RwLock,
+    Cow,
+    /// Any of the `std::sync::atomic` types
+    Atomic,
+    /// A type without a dedicated variant, as written in the source
+    Other(String),
+}
+
+impl KnownStdType {
+    /// The unqualified type name, e.g. `BTreeMap`
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Vec => "Vec",
+            Self::VecDeque => "VecDeque",
+            Self::LinkedList => "LinkedList",
+            Self::HashMap => "HashMap",
+            Self::HashSet => "HashSet",
+            Self::BTreeMap => "BTreeMap",
+            Self::BTreeSet => "BTreeSet",
+            Self::BinaryHeap => "BinaryHeap",
+            Self::String => "String",
+            Self::Box => "Box",
+            Self::Rc => "Rc",
+            Self::Arc => "Arc",
+            Self::Cell => "Cell",
+            Self::RefCell => "RefCell",
+            Self::Mutex => "Mutex",
+            Self::RwLock => "RwLock",
+            Self::Cow => "Cow",
+            Self::Atomic => "Atomic",
+            Self::Other(name) => name,
+        }
+    }
+}
+
+impl FromStr for KnownStdType {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        // Drop generic parameters: `HashMap<K, V>` -> `HashMap`
+        let path = s.split('<').next().unwrap_or(s);
+        let segments: Vec<&str> = path.split("::").map(str::trim).collect();
+
+        if segments.contains(&"atomic") {
+            return Ok(Self::Atomic);
+        }
+
+        // The type is the last capitalized segment, so methods such as
+        // `Vec::binary_search` resolve to their type
+        let name = segments
+            .iter()
+            .rev()
+            .find(|seg| seg.starts_with(|c: char| c.is_ascii_uppercase()))
+            .copied()
+            .unwrap_or("");
+
+        Ok(match name {
+            "Vec" => Self::Vec,
+            "VecDeque" => Self::VecDeque,
+            "LinkedList" => Self::LinkedList,
+            "HashMap" => Self::HashMap,
+            "HashSet" => Self::HashSet,
+            "BTreeMap" => Self::BTreeMap,
+            "BTreeSet" => Self::BTreeSet,
+            "BinaryHeap" => Self::BinaryHeap,
+            "String" => Self::String,
+            "Box" => Self::Box,
+            "Rc" => Self::Rc,
+            "Arc" => Self::Arc,
+            "Cell" => Self::Cell,
+            "RefCell" => Self::RefCell,
+            "Mutex" => Self::Mutex,
+            "RwLock" => Self::RwLock,
+            "Cow" => Self::Cow,
+            n if n.starts_with("Atomic") => Self::Atomic,
+            _ => Self::Other(s.to_string()),
+        })
+    }
+}
+
+impl From<&str> for KnownStdType {
+    fn from(s: &str) -> Self {
+        let Ok(ty) = s.parse();
+        ty
+    }
+}
+
+impl fmt::Display for KnownStdType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Heuristic {
+    /// The standard library types of this heuristic as typed values
+    pub fn known_std_types(&self) -> Vec<KnownStdType> {
+        self.std_types
+            .iter()
+            .map(|ty| KnownStdType::from(ty.as_str()))
+            .collect()
+    }
+}
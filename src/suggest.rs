@@ -0,0 +1,184 @@
+//! Reading lists tailored to a crate's dependencies.
+//!
+//! [`HeuristicDb::suggest`] reads the dependencies and features of a
+//! `Cargo.toml` and ranks the heuristics that recommend or discuss them:
+//! depending on `lru` points at the caching heuristics, `rayon` at the
+//! data-parallel ones. The manifest is scanned line by line rather than
+//! parsed as TOML, so this works without the `toml` feature.
+
+use crate::{Alternative, Heuristic, HeuristicDb};
+
+/// A heuristic worth reading given a manifest, with the reasons why
+#[derive(Debug, Clone)]
+pub struct Suggestion<'a> {
+    /// The heuristic to read
+    pub heuristic: &'a Heuristic,
+    /// Dependencies the heuristic recommends, then dependencies and
+    /// features it only mentions
+    pub because: Vec<String>,
+    /// Relevance; recommending a dependency counts more than mentioning it
+    pub score: usize,
+}
+
+/// Score for a heuristic recommending a dependency outright
+const RECOMMENDS: usize = 3;
+
+/// Score for a heuristic mentioning a dependency or feature name
+const MENTIONS: usize = 1;
+
+/// Dependency and feature names declared by a `Cargo.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    /// Crates from every dependency table, by their name on crates.io
+    pub dependencies: Vec<String>,
+    /// Features the manifest declares, except `default`
+    pub features: Vec<String>,
+}
+
+impl Manifest {
+    /// Read the dependency tables and `[features]` of a `Cargo.toml`
+    ///
+    /// Covers `[dependencies]`, `[dev-dependencies]`,
+    /// `[build-dependencies]`, their `[target.*]` and `[workspace]`
+    /// variants, and `[dependencies.name]` tables. Renamed dependencies are
+    /// listed under their `package` name.
+    pub fn parse(text: &str) -> Self {
+        let mut manifest = Manifest::default();
+        let mut section = String::new();
+        for line in text.lines() {
+            let line = line.split(" #").next().unwrap_or_default().trim();
+            if line.starts_with('#') || line.is_empty() {
+                continue;
+            }
+            if let Some(header) =
+                line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']'))
+            {
+                section = header.trim().to_string();
+                // `[dependencies.name]` declares one dependency as a table
+                if let Some((table, name)) = section.rsplit_once('.')
+                    && table.ends_with("dependencies")
+                {
+                    manifest.add_dependency(unquote(name));
+                }
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else { continue };
+            let (key, value) = (unquote(key.trim()), value.trim());
+            if section.ends_with("dependencies") {
+                manifest.add_dependency(package_name(value).unwrap_or(key));
+            } else if section == "features" && key != "default" {
+                manifest.features.push(key.to_string());
+            } else if key == "package"
+                && let Some((table, _)) = section.rsplit_once('.')
+                && table.ends_with("dependencies")
+            {
+                // A renamed table dependency: swap the alias for the real name
+                manifest.dependencies.pop();
+                manifest.add_dependency(unquote(value));
+            }
+        }
+        manifest
+    }
+
+    fn add_dependency(&mut self, name: &str) {
+        if !self.dependencies.iter().any(|d| d == name) {
+            self.dependencies.push(name.to_string());
+        }
+    }
+}
+
+/// The `package = "..."` of an inline dependency table
+fn package_name(value: &str) -> Option<&str> {
+    let inline = value.strip_prefix('{')?;
+    inline.split(',').find_map(|field| {
+        let (key, value) = field.split_once('=')?;
+        (key.trim() == "package")
+            .then(|| unquote(value.trim().trim_end_matches('}').trim()))
+    })
+}
+
+fn unquote(text: &str) -> &str {
+    text.trim().trim_matches(['"', '\''])
+}
+
+impl HeuristicDb {
+    /// Heuristics relevant to a manifest's dependencies and features, most
+    /// relevant first
+    ///
+    /// Heuristics recommending a dependency, as a crate or an alternative,
+    /// rank above those that only mention a dependency or feature name.
+    pub fn suggest(&self, manifest: &Manifest) -> Vec<Suggestion<'_>> {
+        let mut suggestions: Vec<Suggestion> = Vec::new();
+        for dependency in &manifest.dependencies {
+            for heuristic in
+                self.heuristics.iter().filter(|h| recommends(h, dependency))
+            {
+                add_reason(
+                    &mut suggestions,
+                    heuristic,
+                    dependency,
+                    RECOMMENDS,
+                );
+            }
+        }
+        for name in manifest.dependencies.iter().chain(&manifest.features) {
+            for heuristic in
+                self.heuristics.iter().filter(|h| mentions(h, name))
+            {
+                add_reason(&mut suggestions, heuristic, name, MENTIONS);
+            }
+        }
+
+        suggestions.sort_by_key(|s| std::cmp::Reverse(s.score));
+        suggestions
+    }
+}
+
+/// Count `name` as a reason to read `heuristic`, once
+fn add_reason<'a>(
+    suggestions: &mut Vec<Suggestion<'a>>,
+    heuristic: &'a Heuristic,
+    name: &str,
+    score: usize,
+) {
+    match suggestions.iter_mut().find(|s| s.heuristic.id == heuristic.id) {
+        Some(suggestion) if suggestion.because.iter().any(|b| b == name) => {}
+        Some(suggestion) => {
+            suggestion.because.push(name.to_string());
+            suggestion.score += score;
+        }
+        None => suggestions.push(Suggestion {
+            heuristic,
+            because: vec![name.to_string()],
+            score,
+        }),
+    }
+}
+
+/// Whether `name` is one of the heuristic's search keywords
+///
+/// Only whole keywords count: a `cli` feature has nothing to do with
+/// `tikv-client`.
+fn mentions(heuristic: &Heuristic, name: &str) -> bool {
+    heuristic
+        .keywords
+        .iter()
+        .chain(&heuristic.body_keywords)
+        .any(|keyword| keyword.eq_ignore_ascii_case(name))
+}
+
+/// Whether `heuristic` lists `name` among its crates or alternatives
+///
+/// Crates.io treats `-` and `_` alike, and so does this.
+fn recommends(heuristic: &Heuristic, name: &str) -> bool {
+    let same = |candidate: &str| {
+        candidate
+            .replace('_', "-")
+            .eq_ignore_ascii_case(&name.replace('_', "-"))
+    };
+    heuristic.crates.iter().any(|c| same(c))
+        || heuristic
+            .alternatives
+            .iter()
+            .any(|a| matches!(a, Alternative::Crate(c) if same(c)))
+}
@@ -0,0 +1,119 @@
+//! Decision-tree navigation over heuristics.
+//!
+//! Heuristics name their parent question with `**Parent:**`, so content
+//! written as "Need key-value lookups?" → "Need fast search on static
+//! data?" can be walked one question at a time instead of searched.
+
+use std::fmt;
+
+use crate::{Heuristic, HeuristicDb};
+
+/// A position in the decision tree
+///
+/// The root is virtual: its children are all heuristics without a parent.
+#[derive(Clone, Copy)]
+pub struct DecisionNode<'a> {
+    db: &'a HeuristicDb,
+    heuristic: Option<&'a Heuristic>,
+}
+
+impl<'a> DecisionNode<'a> {
+    /// The heuristic at this node, or `None` for the root
+    pub fn heuristic(&self) -> Option<&'a Heuristic> {
+        self.heuristic
+    }
+
+    /// Whether this is the virtual root of the tree
+    pub fn is_root(&self) -> bool {
+        self.heuristic.is_none()
+    }
+
+    /// Follow-up questions below this node, in database order
+    pub fn children(&self) -> Vec<DecisionNode<'a>> {
+        let children = match self.heuristic {
+            Some(parent) => self.db.children_of(parent),
+            None => self
+                .db
+                .heuristics
+                .iter()
+                .filter(|h| self.db.parent_of(h).is_none())
+                .collect(),
+        };
+        children
+            .into_iter()
+            .map(|h| DecisionNode { db: self.db, heuristic: Some(h) })
+            .collect()
+    }
+
+    /// Whether this node is a final recommendation
+    pub fn is_leaf(&self) -> bool {
+        self.children().is_empty()
+    }
+
+    /// The node above this one, or `None` at the root
+    pub fn parent(&self) -> Option<DecisionNode<'a>> {
+        let heuristic = self.heuristic?;
+        Some(DecisionNode {
+            db: self.db,
+            heuristic: self.db.parent_of(heuristic),
+        })
+    }
+}
+
+impl fmt::Debug for DecisionNode<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DecisionNode")
+            .field("heuristic", &self.heuristic.map(|h| h.id.as_str()))
+            .finish()
+    }
+}
+
+impl HeuristicDb {
+    /// The virtual root of the decision tree
+    pub fn decision_root(&self) -> DecisionNode<'_> {
+        DecisionNode { db: self, heuristic: None }
+    }
+
+    /// The decision-tree node of a heuristic
+    pub fn decision_node<'a>(
+        &'a self,
+        heuristic: &'a Heuristic,
+    ) -> DecisionNode<'a> {
+        DecisionNode { db: self, heuristic: Some(heuristic) }
+    }
+
+    /// The heuristic named by `heuristic.parent`, if it resolves
+    pub fn parent_of(&self, heuristic: &Heuristic) -> Option<&Heuristic> {
+        let parent = self.resolve(heuristic.parent.as_deref()?)?;
+        (parent.id != heuristic.id).then_some(parent)
+    }
+
+    /// Heuristics whose parent is `heuristic`
+    pub fn children_of(&self, heuristic: &Heuristic) -> Vec<&Heuristic> {
+        self.heuristics
+            .iter()
+            .filter(|h| {
+                self.parent_of(h).is_some_and(|p| p.id == heuristic.id)
+            })
+            .collect()
+    }
+
+    /// Ancestors of a heuristic from the top of the tree down to itself
+    pub fn path_to<'a>(
+        &'a self,
+        heuristic: &'a Heuristic,
+    ) -> Vec<&'a Heuristic> {
+        let mut path = vec![heuristic];
+        let mut current = heuristic;
+        while let Some(parent) = self.parent_of(current) {
+            // Stop at cycles rather than looping forever
+            if path.iter().any(|h| h.id == parent.id) {
+                break;
+            }
+            path.push(parent);
+            current = parent;
+        }
+        path.reverse();
+        path
+    }
+}
@@ -0,0 +1,192 @@
+//! Consistency checks for heuristics and heuristic packs.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    Alternative, Heuristic, HeuristicDb, LoadError, MergePolicy, Provenance,
+    heuristic_files, parse_file,
+};
+
+/// A problem found while validating a heuristic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationIssue {
+    /// No `**Action:**` line was given
+    MissingAction,
+    /// Nothing was indexed for search, so the heuristic can't be found
+    EmptyKeywords,
+    /// The heuristic isn't filed under a usable category
+    UnknownCategory(String),
+    /// A reference to another heuristic doesn't match any heuristic
+    UnresolvedReference(String),
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingAction => write!(f, "missing action"),
+            Self::EmptyKeywords => write!(f, "no searchable keywords"),
+            Self::UnknownCategory(cat) if cat.is_empty() => {
+                write!(f, "no category")
+            }
+            Self::UnknownCategory(cat) => {
+                write!(f, "unknown category '{}'", cat)
+            }
+            Self::UnresolvedReference(target) => {
+                write!(f, "unresolved reference to '{}'", target)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationIssue {}
+
+/// A problem in a heuristic file, found by [`check_files`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileIssue {
+    /// The file with the problem
+    pub path: PathBuf,
+    /// 1-based line number, when the problem is on one line
+    pub line: Option<usize>,
+    /// What is wrong
+    pub message: String,
+}
+
+impl fmt::Display for FileIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => {
+                write!(f, "{}:{}: {}", self.path.display(), line, self.message)
+            }
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+/// Parse the heuristic file at `path`, or every one in a directory, and
+/// report each problem instead of working around it
+///
+/// The issues are those strict parsing rejects, files that don't parse at
+/// all, and ids repeated across files. The heuristics that did parse are
+/// returned too, for [`HeuristicDb::validate`] and link checks; only an
+/// unreadable file or directory is an error.
+pub fn check_files(
+    path: impl AsRef<Path>,
+) -> Result<(HeuristicDb, Vec<FileIssue>), LoadError> {
+    let path = path.as_ref();
+    let files = if path.is_dir() {
+        heuristic_files(path)?
+    } else {
+        vec![path.to_path_buf()]
+    };
+    let mut db = HeuristicDb::new(Vec::new());
+    let mut issues = Vec::new();
+    let mut first_seen: HashMap<String, PathBuf> = HashMap::new();
+    for file in files {
+        let content = std::fs::read_to_string(&file)
+            .map_err(|source| LoadError::Io { path: file.clone(), source })?;
+        let document = match parse_file(&file, &content) {
+            Ok(document) => document,
+            Err(err) => {
+                issues.push(FileIssue {
+                    path: file,
+                    line: None,
+                    message: err.to_string(),
+                });
+                continue;
+            }
+        };
+        issues.extend(document.diagnostics.iter().map(|diagnostic| {
+            FileIssue {
+                path: file.clone(),
+                line: Some(diagnostic.line),
+                message: diagnostic.message.clone(),
+            }
+        }));
+        for heuristic in &document.heuristics {
+            match first_seen.get(&heuristic.id) {
+                Some(first) => issues.push(FileIssue {
+                    path: file.clone(),
+                    line: None,
+                    message: format!(
+                        "duplicate id '{}', also in {}",
+                        heuristic.id,
+                        first.display()
+                    ),
+                }),
+                None => {
+                    first_seen.insert(heuristic.id.clone(), file.clone());
+                }
+            }
+        }
+        let checked = HeuristicDb::from(document)
+            .with_provenance(Provenance::File(file));
+        db = db
+            .merge(checked, MergePolicy::PreferTheirs)
+            .expect("preferring theirs never conflicts");
+    }
+    Ok((db, issues))
+}
+
+impl Heuristic {
+    /// Check the heuristic on its own, without the rest of its pack
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        if self.action.trim().is_empty() {
+            issues.push(ValidationIssue::MissingAction);
+        }
+        if self.keywords.is_empty() && self.body_keywords.is_empty() {
+            issues.push(ValidationIssue::EmptyKeywords);
+        }
+        if self.category_path().is_empty() {
+            issues
+                .push(ValidationIssue::UnknownCategory(self.category.clone()));
+        }
+        issues
+    }
+}
+
+impl HeuristicDb {
+    /// Create a database, rejecting it if any heuristic fails validation
+    pub fn new_strict(
+        heuristics: Vec<Heuristic>,
+    ) -> Result<Self, Vec<(String, ValidationIssue)>> {
+        let db = Self::new(heuristics);
+        let issues = db.validate();
+        if issues.is_empty() { Ok(db) } else { Err(issues) }
+    }
+
+    /// Validate every heuristic, returning issues keyed by heuristic id
+    ///
+    /// On top of [`Heuristic::validate`], this checks that references to
+    /// other heuristics resolve within the database.
+    pub fn validate(&self) -> Vec<(String, ValidationIssue)> {
+        let mut issues = Vec::new();
+        for h in &self.heuristics {
+            issues.extend(
+                h.validate().into_iter().map(|issue| (h.id.clone(), issue)),
+            );
+
+            if let Some(parent) = &h.parent
+                && self.parent_of(h).is_none()
+            {
+                let issue =
+                    ValidationIssue::UnresolvedReference(parent.clone());
+                issues.push((h.id.clone(), issue));
+            }
+
+            for alternative in &h.alternatives {
+                if let Alternative::Heuristic(reference) = alternative
+                    && self.resolve(reference).is_none()
+                {
+                    let issue = ValidationIssue::UnresolvedReference(
+                        reference.clone(),
+                    );
+                    issues.push((h.id.clone(), issue));
+                }
+            }
+        }
+        issues
+    }
+}
@@ -0,0 +1,230 @@
+//! Obsidian vaults for reading a pack alongside personal notes.
+//!
+//! [`to_vault`] writes a note per heuristic and per crate, linked with
+//! `[[wikilinks]]`, so the backlinks pane and graph view show which
+//! heuristics refer to each other and which recommend a crate. Logseq reads
+//! the same notes.
+
+use std::path::PathBuf;
+
+use crate::parser::{Fences, split_prose};
+use crate::site::json_string;
+use crate::{Alternative, Heuristic, category_path, resolve_among, slug};
+
+/// A vault with every heuristic, as paths relative to the vault's root and
+/// their contents
+///
+/// Heuristics are `<id>.md`, since titles hold characters note names can't,
+/// with the title as an alias. Their front matter has the id, category,
+/// tags (the category path as a nested tag among them), level, crates, and
+/// linked heuristics, and their body is the heuristic file form, with
+/// `[...](#id)` links, references, and crate names as wikilinks. Crates
+/// get a note under `crates/` with the pack's descriptions of them.
+pub fn to_vault<'a>(
+    heuristics: impl IntoIterator<Item = &'a Heuristic>,
+) -> Vec<(PathBuf, String)> {
+    let heuristics: Vec<&Heuristic> = heuristics.into_iter().collect();
+    let mut files = Vec::new();
+    // Crates by name as first spelled, with their descriptions
+    let mut crates: Vec<(String, Vec<String>)> = Vec::new();
+
+    for heuristic in &heuristics {
+        let (_, descriptions) = split_prose(&heuristic.content);
+        let offered =
+            heuristic.alternatives.iter().filter_map(|alternative| {
+                match alternative {
+                    Alternative::Crate(name) => Some(name),
+                    _ => None,
+                }
+            });
+        let names: Vec<&String> =
+            heuristic.crates.iter().chain(offered).collect();
+        for &name in &names {
+            let index = match crates
+                .iter()
+                .position(|(other, _)| other.eq_ignore_ascii_case(name))
+            {
+                Some(index) => index,
+                None => {
+                    crates.push((name.clone(), Vec::new()));
+                    crates.len() - 1
+                }
+            };
+            if let Some(description) = descriptions.get(name)
+                && !crates[index].1.contains(description)
+            {
+                crates[index].1.push(description.clone());
+            }
+        }
+
+        let link = |reference: &str| {
+            resolve_among(&heuristics, reference)
+                .map(|other| wikilink(&other.id, &other.title))
+        };
+        let references =
+            heuristic.related.iter().chain(&heuristic.parent).chain(
+                heuristic.alternatives.iter().filter_map(|alternative| {
+                    match alternative {
+                        Alternative::Heuristic(reference) => Some(reference),
+                        _ => None,
+                    }
+                }),
+            );
+        let mut related: Vec<String> = Vec::new();
+        for reference in references {
+            if let Some(other) = resolve_among(&heuristics, reference)
+                && other.id != heuristic.id
+                && !related.contains(&other.id)
+            {
+                related.push(other.id.clone());
+            }
+        }
+
+        let mut front_matter =
+            format!("---\nid: {}\naliases:\n", json_string(&heuristic.id));
+        for alias in
+            std::iter::once(&heuristic.title).chain(&heuristic.aliases)
+        {
+            front_matter.push_str(&format!("  - {}\n", json_string(alias)));
+        }
+        front_matter.push_str(&format!(
+            "category: {}\n",
+            json_string(&heuristic.category)
+        ));
+        let category = category_path(&heuristic.category)
+            .iter()
+            .map(|level| slug(level))
+            .collect::<Vec<_>>()
+            .join("/");
+        let tags: Vec<String> = std::iter::once(category)
+            .chain(heuristic.tags.iter().map(|tag| slug(tag)))
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        yaml_list(
+            &mut front_matter,
+            "tags",
+            tags.iter().map(|tag| json_string(tag)),
+        );
+        if let Some(level) = heuristic.level {
+            front_matter.push_str(&format!("level: {}\n", level));
+        }
+        yaml_list(
+            &mut front_matter,
+            "crates",
+            names.iter().map(|name| json_string(&format!("[[{}]]", name))),
+        );
+        yaml_list(
+            &mut front_matter,
+            "related",
+            related.iter().map(|id| json_string(&format!("[[{}]]", id))),
+        );
+        front_matter.push_str("---\n");
+
+        let block = heuristic.to_markdown_block();
+        let rest = block.split_once('\n').map_or("", |(_, rest)| rest);
+        let rest = rest
+            .strip_prefix("---\n")
+            .and_then(|meta| meta.split_once("---\n"))
+            .map_or(rest, |(_, rest)| rest);
+        let mut body = format!("# {}\n\n", heuristic.title);
+        let mut fences = Fences::default();
+        let mut section = "";
+        for line in rest.lines() {
+            if fences.contains(line) {
+                body.push_str(line);
+                body.push('\n');
+                continue;
+            }
+            let trimmed = line.trim_start();
+            if let Some((label, _)) = trimmed
+                .strip_prefix("- **")
+                .and_then(|rest| rest.split_once(":**"))
+            {
+                section = label;
+            } else if !line.starts_with(char::is_whitespace) {
+                section = "";
+            }
+            if line.starts_with("**Id:** ") {
+                continue;
+            }
+            let mut line = wikilinks(line, &heuristics);
+            if let Some(parent) = line.strip_prefix("**Parent:** ")
+                && let Some(parent) = link(parent)
+            {
+                line = format!("**Parent:** {}", parent);
+            }
+            if matches!(section, "Crates" | "Alternatives") {
+                for name in &names {
+                    line = line.replace(
+                        &format!("`{}`", name),
+                        &format!("[[{}]]", name),
+                    );
+                }
+            }
+            if matches!(section, "Alternatives" | "See also")
+                && let Some(item) = line.strip_prefix("  - ")
+                && let Some(other) = link(item)
+            {
+                line = format!("  - {}", other);
+            }
+            body.push_str(&line);
+            body.push('\n');
+        }
+        files.push((
+            PathBuf::from(format!("{}.md", heuristic.id)),
+            format!("{}{}", front_matter, body),
+        ));
+    }
+
+    for (name, descriptions) in crates {
+        let mut note = format!("---\ntags:\n  - crate\n---\n# {}\n\n", name);
+        for description in descriptions {
+            note.push_str(&format!("{}\n\n", description));
+        }
+        note.push_str(&format!("[docs.rs](https://docs.rs/{})\n", name));
+        files.push((PathBuf::from(format!("crates/{}.md", name)), note));
+    }
+    files
+}
+
+/// A link to the note of heuristic `id`, showing its title
+fn wikilink(id: &str, title: &str) -> String {
+    format!("[[{}|{}]]", id, title)
+}
+
+/// `[text](#id)` links to heuristics in the vault as wikilinks showing
+/// `text`; links to anything else are left alone
+fn wikilinks(line: &str, heuristics: &[&Heuristic]) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find('[') {
+        let after = &rest[start + 1..];
+        if let Some((text, tail)) = after.split_once("](#")
+            && !text.contains(['[', ']'])
+            && let Some((id, tail)) = tail.split_once(')')
+            && heuristics.iter().any(|h| h.id == id)
+        {
+            out.push_str(&rest[..start]);
+            out.push_str(&wikilink(id, text));
+            rest = tail;
+        } else {
+            out.push_str(&rest[..=start]);
+            rest = after;
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Append `key` with `items` as a block list, unless there are none
+fn yaml_list(
+    out: &mut String,
+    key: &str,
+    items: impl Iterator<Item = String>,
+) {
+    let items: Vec<String> =
+        items.map(|item| format!("  - {}\n", item)).collect();
+    if !items.is_empty() {
+        out.push_str(&format!("{}:\n{}", key, items.concat()));
+    }
+}
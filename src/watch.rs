@@ -0,0 +1,119 @@
+//! Reloading a heuristic file when it changes, enabled by the `watch` feature.
+//!
+//! Long-running front ends (a server, a TUI) hold a [`WatchedDb`] and call
+//! [`WatchedDb::current`] per request, so edits to the source file show up
+//! without a restart.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use notify::{RecursiveMode, Watcher};
+
+use crate::{HeuristicDb, LoadError, load_heuristics_from_path};
+
+/// How long to wait for a burst of change events to settle before reloading
+const SETTLE: Duration = Duration::from_millis(50);
+
+/// A database that is reloaded whenever its source file changes
+///
+/// Watching stops when this is dropped.
+pub struct WatchedDb {
+    current: Arc<RwLock<Arc<HeuristicDb>>>,
+    _watcher: notify::RecommendedWatcher,
+}
+
+impl WatchedDb {
+    /// The most recent database that loaded successfully
+    ///
+    /// The returned snapshot stays valid while later reloads swap in new ones.
+    pub fn current(&self) -> Arc<HeuristicDb> {
+        self.current
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+impl std::fmt::Debug for WatchedDb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WatchedDb")
+            .field("heuristics", &self.current().all().len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl HeuristicDb {
+    /// Load a heuristic file and reload it whenever it changes
+    ///
+    /// After each change the file is re-parsed; on success the new database
+    /// replaces the old one in a single swap and is passed to `callback`.
+    /// On failure `callback` gets the error and the last good database stays
+    /// in place, so a half-saved file never empties the database. The initial
+    /// load must succeed.
+    ///
+    /// The file's directory is watched rather than the file itself, so
+    /// editors that save by replacing the file are picked up too.
+    pub fn watch<F>(
+        path: impl AsRef<Path>,
+        mut callback: F,
+    ) -> Result<WatchedDb, LoadError>
+    where
+        F: FnMut(Result<Arc<HeuristicDb>, LoadError>) + Send + 'static,
+    {
+        let path = path.as_ref().to_path_buf();
+        let current =
+            Arc::new(RwLock::new(Arc::new(load_heuristics_from_path(&path)?)));
+
+        let watch_error = |err: notify::Error| LoadError::Io {
+            path: path.clone(),
+            source: std::io::Error::other(err),
+        };
+        let (events, changes) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(events).map_err(watch_error)?;
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .map_err(watch_error)?;
+
+        let shared = Arc::clone(&current);
+        let name = path.file_name().map(|name| name.to_os_string());
+        std::thread::spawn(move || {
+            let touches_file =
+                |event: &notify::Result<notify::Event>| match event {
+                    Ok(event) => {
+                        !event.kind.is_access()
+                            && event.paths.iter().any(|p| {
+                                p.file_name().map(|n| n.to_os_string()) == name
+                            })
+                    }
+                    Err(_) => false,
+                };
+            // The sender lives in the watcher, so this ends once it is dropped
+            while let Ok(event) = changes.recv() {
+                if !touches_file(&event) {
+                    continue;
+                }
+                while changes.recv_timeout(SETTLE).is_ok() {}
+
+                match load_heuristics_from_path(&path) {
+                    Ok(db) => {
+                        let db = Arc::new(db);
+                        *shared.write().unwrap_or_else(|poisoned| {
+                            poisoned.into_inner()
+                        }) = Arc::clone(&db);
+                        callback(Ok(db));
+                    }
+                    Err(err) => callback(Err(err)),
+                }
+            }
+        });
+
+        Ok(WatchedDb { current, _watcher: watcher })
+    }
+}
@@ -0,0 +1,258 @@
+//! Writing databases back out as heuristic markdown.
+//!
+//! [`HeuristicDb::to_markdown`] is the inverse of parsing: fields are
+//! written in one canonical order and format, and the prose the parser only
+//! keeps as content follows them unchanged. Parsing the output yields the
+//! same heuristics, so a pack can be parsed, edited in code, and saved.
+
+use crate::parser::split_prose;
+use crate::{Alternative, DEFAULT_NAMESPACE, Heuristic, HeuristicDb, slug};
+
+impl HeuristicDb {
+    /// Regenerate a heuristic file from the structured data
+    ///
+    /// A file holds a single namespace, so the front matter carries the pack
+    /// metadata and the settings of the first heuristic's namespace.
+    /// Categories are written in database order, each with its description.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let namespace = self
+            .all()
+            .first()
+            .map_or(DEFAULT_NAMESPACE, |h| h.namespace.as_str());
+
+        let mut front_matter = Vec::new();
+        if let Some(pack) = self.pack_info() {
+            front_matter.push(format!("version: {}", pack.version));
+            let optional = [
+                ("pack-name", &pack.name),
+                ("license", &pack.license),
+                ("min-crate-version", &pack.min_crate_version),
+            ];
+            for (key, value) in optional {
+                if let Some(value) = value {
+                    front_matter.push(format!("{}: {}", key, value));
+                }
+            }
+        }
+        if namespace != DEFAULT_NAMESPACE {
+            front_matter.push(format!("namespace: {}", namespace));
+        }
+        if let Some(corpus) = self.corpus(namespace) {
+            for (key, terms) in
+                [("stopwords", &corpus.stopwords), ("boost", &corpus.boost)]
+            {
+                if !terms.is_empty() {
+                    front_matter.push(format!(
+                        "{}: {}",
+                        key,
+                        terms.join(", ")
+                    ));
+                }
+            }
+        }
+        if !front_matter.is_empty() {
+            out.push_str(&format!(
+                "---\n{}\n---\n\n",
+                front_matter.join("\n")
+            ));
+        }
+
+        let mut category = None;
+        for heuristic in self.all() {
+            // Uncategorized heuristics only stay so before the first header
+            if category != Some(&heuristic.category)
+                && !heuristic.category.is_empty()
+            {
+                category = Some(&heuristic.category);
+                out.push_str(&format!("## {}\n\n", heuristic.category));
+                let description =
+                    self.corpus(&heuristic.namespace).and_then(|corpus| {
+                        corpus.category_descriptions.get(&heuristic.category)
+                    });
+                if let Some(description) = description {
+                    out.push_str(&format!("{}\n\n", description));
+                }
+            }
+            out.push_str(&heuristic.to_markdown_block());
+            out.push('\n');
+        }
+
+        out
+    }
+}
+
+impl Heuristic {
+    /// The heuristic as a block of a heuristic file, header included
+    pub(crate) fn to_markdown_block(&self) -> String {
+        let (prose, descriptions) = split_prose(&self.content);
+        let code = |items: &[String]| {
+            items
+                .iter()
+                .map(|i| format!("`{}`", i))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let level =
+            if self.content.starts_with("#### ") { "####" } else { "###" };
+
+        let mut out = format!("{} {}\n", level, self.title);
+        // Tags and level have no label, so they go in a metadata block
+        if !self.tags.is_empty() || self.level.is_some() {
+            out.push_str("---\n");
+            if !self.tags.is_empty() {
+                out.push_str(&format!("tags: {}\n", self.tags.join(", ")));
+            }
+            if let Some(level) = self.level {
+                out.push_str(&format!("level: {}\n", level));
+            }
+            out.push_str("---\n");
+        }
+        if !self.action.is_empty() {
+            out.push_str(&format!("**Action:** {}\n", self.action));
+        }
+        if self.id != slug(&self.title) {
+            out.push_str(&format!("**Id:** {}\n", self.id));
+        }
+        if let Some(parent) = &self.parent {
+            out.push_str(&format!("**Parent:** {}\n", parent));
+        }
+        if !self.aliases.is_empty() {
+            let quoted: Vec<String> =
+                self.aliases.iter().map(|a| format!("\"{}\"", a)).collect();
+            out.push_str(&format!(
+                "**Also asked as:** {}\n",
+                quoted.join(", ")
+            ));
+        }
+        for (label, value) in
+            [("Since", &self.since), ("Updated", &self.updated)]
+        {
+            if let Some(value) = value {
+                out.push_str(&format!("**{}:** {}\n", label, value));
+            }
+        }
+        out.push('\n');
+
+        if !self.std_types.is_empty() {
+            out.push_str(&format!(
+                "- **Std types:** {}\n",
+                code(&self.std_types)
+            ));
+        }
+        if self.crates.iter().any(|name| descriptions.contains_key(name)) {
+            out.push_str("- **Crates:**\n");
+            for name in &self.crates {
+                match descriptions.get(name) {
+                    Some(description) => out.push_str(&format!(
+                        "  - `{}` - {}\n",
+                        name, description
+                    )),
+                    None => out.push_str(&format!("  - `{}`\n", name)),
+                }
+            }
+        } else if !self.crates.is_empty() {
+            out.push_str(&format!("- **Crates:** {}\n", code(&self.crates)));
+        }
+        if let Some(memory) = &self.memory_overhead {
+            out.push_str(&format!("- **Memory:** {}\n", memory.text));
+        }
+        if !self.crate_requirements.is_empty() {
+            let items: Vec<String> = self
+                .crate_requirements
+                .iter()
+                .map(|r| {
+                    let mut item = format!("`{}`", r.name);
+                    if let Some(msrv) = r.msrv {
+                        item.push_str(&format!(" {}", msrv));
+                    }
+                    if let Some(edition) = &r.edition {
+                        item.push_str(&format!(" (edition {})", edition));
+                    }
+                    item
+                })
+                .collect();
+            out.push_str(&format!("- **MSRV:** {}\n", items.join(", ")));
+        }
+        if self.applies_to != Default::default() {
+            let platforms = &self.applies_to;
+            let names: Vec<&str> = [
+                (platforms.no_std, "no_std"),
+                (platforms.wasm, "wasm"),
+                (platforms.embedded, "embedded"),
+                (platforms.threads_required, "threads"),
+            ]
+            .into_iter()
+            .filter_map(|(applies, name)| {
+                (applies == Some(true)).then_some(name)
+            })
+            .collect();
+            let value = if names.is_empty() {
+                "none".to_string()
+            } else {
+                names.join(", ")
+            };
+            out.push_str(&format!("- **Platforms:** {}\n", value));
+        }
+
+        let mut list = |label: &str, items: Vec<String>| {
+            if !items.is_empty() {
+                out.push_str(&format!("- **{}:**\n", label));
+                for item in items {
+                    out.push_str(&format!("  - {}\n", item));
+                }
+            }
+        };
+        list(
+            "Benchmarks",
+            self.benchmarks
+                .iter()
+                .map(|l| format!("[{}]({})", l.text, l.url))
+                .collect(),
+        );
+        let alternatives: Vec<String> = self
+            .alternatives
+            .iter()
+            .map(|a| match a {
+                Alternative::Heuristic(reference) => reference.clone(),
+                Alternative::Crate(name) | Alternative::StdType(name) => {
+                    format!("`{}`", name)
+                }
+            })
+            .collect();
+        // References written as `#id` links elsewhere are found again on parsing
+        let linked = |id: &String| {
+            let anchor = format!("](#{})", id);
+            prose.contains(&anchor)
+                || alternatives.iter().any(|a| a.contains(&anchor))
+        };
+        let related =
+            self.related.iter().filter(|id| !linked(id)).cloned().collect();
+        list("Alternatives", alternatives);
+        list("See also", related);
+        list("Pros", self.tradeoffs.pros.clone());
+        list("Cons", self.tradeoffs.cons.clone());
+        list(
+            "Sources",
+            self.sources
+                .iter()
+                .map(|s| {
+                    if s.title == s.url {
+                        s.url.clone()
+                    } else {
+                        format!("[{}]({})", s.title, s.url)
+                    }
+                })
+                .collect(),
+        );
+
+        if !prose.is_empty() {
+            if !out.ends_with("\n\n") {
+                out.push('\n');
+            }
+            out.push_str(&prose);
+            out.push('\n');
+        }
+        out
+    }
+}
@@ -1,4 +1,26 @@
 use heuristics::*;
+use serde_json::Value;
+use std::process::Command;
+
+/// Keep searches run by tests out of the real search history
+const TEST_DATA_HOME: &str = "XDG_DATA_HOME";
+
+fn test_data_home() -> std::path::PathBuf {
+    std::env::temp_dir().join("heuristics-test-data")
+}
+
+/// Run the CLI with `--format json` and parse its output
+fn cli_json(args: &[&str]) -> Value {
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .env(TEST_DATA_HOME, test_data_home())
+        .args(args)
+        .args(["--format", "json"])
+        .output()
+        .expect("Failed to run heuristics binary");
+    assert!(output.status.success(), "CLI should exit successfully");
+    serde_json::from_slice(&output.stdout)
+        .expect("CLI should print valid JSON")
+}
 
 #[test]
 fn test_load_heuristics_not_empty() {
@@ -17,7 +39,10 @@ fn test_search_basic() {
 fn test_search_multiple_keywords() {
     let db = load_heuristics();
     let results = db.search(&["hashmap", "lookup"]);
-    assert!(!results.is_empty(), "Should find heuristics matching multiple keywords");
+    assert!(
+        !results.is_empty(),
+        "Should find heuristics matching multiple keywords"
+    );
 }
 
 #[test]
@@ -27,8 +52,16 @@ fn test_search_case_insensitive() {
     let results_upper = db.search(&["HashMap"]);
     let results_mixed = db.search(&["HashMAP"]);
 
-    assert_eq!(results_lower.len(), results_upper.len(), "Search should be case-insensitive");
-    assert_eq!(results_lower.len(), results_mixed.len(), "Search should be case-insensitive");
+    assert_eq!(
+        results_lower.len(),
+        results_upper.len(),
+        "Search should be case-insensitive"
+    );
+    assert_eq!(
+        results_lower.len(),
+        results_mixed.len(),
+        "Search should be case-insensitive"
+    );
 }
 
 #[test]
@@ -58,7 +91,11 @@ fn test_by_category_case_insensitive() {
         let results_lower = db.by_category(&first_category.to_lowercase());
         let results_upper = db.by_category(&first_category.to_uppercase());
 
-        assert_eq!(results_lower.len(), results_upper.len(), "Category search should be case-insensitive");
+        assert_eq!(
+            results_lower.len(),
+            results_upper.len(),
+            "Category search should be case-insensitive"
+        );
     }
 }
 
@@ -96,7 +133,10 @@ fn test_search_no_results() {
     let results = db.search(&["zzzzzznonexistent"]);
 
     // Should return empty vector, not panic
-    assert!(results.is_empty(), "Should handle searches with no results gracefully");
+    assert!(
+        results.is_empty(),
+        "Should handle searches with no results gracefully"
+    );
 }
 
 #[test]
@@ -127,5 +167,3612 @@ fn test_all_returns_all_heuristics() {
         total_from_categories += db.by_category(cat).len();
     }
 
-    assert_eq!(all.len(), total_from_categories, "all() should return all heuristics");
+    assert_eq!(
+        all.len(),
+        total_from_categories,
+        "all() should return all heuristics"
+    );
+}
+
+#[test]
+fn test_tradeoffs_parsed() {
+    let db = load_heuristics();
+    let with_tradeoffs: Vec<_> =
+        db.all().iter().filter(|h| !h.tradeoffs.is_empty()).collect();
+
+    assert!(
+        !with_tradeoffs.is_empty(),
+        "Some heuristics should list pros/cons"
+    );
+    for h in with_tradeoffs {
+        assert!(!h.content.is_empty());
+        assert!(
+            h.tradeoffs
+                .pros
+                .iter()
+                .chain(&h.tradeoffs.cons)
+                .all(|item| !item.is_empty())
+        );
+    }
+}
+
+#[test]
+fn test_sample_distinct_and_bounded() {
+    let db = load_heuristics();
+    let picks = db.sample_seeded(5, SampleBias::Uniform, 42);
+    assert_eq!(picks.len(), 5);
+
+    let mut titles: Vec<_> = picks.iter().map(|h| &h.title).collect();
+    titles.sort();
+    titles.dedup();
+    assert_eq!(titles.len(), 5, "Samples should not repeat heuristics");
+
+    let all = db.sample_seeded(
+        db.all().len() + 10,
+        SampleBias::ByCategoryCoverage,
+        7,
+    );
+    assert_eq!(
+        all.len(),
+        db.all().len(),
+        "Cannot sample more than the database holds"
+    );
+}
+
+#[test]
+fn test_sample_seeded_is_deterministic() {
+    let db = load_heuristics();
+    let a = db.sample_seeded(3, SampleBias::Uniform, 1234);
+    let b = db.sample_seeded(3, SampleBias::Uniform, 1234);
+    let titles = |v: &[&Heuristic]| {
+        v.iter().map(|h| h.title.clone()).collect::<Vec<_>>()
+    };
+    assert_eq!(titles(&a), titles(&b));
+}
+
+#[test]
+fn test_sample_under_viewed_prefers_unseen() {
+    let db = load_heuristics();
+    let mut log = UsageLog::new();
+    let (unseen, seen) = db.all().split_last().unwrap();
+    for h in seen {
+        for _ in 0..1000 {
+            log.record(h);
+        }
+    }
+
+    let first_picks = (0..20)
+        .filter(|&seed| {
+            db.sample_seeded(1, SampleBias::UnderViewed(&log), seed)[0].title
+                == unseen.title
+        })
+        .count();
+    assert!(first_picks > 10, "Unseen heuristic should dominate picks");
+
+    let parsed = UsageLog::parse(&log.to_string());
+    assert_eq!(parsed, log, "Usage log should round-trip through text");
+}
+
+// JSON output compatibility: scripts rely on these fields. Changing them
+// requires bumping the schema version.
+
+#[test]
+fn test_json_schema_version() {
+    for args in [
+        &["search", "cache"][..],
+        &["categories"],
+        &["list"],
+        &["category", "Distributed Systems Heuristics"],
+    ] {
+        let doc = cli_json(args);
+        assert_eq!(
+            doc["schema"], 1,
+            "Every JSON document carries the schema version"
+        );
+        assert!(doc["command"].is_string());
+    }
+}
+
+#[test]
+fn test_json_search_fields() {
+    let doc = cli_json(&["search", "cache", "--limit", "2"]);
+    assert!(doc["total"].as_u64().unwrap() >= 1);
+    assert_eq!(doc["query"], serde_json::json!(["cache"]));
+
+    let results = doc["results"].as_array().unwrap();
+    assert!(!results.is_empty() && results.len() <= 2);
+    for h in results {
+        for field in ["title", "action", "category", "namespace", "content"] {
+            assert!(h[field].is_string(), "{} should be a string", field);
+        }
+        for field in ["crates", "std_types", "keywords", "sources"] {
+            assert!(h[field].is_array(), "{} should be an array", field);
+        }
+        assert!(
+            h["tradeoffs"]["pros"].is_array()
+                && h["tradeoffs"]["cons"].is_array()
+        );
+        assert!(h["since"].is_string() || h["since"].is_null());
+    }
+}
+
+#[test]
+fn test_json_categories_and_list() {
+    let cats = cli_json(&["categories"]);
+    assert!(
+        cats["categories"].as_array().unwrap().iter().all(Value::is_string)
+    );
+
+    let list = cli_json(&["list"]);
+    assert_eq!(
+        list["results"].as_array().unwrap().len(),
+        load_heuristics().all().len()
+    );
+}
+
+#[test]
+fn test_minimal_output_is_plain() {
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["--minimal", "categories"])
+        .env("CLICOLOR_FORCE", "1")
+        .output()
+        .expect("Failed to run heuristics binary");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(output.status.success());
+    assert!(
+        !stdout.contains('\x1b'),
+        "Minimal mode should never emit ANSI escapes"
+    );
+    assert!(stdout.is_ascii(), "Minimal mode should print plain ASCII");
+}
+
+#[test]
+fn test_hierarchical_categories() {
+    let mk = |category: &str| Heuristic {
+        title: format!("In {}", category),
+        category: category.into(),
+        ..Default::default()
+    };
+    let db = HeuristicDb::new(vec![
+        mk("Data Structures / Probabilistic"),
+        mk("Data Structures/Trees"),
+        mk("Data Structures Extra"),
+    ]);
+
+    assert_eq!(
+        db.all()[0].category_path(),
+        vec!["Data Structures", "Probabilistic"]
+    );
+    assert_eq!(
+        db.by_category("data structures").len(),
+        2,
+        "Prefix should match whole levels only"
+    );
+    assert_eq!(db.by_category("Data Structures / Trees").len(), 1);
+    assert!(db.by_category("Data").is_empty());
+}
+
+#[test]
+fn test_known_std_type_parsing() {
+    assert_eq!(KnownStdType::from("HashMap"), KnownStdType::HashMap);
+    assert_eq!(
+        KnownStdType::from("std::collections::BTreeMap"),
+        KnownStdType::BTreeMap
+    );
+    assert_eq!(KnownStdType::from("HashMap<K, V>"), KnownStdType::HashMap);
+    assert_eq!(KnownStdType::from("Vec::binary_search"), KnownStdType::Vec);
+    assert_eq!(
+        KnownStdType::from("std::sync::atomic::*"),
+        KnownStdType::Atomic
+    );
+    assert_eq!(
+        KnownStdType::from("SmallVec"),
+        KnownStdType::Other("SmallVec".into())
+    );
+}
+
+#[test]
+fn test_known_std_types_in_db() {
+    let db = load_heuristics();
+    let uses_hashmap = db
+        .all()
+        .iter()
+        .filter(|h| h.known_std_types().contains(&KnownStdType::HashMap))
+        .count();
+    assert!(uses_hashmap > 0, "Some heuristics should recommend HashMap");
+}
+
+#[test]
+fn test_cli_platform_filter() {
+    let all = cli_json(&["list"])["results"].as_array().unwrap().len();
+    let no_std =
+        cli_json(&["list", "--no-std"])["results"].as_array().unwrap().len();
+    assert!(no_std < all, "--no-std should hide heuristics that need threads");
+    assert!(no_std > 0, "Unannotated heuristics should still be shown");
+}
+
+#[test]
+fn test_links_extracted_and_doctor_clean() {
+    let db = load_heuristics();
+    assert!(
+        db.all().iter().any(|h| !h.links.is_empty()),
+        "Some heuristics should link to references"
+    );
+
+    let doctor = cli_json(&["doctor"]);
+    assert_eq!(
+        doctor["issues"],
+        serde_json::json!([]),
+        "Built-in links should be well-formed"
+    );
+}
+
+#[test]
+fn test_snapshot_rebuilds_db() {
+    let db = load_heuristics();
+    let rebuilt = HeuristicDb::from(db.snapshot());
+    assert_eq!(rebuilt.all().len(), db.all().len());
+    assert_eq!(rebuilt.search(&["cache"]).len(), db.search(&["cache"]).len());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_round_trip() {
+    let db = load_heuristics();
+    let json = serde_json::to_string(&db.snapshot()).unwrap();
+    let snapshot: DbSnapshot = serde_json::from_str(&json).unwrap();
+    let rebuilt = HeuristicDb::from(snapshot);
+
+    assert_eq!(rebuilt.all().len(), db.all().len());
+    assert_eq!(rebuilt.all()[0].title, db.all()[0].title);
+    assert_eq!(rebuilt.all()[0].tradeoffs, db.all()[0].tradeoffs);
+
+    let partial: Heuristic =
+        serde_json::from_str(r#"{"title": "Need X?"}"#).unwrap();
+    assert_eq!(partial.title, "Need X?", "Missing fields should default");
+}
+
+#[test]
+fn test_render_styles() {
+    let db = load_heuristics();
+    let h = &db.all()[0];
+
+    let plain = h.render(RenderStyle::Plain);
+    assert!(plain.starts_with(&h.title));
+    assert!(plain.contains(&format!("Action: {}", h.action)));
+    assert!(!plain.contains('\x1b'));
+    assert_eq!(
+        h.to_string(),
+        plain.trim_end(),
+        "Display should match plain rendering"
+    );
+
+    let ansi = h.render(RenderStyle::Ansi);
+    assert!(ansi.contains("\x1b["), "ANSI rendering should be colored");
+
+    let markdown = h.render(RenderStyle::Markdown);
+    assert!(markdown.starts_with(&format!("### {}", h.title)));
+    assert!(markdown.contains(&format!("**Action:** {}", h.action)));
+}
+
+#[test]
+fn test_ids_unique_and_stable() {
+    use std::collections::HashSet;
+
+    let db = load_heuristics();
+    let ids: HashSet<&str> = db.all().iter().map(|h| h.id.as_str()).collect();
+    assert_eq!(ids.len(), db.all().len(), "Ids should be unique");
+
+    let first = &db.all()[0];
+    assert_eq!(first.id, slug(&first.title));
+    assert_eq!(db.get(&first.id).map(|h| &h.title), Some(&first.title));
+
+    // Identity ignores everything but the id
+    let mut edited = first.clone();
+    edited.action = "Something else".into();
+    assert_eq!(&edited, first);
+
+    let set: HashSet<Heuristic> =
+        db.all().iter().cloned().chain(db.all().iter().cloned()).collect();
+    assert_eq!(
+        set.len(),
+        db.all().len(),
+        "Duplicates should collapse in sets"
+    );
+
+    let mut sorted = db.all().to_vec();
+    sorted.sort();
+    assert!(sorted.windows(2).all(|w| w[0].id < w[1].id));
+
+    // A repeated title skips suffixes other heuristics declare
+    let md = "## Cat\n### Need foo?\n**Action:** A.\n### Need foo?\n**Action:** B.\n### Other\n**Action:** C.\n**Id:** need-foo-2\n";
+    let db = HeuristicDb::from_markdown(md).unwrap();
+    let ids: Vec<&str> = db.all().iter().map(|h| h.id.as_str()).collect();
+    assert_eq!(ids, ["need-foo", "need-foo-3", "need-foo-2"]);
+}
+
+#[test]
+fn test_validate() {
+    let db = load_heuristics();
+    assert!(db.validate().is_empty(), "Built-in heuristics should validate");
+
+    let bare = Heuristic { title: "Need X?".into(), ..Default::default() };
+    let issues = bare.validate();
+    assert!(issues.contains(&ValidationIssue::MissingAction));
+    assert!(issues.contains(&ValidationIssue::EmptyKeywords));
+    assert!(issues.contains(&ValidationIssue::UnknownCategory(String::new())));
+
+    let errors = HeuristicDb::new_strict(vec![bare])
+        .err()
+        .expect("Strict mode should reject invalid heuristics");
+    assert_eq!(errors[0].0, "need-x");
+    assert!(HeuristicDb::new_strict(db.all().to_vec()).is_ok());
+}
+
+#[test]
+fn test_alternatives_resolve() {
+    let db = load_heuristics();
+    let lookups = db
+        .get("need-o-1-average-case-lookups-or-inserts")
+        .expect("HashMap heuristic should exist");
+    assert!(!lookups.alternatives.is_empty());
+
+    let alternatives = db.alternatives_of(lookups);
+    assert!(
+        alternatives.iter().any(
+            |h| h.id == "need-fast-search-on-static-or-mostly-static-data"
+        )
+    );
+    assert!(
+        alternatives.iter().all(|h| h.id != lookups.id),
+        "A heuristic is not its own alternative"
+    );
+
+    let mut broken = lookups.clone();
+    broken.alternatives =
+        vec![Alternative::Heuristic("no-such-heuristic".into())];
+    let db = HeuristicDb::new(vec![broken]);
+    assert!(db.validate().iter().any(|(_, issue)| matches!(issue, ValidationIssue::UnresolvedReference(r) if r == "no-such-heuristic")));
+}
+
+#[test]
+fn test_tables_parsed() {
+    let db = load_heuristics();
+    let table = db
+        .all()
+        .iter()
+        .flat_map(|h| &h.tables)
+        .next()
+        .expect("base.md should contain a table");
+    assert!(!table.headers.is_empty());
+    assert!(table.rows.iter().all(|row| row.len() == table.headers.len()));
+}
+
+#[test]
+fn test_memory_overhead_sorting() {
+    let db = load_heuristics();
+    let annotated = db.by_memory_overhead();
+    assert!(
+        !annotated.is_empty(),
+        "Some heuristics should annotate memory overhead"
+    );
+    assert!(
+        annotated
+            .windows(2)
+            .all(|w| w[0].memory_overhead <= w[1].memory_overhead)
+    );
+}
+
+#[test]
+fn test_decision_tree() {
+    let db = load_heuristics();
+    let root = db.decision_root();
+    assert!(root.is_root());
+
+    let lookups = root
+        .children()
+        .into_iter()
+        .find(|n| {
+            n.heuristic().is_some_and(|h| h.id == "need-key-value-lookups")
+        })
+        .expect("Top-level question should be a child of the root");
+    assert!(!lookups.is_leaf());
+
+    let child = lookups.children()[0];
+    assert!(
+        child
+            .parent()
+            .and_then(|p| p.heuristic())
+            .is_some_and(|p| p.id == "need-key-value-lookups")
+    );
+    assert!(
+        root.children().iter().all(|n| n
+            .heuristic()
+            .unwrap()
+            .parent
+            .is_none())
+    );
+
+    let path: Vec<_> = db
+        .path_to(child.heuristic().unwrap())
+        .iter()
+        .map(|h| h.id.as_str())
+        .collect();
+    assert_eq!(path.first(), Some(&"need-key-value-lookups"));
+    assert_eq!(path.len(), 2);
+}
+
+#[test]
+fn test_cli_msrv_filter() {
+    let cache = "need-to-cache-expensive-results";
+    let ids = |output: Value| -> Vec<String> {
+        output["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|h| h["id"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let old = ids(cli_json(&["--msrv", "1.60", "list"]));
+    assert!(
+        !old.iter().any(|id| id == cache),
+        "Crates needing Rust 1.70 should be hidden"
+    );
+    let new = ids(cli_json(&["--msrv", "1.80", "list"]));
+    assert!(new.iter().any(|id| id == cache));
+
+    let cached = cli_json(&["search", "cache"]);
+    let requirements = &cached["results"][0]["crate_requirements"];
+    assert_eq!(requirements[0]["msrv"], "1.70");
+}
+
+#[test]
+fn test_cli_benchmarks_flag() {
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["--minimal", "search", "cache", "--limit", "1", "--benchmarks"])
+        .output()
+        .expect("Failed to run heuristics binary");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Benchmarks:"));
+    assert!(stdout.contains("https://github.com/moka-rs/mokabench"));
+
+    let cached = cli_json(&["search", "cache"]);
+    assert_eq!(
+        cached["results"][0]["benchmarks"][0]["url"],
+        "https://github.com/moka-rs/mokabench"
+    );
+}
+
+#[test]
+fn test_render_markdown_benchmark_footnotes() {
+    let db = load_heuristics();
+    let hashmap = db.get("need-o-1-average-case-lookups-or-inserts").unwrap();
+    let markdown = hashmap.render(RenderStyle::Markdown);
+    assert!(markdown.contains("- **Benchmarks:** [^1]"));
+    assert!(markdown.contains("[^1]: [hashbrown performance comparison](https://github.com/rust-lang/hashbrown#performance)"));
+}
+
+#[test]
+fn test_category_info() {
+    let db = load_heuristics();
+    let info = db.category_info();
+    assert_eq!(info.len(), db.categories().len());
+    assert_eq!(
+        info.iter().map(|c| c.heuristic_count).sum::<usize>(),
+        db.all().len()
+    );
+
+    let concurrency = info
+        .iter()
+        .find(|c| c.name == "Concurrency & Lock-Free Heuristics")
+        .unwrap();
+    assert!(concurrency.description.contains("threads"));
+
+    let output = cli_json(&["categories"]);
+    assert_eq!(output["details"][0]["name"], output["categories"][0]);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["--minimal", "categories", "--verbose"])
+        .output()
+        .expect("Failed to run heuristics binary");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains(&concurrency.description)
+            && stdout.contains(&format!("({})", concurrency.heuristic_count))
+    );
+    assert!(output.stderr.is_empty(), "Details shouldn't turn on logging");
+}
+
+#[test]
+fn test_search_by_alias() {
+    let db = load_heuristics();
+    let results = db.search(&["memoize"]);
+    assert_eq!(results[0].id, "need-to-cache-expensive-results");
+    assert!(
+        results[0].aliases.iter().any(|a| a == "How do I memoize results?")
+    );
+}
+
+#[test]
+fn test_load_heuristics_from_path() {
+    let path = std::env::temp_dir()
+        .join(format!("heuristics-load-{}.md", std::process::id()));
+    std::fs::write(&path, "---\nnamespace: team\n---\n## Team\n### Need a team cache?\n**Action:** Use the shared cache.\n").unwrap();
+    let db = load_heuristics_from_path(&path).expect("File should load");
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(db.all().len(), 1);
+    assert_eq!(db.all()[0].namespace, "team");
+
+    let missing = load_heuristics_from_path(path.with_extension("missing"));
+    assert!(matches!(missing, Err(LoadError::Io { .. })));
+}
+
+#[test]
+fn test_from_markdown_matches_embedded_pack() {
+    let db = HeuristicDb::from_markdown(include_str!("../base.md"))
+        .expect("Embedded pack should parse");
+    assert_eq!(db.all().len(), load_heuristics().all().len());
+}
+
+#[test]
+fn test_load_heuristics_from_dir() {
+    let dir = std::env::temp_dir()
+        .join(format!("heuristics-dir-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a-caching.md"), "## Caching\n\nKeeping results.\n\n### Need a cache?\n**Action:** Cache it.\n").unwrap();
+    std::fs::write(
+        dir.join("b-storage.md"),
+        "## Storage\n### Need a cache?\n**Action:** Cache on disk.\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("c-broken.md"), "---\nnamespace: x\n").unwrap();
+    std::fs::write(dir.join("notes.txt"), "### Ignored?\n").unwrap();
+
+    let (db, errors) =
+        load_heuristics_from_dir(&dir).expect("Directory should load");
+    std::fs::remove_dir_all(&dir).ok();
+
+    let ids: Vec<&str> = db.all().iter().map(|h| h.id.as_str()).collect();
+    assert_eq!(ids, vec!["need-a-cache", "need-a-cache-2"]);
+    assert_eq!(db.category_info()[0].description, "Keeping results.");
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].to_string().contains("c-broken.md"));
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn test_from_toml() {
+    let toml = r#"
+namespace = "team"
+
+[categories]
+"Caching" = "Keeping results around."
+
+[[heuristic]]
+title = "Need a team cache?"
+action = "Use `moka`."
+category = "Caching"
+crates = ["moka"]
+keywords = ["memoize"]
+alternatives = ["`lru`"]
+msrv = ["`moka` 1.70"]
+"#;
+    let db = HeuristicDb::from_toml(toml).expect("Definition should load");
+    let h = &db.all()[0];
+    assert_eq!(h.id, "need-a-team-cache");
+    assert_eq!(h.namespace, "team");
+    assert_eq!(h.alternatives, vec![Alternative::Crate("lru".to_string())]);
+    assert!(matches!(h.structured_action(), Action::UseCrate { .. }));
+    assert_eq!(db.search(&["memoize"]).len(), 1);
+    assert_eq!(db.category_info()[0].description, "Keeping results around.");
+
+    let repeated = HeuristicDb::from_toml("[[heuristic]]\ntitle = \"Need x?\"\n\n[[heuristic]]\ntitle = \"Need x?\"\n").unwrap();
+    let ids: Vec<&str> =
+        repeated.all().iter().map(|h| h.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        ["need-x", "need-x-2"],
+        "A repeated title gets a suffix, as in markdown"
+    );
+
+    let unknown = HeuristicDb::from_toml(
+        "[[heuristic]]\ntitle = \"X\"\nflavor = \"y\"\n",
+    );
+    assert!(matches!(unknown, Err(ParseError::InvalidDefinition(_))));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_from_json_round_trip() {
+    let db = load_heuristics();
+    let json = serde_json::to_string(&db.snapshot()).unwrap();
+
+    let restored =
+        HeuristicDb::from_json(&json).expect("Snapshot should load");
+    assert_eq!(restored.all(), db.all());
+    assert_eq!(restored.search(&["cache"])[0].id, db.search(&["cache"])[0].id);
+
+    let from_reader = HeuristicDb::from_reader(json.as_bytes())
+        .expect("Snapshot should load");
+    assert_eq!(from_reader.category_info(), db.category_info());
+
+    assert!(matches!(
+        HeuristicDb::from_json("{\"heuristics\": 1}"),
+        Err(ParseError::InvalidDefinition(_))
+    ));
+    let error = HeuristicDb::from_reader(&b"not json"[..]).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_to_json_document() {
+    let db = HeuristicDb::from_markdown("---\npack-name: Team pack\nnamespace: team\nboost: queue\n---\n## Team\n### Need a team queue?\n**Action:** Use the team queue.\n").unwrap();
+    let json = db.to_json();
+    let document: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(document["schema"], SNAPSHOT_SCHEMA_VERSION);
+    assert_eq!(document["pack"]["name"], "Team pack");
+    assert_eq!(document["heuristics"][0]["id"], "need-a-team-queue");
+    assert_eq!(document["corpora"][0]["namespace"], "team");
+
+    let mut written = Vec::new();
+    db.to_writer_json(&mut written).unwrap();
+    assert_eq!(written, json.as_bytes());
+
+    let restored =
+        HeuristicDb::from_json(&json).expect("Document should load");
+    assert_eq!(restored.all(), db.all());
+    assert_eq!(restored.pack_info(), db.pack_info());
+    assert_eq!(restored.corpus("team"), db.corpus("team"));
+
+    let newer = json.replacen("\"schema\":1", "\"schema\":2", 1);
+    assert!(matches!(
+        HeuristicDb::from_json(&newer),
+        Err(ParseError::UnsupportedVersion { found: 2, supported: 1 })
+    ));
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn test_to_yaml() {
+    let db = HeuristicDb::from_markdown("---\npack-name: Team pack\n---\n## Team\n### Need a team cache?\n**Action:** Use the team cache.\n\n**Alternatives:** `lru`\n").unwrap();
+    let heuristic = db.all()[0].to_yaml();
+    assert!(heuristic.starts_with("id: need-a-team-cache\n"), "{}", heuristic);
+    assert!(
+        heuristic.contains("alternatives:\n- Crate: lru\n"),
+        "{}",
+        heuristic
+    );
+
+    let yaml = db.to_yaml();
+    assert!(yaml.starts_with("schema: 1\npack:\n"), "{}", yaml);
+    assert!(yaml.contains("  name: Team pack\n"), "{}", yaml);
+    assert!(yaml.contains("- id: need-a-team-cache\n"), "{}", yaml);
+}
+
+#[test]
+fn test_merge_policies() {
+    let team = "---\nnamespace: team\nboost: team\n---\n## Team\n### Need to cache expensive results?\n**Action:** Use the team cache.\n\n### Need a team queue?\n**Action:** Use the team queue.\n";
+    let builtin_len = load_heuristics().all().len();
+
+    let ours = load_heuristics()
+        .merge(
+            HeuristicDb::from_markdown(team).unwrap(),
+            MergePolicy::PreferOurs,
+        )
+        .unwrap();
+    assert_eq!(ours.all().len(), builtin_len + 1);
+    assert!(
+        ours.get("need-to-cache-expensive-results")
+            .unwrap()
+            .action
+            .starts_with("Always add")
+    );
+    assert!(ours.corpus("team").is_some());
+
+    let theirs = load_heuristics()
+        .merge(
+            HeuristicDb::from_markdown(team).unwrap(),
+            MergePolicy::PreferTheirs,
+        )
+        .unwrap();
+    assert_eq!(
+        theirs.get("need-to-cache-expensive-results").unwrap().action,
+        "Use the team cache."
+    );
+    assert!(
+        theirs.search(&["team"]).iter().any(|h| h.id == "need-a-team-queue"),
+        "Merged heuristics are indexed"
+    );
+
+    let conflict = load_heuristics()
+        .merge(HeuristicDb::from_markdown(team).unwrap(), MergePolicy::Error);
+    assert_eq!(
+        conflict.err(),
+        Some(MergeError::DuplicateId(
+            "need-to-cache-expensive-results".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_merge_near_duplicates() {
+    let team = "---\nnamespace: team\n---\n## Team\n### Need to cache expensive results!\n**Action:** Use the team cache.\n**Id:** team-cache\n- **Crates:** `team-cache`\n\n### Need tamper-proof sync between nodes?\n**Action:** Merkle tree over distributed nodes.\n- **Crates:** `merkle`, `rs-merkle`, `merkle-tree-stream`\n\n### Need a team queue?\n**Action:** Use the team queue.\n";
+    let builtin = load_heuristics();
+    let pack = HeuristicDb::from_markdown(team).unwrap();
+
+    let duplicates = builtin.near_duplicates(&pack);
+    let pairs: Vec<(&str, &str)> = duplicates
+        .iter()
+        .map(|d| (d.ours.as_str(), d.theirs.as_str()))
+        .collect();
+    assert!(
+        pairs.contains(&("need-to-cache-expensive-results", "team-cache"))
+    );
+    assert!(pairs.contains(&(
+        "need-tamper-detection-efficient-sync-between-nodes",
+        "need-tamper-proof-sync-between-nodes"
+    )));
+    assert!(!pairs.iter().any(|(_, theirs)| *theirs == "need-a-team-queue"));
+    assert_eq!(duplicates[0].similarity, Similarity::SameTitle);
+
+    let combined =
+        load_heuristics().merge(pack, MergePolicy::Combine).unwrap();
+    assert_eq!(
+        combined.all().len(),
+        builtin.all().len() + 1,
+        "Only the queue is new advice"
+    );
+    let cache = combined.get("need-to-cache-expensive-results").unwrap();
+    assert!(
+        cache.action.starts_with("Always add"),
+        "The existing text is kept"
+    );
+    assert!(
+        cache.crates.contains(&"team-cache".to_string()),
+        "Metadata is merged in"
+    );
+}
+
+#[test]
+fn test_overlay_patches_heuristics() {
+    let overlay = "## Overlay\n### need-to-cache-expensive-results\n**Action:** Use our `cachekit` wrapper.\n- **Crates:** `cachekit`\n\nWe standardized on cachekit; see the platform wiki.\n\n### Need O(1) average-case lookups or inserts?\n- **Cons:**\n  - Our profiler flags hashing of large keys\n";
+    let path = std::env::temp_dir()
+        .join(format!("heuristics-overlay-{}.md", std::process::id()));
+    std::fs::write(&path, overlay).unwrap();
+    let builtin = load_heuristics();
+    let db =
+        load_heuristics().with_overlay(&path).expect("Overlay should apply");
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(
+        db.all().len(),
+        builtin.all().len(),
+        "Overlays never add heuristics"
+    );
+    let cache = db.get("need-to-cache-expensive-results").unwrap();
+    assert_eq!(cache.action, "Use our `cachekit` wrapper.");
+    assert!(
+        cache.crates.contains(&"moka".to_string())
+            && cache.crates.contains(&"cachekit".to_string())
+    );
+    assert!(
+        cache.content.contains("**Action:** Use our `cachekit` wrapper.")
+            && cache.content.ends_with("see the platform wiki.")
+    );
+    assert_eq!(
+        cache.category,
+        builtin.get("need-to-cache-expensive-results").unwrap().category
+    );
+    assert!(
+        db.search(&["cachekit"]).iter().any(|h| h.id == cache.id),
+        "Patched heuristics are reindexed"
+    );
+
+    let hash = db.get("need-o-1-average-case-lookups-or-inserts").unwrap();
+    assert_eq!(
+        hash.tradeoffs.cons.last().map(String::as_str),
+        Some("Our profiler flags hashing of large keys")
+    );
+
+    let unknown = load_heuristics()
+        .apply_overlay("### no-such-heuristic\n**Action:** Nothing.\n");
+    assert_eq!(
+        unknown.err(),
+        Some(ParseError::UnknownHeuristic("no-such-heuristic".to_string()))
+    );
+}
+
+#[test]
+fn test_extras_layer_over_builtin() {
+    let extra = "## Team\n### Need to cache expensive results?\n**Action:** Use our `cachekit` wrapper.\n\n### Need to cache team builds?\n**Action:** Use the shared build cache.\n";
+    let path = std::env::temp_dir()
+        .join(format!("heuristics-extra-{}.md", std::process::id()));
+    std::fs::write(&path, extra).unwrap();
+    let db =
+        HeuristicDb::load_default_with_extras(std::slice::from_ref(&path))
+            .expect("Extras should load");
+    let json = cli_json(&[
+        "search",
+        "cache",
+        "--limit",
+        "20",
+        "--extra",
+        path.to_str().unwrap(),
+    ]);
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(
+        db.all().len(),
+        load_heuristics().all().len() + 1,
+        "Matching ids replace built-in heuristics"
+    );
+    let cache = db.get("need-to-cache-expensive-results").unwrap();
+    assert_eq!(cache.action, "Use our `cachekit` wrapper.");
+    assert_eq!(cache.provenance, Provenance::File(path.clone()));
+    assert_eq!(
+        db.get("need-o-1-average-case-lookups-or-inserts").unwrap().provenance,
+        Provenance::Builtin
+    );
+
+    let results = json["results"].as_array().unwrap();
+    let team = results
+        .iter()
+        .find(|h| h["id"] == "need-to-cache-team-builds")
+        .expect("Extras should be searchable");
+    assert_eq!(team["provenance"], path.to_str().unwrap());
+
+    let missing = HeuristicDb::load_default_with_extras(&[path]);
+    assert!(
+        matches!(missing, Err(LoadError::Io { .. })),
+        "A missing extra fails the load"
+    );
+}
+
+#[test]
+fn test_body_keywords_rank_below_headline() {
+    let markdown = "## Storage\n### Need to cache lookups?\n**Action:** Use `moka`.\n\n### Need durable writes?\n**Action:** Write ahead.\n\nFor read-heavy paths, put a cache in front of the store.\n";
+    let db = HeuristicDb::from_markdown(markdown).unwrap();
+    let durable = db.get("need-durable-writes").unwrap();
+    assert!(
+        durable.body_keywords.contains(&"cache".to_string()),
+        "Body advice should be extracted"
+    );
+    assert!(!durable.keywords.contains(&"cache".to_string()));
+
+    let ids: Vec<&str> =
+        db.search(&["cache"]).iter().map(|h| h.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        ["need-to-cache-lookups", "need-durable-writes"],
+        "Headline matches rank first"
+    );
+}
+
+#[test]
+fn test_see_also_resolves_to_ids() {
+    let markdown = "## Caching\n### Need to cache lookups?\n**Action:** Use `moka`.\n- **See also:** Need cache eviction?, `no-such-cache`\n\nTo bound it, see [eviction](#need-cache-eviction) or [the lookup itself](#need-to-cache-lookups).\n\n### Need cache eviction?\n**Action:** Use an LRU cache.\n- **See also:**\n  - [Caching lookups](#need-to-cache-lookups)\n";
+    let document = parse_markdown(markdown).unwrap();
+    let messages: Vec<String> =
+        document.diagnostics.iter().map(|d| d.to_string()).collect();
+    assert_eq!(
+        messages,
+        [
+            "line 2: 'Need to cache lookups?' refers to unknown heuristic 'no-such-cache'"
+        ]
+    );
+
+    let db = HeuristicDb::from(document);
+    let lookups = db.get("need-to-cache-lookups").unwrap();
+    assert_eq!(
+        lookups.related,
+        ["need-cache-eviction"],
+        "Titles and anchors resolve once; self-links are dropped"
+    );
+    let eviction = db.get("need-cache-eviction").unwrap();
+    let related: Vec<&str> =
+        db.related_to(eviction).iter().map(|h| h.id.as_str()).collect();
+    assert_eq!(related, ["need-to-cache-lookups"]);
+
+    let reloaded = HeuristicDb::from_markdown(&db.to_markdown()).unwrap();
+    assert_eq!(
+        reloaded.get("need-cache-eviction").unwrap().related,
+        eviction.related
+    );
+    assert_eq!(
+        reloaded.get("need-to-cache-lookups").unwrap().related,
+        lookups.related
+    );
+}
+
+#[test]
+fn test_import_foreign_formats() {
+    let tldr = "# tar\n\n> Archiving utility.\n> More information: <https://www.gnu.org/software/tar>.\n\n- E[x]tract an archive [f]ile:\n\n`tar xf {{path/to/source.tar}}`\n";
+    let db = HeuristicDb::from_source(&TldrSource, tldr).unwrap();
+    let extract = db.get("extract-an-archive-file").unwrap();
+    assert_eq!(
+        (extract.category.as_str(), extract.action.as_str()),
+        ("tar", "`tar xf {{path/to/source.tar}}`")
+    );
+    assert_eq!(extract.sources[0].url, "https://www.gnu.org/software/tar");
+
+    let cheat = "---\nsyntax: bash\n---\n# To list branches:\ngit branch\n\n# To set your identity:\ngit config user.name \"Jo\"\ngit config user.email jo@example.com\n";
+    let db =
+        HeuristicDb::from_source(&CheatSource::new("git"), cheat).unwrap();
+    let titles: Vec<&str> =
+        db.all().iter().map(|h| h.title.as_str()).collect();
+    assert_eq!(titles, ["To list branches", "To set your identity"]);
+    assert!(
+        db.all()[1].content.contains("git config user.email jo@example.com"),
+        "Multi-line snippets are kept"
+    );
+
+    let notes = "# Caching\n\n## Memoize pure functions\nCache results keyed by the arguments.\n\nInvalidate on deploy.\n\n## Evict\n```rust\n# not a heading\n```\n";
+    let path = std::env::temp_dir()
+        .join(format!("heuristics-notes-{}.md", std::process::id()));
+    std::fs::write(&path, notes).unwrap();
+    let json =
+        cli_json(&["import", "--from", "notes", path.to_str().unwrap()]);
+    std::fs::remove_file(&path).ok();
+    let results = json["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2, "Fenced lines are never headings");
+    assert_eq!(results[0]["category"], "Caching");
+    assert_eq!(results[0]["action"], "Cache results keyed by the arguments.");
+    let reparsed =
+        HeuristicDb::from_markdown(json["markdown"].as_str().unwrap())
+            .unwrap();
+    assert!(
+        reparsed
+            .get("memoize-pure-functions")
+            .unwrap()
+            .content
+            .ends_with("Invalidate on deploy.")
+    );
+}
+
+#[test]
+fn test_import_exports() {
+    let md = "## Maps\n### Need a map, fast?\n---\ntags: hashing\nlevel: beginner\n---\n**Action:** Use `HashMap`.\n**Id:** fast-map\n**Since:** 0.2.0\n\
+              - **Crates:** `hashbrown`, `ahash`\n- **Pros:**\n  - Fast\n- **Sources:**\n  - https://docs.rs/hashbrown\n";
+    let db = HeuristicDb::from_markdown(md).unwrap();
+    let imported = HeuristicDb::from_source(
+        &CsvSource::new("Imported"),
+        &to_csv(db.all()),
+    )
+    .unwrap();
+    let (original, map) = (&db.all()[0], &imported.all()[0]);
+    assert_eq!(
+        (map.id.as_str(), map.title.as_str(), map.category.as_str()),
+        ("fast-map", "Need a map, fast?", "Maps")
+    );
+    assert_eq!(
+        (&map.crates, &map.tags, map.level),
+        (&original.crates, &original.tags, original.level)
+    );
+    assert_eq!(
+        (&map.tradeoffs, &map.sources, &map.since),
+        (&original.tradeoffs, &original.sources, &original.since)
+    );
+    assert!(
+        !map.keywords.is_empty(),
+        "Parsing fills in keywords: {:?}",
+        map.keywords
+    );
+
+    // Columns go by name, and quoted fields may hold separators and line breaks
+    let csv = "notes,action,title\r\n\"a\nb\",\"Use it, or don't\",\"Need \"\"quotes\"\"?\"\r\n\r\n,,\r\n";
+    let db =
+        HeuristicDb::from_source(&CsvSource::new("Imported"), csv).unwrap();
+    assert_eq!(db.all().len(), 1, "Blank rows are skipped");
+    assert_eq!(
+        (db.all()[0].title.as_str(), db.all()[0].action.as_str()),
+        ("Need \"quotes\"?", "Use it, or don't")
+    );
+    assert!(
+        HeuristicDb::from_source(
+            &CsvSource::new("Imported"),
+            "title,action\n,Orphan\n"
+        )
+        .is_err()
+    );
+    let db = HeuristicDb::from_source(
+        &CsvSource::new("Imported"),
+        "title,category\nNeed a set?,Sets\nNeed a bag?,\n",
+    )
+    .unwrap();
+    assert_eq!(
+        db.get("need-a-bag").unwrap().category,
+        "Imported",
+        "Rows without a category don't join the row before"
+    );
+
+    let dir = std::env::temp_dir()
+        .join(format!("heuristics-import-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (csv, pack) = (dir.join("export.csv"), dir.join("pack.md"));
+    std::fs::write(&csv, "id,title,category,action\nneed-a-set,Need a set?,Sets,Use `BTreeSet`.\n").unwrap();
+    std::fs::write(&pack, "## Sets\n### Need a set?\n**Action:** Use a `Vec`.\n### Need a bag?\n**Action:** Count.\n").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args([
+            "import",
+            csv.to_str().unwrap(),
+            "--into",
+            pack.to_str().unwrap(),
+        ])
+        .env(TEST_DATA_HOME, test_data_home())
+        .output()
+        .expect("Failed to run heuristics binary");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let merged = load_heuristics_from_path(&pack).unwrap();
+    assert_eq!(merged.all().len(), 2);
+    assert_eq!(
+        merged.get("need-a-set").unwrap().action,
+        "Use `BTreeSet`.",
+        "Imported heuristics replace the pack's"
+    );
+
+    // --category is the category for rows without one
+    std::fs::write(&csv, "title,category\nNeed a set?,Sets\nNeed a bag?,\n")
+        .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args([
+            "--format",
+            "json",
+            "import",
+            csv.to_str().unwrap(),
+            "--category",
+            "Bags",
+        ])
+        .env(TEST_DATA_HOME, test_data_home())
+        .output()
+        .expect("Failed to run heuristics binary");
+    let json: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let categories: Vec<&str> = json["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|h| h["category"].as_str().unwrap())
+        .collect();
+    assert_eq!(categories, ["Sets", "Bags"]);
+
+    std::fs::write(dir.join("sheet"), "# To list:\nls\n").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["import", dir.join("sheet").to_str().unwrap()])
+        .env(TEST_DATA_HOME, test_data_home())
+        .output()
+        .expect("Failed to run heuristics binary");
+    std::fs::remove_dir_all(&dir).ok();
+    assert_eq!(
+        output.status.code(),
+        Some(2),
+        "Without an extension the format must be given"
+    );
+}
+
+#[test]
+fn test_heuristic_metadata_block() {
+    let markdown = "## Caching\n### Need a cache?\n---\nid: cache-basics\ntags: [memoize, Hot Path]\nlevel: beginner\nsince: 0.2.0\n---\n**Action:** Use `moka`.\n\n### Need cache eviction?\n+++\ntags = [\"eviction\"]\nlevel = \"expert\"\ncolor = \"red\"\n+++\n**Action:** Use an LRU cache.\n\n---\n\nA thematic break further down stays content.\n";
+    let document = parse_markdown(markdown).unwrap();
+    let messages: Vec<String> =
+        document.diagnostics.iter().map(|d| d.to_string()).collect();
+    assert_eq!(
+        messages,
+        [
+            "line 14: level 'expert' is not beginner, intermediate, or advanced; ignored",
+            "line 15: unknown metadata key 'color' ignored",
+        ]
+    );
+
+    let db = HeuristicDb::from(document);
+    let basics = db.get("cache-basics").expect("The block sets the id");
+    assert_eq!(basics.tags, ["memoize", "hot path"]);
+    assert_eq!(
+        (basics.level, basics.since.as_deref()),
+        (Some(Level::Beginner), Some("0.2.0"))
+    );
+    assert!(
+        !basics.content.contains("level:"),
+        "Metadata stays out of the prose"
+    );
+    assert_eq!(
+        db.search(&["memoize"])[0].id,
+        "cache-basics",
+        "Tags are searchable"
+    );
+
+    let eviction = db.get("need-cache-eviction").unwrap();
+    assert_eq!(
+        (eviction.tags.as_slice(), eviction.level),
+        (&["eviction".to_string()][..], None)
+    );
+    assert!(eviction.content.contains("---\n\nA thematic break"));
+
+    let reloaded = HeuristicDb::from_markdown(&db.to_markdown()).unwrap();
+    let basics_again = reloaded.get("cache-basics").unwrap();
+    assert_eq!(
+        (&basics_again.tags, basics_again.level),
+        (&basics.tags, basics.level)
+    );
+}
+
+#[test]
+fn test_show_renders_full_content() {
+    let show = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .arg("show")
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let output = show(&[
+        "Need to compress data aggressively?",
+        "--minimal",
+        "--width",
+        "40",
+    ]);
+    assert!(output.status.success());
+    let text = String::from_utf8_lossy(&output.stdout);
+    assert!(text.contains("   Codec          Ratio   Compression  Decompression\n   -------------  ------"), "Tables are aligned");
+    assert!(
+        text.contains("     use zstd::stream::{encode_all, decode_all};")
+            && !text.contains("```"),
+        "Code is indented, fences dropped"
+    );
+    assert!(
+        text.lines()
+            .filter(|l| !l.starts_with("     ") && !l.contains("http"))
+            .all(|l| l.chars().count() <= 80)
+    );
+    assert!(!text.contains("**"));
+
+    // Without --width, prose fits the terminal, here given by $COLUMNS
+    let narrow = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .env(TEST_DATA_HOME, test_data_home())
+        .env("COLUMNS", "50")
+        .args(["show", "need-to-cache-expensive-results"])
+        .output()
+        .unwrap();
+    let text = String::from_utf8_lossy(&narrow.stdout);
+    assert!(text.contains("   Action: Always add an LRU or TTL cache layer in\n     front of slow operations.\n"), "{}", text);
+    assert!(
+        text.lines()
+            .filter(|l| !l.starts_with("     ") && !l.contains("http"))
+            .all(|l| l.chars().count() <= 50)
+    );
+
+    let json = cli_json(&["show", "need-o-1-average-case-lookups-or-inserts"]);
+    assert_eq!(
+        json["result"]["id"],
+        "need-o-1-average-case-lookups-or-inserts"
+    );
+    assert!(
+        json["result"]["content"]
+            .as_str()
+            .unwrap()
+            .contains("**When to use:**")
+    );
+    let related = json["related"].as_array().unwrap();
+    assert!(related.iter().any(
+        |h| h["id"] == "need-fast-search-on-static-or-mostly-static-data"
+    ));
+
+    assert!(!show(&["no-such-heuristic"]).status.success());
+}
+
+#[test]
+fn test_pick_reads_choice_from_stdin() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let pick = |args: &[&str], input: &str| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .arg("pick")
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+        child.wait_with_output().unwrap()
+    };
+
+    let output = pick(&["--print-id"], "cache expensive\n\n");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "need-to-cache-expensive-results\n",
+        "The menu stays off stdout"
+    );
+
+    let output = pick(&["compress", "--minimal"], "1\n");
+    assert!(
+        String::from_utf8_lossy(&output.stdout)
+            .starts_with("Need to compress data aggressively?")
+    );
+
+    assert!(
+        !pick(&["--print-id"], "").status.success(),
+        "End of input picks nothing"
+    );
+}
+
+#[test]
+fn test_output_formats() {
+    let results = cli_json(&["search", "cache"]);
+    let first = &results["results"][0];
+    assert!(
+        first["score"].as_u64().unwrap() > 0,
+        "Search results should carry their score"
+    );
+
+    let run = |format: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .env(TEST_DATA_HOME, test_data_home())
+            .args(["search", "cache", "--limit", "2", "--format", format])
+            .output()
+            .expect("Failed to run heuristics binary");
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let yaml = run("yaml");
+    assert!(
+        yaml.starts_with("command: \"search\"\n"),
+        "YAML should be a block mapping: {}",
+        yaml
+    );
+    assert!(
+        yaml.contains(&format!("id: \"{}\"", first["id"].as_str().unwrap()))
+    );
+
+    let markdown = run("markdown");
+    assert!(
+        markdown.starts_with("### "),
+        "Markdown should start with a heading: {}",
+        markdown
+    );
+    assert!(!markdown.contains('\x1b'), "Markdown should have no color codes");
+}
+
+#[test]
+fn test_color_choice() {
+    let run = |args: &[&str], no_color: bool| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_heuristics"));
+        command
+            .env(TEST_DATA_HOME, test_data_home())
+            .args(["search", "cache", "--limit", "1"])
+            .args(args);
+        command.env_remove("CLICOLOR_FORCE").env_remove("NO_COLOR");
+        if no_color {
+            command.env("NO_COLOR", "1");
+        }
+        let output =
+            command.output().expect("Failed to run heuristics binary");
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert!(
+        !run(&[], false).contains('\x1b'),
+        "Piped output should be plain by default"
+    );
+    assert!(!run(&["--color", "auto"], true).contains('\x1b'));
+    assert!(
+        run(&["--color", "always"], true).contains('\x1b'),
+        "--color always should beat NO_COLOR"
+    );
+    assert!(
+        !run(&["--color", "always", "--minimal"], false).contains('\x1b'),
+        "--minimal is always plain"
+    );
+    assert!(!run(&["--no-color"], false).contains('\x1b'));
+}
+
+#[test]
+fn test_random_heuristic() {
+    let daily = cli_json(&["random", "--seed-by-date"]);
+    assert_eq!(
+        daily["result"]["id"],
+        cli_json(&["random", "--seed-by-date"])["result"]["id"],
+        "Daily pick should be stable"
+    );
+
+    let category = "Concurrency & Lock-Free Heuristics";
+    for _ in 0..5 {
+        let random = cli_json(&["random", "--category", category]);
+        assert_eq!(random["result"]["category"], category);
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["random", "--category", "No Such Category"])
+        .output()
+        .expect("Failed to run heuristics binary");
+    assert!(!output.status.success(), "An unknown category should fail");
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("No category found: No Such Category")
+    );
+
+    // Everything filtered out isn't a missing category
+    let dir = std::env::temp_dir()
+        .join(format!("heuristics-random-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("heuristics")).unwrap();
+    std::fs::write(
+        dir.join("heuristics").join("config.toml"),
+        "categories = [\"No Such Category\"]\n",
+    )
+    .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .env("XDG_CONFIG_HOME", &dir)
+        .env(TEST_DATA_HOME, test_data_home())
+        .arg("random")
+        .output()
+        .expect("Failed to run heuristics binary");
+    std::fs::remove_dir_all(&dir).ok();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("No heuristics to choose from.")
+            && !stderr.contains("No category found"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_crate_mentions() {
+    let md = "## Cat\n### Need a cache?\n**Action:** Cache.\n- **Crates:** `moka`, `lru`\n\n\
+              ### Need a bounded cache?\n**Action:** Bound it.\n- **Crates:** `Moka`\n- **Alternatives:** `quick_cache`\n";
+    let db = HeuristicDb::from_source(&MarkdownSource::default(), md).unwrap();
+    let mentions = db.crate_mentions();
+    let names: Vec<&str> = mentions.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, vec!["lru", "moka", "quick_cache"]);
+    assert_eq!(
+        mentions[1].heuristics,
+        vec!["need-a-cache", "need-a-bounded-cache"],
+        "Case shouldn't split a crate"
+    );
+
+    let crates = cli_json(&["crates", "--sort", "count"]);
+    let counts: Vec<u64> = crates["crates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["count"].as_u64().unwrap())
+        .collect();
+    assert!(!counts.is_empty());
+    assert!(
+        counts.windows(2).all(|pair| pair[0] >= pair[1]),
+        "Most mentioned crates should come first"
+    );
+}
+
+#[test]
+fn test_export_formats() {
+    let md = "## Cat\n### Need a \"fast\", small map?\n**Action:** Use <code>FxHashMap</code>.\n- **Crates:** `rustc-hash`, `ahash`\n";
+    let db = HeuristicDb::from_source(&MarkdownSource::default(), md).unwrap();
+
+    let csv = to_csv(db.all());
+    let mut rows = csv.lines();
+    assert!(
+        rows.next().unwrap().starts_with("id,title,category,action,crates")
+    );
+    assert!(
+        rows.next().unwrap().starts_with(
+            "need-a-fast-small-map,\"Need a \"\"fast\"\", small map?\",Cat,"
+        ),
+        "Commas and quotes should be quoted: {}",
+        csv
+    );
+    assert!(csv.contains(",rustc-hash; ahash,"));
+
+    let html = to_html("Pack", db.all());
+    assert!(html.contains("<section id=\"need-a-fast-small-map\">"));
+    assert!(
+        html.contains("&lt;code&gt;FxHashMap&lt;/code&gt;"),
+        "Raw HTML should be escaped: {}",
+        html
+    );
+    assert!(html.contains("<code>rustc-hash</code>"));
+
+    let md = "## Cat\n### Need links?\n**Action:** Read [the docs](https://docs.rs), [this](#need-links), [mail](mailto:a@b.c), \
+              [bad](javascript:alert(1)), [worse]( JavaScript:alert(1)) and ![pic](data:text/html,x).\n";
+    let html = to_html("Pack", HeuristicDb::from_markdown(md).unwrap().all());
+    for kept in [
+        "href=\"https://docs.rs\"",
+        "href=\"#need-links\"",
+        "href=\"mailto:a@b.c\"",
+    ] {
+        assert!(html.contains(kept), "{} should be kept: {}", kept, html);
+    }
+    assert!(
+        !html.to_lowercase().contains("javascript:")
+            && !html.contains("data:"),
+        "Script URLs should be dropped: {}",
+        html
+    );
+    assert!(html.contains("<a>bad</a>"));
+
+    let anki = to_anki(db.all());
+    assert!(anki.starts_with("#separator:tab\n#html:true\n"));
+    let card: Vec<&str> = anki.lines().last().unwrap().split('\t').collect();
+    assert_eq!(
+        card[0], "need-a-fast-small-map",
+        "Notes should be keyed by id"
+    );
+    assert_eq!(card[1], "Need a &quot;fast&quot;, small map?");
+    assert!(
+        card[2].contains("<strong>Crates:</strong> <code>rustc-hash</code>"),
+        "{}",
+        card[2]
+    );
+    assert_eq!(card[3], "cat");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["search", "cache", "--format", "anki"])
+        .output()
+        .expect("Failed to run heuristics binary");
+    assert_eq!(output.status.code(), Some(2), "Only export writes decks");
+
+    let path = std::env::temp_dir()
+        .join(format!("heuristics-export-{}.csv", std::process::id()));
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["export", "--format", "csv", "-o"])
+        .arg(&path)
+        .output()
+        .expect("Failed to run heuristics binary");
+    assert!(output.status.success());
+    let exported = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(
+        exported.lines().count(),
+        load_heuristics().all().len() + 1,
+        "One row per heuristic plus a header"
+    );
+}
+
+#[test]
+fn test_static_site_export() {
+    let md = "## Maps\n### Need a map?\n**Action:** Use a [hash map](#need-a-hash-map).\n\n\
+              ### Need a hash map?\n**Action:** Use `HashMap`.\n**Parent:** Need a map?\n\n\
+              ## Sets / Ordered\n### Need a sorted set?\n**Action:** Use `BTreeSet`.\n";
+    let db = HeuristicDb::from_markdown(md).unwrap();
+    let files: std::collections::HashMap<String, String> =
+        to_site("Pack", db.all())
+            .into_iter()
+            .map(|(path, contents)| (path.display().to_string(), contents))
+            .collect();
+
+    let index = &files["index.html"];
+    assert!(
+        index.contains("<a href=\"category/maps.html\">Maps</a> (2)"),
+        "{}",
+        index
+    );
+    assert!(
+        index.contains(
+            "<a href=\"category/sets-ordered.html\">Sets / Ordered</a> (1)"
+        ),
+        "{}",
+        index
+    );
+    assert!(
+        files["category/maps.html"]
+            .contains("href=\"../heuristic/need-a-hash-map.html\"")
+    );
+
+    let map = &files["heuristic/need-a-map.html"];
+    assert!(
+        map.contains("<a href=\"need-a-hash-map.html\">hash map</a>"),
+        "In-page links should point at pages: {}",
+        map
+    );
+    assert!(map.contains("<script src=\"../search.js\" data-root=\"../\">"));
+    assert!(
+        files["heuristic/need-a-hash-map.html"].contains("<h2>Related</h2>"),
+        "Parents should be cross-linked"
+    );
+
+    let search: Value = serde_json::from_str(&files["search-index.json"])
+        .expect("The search index should be JSON");
+    assert_eq!(search.as_array().unwrap().len(), 3);
+    assert_eq!(search[2]["id"], "need-a-sorted-set");
+    assert!(files.contains_key("search.js"));
+
+    let dir = std::env::temp_dir()
+        .join(format!("heuristics-site-{}", std::process::id()));
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["export", "--format", "html", "--out"])
+        .arg(&dir)
+        .env(TEST_DATA_HOME, test_data_home())
+        .output()
+        .expect("Failed to run heuristics binary");
+    assert!(output.status.success());
+    let pages = std::fs::read_dir(dir.join("heuristic")).unwrap().count();
+    std::fs::remove_dir_all(&dir).ok();
+    assert_eq!(pages, load_heuristics().all().len());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["export", "--format", "csv", "--out"])
+        .arg(&dir)
+        .output()
+        .expect("Failed to run heuristics binary");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(!dir.exists());
+}
+
+#[test]
+fn test_mdbook_export() {
+    let md = "## Maps\n### Need a map?\n**Action:** Use a [sorted set](#need-a-sorted-set) for keys alone.\n\n\
+              ## Sets / Ordered\n### Need a sorted set?\n---\ntags: btree\nlevel: beginner\n---\n**Action:** Use `BTreeSet`.\n";
+    let db = HeuristicDb::from_markdown(md).unwrap();
+    let files: std::collections::HashMap<String, String> =
+        to_mdbook("Pack \"One\"", db.all())
+            .into_iter()
+            .map(|(path, contents)| (path.display().to_string(), contents))
+            .collect();
+
+    assert_eq!(
+        files["book.toml"],
+        "[book]\ntitle = \"Pack \\\"One\\\"\"\nsrc = \"src\"\n"
+    );
+    let summary = &files["src/SUMMARY.md"];
+    assert!(
+        summary.contains(
+            "\n- [Maps](maps.md)\n- [Sets]()\n  - [Ordered](sets-ordered.md)\n"
+        ),
+        "{}",
+        summary
+    );
+
+    let maps = &files["src/maps.md"];
+    assert!(
+        maps.starts_with("# Maps\n\n### Need a map? {#need-a-map}\n"),
+        "{}",
+        maps
+    );
+    assert!(
+        maps.contains("[sorted set](sets-ordered.md#need-a-sorted-set)"),
+        "Links should cross chapters: {}",
+        maps
+    );
+    let sets = &files["src/sets-ordered.md"];
+    assert!(
+        sets.contains(
+            "{#need-a-sorted-set}\n**Tags:** btree\n**Level:** beginner\n"
+        ),
+        "{}",
+        sets
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["export", "--format", "mdbook"])
+        .env(TEST_DATA_HOME, test_data_home())
+        .output()
+        .expect("Failed to run heuristics binary");
+    assert_eq!(output.status.code(), Some(2), "A book needs --out");
+}
+
+#[test]
+fn test_search_index_export() {
+    let db = load_heuristics();
+    let index: Value = serde_json::from_str(&db.search_index_json())
+        .expect("The search index should be JSON");
+    assert_eq!(index["version"], SEARCH_INDEX_VERSION);
+    let ids = index["ids"].as_array().unwrap();
+    assert_eq!(ids.len(), db.all().len());
+    assert_eq!(ids[0], db.all()[0].id.as_str());
+
+    // Scoring as documented ranks the same as the database's own search
+    let (exact, partial) = (
+        index["weights"]["exact"].as_u64().unwrap(),
+        index["weights"]["partial"].as_u64().unwrap(),
+    );
+    let mut scores: std::collections::HashMap<usize, u64> =
+        std::collections::HashMap::new();
+    for word in ["cache", "lock"] {
+        for (term, postings) in index["terms"].as_object().unwrap() {
+            let weight = (term == word) as u64 * exact
+                + (term.contains(word) || word.contains(term.as_str())) as u64
+                    * partial;
+            for posting in postings.as_array().unwrap() {
+                *scores
+                    .entry(posting[0].as_u64().unwrap() as usize)
+                    .or_default() += weight * posting[1].as_u64().unwrap();
+            }
+        }
+    }
+    let mut ranked: Vec<(usize, u64)> =
+        scores.into_iter().filter(|&(_, score)| score > 0).collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let expected: Vec<(String, u64)> = db
+        .search_scored(&["cache", "lock"])
+        .iter()
+        .map(|(h, score)| (h.id.clone(), *score as u64))
+        .collect();
+    let ranked: Vec<(String, u64)> = ranked
+        .iter()
+        .map(|&(i, score)| (ids[i].as_str().unwrap().to_string(), score))
+        .collect();
+    assert!(!expected.is_empty());
+    assert_eq!(ranked, expected);
+
+    let dir = std::env::temp_dir()
+        .join(format!("heuristics-search-index-{}", std::process::id()));
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args([
+            "export",
+            "--format",
+            "search-index",
+            "--category",
+            "Concurrency & Lock-Free Heuristics",
+            "--out",
+        ])
+        .arg(&dir)
+        .env(TEST_DATA_HOME, test_data_home())
+        .output()
+        .expect("Failed to run heuristics binary");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let index: Value = serde_json::from_str(
+        &std::fs::read_to_string(dir.join("search-index.json")).unwrap(),
+    )
+    .unwrap();
+    let content: Value = serde_json::from_str(
+        &std::fs::read_to_string(dir.join("heuristics.json")).unwrap(),
+    )
+    .unwrap();
+    std::fs::remove_dir_all(&dir).ok();
+    let results = content["results"].as_array().unwrap();
+    assert_eq!(index["ids"].as_array().unwrap().len(), results.len());
+    assert!(
+        results
+            .iter()
+            .zip(index["ids"].as_array().unwrap())
+            .all(|(result, id)| result["id"] == *id)
+    );
+    assert!(results[0]["content"].is_string());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["export", "--format", "search-index"])
+        .env(TEST_DATA_HOME, test_data_home())
+        .output()
+        .expect("Failed to run heuristics binary");
+    assert_eq!(output.status.code(), Some(2), "The bundle needs --out");
+}
+
+#[test]
+fn test_obsidian_export() {
+    let md = "## Sets / Ordered\n### Need a map?\n---\ntags: Key Value, btree\nlevel: beginner\n---\n**Action:** Use a [sorted set](#need-a-sorted-set) for keys alone.\n\
+              - **Crates:**\n  - `hashbrown` - Fast maps\n- **Alternatives:**\n  - `indexmap`\n  - need-a-sorted-set\n\n\
+              ### Need a sorted set?\n**Action:** Use `BTreeSet`, not [a map](#need-a-map) or [the docs](#nowhere).\n**Parent:** Need a map?\n";
+    let db = HeuristicDb::from_markdown(md).unwrap();
+    let files: std::collections::HashMap<String, String> = to_vault(db.all())
+        .into_iter()
+        .map(|(path, contents)| (path.display().to_string(), contents))
+        .collect();
+    assert_eq!(files.len(), 4, "{:?}", files.keys());
+
+    let map = &files["need-a-map.md"];
+    assert!(map.starts_with("---\nid: \"need-a-map\"\naliases:\n  - \"Need a map?\"\ncategory: \"Sets / Ordered\"\n"), "{}", map);
+    assert!(map.contains("tags:\n  - \"sets/ordered\"\n  - \"key-value\"\n  - \"btree\"\nlevel: beginner\n"), "{}", map);
+    assert!(map.contains("crates:\n  - \"[[hashbrown]]\"\n  - \"[[indexmap]]\"\nrelated:\n  - \"[[need-a-sorted-set]]\"\n---\n# Need a map?\n"), "{}", map);
+    assert!(
+        map.contains("Use a [[need-a-sorted-set|sorted set]] for keys alone."),
+        "{}",
+        map
+    );
+    assert!(map.contains("  - [[hashbrown]] - Fast maps\n"), "{}", map);
+    assert!(
+        map.contains(
+            "  - [[indexmap]]\n  - [[need-a-sorted-set|Need a sorted set?]]\n"
+        ),
+        "{}",
+        map
+    );
+
+    let set = &files["need-a-sorted-set.md"];
+    assert!(
+        set.contains("not [[need-a-map|a map]] or [the docs](#nowhere)."),
+        "Only links into the vault change: {}",
+        set
+    );
+    assert!(
+        set.contains("**Parent:** [[need-a-map|Need a map?]]\n"),
+        "{}",
+        set
+    );
+    assert!(
+        files["crates/hashbrown.md"].contains("# hashbrown\n\nFast maps\n")
+    );
+    assert!(files.contains_key("crates/indexmap.md"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["export", "--format", "obsidian"])
+        .env(TEST_DATA_HOME, test_data_home())
+        .output()
+        .expect("Failed to run heuristics binary");
+    assert_eq!(output.status.code(), Some(2), "A vault needs --out");
+}
+
+#[test]
+fn test_hugo_zola_export() {
+    let md = "## Maps\n### Need a \"map\"?\n---\ntags: btree, std\n---\n**Action:** Use a [sorted set](#need-a-sorted-set) for keys alone.\n**Id:** need-a-map\n\
+              **Since:** 0.3.0\n**Updated:** 2025-11-17\n\n### Need a sorted set?\n**Action:** Use `BTreeSet`.\n**Since:** 2024-02-01\n";
+    let db = HeuristicDb::from_markdown(md).unwrap();
+    let files = |files: Vec<(std::path::PathBuf, String)>| -> std::collections::HashMap<String, String> {
+        files.into_iter().map(|(path, contents)| (path.display().to_string(), contents)).collect()
+    };
+
+    let hugo = files(to_hugo("Pack", db.all()));
+    assert_eq!(
+        hugo["content/heuristics/_index.md"],
+        "---\ntitle: \"Pack\"\n---\n"
+    );
+    let map = &hugo["content/heuristics/need-a-map.md"];
+    assert!(
+        map.starts_with(
+            "---\ntitle: \"Need a \\\"map\\\"?\"\nslug: \"need-a-map\"\ndescription: \"Use a [sorted set](#need-a-sorted-set) for keys alone.\"\n\
+             weight: 1\ndate: 2025-11-17\nlastmod: 2025-11-17\ncategories: [\"Maps\"]\ntags: [\"btree\", \"std\"]\n---\n\n**Action:**"
+        ),
+        "A version isn't a date: {}",
+        map
+    );
+    assert!(
+        map.contains(
+            "Use a [sorted set](../need-a-sorted-set/) for keys alone.\n"
+        ),
+        "{}",
+        map
+    );
+    assert!(!map.contains("**Id:**") && !map.contains("### "), "{}", map);
+    assert!(
+        hugo["content/heuristics/need-a-sorted-set.md"]
+            .contains("weight: 2\ndate: 2024-02-01\ncategories:")
+    );
+
+    let zola = files(to_zola("Pack", db.all()));
+    assert_eq!(
+        zola["content/heuristics/_index.md"],
+        "+++\ntitle = \"Pack\"\nsort_by = \"weight\"\n+++\n"
+    );
+    let map = &zola["content/heuristics/need-a-map.md"];
+    assert!(
+        map.starts_with(
+            "+++\ntitle = \"Need a \\\"map\\\"?\"\nslug = \"need-a-map\"\n"
+        ),
+        "{}",
+        map
+    );
+    assert!(map.contains("updated = 2025-11-17\n\n[taxonomies]\ncategories = [\"Maps\"]\ntags = [\"btree\", \"std\"]\n+++\n\n"), "{}", map);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["export", "--format", "hugo"])
+        .env(TEST_DATA_HOME, test_data_home())
+        .output()
+        .expect("Failed to run heuristics binary");
+    assert_eq!(output.status.code(), Some(2), "Content goes to a directory");
+}
+
+#[test]
+fn test_pack_diff() {
+    let old = HeuristicDb::from_markdown(
+        "## Maps\n### Need a map?\n**Action:** Use `HashMap`.\n- **Crates:** `ahash`, `hashbrown`\n\n### Need a set?\n**Action:** Use `HashSet`.\n\n### Need a bag?\n**Action:** Count.\n",
+    )
+    .unwrap();
+    let new = HeuristicDb::from_markdown(
+        "## Maps\n### Need a map?\n**Action:** Use `BTreeMap`.\n- **Crates:** `Hashbrown`, `foldhash`\n- **Pros:**\n  - Ordered\n\n### Need a bag?\n**Action:** Count.\n\n### Need a queue?\n**Action:** Use `VecDeque`.\n",
+    )
+    .unwrap();
+    let diff = old.diff(&new);
+    let ids = |heuristics: &[&Heuristic]| -> Vec<String> {
+        heuristics.iter().map(|h| h.id.clone()).collect()
+    };
+    assert_eq!(
+        (ids(&diff.added), ids(&diff.removed)),
+        (vec!["need-a-queue".to_string()], vec!["need-a-set".to_string()])
+    );
+    assert_eq!(diff.changed.len(), 1, "Unchanged heuristics aren't listed");
+    let map = &diff.changed[0];
+    assert!(map.action_changed);
+    assert_eq!(
+        (map.crates_added.as_slice(), map.crates_removed.as_slice()),
+        (["foldhash"].as_slice(), ["ahash"].as_slice()),
+        "Crate names ignore case"
+    );
+    assert_eq!(map.fields, ["Pros"]);
+    assert!(new.diff(&new).is_empty());
+    let rendered = diff.render(RenderStyle::Plain, 80);
+    assert!(
+        rendered.contains("Action was: Use `HashMap`.")
+            && rendered.contains("No longer recommends: ahash"),
+        "{}",
+        rendered
+    );
+
+    let dir = std::env::temp_dir()
+        .join(format!("heuristics-diff-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (old_pack, new_pack) = (dir.join("old.md"), dir.join("new.md"));
+    std::fs::write(&old_pack, old.to_markdown()).unwrap();
+    std::fs::write(&new_pack, new.to_markdown()).unwrap();
+    let diff = |new_pack: &std::path::Path| {
+        Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .args([
+                "--format",
+                "json",
+                "diff",
+                old_pack.to_str().unwrap(),
+                new_pack.to_str().unwrap(),
+            ])
+            .env(TEST_DATA_HOME, test_data_home())
+            .output()
+            .expect("Failed to run heuristics binary")
+    };
+    let (changed, same) = (diff(&new_pack), diff(&old_pack));
+    let missing = diff(&dir.join("missing.md"));
+    // With one pack, it's compared with the stock pack, not the user's packs on top
+    let stock = dir.join("stock.md");
+    std::fs::write(&stock, load_heuristics().to_markdown()).unwrap();
+    let against_stock = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args([
+            "diff",
+            stock.to_str().unwrap(),
+            "--extra",
+            new_pack.to_str().unwrap(),
+        ])
+        .env(TEST_DATA_HOME, test_data_home())
+        .env("XDG_CACHE_HOME", &dir)
+        .output()
+        .expect("Failed to run heuristics binary");
+    std::fs::remove_dir_all(&dir).ok();
+    assert_eq!(missing.status.code(), Some(2), "Errors exit 2, like diff");
+    assert!(
+        against_stock.status.success(),
+        "{}",
+        String::from_utf8_lossy(&against_stock.stdout)
+    );
+    assert_eq!(
+        changed.status.code(),
+        Some(1),
+        "Differing packs exit 1, like diff"
+    );
+    let json: Value = serde_json::from_slice(&changed.stdout).unwrap();
+    assert_eq!(json["removed"], serde_json::json!(["need-a-set"]));
+    assert_eq!(json["changed"][0]["action"]["new"], "Use `BTreeMap`.");
+    assert!(same.status.success());
+}
+
+#[test]
+fn test_dot_export() {
+    let md = "## Maps\n### Need a map?\n**Action:** Use a [sorted map](#need-a-sorted-map) when order matters.\n\
+              - **Crates:**\n  - `hashbrown` - Fast maps\n\n\
+              ### Need a \"sorted\" map?\n**Id:** need-a-sorted-map\n**Action:** Use `BTreeMap`.\n**Parent:** Need a map?\n\
+              - **Crates:**\n  - `hashbrown` - Also fast\n- **Alternatives:** need-a-map, need-a-missing-one\n";
+    let db = HeuristicDb::from_markdown(md).unwrap();
+    let dot = to_dot(db.all());
+    assert!(dot.starts_with("digraph heuristics {\n"));
+    assert!(
+        dot.contains("  subgraph cluster_0 {\n    label=\"Maps\";\n"),
+        "{}",
+        dot
+    );
+    assert!(dot.contains("\"heuristic:need-a-sorted-map\" [label=\"Need a \\\"sorted\\\" map?\"];"), "{}", dot);
+    assert_eq!(
+        dot.matches("[shape=ellipse, label=\"hashbrown\"]").count(),
+        1,
+        "Crates are one node: {}",
+        dot
+    );
+    assert!(dot.contains("\"heuristic:need-a-map\" -> \"heuristic:need-a-sorted-map\" [style=dashed];"));
+    assert!(dot.contains("\"heuristic:need-a-sorted-map\" -> \"heuristic:need-a-map\" [style=dotted];"));
+    assert!(
+        dot.contains(
+            "\"heuristic:need-a-map\" -> \"heuristic:need-a-sorted-map\";\n"
+        ),
+        "Parents point at children"
+    );
+    assert!(
+        !dot.contains("missing"),
+        "Unknown references are left out: {}",
+        dot
+    );
+    assert!(dot.ends_with("}\n"));
+}
+
+#[test]
+fn test_compare_heuristics() {
+    let md = "## Cat\n### Need sorted keys?\n**Action:** Use a B-tree for O(log(n)) lookups, or sort once: O(n log n).\n\
+              - **Pros:** Ordered iteration\n\n```rust\nlet x = O(2);\n```\n\n\
+              ### Need log(n) lookups?\n**Action:** Use `BTreeMap`.\n";
+    let db = HeuristicDb::from_source(&MarkdownSource::default(), md).unwrap();
+    let sorted = &db.all()[0];
+    assert_eq!(
+        sorted.complexity(),
+        vec!["O(log(n))", "O(n log n)"],
+        "Code blocks should be skipped"
+    );
+    assert_eq!(db.all()[1].complexity(), vec!["O(log n)"]);
+
+    let table = compare(&[&db.all()[0], &db.all()[1]], RenderStyle::Plain, 80);
+    let header = table.lines().next().unwrap();
+    assert!(
+        header.contains("Need sorted keys?")
+            && header.contains("Need log(n) lookups?"),
+        "{}",
+        table
+    );
+    assert!(
+        table.lines().any(|line| line.starts_with("Pros")
+            && line.contains("Ordered iteration"))
+    );
+    assert!(
+        !table.contains("Memory"),
+        "Rows no heuristic fills should be left out"
+    );
+
+    let compared = cli_json(&["compare", "hashmap", "btreemap"]);
+    let ids: Vec<&str> = compared["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids.len(), 2);
+    assert_ne!(ids[0], ids[1], "Each term should pick a different heuristic");
+    assert_eq!(ids[0], "need-o-1-average-case-lookups-or-inserts");
+}
+
+#[test]
+fn test_tree_walks_questions_from_stdin() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let path = std::env::temp_dir()
+        .join(format!("heuristics-tree-{}.md", std::process::id()));
+    std::fs::write(
+        &path,
+        "## Tree Test\n### Need a map?\n**Action:** Use `HashMap`.\n\n\
+         ### Need ordering?\n**Action:** Use `BTreeMap`.\n- **Parent:** need-a-map\n\n\
+         ### Need speed?\n**Action:** Use `hashbrown`.\n- **Parent:** need-a-map\n",
+    )
+    .unwrap();
+    let tree = |input: &str| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .args(["tree", "--print-id", "--category", "Tree Test", "--extra"])
+            .arg(&path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+        let output = child.wait_with_output().unwrap();
+        output.status.success().then(|| {
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        })
+    };
+
+    assert_eq!(tree("y\ny\n").as_deref(), Some("need-ordering"));
+    assert_eq!(tree("y\nn\ny\n").as_deref(), Some("need-speed"));
+    assert_eq!(
+        tree("y\nn\nb\nmaybe\ny\n").as_deref(),
+        Some("need-ordering"),
+        "b should re-ask the previous question"
+    );
+    assert_eq!(
+        tree("y\nn\nn\n").as_deref(),
+        Some("need-a-map"),
+        "With no fitting follow-up the parent's advice applies"
+    );
+    assert_eq!(
+        tree("n\n"),
+        None,
+        "Declining every question recommends nothing"
+    );
+    assert_eq!(tree(""), None);
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_suggest_from_manifest() {
+    let manifest = Manifest::parse(
+        "[package]\nname = \"demo\"\n\n[dependencies]\nlru = \"0.12\" # caching\n\
+         fast = { package = \"quick_cache\", version = \"0.6\" }\nsled = { version = \"0.34\", features = [\n    \"compression\",\n] }\n\n\
+         [target.'cfg(unix)'.dev-dependencies.moka]\nversion = \"0.12\"\n\n[features]\ndefault = [\"cache\"]\ncache = []\n",
+    );
+    assert_eq!(
+        manifest.dependencies,
+        vec!["lru", "quick_cache", "sled", "moka"]
+    );
+    assert_eq!(manifest.features, vec!["cache"]);
+
+    let db = load_heuristics();
+    let suggestions = db.suggest(&manifest);
+    let cache = suggestions
+        .iter()
+        .find(|s| s.heuristic.id == "need-to-cache-expensive-results")
+        .unwrap();
+    assert_eq!(cache.because, vec!["lru", "quick_cache", "moka", "cache"]);
+    assert_eq!(
+        suggestions[0].heuristic.id, cache.heuristic.id,
+        "Recommending several dependencies ranks first"
+    );
+    assert!(suggestions.windows(2).all(|pair| pair[0].score >= pair[1].score));
+
+    let path = std::env::temp_dir()
+        .join(format!("heuristics-suggest-{}.toml", std::process::id()));
+    std::fs::write(&path, "[dependencies]\nlru = \"0.12\"\n").unwrap();
+    let suggested = cli_json(&["suggest", path.to_str().unwrap()]);
+    std::fs::remove_file(&path).ok();
+    assert_eq!(suggested["dependencies"], serde_json::json!(["lru"]));
+    assert_eq!(suggested["results"][0]["because"], serde_json::json!(["lru"]));
+}
+
+#[test]
+fn test_search_history() {
+    let data_home = std::env::temp_dir()
+        .join(format!("heuristics-history-{}", std::process::id()));
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .env(TEST_DATA_HOME, &data_home)
+            .args(args)
+            .output()
+            .expect("Failed to run heuristics binary");
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+    let history = || -> Vec<Value> {
+        let text = run(&["history", "--format", "json"]).unwrap();
+        serde_json::from_str::<Value>(&text).unwrap()["entries"]
+            .as_array()
+            .unwrap()
+            .clone()
+    };
+
+    run(&["search", "cache"]).unwrap();
+    run(&["search", "lock free"]).unwrap();
+    run(&["search", "lock free"]).unwrap();
+    run(&["--minimal", "search", "bloom"]).unwrap();
+    let entries = history();
+    assert_eq!(
+        entries.len(),
+        2,
+        "Repeats and --minimal searches aren't recorded: {:?}",
+        entries
+    );
+    assert_eq!(
+        entries[0]["query"],
+        serde_json::json!(["lock free"]),
+        "Keywords keep their spaces"
+    );
+    assert_eq!(entries[1]["number"], 2);
+
+    let again = run(&["search", "--again", "2", "--format", "json"]).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Value>(&again).unwrap()["query"],
+        serde_json::json!(["cache"])
+    );
+    assert_eq!(
+        history()[0]["query"],
+        serde_json::json!(["cache"]),
+        "A re-run search becomes the latest"
+    );
+    assert!(run(&["search", "--again", "9"]).is_none());
+
+    run(&["history", "--clear"]).unwrap();
+    assert!(history().is_empty());
+    std::fs::remove_dir_all(&data_home).ok();
+}
+
+#[test]
+fn test_notes_show_under_heuristic() {
+    let data_home = std::env::temp_dir()
+        .join(format!("heuristics-notes-{}", std::process::id()));
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .env(TEST_DATA_HOME, &data_home)
+            .args(args)
+            .output()
+            .expect("Failed to run heuristics binary");
+        assert!(output.status.success(), "{:?} should succeed", args);
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    let id = "need-to-cache-expensive-results";
+
+    run(&["note", id, "we use moka 0.12\nin service-x"]);
+    run(&[
+        "note",
+        "Need to cache expensive results?",
+        "evictions tuned to 10k",
+    ]);
+    let shown: Value =
+        serde_json::from_str(&run(&["show", id, "--format", "json"])).unwrap();
+    assert_eq!(
+        shown["notes"],
+        serde_json::json!([
+            "we use moka 0.12 in service-x",
+            "evictions tuned to 10k"
+        ])
+    );
+    assert!(
+        !run(&["show", id, "--minimal"]).contains("Notes:"),
+        "--minimal leaves notes out"
+    );
+    assert!(
+        run(&["show", id])
+            .contains("Notes:\n  • we use moka 0.12 in service-x\n")
+    );
+
+    run(&["note", id, "--clear"]);
+    let listed: Value =
+        serde_json::from_str(&run(&["note", id, "--format", "json"])).unwrap();
+    assert_eq!(listed["notes"], serde_json::json!([]));
+    std::fs::remove_dir_all(&data_home).ok();
+}
+
+#[test]
+fn test_add_appends_to_user_pack() {
+    use std::process::Stdio;
+
+    let dir = std::env::temp_dir()
+        .join(format!("heuristics-add-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let written = dir.join("written.md");
+    std::fs::write(
+        &written,
+        "<!-- an instruction left in -->\n## Team Rules\n### Need a bounded session cache?\n\
+         **Action:** Use `moka` with a max capacity and a TTL.\n- **Crates:** `moka`\n",
+    )
+    .unwrap();
+    let add = |editor: &str| {
+        Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .env(TEST_DATA_HOME, &dir)
+            .env_remove("VISUAL")
+            .env("EDITOR", editor)
+            .args(["add", "--category", "Team Rules"])
+            .stdin(Stdio::null())
+            .output()
+            .expect("Failed to run heuristics binary")
+    };
+    // The "editor" copies the finished heuristic over the template
+    let editor = format!("cp {}", written.display());
+
+    assert!(add(&editor).status.success());
+    let pack = std::fs::read_to_string(dir.join("heuristics").join("pack.md"))
+        .unwrap();
+    assert!(
+        pack.starts_with("## Team Rules\n### Need a bounded session cache?"),
+        "Instructions are removed: {}",
+        pack
+    );
+
+    let shown = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .env(TEST_DATA_HOME, &dir)
+        .args(["show", "need-a-bounded-session-cache", "--format", "json"])
+        .output()
+        .unwrap();
+    let shown: Value = serde_json::from_slice(&shown.stdout)
+        .expect("The added heuristic should load");
+    assert!(
+        shown["result"]["provenance"].as_str().unwrap().ends_with("pack.md")
+    );
+
+    let duplicate = add(&editor);
+    assert!(!duplicate.status.success());
+    assert!(
+        String::from_utf8_lossy(&duplicate.stderr).contains("already exists")
+    );
+    assert!(
+        !add("true").status.success(),
+        "An untouched template adds nothing"
+    );
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_edit_overrides_builtin_from_user_pack() {
+    use std::process::Stdio;
+
+    let dir = std::env::temp_dir()
+        .join(format!("heuristics-edit-{}", std::process::id()));
+    let edit = |editor: &str| {
+        Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .env(TEST_DATA_HOME, &dir)
+            .env_remove("VISUAL")
+            .env("EDITOR", editor)
+            .args(["edit", "need-to-cache-expensive-results"])
+            .stdin(Stdio::null())
+            .output()
+            .expect("Failed to run heuristics binary")
+    };
+    let show = || {
+        let shown = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .env(TEST_DATA_HOME, &dir)
+            .args([
+                "show",
+                "need-to-cache-expensive-results",
+                "--format",
+                "json",
+            ])
+            .output()
+            .unwrap();
+        serde_json::from_slice::<Value>(&shown.stdout)
+            .expect("The edited heuristic should load")
+    };
+
+    // The "editors" rewrite the action in place
+    assert!(edit("sed -i s/Always/Usually/").status.success());
+    let shown = show();
+    assert!(
+        shown["result"]["action"]
+            .as_str()
+            .unwrap()
+            .starts_with("Usually add an LRU or TTL cache")
+    );
+    assert!(
+        shown["result"]["provenance"].as_str().unwrap().ends_with("pack.md")
+    );
+
+    // Editing again changes the pack's copy rather than adding another
+    assert!(edit("sed -i s/TTL/expiring/").status.success());
+    let pack = std::fs::read_to_string(dir.join("heuristics").join("pack.md"))
+        .unwrap();
+    assert_eq!(
+        pack.matches("### Need to cache expensive results?").count(),
+        1,
+        "{}",
+        pack
+    );
+    assert!(
+        show()["result"]["action"]
+            .as_str()
+            .unwrap()
+            .contains("LRU or expiring")
+    );
+
+    // Retitling would change the id and leave the built-in untouched
+    let retitled = edit("sed -i /^###/s/expensive/costly/");
+    assert!(!retitled.status.success());
+    assert!(
+        String::from_utf8_lossy(&retitled.stderr).contains("the id changed")
+    );
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_config_file_defaults() {
+    let dir = std::env::temp_dir()
+        .join(format!("heuristics-config-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("heuristics")).unwrap();
+    let config = dir.join("heuristics").join("config.toml");
+    std::fs::write(
+        &config,
+        "# Team defaults\nlimit = 1\nformat = \"json\"\ncategories = [\"Concurrency & Lock-Free Heuristics\"]  # our focus\n\n[weights]\nexact = 3\n",
+    )
+    .unwrap();
+    let search = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .env("XDG_CONFIG_HOME", &dir)
+            .env(TEST_DATA_HOME, test_data_home())
+            .args(["search", "lock"])
+            .args(args)
+            .output()
+            .expect("Failed to run heuristics binary");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        output.stdout
+    };
+
+    let configured: Value = serde_json::from_slice(&search(&[]))
+        .expect("The config sets JSON output");
+    let results = configured["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(
+        results[0]["category"].as_str().unwrap().starts_with("Concurrency")
+    );
+    assert!(configured["total"].as_u64().unwrap() > 1);
+
+    // Flags override the config file, and --minimal ignores it
+    let flagged: Value =
+        serde_json::from_slice(&search(&["--limit", "3"])).unwrap();
+    assert_eq!(
+        flagged["results"].as_array().unwrap().len(),
+        3.min(flagged["total"].as_u64().unwrap() as usize)
+    );
+    assert!(
+        String::from_utf8_lossy(&search(&["--format", "text"]))
+            .contains("Found")
+    );
+    assert!(
+        String::from_utf8_lossy(&search(&["--minimal"])).contains("Found")
+    );
+
+    // Commas inside strings don't split arrays, and basic strings unescape
+    std::fs::write(
+        dir.join("heuristics").join("a,b.md"),
+        "## Team\n### Need a comma?\n**Action:** Quote it.\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("heuristics").join("c\\d.md"),
+        "## Team\n### Need a backslash?\n**Action:** Escape it.\n",
+    )
+    .unwrap();
+    std::fs::write(&config, "extra = [\"a,b.md\", 'c\\d.md']  # two packs\n")
+        .unwrap();
+    let list = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .env("XDG_CONFIG_HOME", &dir)
+        .args(["list", "--output", "ids"])
+        .output()
+        .unwrap();
+    let ids = String::from_utf8_lossy(&list.stdout);
+    assert!(
+        ids.contains("need-a-comma") && ids.contains("need-a-backslash"),
+        "{}",
+        String::from_utf8_lossy(&list.stderr)
+    );
+    std::fs::write(&config, "extra = [\"a,b.md\", \"c\\\\d.md\"]\n").unwrap();
+    let list = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .env("XDG_CONFIG_HOME", &dir)
+        .args(["list", "--output", "ids"])
+        .output()
+        .unwrap();
+    assert!(
+        String::from_utf8_lossy(&list.stdout).contains("need-a-backslash"),
+        "{}",
+        String::from_utf8_lossy(&list.stderr)
+    );
+
+    std::fs::write(&config, "limit = \"many\"\n").unwrap();
+    let broken = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .env("XDG_CONFIG_HOME", &dir)
+        .arg("list")
+        .output()
+        .unwrap();
+    assert!(!broken.status.success());
+    assert!(
+        String::from_utf8_lossy(&broken.stderr)
+            .contains("config.toml:1: limit: expected a whole number")
+    );
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_repl_keeps_bookmarks() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let dir = std::env::temp_dir()
+        .join(format!("heuristics-repl-{}", std::process::id()));
+    let repl = |input: &str| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .env(TEST_DATA_HOME, &dir)
+            .args(["repl", "--limit", "2"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success());
+        (
+            String::from_utf8_lossy(&output.stdout).to_string(),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        )
+    };
+
+    let (out, err) = repl("search cache\nshow 1\nbookmark 1\nfrobnicate\n");
+    assert!(
+        out.contains("1. Need to cache expensive results?")
+            && !out.contains("3. "),
+        "{}",
+        out
+    );
+    assert!(out.contains("Pros:"), "`show 1` shows the first result in full");
+    assert!(out.contains("Bookmarked Need to cache expensive results?"));
+    assert!(err.contains("Unknown command `frobnicate`"));
+
+    // A category that isn't there keeps the previous list
+    let (out, err) = repl("search cache\ncategory Nope\nshow 1\n");
+    assert!(
+        err.contains("No category found: Nope")
+            && !err.contains("No heuristic found"),
+        "{}",
+        err
+    );
+    assert!(out.contains("Pros:"), "{}", out);
+
+    // Bookmarks outlive the session, and `quit` ends it early
+    let (out, _) = repl("bookmark\nquit\nsearch cache\n");
+    assert!(out.starts_with("1. Need to cache expensive results?"), "{}", out);
+    assert!(!out.contains("2. "));
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_grep_matches_phrases_and_regexes() {
+    // A literal pattern needs no escaping
+    let literal = cli_json(&["grep", "log(n) lookups"]);
+    let results = literal["results"].as_array().unwrap();
+    assert_eq!(
+        results[0]["id"],
+        "need-log-n-lookups-when-working-with-disk-backed-data"
+    );
+    assert_eq!(
+        results[0]["matches"][0]["line"], 1,
+        "The header is the first line"
+    );
+
+    let regex = cli_json(&["grep", "--regex", "-i", "SKIP ?LISTS?"]);
+    let matches = regex["results"][0]["matches"].as_array().unwrap();
+    assert!(matches.len() > 1);
+    assert!(
+        matches.iter().all(|m| m["text"]
+            .as_str()
+            .unwrap()
+            .to_lowercase()
+            .contains("skip"))
+    );
+
+    let text = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["grep", "-C", "0", "Lock-free concurrent skip list"])
+        .output()
+        .unwrap();
+    let text = String::from_utf8_lossy(&text.stdout);
+    assert!(
+        text.contains(
+            ":   - `crossbeam-skiplist` - Lock-free concurrent skip list"
+        ),
+        "{}",
+        text
+    );
+
+    let invalid = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["grep", "--regex", "(unclosed"])
+        .output()
+        .unwrap();
+    assert_eq!(invalid.status.code(), Some(2));
+}
+
+#[test]
+fn test_doctor_checks_a_pack_directory() {
+    let dir = std::env::temp_dir()
+        .join(format!("heuristics-doctor-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("a.md"),
+        "## Team\n### Need a session cache?\n**Action:** Use `moka`.\n- **Parent:** need-to-cache-expensive-results\n\n\
+         ### Need a queue?\n- **Parent:** need-a-missing-question\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("b.md"),
+        "## Team\n### Need a session cache?\n**Action:** Use `quick_cache`.\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["doctor", "--format", "json"])
+        .arg(&dir)
+        .output()
+        .expect("Failed to run heuristics binary");
+    assert!(!output.status.success(), "Problems fail the check");
+    let doctor: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let problems: Vec<&str> = doctor["issues"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|i| i["problem"].as_str().unwrap())
+        .collect();
+
+    assert!(
+        problems.contains(&"'Need a queue?' has no **Action:** line"),
+        "{:?}",
+        problems
+    );
+    assert!(problems.iter().any(|p| {
+        p.starts_with("duplicate id 'need-a-session-cache', also in")
+    }));
+    assert!(
+        problems
+            .contains(&"unresolved reference to 'need-a-missing-question'")
+    );
+    // Built-in heuristics resolve, but aren't checked themselves
+    assert!(
+        !problems
+            .iter()
+            .any(|p| p.contains("need-to-cache-expensive-results"))
+    );
+    assert_eq!(doctor["issues"][0]["line"], 6);
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_search_filters() {
+    let ids = |args: &[&str]| -> Vec<String> {
+        let json =
+            cli_json(&[&["search", "map", "--limit", "50"], args].concat());
+        json["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["id"].as_str().unwrap().to_string())
+            .collect()
+    };
+    let all = ids(&[]);
+    let btree = ids(&["--std-type", "BTreeMap"]);
+    assert!(!btree.is_empty() && btree.len() < all.len());
+    assert!(
+        btree.iter().all(|id| all.contains(id)),
+        "Filters only narrow the keyword results"
+    );
+
+    let db = load_heuristics();
+    assert!(btree.iter().all(|id| {
+        db.get(id).unwrap().std_types.iter().any(|ty| ty.contains("BTreeMap"))
+    }));
+    let hashbrown = ids(&[
+        "--crate",
+        "HashBrown",
+        "--category",
+        "General-Purpose Performance Heuristics",
+    ]);
+    assert!(
+        hashbrown
+            .contains(&"need-o-1-average-case-lookups-or-inserts".to_string())
+    );
+    assert!(ids(&["--crate", "no-such-crate"]).is_empty());
+}
+
+#[test]
+fn test_script_output() {
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .env(TEST_DATA_HOME, test_data_home())
+            .args(args)
+            .output()
+            .unwrap();
+        (output.status.code(), String::from_utf8(output.stdout).unwrap())
+    };
+
+    let (code, ids) =
+        run(&["search", "cache", "--output", "ids", "--limit", "3"]);
+    assert_eq!(code, Some(0));
+    assert_eq!(ids.lines().count(), 3);
+    assert_eq!(ids.lines().next(), Some("need-to-cache-expensive-results"));
+
+    let (_, tsv) = run(&[
+        "category",
+        "Concurrency & Lock-Free Heuristics",
+        "--output",
+        "tsv",
+    ]);
+    let db = load_heuristics();
+    for line in tsv.lines() {
+        let [id, category, title, action] =
+            line.split('\t').collect::<Vec<_>>()[..]
+        else {
+            panic!("expected four columns: {}", line)
+        };
+        let h = db.get(id).unwrap();
+        assert_eq!(
+            (category, title, action),
+            (h.category.as_str(), h.title.as_str(), h.action.as_str())
+        );
+    }
+    assert_eq!(
+        tsv.lines().count(),
+        db.by_category("Concurrency & Lock-Free Heuristics").len()
+    );
+
+    assert_eq!(
+        run(&["search", "zzzqqq", "--output", "ids"]),
+        (Some(0), String::new())
+    );
+    assert_eq!(
+        run(&["search", "zzzqqq", "--output", "ids", "--fail-if-empty"]),
+        (Some(1), String::new())
+    );
+    assert_eq!(
+        run(&["category", "No Such Category", "--fail-if-empty"]).0,
+        Some(1)
+    );
+    assert_eq!(
+        run(&["list", "--output", "ids", "--fail-if-empty"]).1.lines().count(),
+        db.all().len()
+    );
+
+    // --count ignores --limit; --quiet keeps the results but not the decoration
+    let total = db.search(&["cache"]).len();
+    assert_eq!(
+        run(&["search", "cache", "--count", "--limit", "1"]),
+        (Some(0), format!("{}\n", total))
+    );
+    assert_eq!(
+        run(&["category", "No Such Category", "--count", "--fail-if-empty"]),
+        (Some(1), "0\n".to_string())
+    );
+    let (_, quiet) =
+        run(&["search", "cache", "--quiet", "--limit", "1", "--minimal"]);
+    assert!(
+        quiet.starts_with("1. Need to cache expensive results?"),
+        "{}",
+        quiet
+    );
+    assert!(!quiet.contains("Found") && !quiet.contains("more"));
+    assert_eq!(run(&["search", "zzzqqq", "-q"]), (Some(0), String::new()));
+}
+
+#[test]
+fn test_man_pages() {
+    let md = "## Cat\n### Need a fast-path?\n**Action:** Try `\\d` first, see [docs](https://example.com).\n\n```rust\n.unwrap()\n```\n";
+    let db = HeuristicDb::from_source(&MarkdownSource::default(), md).unwrap();
+    let page = db.all()[0].to_roff();
+    assert!(
+        page.starts_with(
+            ".TH \"NEED\\-A\\-FAST\\-PATH\" 7 \"\" \"heuristics\" \"Cat\"\n"
+        ),
+        "{}",
+        page
+    );
+    assert!(page.contains(
+        ".SH NAME\nneed\\-a\\-fast\\-path \\- Need a fast\\-path?\n"
+    ));
+    assert!(
+        page.contains("\\fB\\ed\\fR first, see docs <https://example.com>")
+    );
+    assert!(
+        page.contains(".nf\n\\&.unwrap()\n.fi"),
+        "Lines starting with a dot are protected: {}",
+        page
+    );
+
+    let man = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .arg("man")
+            .args(args)
+            .output()
+            .expect("Failed to run heuristics binary")
+    };
+    let cli_page = String::from_utf8(man(&[]).stdout).unwrap();
+    assert!(cli_page.contains(".TH heuristics 1"), "{}", cli_page);
+    assert!(cli_page.contains("search"));
+    let output = man(&["Need to cache expensive results?"]);
+    assert!(output.status.success());
+    assert!(
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .starts_with(".TH \"NEED\\-TO\\-CACHE\\-EXPENSIVE\\-RESULTS\" 7")
+    );
+    assert!(!man(&["no-such-heuristic"]).status.success());
+
+    let pages = to_man_pages(db.all());
+    assert_eq!(
+        pages[0].0,
+        std::path::PathBuf::from("man7/heuristic-need-a-fast-path.7")
+    );
+    assert!(
+        pages[0].1.starts_with(".TH \"HEURISTIC\\-NEED\\-A\\-FAST\\-PATH\" 7"),
+        "{}",
+        pages[0].1
+    );
+    assert!(pages[0].1.contains(".SH NAME\nheuristic\\-need\\-a\\-fast\\-path \\- Need a fast\\-path?\n"));
+
+    let dir = std::env::temp_dir()
+        .join(format!("heuristics-man-{}", std::process::id()));
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["export", "--format", "man", "--out"])
+        .arg(&dir)
+        .env(TEST_DATA_HOME, test_data_home())
+        .output()
+        .expect("Failed to run heuristics binary");
+    assert!(output.status.success());
+    let cache_page =
+        dir.join("man7/heuristic-need-to-cache-expensive-results.7");
+    let exported = std::fs::read_dir(dir.join("man7")).unwrap().count();
+    let found = cache_page.is_file();
+    std::fs::remove_dir_all(&dir).ok();
+    assert!(found);
+    assert_eq!(exported, load_heuristics().all().len());
+}
+
+#[test]
+fn test_open_docs() {
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .env(TEST_DATA_HOME, test_data_home())
+            .env("BROWSER", "echo opened")
+            .args(args)
+            .output()
+            .expect("Failed to run heuristics binary")
+    };
+
+    let output = run(&["open-docs", "MOKA", "--crates-io", "--print"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "https://docs.rs/moka\nhttps://crates.io/crates/moka\n"
+    );
+    let opened = run(&["open-docs", "dashmap"]);
+    assert_eq!(
+        String::from_utf8_lossy(&opened.stdout),
+        "opened https://docs.rs/dashmap\n"
+    );
+    assert!(!run(&["open-docs", "no-such-crate", "--print"]).status.success());
+
+    let json = cli_json(&["open-docs", "lru"]);
+    assert_eq!(json["urls"], serde_json::json!(["https://docs.rs/lru"]));
+
+    let shown = run(&["show", "need-to-cache-expensive-results", "--open"]);
+    assert!(
+        String::from_utf8_lossy(&shown.stdout)
+            .ends_with("opened https://docs.rs/lru\n")
+    );
+}
+
+#[test]
+fn test_query_aliases() {
+    let dir = std::env::temp_dir()
+        .join(format!("heuristics-alias-{}", std::process::id()));
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .env("XDG_CONFIG_HOME", &dir)
+            .env(TEST_DATA_HOME, test_data_home())
+            .args(args)
+            .output()
+            .expect("Failed to run heuristics binary")
+    };
+
+    let set = run(&[
+        "alias",
+        "set",
+        "kv",
+        "category:\"General-Purpose Performance Heuristics\" lookup",
+    ]);
+    assert!(set.status.success(), "{}", String::from_utf8_lossy(&set.stderr));
+    let saved = run(&["kv", "--output", "ids"]);
+    assert!(
+        saved.status.success(),
+        "{}",
+        String::from_utf8_lossy(&saved.stderr)
+    );
+    let searched = run(&[
+        "search",
+        "lookup",
+        "--category",
+        "General-Purpose Performance Heuristics",
+        "--output",
+        "ids",
+    ]);
+    assert!(!saved.stdout.is_empty());
+    assert_eq!(saved.stdout, searched.stdout);
+
+    let listed: Value = serde_json::from_slice(
+        &run(&["alias", "list", "--format", "json"]).stdout,
+    )
+    .unwrap();
+    assert_eq!(listed["aliases"][0]["name"], "kv");
+
+    // Queries with both quotes and backslashes are escaped and read back as written
+    assert!(
+        run(&["alias", "set", "quoted", r#"it's "lock-free" \ fast"#])
+            .status
+            .success()
+    );
+    let listed: Value = serde_json::from_slice(
+        &run(&["alias", "list", "--format", "json"]).stdout,
+    )
+    .unwrap();
+    let quoted = listed["aliases"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|alias| alias["name"] == "quoted")
+        .unwrap();
+    assert_eq!(quoted["query"], r#"it's "lock-free" \ fast"#);
+
+    // Built-in commands can't be shadowed, and unknown names are usage errors
+    assert_eq!(
+        run(&["alias", "set", "search", "lock"]).status.code(),
+        Some(2)
+    );
+    assert!(run(&["alias", "remove", "kv"]).status.success());
+    assert_eq!(run(&["kv"]).status.code(), Some(2));
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_list_sorting_and_grouping() {
+    let dir = std::env::temp_dir()
+        .join(format!("heuristics-list-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let pack = dir.join("pack.md");
+    std::fs::write(
+        &pack,
+        "## Zeta Listing\n### Need listed B?\n**Updated:** 2024-01-02\n- **Crates:**\n  - `listfoo` - Foo\n  - `listbar` - Bar\n\
+         ### Need listed A?\n**Since:** 2025-06-01\n## Alpha Listing\n### Need listed C?\n- **Crates:**\n  - `listfoo` - Foo\n",
+    )
+    .unwrap();
+    let list = |args: &[&str]| -> Value {
+        let mut full = vec!["list", "--extra", pack.to_str().unwrap()];
+        full.extend(args);
+        cli_json(&full)
+    };
+    // Only this test's heuristics, in the order listed
+    let ids = |document: &Value| -> Vec<String> {
+        let ids = document["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|h| h["id"].as_str().unwrap().to_string());
+        ids.filter(|id| id.starts_with("need-listed-"))
+            .map(|id| id.replace("need-listed-", ""))
+            .collect()
+    };
+
+    assert_eq!(ids(&list(&[])), ["b", "a", "c"]);
+    assert_eq!(ids(&list(&["--sort", "title"])), ["a", "b", "c"]);
+    assert_eq!(ids(&list(&["--sort", "category"])), ["c", "b", "a"]);
+    assert_eq!(ids(&list(&["--sort", "crate-count"])), ["b", "c", "a"]);
+    assert_eq!(ids(&list(&["--sort", "recently-updated"])), ["a", "b", "c"]);
+
+    let by_crate = list(&["--group-by", "crate"]);
+    let groups = by_crate["groups"].as_array().unwrap();
+    let group =
+        |name: &str| groups.iter().position(|g| g["name"] == name).unwrap();
+    assert_eq!(
+        groups[group("listfoo")]["ids"],
+        serde_json::json!(["need-listed-b", "need-listed-c"])
+    );
+    assert_eq!(
+        groups[group("listbar")]["ids"],
+        serde_json::json!(["need-listed-b"])
+    );
+    assert!(group("listbar") < group("listfoo"));
+    assert_eq!(group("No crate"), groups.len() - 1);
+
+    let by_level = list(&["--group-by", "level"]);
+    assert_eq!(
+        by_level["groups"].as_array().unwrap().last().unwrap()["name"],
+        "unspecified"
+    );
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_search_select() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let select = |input: &str| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .env(TEST_DATA_HOME, test_data_home())
+            .args(["--minimal", "search", "cache", "--limit", "3", "--select"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success());
+        (
+            String::from_utf8_lossy(&output.stdout).to_string(),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        )
+    };
+
+    // The listing alone, then each chosen result in full
+    let (listing, _) = select("\n");
+    let (out, err) = select("9\n3, 1\n");
+    assert!(err.contains("Choose numbers from 1 to 3, not '9'"), "{}", err);
+    let shown = out.strip_prefix(&listing).expect("the listing comes first");
+    let third = shown.find("Need horizontal scalability?").unwrap();
+    let first = shown.find("Need to cache expensive results?").unwrap();
+    assert!(third < first, "results are shown in the order chosen");
+    assert!(shown.contains("When to use"));
+    assert!(!shown.contains("Need speed when dealing with complex queries?"));
+
+    let structured = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["search", "cache", "-s", "--format", "json"])
+        .output()
+        .unwrap();
+    assert_eq!(structured.status.code(), Some(2));
+}
+
+#[test]
+fn test_verbose_logging() {
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .args(args)
+            .env(TEST_DATA_HOME, test_data_home())
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success());
+        String::from_utf8(output.stderr).unwrap()
+    };
+
+    assert_eq!(run(&["search", "cache"]), "");
+
+    let info = run(&["-v", "search", "cache"]);
+    assert!(info.contains("loaded the built-in pack"), "{}", info);
+    assert!(!info.contains("searched"), "{}", info);
+
+    let debug = run(&["search", "cache", "-vv"]);
+    assert!(debug.contains("built search index"), "{}", debug);
+    assert!(debug.contains("searched keywords=[\"cache\"]"), "{}", debug);
+    assert!(!debug.contains('\x1b'), "stderr isn't a terminal: {}", debug);
+}
+
+#[test]
+fn test_message_languages() {
+    let run = |env: &[(&str, &str)], args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .env_remove("LC_ALL")
+            .env_remove("LC_MESSAGES")
+            .envs(env.iter().copied())
+            .env(TEST_DATA_HOME, test_data_home())
+            .args(args)
+            .output()
+            .unwrap();
+        (
+            output.status.code(),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+        )
+    };
+
+    let (_, german) = run(&[], &["--lang", "de", "search", "zzzqqq"]);
+    assert!(german.starts_with("Keine Heuristiken"), "{}", german);
+    let (_, from_env) =
+        run(&[("LANG", "de_DE.UTF-8")], &["search", "cache", "--limit", "1"]);
+    assert!(from_env.contains("Heuristik(en) gefunden"), "{}", from_env);
+    // --minimal is the same everywhere, and unknown locales fall back to English
+    let (_, minimal) = run(
+        &[("LANG", "de_DE.UTF-8")],
+        &["--minimal", "search", "cache", "--limit", "1"],
+    );
+    assert!(minimal.starts_with("Found"), "{}", minimal);
+    assert!(
+        run(&[("LANG", "C")], &["search", "cache"]).1.starts_with("Found")
+    );
+    assert_eq!(run(&[], &["--lang", "xx", "list"]).0, Some(2));
+
+    // Every locale translates the same messages as English
+    let keys = |name: &str| -> Vec<String> {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("locales")
+            .join(name);
+        let catalog = std::fs::read_to_string(path).unwrap();
+        let lines = catalog.lines().filter(|line| !line.starts_with('#'));
+        lines
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, _)| key.trim().to_string())
+            .collect()
+    };
+    assert_eq!(keys("de.ftl"), keys("en.ftl"));
+}
+
+#[test]
+fn test_explain_crate_digest() {
+    let json = cli_json(&["explain", "moka"]);
+    assert_eq!(json["command"], "explain");
+    assert_eq!(json["crate"], "moka");
+    let recommended = json["recommended_by"].as_array().unwrap();
+    assert!(
+        recommended
+            .iter()
+            .any(|h| h["id"] == "need-to-cache-expensive-results")
+    );
+    assert!(
+        json["alternatives"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|a| a["crate"] == "lru")
+    );
+
+    let missing = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["explain", "no-such-crate"])
+        .output()
+        .unwrap();
+    assert_eq!(missing.status.code(), Some(1));
+}
+
+#[test]
+fn test_daily_tip_cycles() {
+    let dir = std::env::temp_dir()
+        .join(format!("heuristics-daily-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("heuristics")).unwrap();
+    let log = dir.join("heuristics").join("daily");
+    let daily = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .env(TEST_DATA_HOME, &dir)
+            .arg("daily")
+            .args(args)
+            .output()
+            .expect("Failed to run heuristics binary");
+        assert!(output.status.success());
+        output.stdout
+    };
+    let today = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        / 86_400;
+    let ids: Vec<String> =
+        load_heuristics().all().iter().map(|h| h.id.clone()).collect();
+
+    // Two days in a row so far, so today continues the streak with an unseen tip
+    std::fs::write(
+        &log,
+        format!("{}\t{}\n{}\t{}\n", today - 2, ids[0], today - 1, ids[1]),
+    )
+    .unwrap();
+    let tip: Value =
+        serde_json::from_slice(&daily(&["--format", "json"])).unwrap();
+    assert_eq!(tip["streak"], 3);
+    assert_eq!(tip["cycle"]["shown"], 3);
+    let id = tip["result"]["id"].as_str().unwrap().to_string();
+    assert!(id != ids[0] && id != ids[1]);
+    // The same tip all day, and --once shows it only the first time
+    let again: Value =
+        serde_json::from_slice(&daily(&["--format", "json"])).unwrap();
+    assert_eq!(again["result"]["id"], id.as_str());
+    assert!(daily(&["--once"]).is_empty());
+
+    // Once every heuristic has been shown, a new cycle starts
+    let cycle: String =
+        ids.iter().map(|id| format!("{}\t{}\n", today - 1, id)).collect();
+    std::fs::write(&log, cycle).unwrap();
+    let next: Value =
+        serde_json::from_slice(&daily(&["--format", "json"])).unwrap();
+    assert_eq!(next["cycle"]["shown"], 1);
+    assert_eq!(next["streak"], 2);
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_usage_log_records_views() {
+    let dir = std::env::temp_dir()
+        .join(format!("heuristics-usage-{}", std::process::id()));
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .env(TEST_DATA_HOME, &dir)
+            .args(args)
+            .args(["--format", "json"])
+            .output()
+            .expect("Failed to run heuristics binary");
+        assert!(output.status.success());
+        serde_json::from_slice::<Value>(&output.stdout).unwrap()
+    };
+    let id = load_heuristics().all()[0].id.clone();
+    run(&["show", &id]);
+    run(&["show", &id]);
+    let picked = run(&["random"]);
+    let usage = std::fs::read_to_string(dir.join("heuristics").join("usage"))
+        .unwrap_or_default();
+    let log = UsageLog::parse(&usage);
+    let db = load_heuristics();
+    assert_eq!(
+        log.views(db.get(&id).unwrap()),
+        if picked["result"]["id"] == id.as_str() { 3 } else { 2 }
+    );
+    assert!(
+        log.views(db.get(picked["result"]["id"].as_str().unwrap()).unwrap())
+            >= 1,
+        "random counts as a view"
+    );
+
+    // --minimal keeps no log
+    let before =
+        std::fs::read_to_string(dir.join("heuristics").join("usage")).unwrap();
+    run(&["--minimal", "show", &id]);
+    assert_eq!(
+        std::fs::read_to_string(dir.join("heuristics").join("usage")).unwrap(),
+        before
+    );
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_cache_status_and_clear() {
+    let dir = std::env::temp_dir()
+        .join(format!("heuristics-cache-{}", std::process::id()));
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .env(TEST_DATA_HOME, dir.join("data"))
+            .env("XDG_CACHE_HOME", dir.join("cache"))
+            .args(args)
+            .output()
+            .expect("Failed to run heuristics binary");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let packs = dir.join("cache").join("heuristics").join("packs");
+    std::fs::create_dir_all(&packs).unwrap();
+    std::fs::write(packs.join("team.md"), "## Team\n").unwrap();
+    let data = dir.join("data").join("heuristics");
+    std::fs::create_dir_all(&data).unwrap();
+    std::fs::write(
+        data.join("pack.md"),
+        "## Mine\n### Need my own rule?\n**Action:** Keep it.\n",
+    )
+    .unwrap();
+    std::fs::write(
+        data.join("bookmarks"),
+        "need-to-cache-expensive-results\n",
+    )
+    .unwrap();
+    run(&["search", "cache"]);
+
+    let status: Value =
+        serde_json::from_str(&run(&["cache", "status", "--format", "json"]))
+            .unwrap();
+    let files = |status: &Value, name: &str| {
+        status["locations"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|l| l["name"] == name)
+            .unwrap()["files"]
+            .as_u64()
+            .unwrap()
+    };
+    assert_eq!(files(&status, "Downloaded packs"), 1);
+    assert_eq!(files(&status, "Search history"), 1);
+    assert_eq!(
+        run(&["cache", "path"]).trim_end(),
+        dir.join("cache").join("heuristics").to_str().unwrap()
+    );
+
+    // Clearing keeps bookmarks unless told otherwise, and never touches your pack
+    run(&["cache", "clear"]);
+    let cleared: Value =
+        serde_json::from_str(&run(&["cache", "status", "--format", "json"]))
+            .unwrap();
+    assert_eq!(files(&cleared, "Downloaded packs"), 0);
+    assert_eq!(files(&cleared, "Search history"), 0);
+    assert_eq!(files(&cleared, "Bookmarks"), 1);
+    run(&["cache", "clear", "--all"]);
+    let all: Value =
+        serde_json::from_str(&run(&["cache", "status", "--format", "json"]))
+            .unwrap();
+    assert_eq!(files(&all, "Bookmarks"), 0);
+    assert_eq!(files(&all, "Your pack"), 1);
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_builtin_pack_parses_cleanly() {
+    let document = parse_markdown(include_str!("../base.md"))
+        .expect("Embedded pack should parse");
+    assert!(
+        parse_markdown_with(
+            include_str!("../base.md"),
+            &ParseOptions::strict()
+        )
+        .is_ok()
+    );
+    let diagnostics: Vec<String> =
+        document.diagnostics.iter().map(|d| d.to_string()).collect();
+    assert!(
+        diagnostics.is_empty(),
+        "Unexpected diagnostics: {:?}",
+        diagnostics
+    );
+}
+
+/// Serve `(path, body)` pairs over HTTP on a local port, returning its base URL
+#[cfg(feature = "fetch")]
+fn serve(files: Vec<(String, String)>) -> String {
+    use std::io::{BufRead, BufReader, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            reader.read_line(&mut request).ok();
+            let mut header = String::new();
+            while reader.read_line(&mut header).is_ok_and(|n| n > 2) {
+                header.clear();
+            }
+            let path = request.split_whitespace().nth(1).unwrap_or("/");
+            let response = match files.iter().find(|(p, _)| p == path) {
+                Some((_, body)) => format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body),
+                None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+            };
+            stream.write_all(response.as_bytes()).ok();
+        }
+    });
+    base
+}
+
+#[cfg(feature = "fetch")]
+#[test]
+fn test_update_caches_verified_pack() {
+    use sha2::{Digest, Sha256};
+
+    let pack = "## Team\n### Need to cache team results?\n**Action:** Use the team cache.\n";
+    let digest: String =
+        Sha256::digest(pack).iter().map(|b| format!("{:02x}", b)).collect();
+    let base = serve(vec![
+        ("/team.md".to_string(), pack.to_string()),
+        ("/team.md.sha256".to_string(), format!("{}  team.md\n", digest)),
+    ]);
+    let url = format!("{}/team.md", base);
+    let cache = std::env::temp_dir()
+        .join(format!("heuristics-cache-{}", std::process::id()));
+    let update = |extra: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .args(["update", &url, "--format", "json"])
+            .args(extra)
+            .env("XDG_CACHE_HOME", &cache)
+            .output()
+            .unwrap()
+    };
+
+    let status = |output: std::process::Output| {
+        let json: Value = serde_json::from_slice(&output.stdout).unwrap();
+        (output.status.success(), json["status"].as_str().unwrap().to_string())
+    };
+    assert_eq!(status(update(&["--check"])), (false, "missing".to_string()));
+
+    let rejected = update(&["--sha256", &"0".repeat(64)]);
+    assert!(!rejected.status.success(), "A checksum mismatch should fail");
+    assert!(
+        String::from_utf8_lossy(&rejected.stderr)
+            .contains("checksum mismatch")
+    );
+    assert!(
+        !cache.join("heuristics/packs").exists(),
+        "Unverified packs are never cached"
+    );
+
+    let output = update(&[]);
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["heuristics"], 1);
+    let path = json["path"].as_str().unwrap();
+    assert!(
+        path.starts_with(cache.to_str().unwrap()) && path.ends_with(".md")
+    );
+    assert_eq!(
+        std::fs::read_to_string(format!("{}.crate-version", path)).unwrap(),
+        env!("CARGO_PKG_VERSION")
+    );
+    assert_eq!(
+        load_heuristics_from_path(path).unwrap().all()[0].id,
+        "need-to-cache-team-results"
+    );
+    assert_eq!(status(update(&["--check"])), (true, "current".to_string()));
+    assert_eq!(
+        status(update(&["--check", "--sha256", &"0".repeat(64)])),
+        (false, "stale".to_string())
+    );
+
+    std::fs::remove_dir_all(&cache).ok();
+}
+
+#[cfg(feature = "fetch")]
+#[test]
+fn test_cached_stock_pack_needs_same_crate_version() {
+    use sha2::{Digest, Sha256};
+
+    let cache = std::env::temp_dir()
+        .join(format!("heuristics-stock-{}", std::process::id()));
+    let name: String = Sha256::digest(PUBLISHED_PACK_URL)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    let pack = cache
+        .join("heuristics")
+        .join("packs")
+        .join(format!("{}.md", &name[..16]));
+    std::fs::create_dir_all(pack.parent().unwrap()).unwrap();
+    std::fs::write(
+        &pack,
+        "## Old\n### Need an old pack?\n**Action:** Upgrade.\n",
+    )
+    .unwrap();
+    let count = || {
+        let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+            .args(["--minimal", "list", "--format", "json"])
+            .env("XDG_CACHE_HOME", &cache)
+            .output()
+            .unwrap();
+        serde_json::from_slice::<Value>(&output.stdout).unwrap()["results"]
+            .as_array()
+            .unwrap()
+            .len()
+    };
+    let stamp = pack.with_extension("md.crate-version");
+    std::fs::write(&stamp, "0.0.1").unwrap();
+    assert_eq!(
+        count(),
+        load_heuristics().all().len(),
+        "A pack cached by another crate version is ignored"
+    );
+    std::fs::write(&stamp, env!("CARGO_PKG_VERSION")).unwrap();
+    assert_eq!(
+        count(),
+        1,
+        "A pack cached by this version replaces the embedded one"
+    );
+    std::fs::remove_dir_all(&cache).ok();
+}
+
+#[cfg(not(feature = "fetch"))]
+#[test]
+fn test_update_needs_fetch_feature() {
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["update", "https://example.com/pack.md"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[cfg(feature = "watch")]
+#[test]
+fn test_watch_reports_each_save() {
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+    use std::time::Duration;
+
+    let dir = std::env::temp_dir()
+        .join(format!("heuristics-watch-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let pack = dir.join("pack.md");
+    std::fs::write(
+        &pack,
+        "## Cat\n### Need a watched cache?\n**Action:** Cache it.\n",
+    )
+    .unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["watch", pack.to_str().unwrap(), "--format", "json"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let (reports, received) = std::sync::mpsc::channel();
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    std::thread::spawn(move || {
+        stdout
+            .lines()
+            .map_while(Result::ok)
+            .for_each(|line| reports.send(line).unwrap())
+    });
+    let next = || -> Value {
+        serde_json::from_str(
+            &received.recv_timeout(Duration::from_secs(10)).unwrap(),
+        )
+        .unwrap()
+    };
+
+    let first = next();
+    assert_eq!(first["stats"]["heuristics"], 1);
+    assert!(first["issues"].as_array().unwrap().is_empty());
+    std::fs::write(&pack, "## Cat\n### Need a watched cache?\n**Action:** Cache it.\n**Bogus:** hm\n### Need a watched map?\n").unwrap();
+    let second = next();
+    child.kill().ok();
+    child.wait().ok();
+    assert_eq!(second["stats"]["heuristics"], 2);
+    assert!(
+        second["issues"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|issue| issue["line"] == 4)
+    );
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[cfg(not(feature = "sqlite"))]
+#[test]
+fn test_sqlite_export_needs_sqlite_feature() {
+    let path = std::env::temp_dir()
+        .join(format!("heuristics-{}.db", std::process::id()));
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["export", "--format", "sqlite", "-o"])
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(!path.exists());
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_sqlite_export() {
+    let md = "## Maps\n### Need a map?\n**Action:** Use a [sorted map](#need-a-sorted-map) when order matters.\n\
+              - **Crates:**\n  - `hashbrown` - Fast maps\n\n\
+              ### Need a sorted map?\n**Action:** Use `BTreeMap`.\n**Parent:** Need a map?\n- **Alternatives:** `indexmap`\n";
+    let db = HeuristicDb::from_markdown(md).unwrap();
+    let path = std::env::temp_dir()
+        .join(format!("heuristics-export-{}.db", std::process::id()));
+    std::fs::write(&path, "not a database").unwrap();
+    to_sqlite(db.all(), &path)
+        .expect("The database should be written over the old file");
+
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    let rows = |sql: &str| -> Vec<String> {
+        let mut statement = conn.prepare(sql).unwrap();
+        statement
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+    };
+    assert_eq!(
+        rows("SELECT id FROM heuristics ORDER BY id"),
+        ["need-a-map", "need-a-sorted-map"]
+    );
+    assert_eq!(
+        rows("SELECT heuristic_id FROM crates WHERE name = 'hashbrown'"),
+        ["need-a-map"]
+    );
+    assert_eq!(
+        rows("SELECT name FROM alternatives WHERE kind = 'crate'"),
+        ["indexmap"]
+    );
+    assert_eq!(
+        rows(
+            "SELECT from_id || ' ' || kind || ' ' || to_id FROM cross_references ORDER BY from_id"
+        ),
+        [
+            "need-a-map see_also need-a-sorted-map",
+            "need-a-sorted-map parent need-a-map"
+        ]
+    );
+    assert_eq!(
+        rows(
+            "SELECT id FROM heuristics_fts WHERE heuristics_fts MATCH 'btree*'"
+        ),
+        ["need-a-sorted-map"]
+    );
+    drop(conn);
+    std::fs::remove_file(&path).ok();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["export", "--format", "sqlite"])
+        .env(TEST_DATA_HOME, test_data_home())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2), "A database needs -o");
+}
+
+#[cfg(not(feature = "schema"))]
+#[test]
+fn test_schema_needs_schema_feature() {
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .arg("schema")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn test_schema() {
+    let pack: Value = serde_json::from_str(&pack_schema())
+        .expect("The schema should be JSON");
+    assert_eq!(pack["title"], "Heuristic pack");
+    assert!(pack["properties"]["pack-name"].is_object());
+    assert_eq!(
+        pack["additionalProperties"], false,
+        "Unknown keys fail to load"
+    );
+    let heuristic = &pack["$defs"]["HeuristicDefinition"];
+    assert_eq!(heuristic["required"], serde_json::json!(["title"]));
+    assert_eq!(heuristic["additionalProperties"], false);
+
+    // Every field a snapshot holds is described
+    let snapshot: Value = serde_json::from_str(&snapshot_schema()).unwrap();
+    let document: Value =
+        serde_json::from_str(&load_heuristics().to_json()).unwrap();
+    let described = |name: &str, value: &Value| {
+        let properties =
+            snapshot["$defs"][name]["properties"].as_object().unwrap();
+        value
+            .as_object()
+            .unwrap()
+            .keys()
+            .all(|key| properties.contains_key(key))
+    };
+    assert!(
+        document
+            .as_object()
+            .unwrap()
+            .keys()
+            .all(|key| snapshot["properties"][key].is_object())
+    );
+    assert!(
+        document["heuristics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|h| described("Heuristic", h))
+    );
+    assert!(described("PackInfo", &document["pack"]));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["schema", "--snapshot"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), snapshot_schema());
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json_import() {
+    let db = load_heuristics();
+    let imported =
+        HeuristicDb::from_source(&JsonSource::new("Imported"), &db.to_json())
+            .unwrap();
+    assert_eq!(
+        imported.to_markdown(),
+        HeuristicDb::new(db.all().to_vec()).to_markdown(),
+        "Snapshots import unchanged"
+    );
+
+    let path = std::env::temp_dir()
+        .join(format!("heuristics-import-{}.json", std::process::id()));
+    let output = Command::new(env!("CARGO_BIN_EXE_heuristics"))
+        .args(["--format", "json", "export"])
+        .output()
+        .unwrap();
+    std::fs::write(&path, &output.stdout).unwrap();
+    let json = cli_json(&["import", path.to_str().unwrap()]);
+    std::fs::remove_file(&path).ok();
+    assert_eq!(json["results"].as_array().unwrap().len(), db.all().len());
+
+    let objects = r#"[{"title": "Need a set?", "category": "Sets", "level": "Advanced", "pros": ["Ordered"],
+                      "sources": ["https://docs.rs", {"title": "Book", "url": "https://doc.rust-lang.org/book"}]},
+                     {"id": "bag", "title": "Need a bag?", "tradeoffs": {"cons": ["Unordered"]}, "content": "Count things."}]"#;
+    let db = HeuristicDb::from_source(&JsonSource::new("Imported"), objects)
+        .unwrap();
+    let (set, bag) = (db.get("need-a-set").unwrap(), db.get("bag").unwrap());
+    assert_eq!(
+        (set.level, &set.tradeoffs.pros),
+        (Some(Level::Advanced), &vec!["Ordered".to_string()])
+    );
+    assert_eq!(set.sources[1].title, "Book");
+    assert_eq!(
+        (bag.category.as_str(), bag.tradeoffs.cons.len()),
+        ("Imported", 1),
+        "No category means the given one, not the one before"
+    );
+    assert!(bag.content.contains("Count things."));
+    assert!(
+        HeuristicDb::from_source(
+            &JsonSource::new("Imported"),
+            r#"{"results": [{"action": "x"}]}"#
+        )
+        .is_err()
+    );
 }